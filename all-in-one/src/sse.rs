@@ -1,22 +1,50 @@
 use anyhow::Result;
-use tracing_subscriber::{self, EnvFilter};
+use clap::Parser;
+use proxy_server::config::ProxyConfig;
+use proxy_server::embedded_backend::{self, EmbeddedBackendArgs};
+use proxy_server::SseConfig;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "MCP server over SSE with jobworkerp embedded")]
+struct Cli {
+    /// Path of the YAML config file (overrides MCP_CONFIG_FILE)
+    #[arg(long, value_name = "PATH")]
+    config: Option<String>,
+
+    #[command(flatten)]
+    backend: EmbeddedBackendArgs,
+}
 
 /// npx @modelcontextprotocol/inspector cargo run -p mcp-server-examples --example std_io
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     dotenv::dotenv().ok();
+    let cli = Cli::parse();
+
+    let mut proxy_config = match cli.config.as_deref() {
+        Some(path) => ProxyConfig::load_file(path)?,
+        None => ProxyConfig::load()?,
+    };
+    cli.backend.apply(&mut proxy_config.embedded_backend);
+    // the embedded backend reads its settings from the environment (set before the runtime
+    // starts any thread)
+    proxy_config.embedded_backend.apply_env();
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run(proxy_config))
+}
 
-    // Initialize the tracing subscriber with file and stdout logging
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into()))
-        .with_writer(std::io::stderr)
-        .with_ansi(false)
-        .init();
+async fn run(proxy_config: ProxyConfig) -> Result<()> {
+    let _log_guard = proxy_server::logging::init_tracing(&proxy_config.logging)?;
 
     let jobworkerp_server = tokio::spawn(jobworkerp_main::boot_all_in_one());
     // wait for boot
     tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-    let sse_server = tokio::spawn(proxy_server::boot_sse_server());
+    // bundled workflows exposed as tools from the first listing
+    embedded_backend::register_bootstrap_workflows(&proxy_config).await;
+    let config = embedded_backend::router_config(proxy_config);
+    let sse_server = tokio::spawn(proxy_server::boot_sse_server(config, SseConfig::from_env()));
 
     let (sse_result, jobworkerp_result) = tokio::join!(sse_server, jobworkerp_server);
 
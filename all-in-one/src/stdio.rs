@@ -1,68 +1,75 @@
 use anyhow::Result;
-use proxy_server::jobworkerp::JobworkerpRouterConfig;
-use tracing_subscriber::{self, EnvFilter};
+use clap::Parser;
+use proxy_server::config::ProxyConfig;
+use proxy_server::embedded_backend::{self, EmbeddedBackendArgs};
+use proxy_server::{SseConfig, StdioClosed};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "MCP server over stdio with jobworkerp embedded")]
+struct Cli {
+    /// Path of the YAML config file (overrides MCP_CONFIG_FILE)
+    #[arg(long, value_name = "PATH")]
+    config: Option<String>,
+
+    /// Also serve the tools over SSE (settings of the SSE env vars) from the same proxy
+    #[arg(long)]
+    sse: bool,
+
+    #[command(flatten)]
+    backend: EmbeddedBackendArgs,
+}
 
 /// npx @modelcontextprotocol/inspector cargo run -p mcp-server-examples --example std_io
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     dotenv::dotenv().ok();
+    let cli = Cli::parse();
 
-    // Initialize the tracing subscriber with file and stdout logging
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into()))
-        .with_writer(std::io::stderr)
-        .with_ansi(false)
-        .init();
+    let mut proxy_config = match cli.config.as_deref() {
+        Some(path) => ProxyConfig::load_file(path)?,
+        None => ProxyConfig::load()?,
+    };
+    cli.backend.apply(&mut proxy_config.embedded_backend);
+    // the embedded backend reads its settings from the environment (set before the runtime
+    // starts any thread)
+    proxy_config.embedded_backend.apply_env();
+
+    let result = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run(proxy_config, cli.sse));
+    // distinct exit code for the wrapper processes when the client closed the connection
+    if let Some(exit_code) = StdioClosed::exit_code_of(&result) {
+        std::process::exit(exit_code);
+    }
+    result
+}
+
+async fn run(proxy_config: ProxyConfig, sse: bool) -> Result<()> {
+    let _log_guard = proxy_server::logging::init_tracing(&proxy_config.logging)?;
 
     let jobworkerp_server = tokio::spawn(jobworkerp_main::boot_all_in_one());
     // wait for boot
     tokio::time::sleep(std::time::Duration::from_secs(1)).await;
 
-    let jobworkerp_address =
-        std::env::var("JOBWORKERP_ADDR").unwrap_or_else(|_| "http://127.0.0.1:9000".to_string());
-    let request_timeout_sec = std::env::var("REQUEST_TIMEOUT_SEC")
-        .ok()
-        .and_then(|s| s.parse::<u32>().ok());
-    let exclude_runner_as_tool = std::env::var("EXCLUDE_RUNNER_AS_TOOL")
-        .ok()
-        .and_then(|s| s.parse::<bool>().ok())
-        .unwrap_or_default();
-    let exclude_worker_as_tool = std::env::var("EXCLUDE_WORKER_AS_TOOL")
-        .ok()
-        .and_then(|s| s.parse::<bool>().ok())
-        .unwrap_or_default();
-    let set_name = std::env::var("TOOL_SET_NAME")
-        .ok()
-        .and_then(|s| s.parse::<String>().ok());
+    // bundled workflows exposed as tools from the first listing
+    embedded_backend::register_bootstrap_workflows(&proxy_config).await;
+    let config = embedded_backend::router_config(proxy_config);
 
-    tracing::info!(
-        "Starting MCP server {} {}",
-        if exclude_runner_as_tool {
-            "without runner"
-        } else {
-            "with runner"
-        },
-        if exclude_worker_as_tool {
-            "without worker as tool"
-        } else {
-            "with worker as tool"
-        }
-    );
-    let config = JobworkerpRouterConfig {
-        jobworkerp_address,
-        request_timeout_sec,
-        exclude_runner_as_tool,
-        exclude_worker_as_tool,
-        set_name,
+    let stdio_server = if sse {
+        tokio::spawn(proxy_server::boot_stdio_sse_server(
+            config,
+            SseConfig::from_env(),
+        ))
+    } else {
+        tokio::spawn(proxy_server::boot_stdio_server(config))
     };
 
-    let stdio_server = tokio::spawn(proxy_server::boot_stdio_server(config));
-
-    let (stdio_result, jobworkerp_result) = tokio::join!(stdio_server, jobworkerp_server);
-
-    // 各タスクの結果をチェック
-    stdio_result??;
-    jobworkerp_result??;
-
-    Ok(())
+    // the process exits when the stdio client closes the connection
+    tokio::select! {
+        stdio_result = stdio_server => stdio_result?,
+        jobworkerp_result = jobworkerp_server => {
+            jobworkerp_result??;
+            Err(anyhow::anyhow!("jobworkerp server stopped"))
+        }
+    }
 }
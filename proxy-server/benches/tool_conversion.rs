@@ -0,0 +1,205 @@
+//! Benchmarks of the list_tools and call_tool hot paths
+//!
+//! Run with `cargo bench -p proxy-server`.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use jobworkerp_client::jobworkerp::data::{Runner, RunnerData, RunnerType, WorkerData};
+use jobworkerp_client::jobworkerp::function::data::{
+    function_specs, FunctionSchema, FunctionSpecs, McpTool, McpToolList,
+};
+use proxy_server::enum_schema;
+use proxy_server::jobworkerp::repository::JobworkerpRepository;
+use proxy_server::jobworkerp::settings_presets::SettingsPresets;
+use proxy_server::schema_lint;
+use proxy_server::tool_conversion::{ToolConversionOptions, ToolConverter};
+use serde_json::{json, Map, Value};
+
+// object schema nested `depth` levels with a few properties (and a shared def) per level
+fn deep_schema(depth: usize) -> Value {
+    let mut schema = json!({
+        "type": "object",
+        "properties": {
+            "name": {"type": "string", "description": "name of the item"},
+            "level": {"$ref": "#/$defs/Level"},
+            "tags": {"type": "array", "items": {"type": "string"}}
+        },
+        "required": ["name"]
+    });
+    for i in 0..depth {
+        schema = json!({
+            "type": "object",
+            "description": format!("level {}", i),
+            "properties": {
+                "child": schema,
+                "count": {"type": "integer", "minimum": 0},
+                "enabled": {"type": "boolean", "default": true},
+                "mode": {"type": "integer", "enum": [0, 1, 2], "enumNames": ["A", "B", "C"]}
+            }
+        });
+    }
+    if let Value::Object(obj) = &mut schema {
+        obj.insert(
+            "$defs".to_string(),
+            json!({"Level": {"type": "string", "enum": ["low", "high"]}}),
+        );
+    }
+    schema
+}
+
+fn runner_function(index: usize, depth: usize) -> FunctionSpecs {
+    FunctionSpecs {
+        runner_type: RunnerType::Command as i32,
+        runner_id: Some(Default::default()),
+        worker_id: None,
+        name: format!("runner_{}", index),
+        description: "benchmark runner".to_string(),
+        output_type: 0,
+        schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+            settings: Some(deep_schema(depth / 2).to_string()),
+            arguments: deep_schema(depth).to_string(),
+            result_output_schema: Some(deep_schema(1).to_string()),
+        })),
+    }
+}
+
+fn mcp_server_function(index: usize, tools: usize, depth: usize) -> FunctionSpecs {
+    FunctionSpecs {
+        runner_type: RunnerType::McpServer as i32,
+        runner_id: Some(Default::default()),
+        worker_id: None,
+        name: format!("server_{}", index),
+        description: "benchmark mcp server".to_string(),
+        output_type: 0,
+        schema: Some(function_specs::Schema::McpTools(McpToolList {
+            list: (0..tools)
+                .map(|i| McpTool {
+                    name: format!("tool_{}", i),
+                    description: Some("benchmark tool".to_string()),
+                    input_schema: deep_schema(depth).to_string(),
+                    annotations: None,
+                })
+                .collect(),
+        })),
+    }
+}
+
+// `count` tools: half runners and half tools of mcp servers (10 tools per server)
+fn fixtures(count: usize, depth: usize) -> Vec<FunctionSpecs> {
+    let runners = (0..count / 2).map(|i| runner_function(i, depth));
+    let servers = (0..count / 20).map(|i| mcp_server_function(i, 10, depth));
+    runners.chain(servers).collect()
+}
+
+fn bench_convert_functions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("convert_functions_to_mcp_tools");
+    for count in [100, 500] {
+        let functions = fixtures(count, 5);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &functions, |b, f| {
+            b.iter(|| ToolConverter::convert_functions_to_mcp_tools(black_box(f.clone())))
+        });
+    }
+    group.finish();
+}
+
+// the schema combination (SchemaCombiner) of a single function
+fn bench_combine_schema(c: &mut Criterion) {
+    let mut group = c.benchmark_group("combine_schema");
+    let inline = ToolConversionOptions {
+        inline_refs: true,
+        describe_result: true,
+        ..Default::default()
+    };
+    for depth in [2, 10] {
+        let function = runner_function(0, depth);
+        group.bench_with_input(BenchmarkId::new("default", depth), &function, |b, f| {
+            b.iter(|| ToolConverter::convert_normal_function(black_box(f)))
+        });
+        group.bench_with_input(BenchmarkId::new("inline_refs", depth), &function, |b, f| {
+            b.iter(|| ToolConverter::convert_normal_function_with_options(black_box(f), &inline))
+        });
+    }
+    group.finish();
+}
+
+// post-processing of the tool list
+fn bench_list_post_processing(c: &mut Criterion) {
+    let functions = fixtures(200, 5);
+    let tools = ToolConverter::convert_functions_to_mcp_tools(functions.clone())
+        .unwrap()
+        .tools;
+    c.bench_function("rewrite_integer_enums", |b| {
+        b.iter(|| {
+            for tool in tools.iter() {
+                let mut schema = tool.input_schema.as_ref().clone();
+                black_box(enum_schema::rewrite_integer_enums(&mut schema));
+            }
+        })
+    });
+    c.bench_function("lint_functions", |b| {
+        b.iter(|| schema_lint::lint_functions(black_box(&functions)))
+    });
+}
+
+fn call_arguments() -> Map<String, Value> {
+    json!({
+        "settings": deep_schema(3),
+        "arguments": {"command": "echo", "args": (0..100).map(|i| i.to_string()).collect::<Vec<_>>()}
+    })
+    .as_object()
+    .cloned()
+    .unwrap_or_default()
+}
+
+fn bench_prepare_arguments(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let args = call_arguments();
+    let runner = Runner {
+        data: Some(RunnerData {
+            runner_type: RunnerType::Command as i32,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let mcp_runner = Runner {
+        data: Some(RunnerData {
+            runner_type: RunnerType::McpServer as i32,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let worker = WorkerData::default();
+    let presets = SettingsPresets::default();
+    c.bench_function("prepare_runner_call_arguments", |b| {
+        b.to_async(&rt).iter(|| {
+            JobworkerpRepository::prepare_runner_call_arguments(
+                args.clone(),
+                &runner,
+                None,
+                &presets,
+            )
+        })
+    });
+    c.bench_function("prepare_runner_call_arguments_mcp", |b| {
+        b.to_async(&rt).iter(|| {
+            JobworkerpRepository::prepare_runner_call_arguments(
+                args.clone(),
+                &mcp_runner,
+                Some("tool_0".to_string()),
+                &presets,
+            )
+        })
+    });
+    c.bench_function("prepare_worker_call_arguments", |b| {
+        b.to_async(&rt).iter(|| {
+            JobworkerpRepository::prepare_worker_call_arguments(args.clone(), &worker, None)
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_convert_functions,
+    bench_combine_schema,
+    bench_list_post_processing,
+    bench_prepare_arguments
+);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+use rmcp::model::{CallToolResult, Content};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+// fields reported in a tool error
+const MAX_FIELD_ERRORS: usize = 10;
+
+/// A field of the tool arguments which does not match the tool schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    // e.g. `arguments.items[0].count` (empty for the arguments themselves)
+    pub path: String,
+    // expectation of the schema, e.g. `"x" is not of type "integer"`
+    pub message: String,
+    pub received: Value,
+}
+
+// json pointer to a readable path: `/arguments/items/0/count` -> `arguments.items[0].count`
+fn readable_path(pointer: &str) -> String {
+    let mut path = String::new();
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        if segment.parse::<usize>().is_ok() {
+            path.push_str(&format!("[{}]", segment));
+        } else {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(&segment);
+        }
+    }
+    path
+}
+
+/// Fields of the arguments mismatching the schema (empty if valid or the schema is invalid)
+pub fn field_errors(schema: &Map<String, Value>, arguments: &Value) -> Vec<FieldError> {
+    let Ok(validator) = jsonschema::validator_for(&Value::Object(schema.clone())) else {
+        return vec![];
+    };
+    validator
+        .iter_errors(arguments)
+        .take(MAX_FIELD_ERRORS)
+        .map(|e| FieldError {
+            path: readable_path(&e.instance_path.to_string()),
+            message: e.to_string(),
+            received: e.instance.clone().into_owned(),
+        })
+        .collect()
+}
+
+/// Tool error listing the mismatching fields (for the caller to correct the arguments)
+pub fn field_errors_result(cause: &str, errors: &[FieldError]) -> CallToolResult {
+    let mut text = format!("Invalid arguments: {}\n", cause);
+    for e in errors {
+        let path = if e.path.is_empty() {
+            "(arguments)"
+        } else {
+            e.path.as_str()
+        };
+        text.push_str(&format!(
+            "- {}: {} (received: {})\n",
+            path, &e.message, &e.received
+        ));
+    }
+    CallToolResult {
+        content: vec![Content::text(text.trim_end())],
+        is_error: Some(true),
+    }
+}
@@ -0,0 +1,306 @@
+use crate::common::env;
+use anyhow::Result;
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm},
+    Algorithm, DecodingKey, Validation,
+};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Authenticated identity of a client (attached to the session)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthIdentity {
+    pub subject: String,
+    pub scopes: Vec<String>,
+}
+
+/// OAuth 2.1 resource server settings (MCP authorization spec)
+#[derive(Debug, Clone)]
+pub struct OAuthConfig {
+    pub issuer: String,
+    pub jwks_url: String,
+    // required audience of access tokens (None: not validated, opted out explicitly)
+    pub audience: Option<String>,
+    // public url of this server, used as the protected resource identifier
+    pub resource_url: String,
+    pub scopes_supported: Vec<String>,
+}
+
+impl OAuthConfig {
+    /// Settings of the server listening on the bind addresses (`MCP_ADDR`), with or without TLS
+    pub fn from_env(addresses: &str, tls: bool) -> Option<Self> {
+        let issuer = std::env::var("OAUTH_ISSUER").ok()?;
+        let jwks_url = std::env::var("OAUTH_JWKS_URL")
+            .unwrap_or_else(|_| format!("{}/.well-known/jwks.json", issuer.trim_end_matches('/')));
+        let resource_url = std::env::var("OAUTH_RESOURCE_URL").unwrap_or_else(|_| {
+            let url = Self::default_resource_url(addresses, tls);
+            tracing::warn!(
+                "OAUTH_RESOURCE_URL is not set: {} is published as the resource url",
+                &url
+            );
+            url
+        });
+        // tokens issued for other resources are rejected unless opted out
+        let validate_audience = env::parse::<bool>("OAUTH_VALIDATE_AUDIENCE").unwrap_or(true);
+        let audience = match std::env::var("OAUTH_AUDIENCE").ok() {
+            Some(audience) => Some(audience),
+            None if validate_audience => Some(resource_url.clone()),
+            None => {
+                tracing::warn!("audience of access tokens is not validated");
+                None
+            }
+        };
+        Some(Self {
+            issuer,
+            jwks_url,
+            audience,
+            resource_url,
+            scopes_supported: std::env::var("OAUTH_SCOPES")
+                .map(|s| {
+                    s.split([',', ' '])
+                        .filter(|s| !s.is_empty())
+                        .map(|s| s.to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Url of the first of the bind addresses (comma separated), for servers accessed directly
+    pub fn default_resource_url(addresses: &str, tls: bool) -> String {
+        let address = addresses
+            .split(',')
+            .map(|s| s.trim())
+            .find(|s| !s.is_empty())
+            .unwrap_or("127.0.0.1:8000");
+        format!("{}://{}", if tls { "https" } else { "http" }, address)
+    }
+
+    pub fn resource_metadata_url(&self) -> String {
+        format!(
+            "{}/.well-known/oauth-protected-resource",
+            self.resource_url.trim_end_matches('/')
+        )
+    }
+
+    /// Protected resource metadata (RFC 9728)
+    pub fn resource_metadata(&self) -> Value {
+        serde_json::json!({
+            "resource": self.resource_url,
+            "authorization_servers": [self.issuer],
+            "bearer_methods_supported": ["header"],
+            "scopes_supported": self.scopes_supported,
+        })
+    }
+}
+
+/// Validates JWT access tokens with keys fetched (and cached) from the issuer's JWKS
+pub struct JwtValidator {
+    config: OAuthConfig,
+    http_client: reqwest::Client,
+    jwks: RwLock<Option<(JwkSet, Instant)>>,
+}
+
+impl JwtValidator {
+    const JWKS_CACHE_TTL: Duration = Duration::from_secs(60 * 10);
+    // minimum interval of refetching jwks for unknown key ids
+    const JWKS_MIN_REFRESH: Duration = Duration::from_secs(30);
+
+    pub fn new(config: OAuthConfig) -> Self {
+        Self {
+            config,
+            http_client: reqwest::Client::new(),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &OAuthConfig {
+        &self.config
+    }
+
+    async fn fetch_jwks(&self) -> Result<JwkSet> {
+        tracing::debug!("fetch jwks: {}", &self.config.jwks_url);
+        let jwks = self
+            .http_client
+            .get(&self.config.jwks_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+        *self.jwks.write().await = Some((jwks.clone(), Instant::now()));
+        Ok(jwks)
+    }
+
+    async fn find_jwk(&self, kid: Option<&str>) -> Result<Jwk> {
+        let find = |jwks: &JwkSet| match kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None => jwks.keys.first().cloned(),
+        };
+        let cached = self.jwks.read().await.clone();
+        let refetch = match cached.as_ref() {
+            Some((jwks, fetched_at)) => {
+                if let Some(jwk) =
+                    find(jwks).filter(|_| fetched_at.elapsed() < Self::JWKS_CACHE_TTL)
+                {
+                    return Ok(jwk);
+                }
+                // key rotation: refetch unless fetched very recently
+                fetched_at.elapsed() >= Self::JWKS_MIN_REFRESH
+            }
+            None => true,
+        };
+        let jwks = if refetch {
+            self.fetch_jwks().await?
+        } else {
+            cached.map(|(j, _)| j).unwrap_or(JwkSet { keys: vec![] })
+        };
+        find(&jwks).ok_or_else(|| anyhow::anyhow!("signing key not found: {:?}", kid))
+    }
+
+    pub async fn validate(&self, token: &str) -> Result<AuthIdentity> {
+        let header = decode_header(token)?;
+        let jwk = self.find_jwk(header.kid.as_deref()).await?;
+        let key = DecodingKey::from_jwk(&jwk)?;
+        // the algorithm is pinned by the key, not taken from the token
+        let mut validation = Validation::new(Self::jwk_algorithm(&jwk)?);
+        validation.set_issuer(&[self.config.issuer.as_str()]);
+        if let Some(audience) = self.config.audience.as_ref() {
+            validation.set_audience(&[audience.as_str()]);
+        } else {
+            validation.validate_aud = false;
+        }
+        let claims = decode::<Map<String, Value>>(token, &key, &validation)?.claims;
+        Self::identity_from_claims(&claims)
+    }
+
+    // `alg` of the key, or the algorithm of the asymmetric key type
+    fn jwk_algorithm(jwk: &Jwk) -> Result<Algorithm> {
+        if let Some(alg) = jwk.common.key_algorithm.as_ref() {
+            return match alg {
+                KeyAlgorithm::HS256 => Ok(Algorithm::HS256),
+                KeyAlgorithm::HS384 => Ok(Algorithm::HS384),
+                KeyAlgorithm::HS512 => Ok(Algorithm::HS512),
+                KeyAlgorithm::ES256 => Ok(Algorithm::ES256),
+                KeyAlgorithm::ES384 => Ok(Algorithm::ES384),
+                KeyAlgorithm::RS256 => Ok(Algorithm::RS256),
+                KeyAlgorithm::RS384 => Ok(Algorithm::RS384),
+                KeyAlgorithm::RS512 => Ok(Algorithm::RS512),
+                KeyAlgorithm::PS256 => Ok(Algorithm::PS256),
+                KeyAlgorithm::PS384 => Ok(Algorithm::PS384),
+                KeyAlgorithm::PS512 => Ok(Algorithm::PS512),
+                KeyAlgorithm::EdDSA => Ok(Algorithm::EdDSA),
+                alg => Err(anyhow::anyhow!(
+                    "unsupported signing key algorithm: {:?}",
+                    alg
+                )),
+            };
+        }
+        match &jwk.algorithm {
+            AlgorithmParameters::RSA(_) => Ok(Algorithm::RS256),
+            AlgorithmParameters::EllipticCurve(ec) => match ec.curve {
+                EllipticCurve::P256 => Ok(Algorithm::ES256),
+                EllipticCurve::P384 => Ok(Algorithm::ES384),
+                ref curve => Err(anyhow::anyhow!(
+                    "unsupported signing key curve: {:?}",
+                    curve
+                )),
+            },
+            AlgorithmParameters::OctetKeyPair(_) => Ok(Algorithm::EdDSA),
+            // symmetric keys must specify the algorithm
+            AlgorithmParameters::OctetKey(_) => {
+                Err(anyhow::anyhow!("signing key without algorithm"))
+            }
+        }
+    }
+
+    fn identity_from_claims(claims: &Map<String, Value>) -> Result<AuthIdentity> {
+        let subject = claims
+            .get("sub")
+            .and_then(|s| s.as_str())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("token without subject"))?
+            .to_string();
+        // `scope` (space separated string) or `scp` (array)
+        let scopes = match claims.get("scope").or_else(|| claims.get("scp")) {
+            Some(Value::String(s)) => s.split(' ').map(|s| s.to_string()).collect(),
+            Some(Value::Array(a)) => a
+                .iter()
+                .filter_map(|s| s.as_str().map(|s| s.to_string()))
+                .collect(),
+            _ => vec![],
+        };
+        Ok(AuthIdentity { subject, scopes })
+    }
+}
+
+/// Authenticates bearer tokens with static tokens and/or OAuth access tokens
+pub struct Authenticator {
+    // (token, subject)
+    static_tokens: Vec<(String, String)>,
+    jwt: Option<JwtValidator>,
+}
+
+impl Authenticator {
+    pub fn new(static_tokens: Vec<String>, oauth: Option<OAuthConfig>) -> Self {
+        let static_tokens = static_tokens
+            .into_iter()
+            .map(|t| {
+                let subject = Self::static_token_subject(&t);
+                (t, subject)
+            })
+            .collect::<Vec<_>>();
+        if !static_tokens.is_empty() {
+            tracing::info!(
+                "static token subjects: {:?}",
+                static_tokens.iter().map(|(_, s)| s).collect::<Vec<_>>()
+            );
+        }
+        Self {
+            static_tokens,
+            jwt: oauth.map(JwtValidator::new),
+        }
+    }
+
+    /// Subject of a static token (distinct per token without revealing it)
+    pub fn static_token_subject(token: &str) -> String {
+        format!(
+            "static-token:{}",
+            hex::encode(&Sha256::digest(token.as_bytes())[..8])
+        )
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.static_tokens.is_empty() || self.jwt.is_some()
+    }
+
+    pub fn oauth_config(&self) -> Option<&OAuthConfig> {
+        self.jwt.as_ref().map(|j| j.config())
+    }
+
+    // compare without short circuit to avoid leaking the token by timing
+    fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+    }
+
+    pub async fn authenticate(&self, token: &str) -> Result<AuthIdentity> {
+        // compare with all tokens not to leak which one matched by timing
+        let subject = self.static_tokens.iter().fold(None, |found, (t, subject)| {
+            let matched = Self::constant_time_eq(t.as_bytes(), token.as_bytes());
+            found.or(matched.then_some(subject))
+        });
+        if let Some(subject) = subject {
+            return Ok(AuthIdentity {
+                subject: subject.clone(),
+                scopes: vec![],
+            });
+        }
+        match self.jwt.as_ref() {
+            Some(jwt) => jwt.validate(token).await,
+            None => Err(anyhow::anyhow!("invalid token")),
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use proxy_server::client::run_manifest::RunManifest;
+use proxy_server::client::{self, McpClient, OutputFormat};
+use proxy_server::config::ProxyConfig;
+use proxy_server::jobworkerp::{worker_definition, workflow_validation};
+use proxy_server::jobworkerp::{JobworkerpRouter, JobworkerpRouterConfig};
+use proxy_server::mock::MockTools;
+use proxy_server::tool_export::{self, ExportFormat};
+use proxy_server::{SseConfig, StdioClosed};
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "MCP proxy server for jobworkerp")]
+struct Cli {
+    /// Path of the YAML config file (overrides MCP_CONFIG_FILE)
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<String>,
+
+    /// Bearer token sent to the SSE server by the client commands (overrides MCP_CLIENT_TOKEN)
+    #[arg(long, global = true, value_name = "TOKEN")]
+    token: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Serve the MCP server
+    Serve {
+        #[command(subcommand)]
+        transport: Transport,
+
+        /// Retry connecting to jobworkerp for up to SECS seconds at startup
+        #[arg(long, global = true, value_name = "SECS")]
+        wait_for_backend: Option<u64>,
+
+        /// Serve the tools of a manifest file (e.g. the output of `list`) with canned or echoed
+        /// results instead of jobworkerp
+        #[arg(long, global = true, value_name = "TOOLS_JSON")]
+        mock: Option<String>,
+
+        /// Serve the tools and the responses recorded to a directory (`RECORD_CALLS_DIR`) instead
+        /// of jobworkerp: calls are answered by the last recorded call with the same arguments
+        #[arg(long, global = true, value_name = "DIR", conflicts_with = "mock")]
+        replay: Option<String>,
+    },
+    /// Write the tool catalog of jobworkerp as listed to the clients (for reviews and diffs)
+    ExportTools {
+        /// Format of the catalog: the tool list (json) or an OpenAPI document (openapi)
+        #[arg(long, default_value = "json", value_parser = ["json", "openapi"])]
+        format: String,
+
+        /// File to write (stdout if not set)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Retry connecting to jobworkerp for up to SECS seconds
+        #[arg(long, value_name = "SECS")]
+        wait_for_backend: Option<u64>,
+    },
+    /// Validate a workflow definition file (JSON or YAML) against the workflow creation schema
+    /// and the runners and workers of jobworkerp, without creating it
+    ValidateWorkflow {
+        /// Workflow definition, or the arguments of the workflow creation tool (`workflow_data`)
+        file: PathBuf,
+
+        /// Retry connecting to jobworkerp for up to SECS seconds
+        #[arg(long, value_name = "SECS")]
+        wait_for_backend: Option<u64>,
+    },
+    /// Create the workers of a definition file (JSON or YAML) in jobworkerp (existing workers of
+    /// the same names are kept as they are)
+    CreateWorker {
+        /// Worker definition, a list of them, or `workers:` with the list
+        file: PathBuf,
+
+        /// Retry connecting to jobworkerp for up to SECS seconds
+        #[arg(long, value_name = "SECS")]
+        wait_for_backend: Option<u64>,
+    },
+    /// List the tools of a running SSE server
+    List {
+        /// URL of the SSE endpoint
+        #[arg(long, default_value = client::DEFAULT_SSE_URL)]
+        url: String,
+    },
+    /// Call a tool of a running SSE server
+    Call {
+        /// URL of the SSE endpoint
+        #[arg(long, default_value = client::DEFAULT_SSE_URL)]
+        url: String,
+
+        /// Tool name to call
+        #[arg(short, long)]
+        name: String,
+
+        /// Tool arguments as a JSON string
+        #[arg(short, long, conflicts_with = "file")]
+        arguments: Option<String>,
+
+        /// Tool arguments from a JSON file
+        #[arg(short = 'f', long, conflicts_with = "arguments")]
+        file: Option<PathBuf>,
+
+        /// Format of the result: json, yaml, or the text contents (text)
+        #[arg(long, default_value = "json", value_parser = ["json", "yaml", "text"])]
+        format: String,
+
+        /// File to write the result (stdout if not set)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Print only the payload of the result without logs, and exit with an error if the
+        /// tool returns an error (for scripts)
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Run the tool calls of a manifest file (JSON or YAML) against a running SSE server in the
+    /// order of their dependencies, and print the summary
+    Run {
+        /// URL of the SSE endpoint
+        #[arg(long, default_value = client::DEFAULT_SSE_URL)]
+        url: String,
+
+        /// Manifest of the calls (`calls` with `id`, `tool`, `arguments` and `depends_on`)
+        manifest: PathBuf,
+    },
+    /// Load test a running SSE server with concurrent calls of a tool
+    Bench {
+        /// URL of the SSE endpoint
+        #[arg(long, default_value = client::DEFAULT_SSE_URL)]
+        url: String,
+
+        /// Tool name to call
+        #[arg(short, long)]
+        tool: String,
+
+        /// Tool arguments as a JSON string
+        #[arg(short, long, conflicts_with = "file")]
+        arguments: Option<String>,
+
+        /// Tool arguments from a JSON file
+        #[arg(short = 'f', long, conflicts_with = "arguments")]
+        file: Option<PathBuf>,
+
+        /// Number of calls in flight
+        #[arg(short, long, default_value_t = 10)]
+        concurrency: usize,
+
+        /// Total number of calls
+        #[arg(short, long, default_value_t = 100)]
+        requests: usize,
+    },
+    /// Call every tool of a running SSE server with minimal arguments generated from its schema
+    Smoke {
+        /// URL of the SSE endpoint
+        #[arg(long, default_value = client::DEFAULT_SSE_URL)]
+        url: String,
+
+        /// Call the tools (only validate the generated arguments without this)
+        #[arg(long)]
+        real: bool,
+
+        /// Test only the tools whose names contain this string
+        #[arg(long)]
+        filter: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum Transport {
+    /// Serve over stdin/stdout
+    Stdio,
+    /// Serve over HTTP with server-sent events
+    Sse,
+    /// Serve over stdin/stdout and HTTP with server-sent events at the same time
+    StdioSse,
+    /// Serve the plain HTTP API of the tools (`GET /tools`, `POST /tools/{name}/call`) with the
+    /// SSE endpoints (`REST_API` enabled)
+    Http,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+    let cli = Cli::parse();
+    let proxy_config = match cli.config.as_deref() {
+        Some(path) => ProxyConfig::load_file(path)?,
+        None => ProxyConfig::load()?,
+    };
+    // the payload is the only output of a quiet call
+    let quiet = matches!(cli.command, Command::Call { quiet: true, .. });
+    let token = cli
+        .token
+        .or_else(|| std::env::var("MCP_CLIENT_TOKEN").ok())
+        .filter(|t| !t.is_empty());
+    let _log_guard = if quiet {
+        None
+    } else {
+        proxy_server::logging::init_tracing(&proxy_config.logging)?
+    };
+
+    match cli.command {
+        Command::Serve {
+            transport,
+            wait_for_backend,
+            mock,
+            replay,
+        } => {
+            let tools = match (mock, replay) {
+                (Some(path), _) => Some(MockTools::load(&path)?),
+                (None, Some(dir)) => Some(MockTools::load_recording(&dir)?),
+                (None, None) => None,
+            };
+            if let Some(tools) = tools {
+                return match transport {
+                    Transport::Http => {
+                        anyhow::bail!("mock tools are not served by the plain HTTP API")
+                    }
+                    Transport::Stdio => proxy_server::boot_mock_stdio_server(tools).await,
+                    Transport::Sse => {
+                        proxy_server::boot_mock_sse_server(tools, SseConfig::from_env()).await
+                    }
+                    Transport::StdioSse => {
+                        let sse = proxy_server::boot_mock_sse_server(
+                            tools.clone(),
+                            SseConfig::from_env(),
+                        );
+                        tokio::select! {
+                            result = proxy_server::boot_mock_stdio_server(tools) => result,
+                            result = sse => result,
+                        }
+                    }
+                };
+            }
+            let config = JobworkerpRouterConfig {
+                wait_for_backend_sec: wait_for_backend,
+                ..JobworkerpRouterConfig::from_env(proxy_config)
+            };
+            match transport {
+                Transport::Stdio | Transport::StdioSse => {
+                    let result = if matches!(transport, Transport::StdioSse) {
+                        proxy_server::boot_stdio_sse_server(config, SseConfig::from_env()).await
+                    } else {
+                        proxy_server::boot_stdio_server(config).await
+                    };
+                    // distinct exit code for the wrapper processes (logs flushed before exiting)
+                    if let Some(exit_code) = StdioClosed::exit_code_of(&result) {
+                        drop(_log_guard);
+                        std::process::exit(exit_code);
+                    }
+                    result
+                }
+                Transport::Sse => {
+                    proxy_server::boot_sse_server(config, SseConfig::from_env()).await
+                }
+                Transport::Http => {
+                    let sse_config = SseConfig {
+                        rest_api: true,
+                        ..SseConfig::from_env()
+                    };
+                    proxy_server::boot_sse_server(config, sse_config).await
+                }
+            }
+        }
+        Command::ExportTools {
+            format,
+            output,
+            wait_for_backend,
+        } => {
+            let format = ExportFormat::parse(&format)
+                .ok_or_else(|| anyhow::anyhow!("unknown format: {}", format))?;
+            let config = JobworkerpRouterConfig {
+                wait_for_backend_sec: wait_for_backend,
+                ..JobworkerpRouterConfig::from_env(proxy_config)
+            };
+            let tools = JobworkerpRouter::new(config).await?.export_tools().await?;
+            let catalog = tool_export::export(&tools, format)?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, catalog + "\n")?;
+                    eprintln!("{} tools exported to {}", tools.tools.len(), path.display());
+                }
+                None => println!("{}", catalog),
+            }
+            Ok(())
+        }
+        Command::ValidateWorkflow {
+            file,
+            wait_for_backend,
+        } => {
+            let text = std::fs::read_to_string(&file)?;
+            let definition = workflow_validation::parse_definition(&text)?;
+            let config = JobworkerpRouterConfig {
+                wait_for_backend_sec: wait_for_backend,
+                ..JobworkerpRouterConfig::from_env(proxy_config)
+            };
+            let validation = JobworkerpRouter::new(config)
+                .await?
+                .validate_workflow(definition)
+                .await?;
+            if !validation.is_valid() {
+                println!("{}", validation.report());
+                anyhow::bail!("invalid workflow definition: {}", file.display());
+            }
+            println!("valid workflow definition: {}", file.display());
+            Ok(())
+        }
+        Command::CreateWorker {
+            file,
+            wait_for_backend,
+        } => {
+            let text = std::fs::read_to_string(&file)?;
+            let definitions = worker_definition::parse_definitions(&text)?;
+            let config = JobworkerpRouterConfig {
+                wait_for_backend_sec: wait_for_backend,
+                ..JobworkerpRouterConfig::from_env(proxy_config)
+            };
+            let router = JobworkerpRouter::new(config).await?;
+            for definition in definitions.iter() {
+                router
+                    .create_worker(definition)
+                    .await
+                    .with_context(|| format!("Failed to create worker: {}", &definition.name))?;
+                println!("worker ready: {}", &definition.name);
+            }
+            Ok(())
+        }
+        Command::List { url } => {
+            let client = McpClient::connect_with_token(&url, token.as_deref()).await?;
+            let tools = client.list_tools().await?;
+            println!("{}", serde_json::to_string_pretty(&tools)?);
+            client.close().await
+        }
+        Command::Call {
+            url,
+            name,
+            arguments,
+            file,
+            format,
+            output,
+            quiet,
+        } => {
+            let format = OutputFormat::parse(&format)
+                .ok_or_else(|| anyhow::anyhow!("unknown format: {}", format))?;
+            let arguments = client::parse_arguments(arguments.as_deref(), file.as_deref())?;
+            let client = McpClient::connect_with_token(&url, token.as_deref()).await?;
+            // Ctrl+C cancels the pending call instead of leaving it running on the server
+            let interrupt = async {
+                let _ = tokio::signal::ctrl_c().await;
+            };
+            let result = client
+                .call_tool_interruptible(&name, arguments, interrupt)
+                .await;
+            client.close().await?;
+            let result = result?;
+            let rendered = client::render_result(&result, format, quiet)?;
+            match output {
+                Some(path) => std::fs::write(&path, rendered + "\n")?,
+                None => println!("{}", rendered),
+            }
+            if quiet && result.is_error.unwrap_or_default() {
+                anyhow::bail!("tool {} returned an error", name);
+            }
+            Ok(())
+        }
+        Command::Run { url, manifest } => {
+            let text = std::fs::read_to_string(&manifest)?;
+            let manifest = RunManifest::parse(&text)?;
+            let client = McpClient::connect_with_token(&url, token.as_deref()).await?;
+            let report = client.run_manifest(&manifest).await;
+            client.close().await?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            if report.failed + report.skipped > 0 {
+                anyhow::bail!(
+                    "{} of {} calls failed ({} skipped)",
+                    report.failed,
+                    report.results.len(),
+                    report.skipped
+                );
+            }
+            Ok(())
+        }
+        Command::Bench {
+            url,
+            tool,
+            arguments,
+            file,
+            concurrency,
+            requests,
+        } => {
+            let arguments = client::parse_arguments(arguments.as_deref(), file.as_deref())?;
+            let client = McpClient::connect_with_token(&url, token.as_deref()).await?;
+            let report = client.bench(&tool, arguments, concurrency, requests).await;
+            client.close().await?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            Ok(())
+        }
+        Command::Smoke { url, real, filter } => {
+            let client = McpClient::connect_with_token(&url, token.as_deref()).await?;
+            let report = client.smoke(!real, filter.as_deref()).await;
+            client.close().await?;
+            let report = report?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            if report.failed > 0 {
+                anyhow::bail!("{} of {} tools failed", report.failed, report.results.len());
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,603 @@
+pub mod run_manifest;
+
+use crate::jobworkerp::meta_tool;
+use crate::tool_conversion::ToolConverter;
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use rmcp::{
+    model::{
+        CallToolRequest, CallToolRequestParam, CallToolResult, CancelledNotification,
+        CancelledNotificationParam, ClientCapabilities, ClientInfo, ClientNotification,
+        ClientRequest, Implementation, ListToolsResult, ServerResult,
+    },
+    service::{PeerRequestOptions, RequestHandle, RunningService, ServiceError},
+    transport::{sse_client::SseClientConfig, SseClientTransport},
+    RoleClient, ServiceExt,
+};
+use run_manifest::{RunManifest, RunReport};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::future::Future;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_SSE_URL: &str = "http://localhost:8000/sse";
+// wait for the server to end a cancelled call before exiting
+const CANCEL_ACK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// MCP client of the SSE server (for testing and scripting)
+pub struct McpClient {
+    service: RunningService<RoleClient, ClientInfo>,
+}
+
+impl McpClient {
+    pub async fn connect(url: &str) -> Result<Self> {
+        Self::connect_with_token(url, None).await
+    }
+
+    /// Connect sending the bearer token (`Authorization: Bearer <token>`) with every request
+    pub async fn connect_with_token(url: &str, token: Option<&str>) -> Result<Self> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(token) = token {
+            let mut value: reqwest::header::HeaderValue = format!("Bearer {}", token)
+                .parse()
+                .context("invalid bearer token")?;
+            value.set_sensitive(true);
+            headers.insert(reqwest::header::AUTHORIZATION, value);
+        }
+        let http_client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()?;
+        let transport = SseClientTransport::start_with_client(
+            http_client,
+            SseClientConfig {
+                sse_endpoint: url.into(),
+                ..Default::default()
+            },
+        )
+        .await
+        .with_context(|| format!("Failed to connect to SSE server: {}", url))?;
+        let client_info = ClientInfo {
+            protocol_version: Default::default(),
+            capabilities: ClientCapabilities::default(),
+            client_info: Implementation {
+                name: env!("CARGO_PKG_NAME").to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+        };
+        let service = client_info.serve(transport).await.inspect_err(|e| {
+            tracing::error!("client error: {:?}", e);
+        })?;
+        tracing::debug!("Connected to server: {:#?}", service.peer_info());
+        Ok(Self { service })
+    }
+
+    pub async fn list_tools(&self) -> Result<ListToolsResult> {
+        Ok(self.service.list_tools(Default::default()).await?)
+    }
+
+    pub async fn call_tool(
+        &self,
+        name: &str,
+        arguments: Option<Map<String, Value>>,
+    ) -> Result<CallToolResult> {
+        Ok(self
+            .service
+            .call_tool(CallToolRequestParam {
+                name: name.to_string().into(),
+                arguments,
+            })
+            .await?)
+    }
+
+    /// Call the tool until it returns or `interrupt` completes (e.g. Ctrl+C): the call is then
+    /// cancelled by the MCP cancellation notification, waiting briefly for the server to end it
+    pub async fn call_tool_interruptible(
+        &self,
+        name: &str,
+        arguments: Option<Map<String, Value>>,
+        interrupt: impl Future<Output = ()>,
+    ) -> Result<CallToolResult> {
+        let request = CallToolRequest {
+            method: Default::default(),
+            params: CallToolRequestParam {
+                name: name.to_string().into(),
+                arguments,
+            },
+        };
+        let RequestHandle {
+            mut rx, peer, id, ..
+        } = self
+            .service
+            .send_cancellable_request(
+                ClientRequest::CallToolRequest(request),
+                PeerRequestOptions::no_options(),
+            )
+            .await?;
+        let response = tokio::select! {
+            response = &mut rx => response,
+            _ = interrupt => {
+                tracing::info!("cancelling the call of {}", name);
+                peer.send_notification(ClientNotification::CancelledNotification(
+                    CancelledNotification {
+                        method: Default::default(),
+                        params: CancelledNotificationParam {
+                            request_id: id,
+                            reason: Some("interrupted by the client".to_string()),
+                        },
+                    },
+                ))
+                .await?;
+                // the server may still answer the call (e.g. with the cancelled result)
+                let _ = tokio::time::timeout(CANCEL_ACK_TIMEOUT, rx).await;
+                anyhow::bail!("call of {} cancelled", name);
+            }
+        };
+        match response.map_err(|_| anyhow::anyhow!("connection closed"))?? {
+            ServerResult::CallToolResult(result) => Ok(result),
+            _ => Err(ServiceError::UnexpectedResponse.into()),
+        }
+    }
+
+    /// Call the tool `requests` times with up to `concurrency` calls in flight
+    pub async fn bench(
+        &self,
+        name: &str,
+        arguments: Option<Map<String, Value>>,
+        concurrency: usize,
+        requests: usize,
+    ) -> BenchReport {
+        let start = Instant::now();
+        let samples = futures::stream::iter(0..requests)
+            .map(|_| {
+                let arguments = arguments.clone();
+                async move {
+                    let call_start = Instant::now();
+                    let outcome = match self.call_tool(name, arguments).await {
+                        Ok(result) if result.is_error == Some(true) => BenchOutcome::ToolError,
+                        Ok(_) => BenchOutcome::Success,
+                        Err(e) => {
+                            tracing::debug!("call error: {:?}", e);
+                            BenchOutcome::Error
+                        }
+                    };
+                    (call_start.elapsed(), outcome)
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+        BenchReport::new(&samples, start.elapsed(), concurrency)
+    }
+
+    /// Call (or with `dry_run`, only validate the arguments of) every listed tool with
+    /// arguments generated from its input schema
+    pub async fn smoke(&self, dry_run: bool, filter: Option<&str>) -> Result<SmokeReport> {
+        let tools = self.list_tools().await?.tools;
+        let mut results = Vec::new();
+        for tool in tools
+            .iter()
+            .filter(|t| filter.is_none_or(|f| t.name.contains(f)))
+        {
+            let name = tool.name.to_string();
+            let arguments = sample_arguments(tool.input_schema.as_ref());
+            let result = if let Some(reason) = smoke_skip_reason(&name, dry_run) {
+                SmokeResult::new(name, SmokeStatus::Skip, arguments).with_message(reason)
+            } else if tool.description.starts_with("[UNAVAILABLE]") {
+                SmokeResult::new(name, SmokeStatus::Fail, arguments)
+                    .with_message(tool.description.as_ref())
+            } else if let Err(message) = validate_arguments(tool.input_schema.as_ref(), &arguments)
+            {
+                SmokeResult::new(name, SmokeStatus::Fail, arguments)
+                    .with_message(format!("generated arguments are invalid: {}", message))
+            } else if dry_run {
+                SmokeResult::new(name, SmokeStatus::Pass, arguments)
+            } else {
+                let start = Instant::now();
+                let call = self.call_tool(&name, arguments.as_object().cloned()).await;
+                let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+                let result = match call {
+                    Ok(r) if r.is_error == Some(true) => {
+                        let message = serde_json::to_string(&r.content).unwrap_or_default();
+                        SmokeResult::new(name, SmokeStatus::Fail, arguments).with_message(message)
+                    }
+                    Ok(_) => SmokeResult::new(name, SmokeStatus::Pass, arguments),
+                    Err(e) => SmokeResult::new(name, SmokeStatus::Fail, arguments)
+                        .with_message(format!("{:#}", e)),
+                };
+                SmokeResult {
+                    latency_ms: Some(latency_ms),
+                    ..result
+                }
+            };
+            results.push(result);
+        }
+        Ok(SmokeReport::new(dry_run, results))
+    }
+
+    /// Run the calls of a manifest (see [`run_manifest::run`])
+    pub async fn run_manifest(&self, manifest: &RunManifest) -> RunReport {
+        run_manifest::run(manifest, |c| {
+            self.call_tool(&c.tool, Some(c.arguments.clone()))
+        })
+        .await
+    }
+
+    pub async fn close(self) -> Result<()> {
+        self.service.cancel().await?;
+        Ok(())
+    }
+}
+
+/// Formats of a call result printed by the client (`call --format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+    // the text contents of the result, one per line
+    Text,
+}
+
+impl OutputFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "yaml" => Some(Self::Yaml),
+            "text" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Payload of a call result: the contents as values (JSON texts parsed, a single content
+/// unwrapped)
+pub fn result_payload(result: &CallToolResult) -> Value {
+    let mut values = result
+        .content
+        .iter()
+        .map(|content| match content.as_text() {
+            Some(t) => {
+                serde_json::from_str(&t.text).unwrap_or_else(|_| Value::String(t.text.clone()))
+            }
+            None => serde_json::to_value(content).unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    if values.len() == 1 {
+        values.remove(0)
+    } else {
+        Value::Array(values)
+    }
+}
+
+/// Render a call result in the format: the whole result, or only its payload with
+/// `payload_only` (text is always the payload)
+pub fn render_result(
+    result: &CallToolResult,
+    format: OutputFormat,
+    payload_only: bool,
+) -> Result<String> {
+    let value = if payload_only {
+        result_payload(result)
+    } else {
+        serde_json::to_value(result)?
+    };
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(&value)?),
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(&value)?.trim_end().to_string()),
+        OutputFormat::Text => Ok(result
+            .content
+            .iter()
+            .map(|content| match content.as_text() {
+                Some(t) => t.text.clone(),
+                None => serde_json::to_string(content).unwrap_or_default(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")),
+    }
+}
+
+/// Result of a benchmark call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchOutcome {
+    Success,
+    // the tool returned `is_error: true`
+    ToolError,
+    // protocol or transport error
+    Error,
+}
+
+/// Latency (milliseconds) and error summary of a benchmark
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub requests: usize,
+    pub concurrency: usize,
+    pub succeeded: usize,
+    pub tool_errors: usize,
+    pub errors: usize,
+    // (tool errors + errors) / requests
+    pub error_rate: f64,
+    pub elapsed_ms: f64,
+    pub requests_per_sec: f64,
+    pub latency_ms: LatencySummary,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencySummary {
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl BenchReport {
+    pub fn new(
+        samples: &[(Duration, BenchOutcome)],
+        elapsed: Duration,
+        concurrency: usize,
+    ) -> Self {
+        let count = |o: BenchOutcome| samples.iter().filter(|(_, s)| *s == o).count();
+        let (tool_errors, errors) = (count(BenchOutcome::ToolError), count(BenchOutcome::Error));
+        let mut latencies = samples
+            .iter()
+            .map(|(d, _)| d.as_secs_f64() * 1000.0)
+            .collect::<Vec<_>>();
+        latencies.sort_by(|a, b| a.total_cmp(b));
+        let requests = samples.len();
+        let elapsed_sec = elapsed.as_secs_f64();
+        Self {
+            requests,
+            concurrency,
+            succeeded: count(BenchOutcome::Success),
+            tool_errors,
+            errors,
+            error_rate: if requests > 0 {
+                (tool_errors + errors) as f64 / requests as f64
+            } else {
+                0.0
+            },
+            elapsed_ms: elapsed_sec * 1000.0,
+            requests_per_sec: if elapsed_sec > 0.0 {
+                requests as f64 / elapsed_sec
+            } else {
+                0.0
+            },
+            latency_ms: LatencySummary::new(&latencies),
+        }
+    }
+}
+
+impl LatencySummary {
+    // `sorted`: latencies in ascending order
+    fn new(sorted: &[f64]) -> Self {
+        let (Some(min), Some(max)) = (sorted.first(), sorted.last()) else {
+            return Self::default();
+        };
+        Self {
+            min: *min,
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50: Self::percentile(sorted, 50.0),
+            p90: Self::percentile(sorted, 90.0),
+            p95: Self::percentile(sorted, 95.0),
+            p99: Self::percentile(sorted, 99.0),
+            max: *max,
+        }
+    }
+
+    // nearest-rank percentile
+    fn percentile(sorted: &[f64], p: f64) -> f64 {
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
+}
+
+// meta tools of the proxy which cannot be called with generated arguments
+const SMOKE_SKIPPED_TOOLS: [&str; 6] = [
+    meta_tool::BATCH_CALL,
+    meta_tool::MAP_CALL,
+    meta_tool::SCHEDULE_JOB,
+    meta_tool::CANCEL_SCHEDULE,
+    meta_tool::RESUME_CALL,
+    meta_tool::FETCH_RESULT_CHUNK,
+];
+// tools creating workflows or workers in the backend (only validated, never called by smoke)
+const SMOKE_CREATION_TOOLS: [&str; 2] = [meta_tool::AUTHOR_WORKFLOW, "REUSABLE_WORKFLOW"];
+
+/// Why the smoke test does not call the tool (None if it is called)
+pub fn smoke_skip_reason(name: &str, dry_run: bool) -> Option<&'static str> {
+    // kind prefixed names (`runner.REUSABLE_WORKFLOW`) are matched by the backend name
+    let backend_name = ToolConverter::strip_kind_prefix(name).map_or(name, |(_, n)| n);
+    if SMOKE_SKIPPED_TOOLS.contains(&name) {
+        Some("requires arguments referring to other calls")
+    } else if !dry_run
+        && (SMOKE_CREATION_TOOLS.contains(&name) || SMOKE_CREATION_TOOLS.contains(&backend_name))
+    {
+        Some("creates workflows or workers in the backend")
+    } else {
+        None
+    }
+}
+// nesting limit of generated values (deeper values are null)
+const MAX_SAMPLE_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmokeStatus {
+    Pass,
+    Fail,
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SmokeResult {
+    pub tool: String,
+    pub status: SmokeStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub arguments: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+}
+
+impl SmokeResult {
+    fn new(tool: String, status: SmokeStatus, arguments: Value) -> Self {
+        Self {
+            tool,
+            status,
+            message: None,
+            arguments,
+            latency_ms: None,
+        }
+    }
+
+    fn with_message(self, message: impl Into<String>) -> Self {
+        Self {
+            message: Some(message.into()),
+            ..self
+        }
+    }
+}
+
+/// Pass/fail report of the smoke test
+#[derive(Debug, Clone, Serialize)]
+pub struct SmokeReport {
+    pub dry_run: bool,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub results: Vec<SmokeResult>,
+}
+
+impl SmokeReport {
+    pub fn new(dry_run: bool, results: Vec<SmokeResult>) -> Self {
+        let count = |s: SmokeStatus| results.iter().filter(|r| r.status == s).count();
+        Self {
+            dry_run,
+            passed: count(SmokeStatus::Pass),
+            failed: count(SmokeStatus::Fail),
+            skipped: count(SmokeStatus::Skip),
+            results,
+        }
+    }
+}
+
+/// Minimal arguments valid for the input schema (defaults, consts, examples and enums are
+/// preferred, and only the required properties are set)
+pub fn sample_arguments(schema: &Map<String, Value>) -> Value {
+    let root = Value::Object(schema.clone());
+    match sample_value(&root, &root, 0) {
+        Value::Object(obj) => Value::Object(obj),
+        _ => Value::Object(Map::new()),
+    }
+}
+
+fn sample_value(schema: &Value, root: &Value, depth: usize) -> Value {
+    let Some(obj) = schema.as_object().filter(|_| depth < MAX_SAMPLE_DEPTH) else {
+        return Value::Null;
+    };
+    if let Some(target) = obj
+        .get("$ref")
+        .and_then(|r| r.as_str())
+        .and_then(|r| r.strip_prefix('#'))
+        .and_then(|p| root.pointer(p))
+    {
+        return sample_value(target, root, depth + 1);
+    }
+    for key in ["default", "const"] {
+        if let Some(value) = obj.get(key) {
+            return value.clone();
+        }
+    }
+    for key in ["examples", "enum"] {
+        if let Some(value) = obj
+            .get(key)
+            .and_then(|v| v.as_array())
+            .and_then(|v| v.first())
+        {
+            return value.clone();
+        }
+    }
+    for key in ["oneOf", "anyOf", "allOf"] {
+        if let Some(first) = obj
+            .get(key)
+            .and_then(|v| v.as_array())
+            .and_then(|v| v.first())
+        {
+            return sample_value(first, root, depth + 1);
+        }
+    }
+    let schema_type = match obj.get("type") {
+        Some(Value::String(t)) => Some(t.as_str()),
+        // prefer a non-null type
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(|t| t.as_str())
+            .find(|t| *t != "null")
+            .or(Some("null")),
+        _ => obj.contains_key("properties").then_some("object"),
+    };
+    let min = |key: &str| obj.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+    match schema_type {
+        Some("object") => {
+            let properties = obj.get("properties").and_then(|p| p.as_object());
+            let required = obj
+                .get("required")
+                .and_then(|r| r.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|r| r.as_str());
+            let mut sample = Map::new();
+            for name in required {
+                let property = properties.and_then(|p| p.get(name));
+                let value = property.map_or(Value::Null, |p| sample_value(p, root, depth + 1));
+                sample.insert(name.to_string(), value);
+            }
+            Value::Object(sample)
+        }
+        Some("array") => {
+            let item = obj
+                .get("items")
+                .map_or(Value::Null, |i| sample_value(i, root, depth + 1));
+            Value::Array(vec![item; min("minItems")])
+        }
+        Some("string") => Value::String("x".repeat(min("minLength"))),
+        Some("integer") => Value::from(obj.get("minimum").and_then(|m| m.as_i64()).unwrap_or(0)),
+        Some("number") => Value::from(obj.get("minimum").and_then(|m| m.as_f64()).unwrap_or(0.0)),
+        Some("boolean") => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+/// Validate the arguments against the input schema (Err: the first error)
+pub fn validate_arguments(
+    schema: &Map<String, Value>,
+    arguments: &Value,
+) -> std::result::Result<(), String> {
+    let validator = jsonschema::validator_for(&Value::Object(schema.clone()))
+        .map_err(|e| format!("invalid schema: {}", e))?;
+    validator.validate(arguments).map_err(|e| e.to_string())
+}
+
+/// Tool arguments from a JSON string or a JSON file
+pub fn parse_arguments(
+    arguments: Option<&str>,
+    file: Option<&Path>,
+) -> Result<Option<Map<String, Value>>> {
+    let value: Value = match (arguments, file) {
+        (Some(json_str), _) => serde_json::from_str(json_str)
+            .map_err(|e| anyhow::anyhow!("Failed to parse JSON arguments: {}", e))?,
+        (None, Some(file_path)) => {
+            let file_content = std::fs::read_to_string(file_path)
+                .map_err(|e| anyhow::anyhow!("Failed to read arguments file: {}", e))?;
+            serde_json::from_str(&file_content)
+                .map_err(|e| anyhow::anyhow!("Failed to parse JSON from file: {}", e))?
+        }
+        (None, None) => return Ok(None),
+    };
+    match value {
+        Value::Object(map) => Ok(Some(map)),
+        _ => Err(anyhow::anyhow!("Tool arguments must be a JSON object")),
+    }
+}
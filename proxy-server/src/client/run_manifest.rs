@@ -0,0 +1,219 @@
+use super::result_payload;
+use anyhow::{Context, Result};
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use rmcp::model::CallToolResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Tool calls run by the client (`run <MANIFEST>`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunManifest {
+    // number of calls in flight
+    #[serde(default = "RunManifest::default_parallelism")]
+    pub parallelism: usize,
+    pub calls: Vec<ManifestCall>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestCall {
+    pub id: String,
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: Map<String, Value>,
+    // ids of the calls which must succeed before this call
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+impl RunManifest {
+    fn default_parallelism() -> usize {
+        4
+    }
+
+    /// Parse a manifest file (JSON or YAML) and check the ids and the dependencies of the calls
+    pub fn parse(text: &str) -> Result<Self> {
+        let manifest: Self = match serde_json::from_str(text) {
+            Ok(manifest) => manifest,
+            Err(_) => serde_yaml::from_str(text).context("manifest is neither JSON nor YAML")?,
+        };
+        manifest.check()?;
+        Ok(manifest)
+    }
+
+    fn check(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+        for call in self.calls.iter() {
+            if !ids.insert(call.id.as_str()) {
+                anyhow::bail!("duplicate call id: {}", call.id);
+            }
+        }
+        for call in self.calls.iter() {
+            if let Some(dep) = call.depends_on.iter().find(|d| !ids.contains(d.as_str())) {
+                anyhow::bail!("{}: unknown dependency: {}", call.id, dep);
+            }
+        }
+        // resolve the calls in dependency order: the remaining calls are in a cycle
+        let mut resolved = HashSet::new();
+        while resolved.len() < self.calls.len() {
+            let ready = self
+                .calls
+                .iter()
+                .filter(|c| !resolved.contains(c.id.as_str()))
+                .filter(|c| c.depends_on.iter().all(|d| resolved.contains(d.as_str())))
+                .map(|c| c.id.as_str())
+                .collect::<Vec<_>>();
+            if ready.is_empty() {
+                let cycle = self
+                    .calls
+                    .iter()
+                    .filter(|c| !resolved.contains(c.id.as_str()))
+                    .map(|c| c.id.as_str())
+                    .collect::<Vec<_>>();
+                anyhow::bail!("circular dependencies: {}", cycle.join(", "));
+            }
+            resolved.extend(ready);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Succeeded,
+    Failed,
+    // a dependency did not succeed
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunResult {
+    pub id: String,
+    pub tool: String,
+    pub status: RunStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    // payload of the call result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+}
+
+impl RunResult {
+    fn new(call: &ManifestCall, result: Result<CallToolResult>, latency: Duration) -> Self {
+        let (status, message, output) = match result {
+            Ok(r) if r.is_error == Some(true) => {
+                (RunStatus::Failed, None, Some(result_payload(&r)))
+            }
+            Ok(r) => (RunStatus::Succeeded, None, Some(result_payload(&r))),
+            Err(e) => (RunStatus::Failed, Some(format!("{:#}", e)), None),
+        };
+        Self {
+            id: call.id.clone(),
+            tool: call.tool.clone(),
+            status,
+            message,
+            output,
+            latency_ms: Some(latency.as_secs_f64() * 1000.0),
+        }
+    }
+
+    fn skipped(call: &ManifestCall, dependency: &str) -> Self {
+        Self {
+            id: call.id.clone(),
+            tool: call.tool.clone(),
+            status: RunStatus::Skipped,
+            message: Some(format!("dependency {} did not succeed", dependency)),
+            output: None,
+            latency_ms: None,
+        }
+    }
+}
+
+/// Summary of a manifest run (the results in the order of the manifest)
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub elapsed_ms: f64,
+    pub results: Vec<RunResult>,
+}
+
+impl RunReport {
+    pub fn new(results: Vec<RunResult>, elapsed: Duration) -> Self {
+        let count = |s: RunStatus| results.iter().filter(|r| r.status == s).count();
+        Self {
+            succeeded: count(RunStatus::Succeeded),
+            failed: count(RunStatus::Failed),
+            skipped: count(RunStatus::Skipped),
+            elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+            results,
+        }
+    }
+}
+
+/// Run the calls of the manifest with `call`: a call starts when its dependencies succeeded (up
+/// to `parallelism` calls in flight), and is skipped when one of them failed or was skipped
+pub async fn run<'a, F, Fut>(manifest: &'a RunManifest, call: F) -> RunReport
+where
+    F: Fn(&'a ManifestCall) -> Fut,
+    Fut: Future<Output = Result<CallToolResult>>,
+{
+    let start = Instant::now();
+    let mut done: HashMap<&str, RunResult> = HashMap::new();
+    let mut pending = manifest.calls.iter().collect::<Vec<_>>();
+    let mut running = FuturesUnordered::new();
+    loop {
+        // skipping a call may skip its dependents in turn
+        let mut skipped = true;
+        while skipped {
+            skipped = false;
+            pending.retain(|c| {
+                let failed = c.depends_on.iter().find(|d| {
+                    done.get(d.as_str())
+                        .is_some_and(|r| r.status != RunStatus::Succeeded)
+                });
+                match failed {
+                    Some(dep) => {
+                        done.insert(c.id.as_str(), RunResult::skipped(c, dep));
+                        skipped = true;
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
+        while running.len() < manifest.parallelism.max(1) {
+            let Some(index) = pending
+                .iter()
+                .position(|c| c.depends_on.iter().all(|d| done.contains_key(d.as_str())))
+            else {
+                break;
+            };
+            let c = pending.remove(index);
+            let future = call(c);
+            running.push(async move {
+                let call_start = Instant::now();
+                let result = future.await;
+                (c, result, call_start.elapsed())
+            });
+        }
+        let Some((c, result, latency)) = running.next().await else {
+            break;
+        };
+        tracing::debug!("call {} finished", &c.id);
+        done.insert(c.id.as_str(), RunResult::new(c, result, latency));
+    }
+    let results = manifest
+        .calls
+        .iter()
+        .filter_map(|c| done.remove(c.id.as_str()))
+        .collect();
+    RunReport::new(results, start.elapsed())
+}
@@ -0,0 +1,18 @@
+use std::str::FromStr;
+
+/// Environment variable parsed as `T` (None if not set or invalid)
+pub fn parse<T: FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|s| s.parse::<T>().ok())
+}
+
+/// Comma separated values of an environment variable (empty if not set)
+pub fn list(name: &str) -> Vec<String> {
+    std::env::var(name)
+        .map(|s| {
+            s.split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
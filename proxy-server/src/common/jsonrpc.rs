@@ -29,20 +29,39 @@ impl Decoder for JsonRpcFrameCodec {
 }
 
 /// Combines multiple JSON schemas into one large schema
+///
+/// `$defs` (and `definitions`) of the schemas are merged into the `$defs` of the combined schema
+/// (renamed as `{schema name}_{def name}` on collision), and internal `$ref`s are rewritten to
+/// point into the combined schema.
 pub struct SchemaCombiner {
     schemas: HashMap<String, Value>,
     descriptions: HashMap<String, String>,
+    // schema of the job result (not a part of the combined input schema)
+    result_schema: Option<Value>,
+    inline_refs: bool,
 }
 
 impl SchemaCombiner {
+    // nesting limit of inlined refs (deeper refs are kept)
+    const MAX_INLINE_DEPTH: usize = 32;
+
     /// Create a new SchemaCombiner
     pub fn new() -> Self {
         Self {
             schemas: HashMap::new(),
             descriptions: HashMap::new(),
+            result_schema: None,
+            inline_refs: false,
         }
     }
 
+    /// Replace internal `$ref`s with the referenced schemas in the combined schema
+    /// (recursive refs are kept with the `$defs`)
+    pub fn with_inline_refs(mut self, inline_refs: bool) -> Self {
+        self.inline_refs = inline_refs;
+        self
+    }
+
     /// Add a JSON schema from a file with description
     #[allow(dead_code)]
     pub fn add_schema_from_file<P: AsRef<Path>>(
@@ -91,6 +110,85 @@ impl SchemaCombiner {
         Ok(())
     }
 
+    /// Set the schema of the result from a string
+    pub fn add_result_schema_from_string(&mut self, schema_str: &str) -> Result<()> {
+        let schema: Value = serde_json::from_str(schema_str)
+            .with_context(|| "Failed to parse result schema JSON from string")?;
+        self.result_schema = Some(self.clean_schema(schema));
+        Ok(())
+    }
+
+    /// Schema of the result (None if not set or not an object)
+    pub fn generate_result_schema(&self) -> Option<Map<String, Value>> {
+        let mut schema = self.result_schema.as_ref()?.as_object()?.clone();
+        if self.inline_refs {
+            Self::inline_all_refs(&mut schema);
+        }
+        Some(schema)
+    }
+
+    /// Human-readable summary of the result schema (the top level properties)
+    pub fn describe_result(&self) -> Option<String> {
+        let schema = self.result_schema.as_ref()?;
+        let mut lines = vec![format!("returns: {}", Self::describe_type(schema))];
+        if let Some(desc) = schema.get("description").and_then(|d| d.as_str()) {
+            lines.push(desc.to_string());
+        }
+        let required = schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|r| r.iter().filter_map(|n| n.as_str()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+            for (name, property) in properties {
+                let mut line = format!("- {} ({}", name, Self::describe_type(property));
+                if !required.contains(&name.as_str()) {
+                    line.push_str(", optional");
+                }
+                line.push(')');
+                if let Some(desc) = property.get("description").and_then(|d| d.as_str()) {
+                    line.push_str(": ");
+                    line.push_str(desc);
+                }
+                lines.push(line);
+            }
+        }
+        Some(lines.join("\n"))
+    }
+
+    fn describe_type(schema: &Value) -> String {
+        if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+            return format!(
+                "one of {}",
+                values
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+            return reference
+                .rsplit('/')
+                .next()
+                .unwrap_or(reference)
+                .to_string();
+        }
+        match schema.get("type") {
+            Some(Value::String(t)) if t == "array" => match schema.get("items") {
+                Some(items) => format!("array of {}", Self::describe_type(items)),
+                None => "array".to_string(),
+            },
+            Some(Value::String(t)) => t.clone(),
+            Some(Value::Array(types)) => types
+                .iter()
+                .filter_map(|t| t.as_str())
+                .collect::<Vec<_>>()
+                .join(" | "),
+            _ => "any".to_string(),
+        }
+    }
+
     /// Add description for an existing schema
     #[allow(dead_code)]
     pub fn add_description(&mut self, name: &str, description: String) -> Result<()> {
@@ -159,10 +257,15 @@ impl SchemaCombiner {
 
         let mut properties_map = Map::new();
         let mut required_vec = Vec::new();
+        let mut defs = Map::new();
 
-        for (name, schema) in &self.schemas {
+        // sorted for stable output (and def names on collision)
+        let mut names = self.schemas.keys().collect::<Vec<_>>();
+        names.sort();
+        for name in names {
             // XXX clone
-            let mut schema_obj = schema.clone();
+            let mut schema_obj = self.schemas[name].clone();
+            Self::hoist_defs(name, &mut schema_obj, &mut defs);
 
             if let Some(desc) = self.descriptions.get(name) {
                 if let Value::Object(ref mut obj) = schema_obj {
@@ -177,6 +280,12 @@ impl SchemaCombiner {
         if let Value::Object(ref mut obj) = combined {
             obj.insert("properties".to_string(), Value::Object(properties_map));
             obj.insert("required".to_string(), Value::Array(required_vec));
+            if !defs.is_empty() {
+                obj.insert("$defs".to_string(), Value::Object(defs));
+            }
+            if self.inline_refs {
+                Self::inline_all_refs(obj);
+            }
         }
 
         combined
@@ -185,6 +294,159 @@ impl SchemaCombiner {
             .ok_or_else(|| anyhow::anyhow!("Failed to get combined schema object"))
     }
 
+    // escape a json pointer token
+    fn pointer_token(token: &str) -> String {
+        token.replace('~', "~0").replace('/', "~1")
+    }
+
+    /// Move the `$defs` (and `definitions`) of the schema added as `name` into `defs`,
+    /// rewriting its internal refs to point into the combined schema
+    fn hoist_defs(name: &str, schema: &mut Value, defs: &mut Map<String, Value>) {
+        let Value::Object(obj) = schema else {
+            return;
+        };
+        let mut renames = Vec::new();
+        let mut hoisted = Vec::new();
+        for key in ["$defs", "definitions"] {
+            let Some(Value::Object(local)) = obj.remove(key) else {
+                continue;
+            };
+            for (def_name, def) in local {
+                let mut new_name = def_name.clone();
+                let mut suffix = 1;
+                while defs.contains_key(&new_name) {
+                    new_name = if suffix == 1 {
+                        format!("{}_{}", name, def_name)
+                    } else {
+                        format!("{}_{}_{}", name, def_name, suffix)
+                    };
+                    suffix += 1;
+                }
+                // reserve the name
+                defs.insert(new_name.clone(), Value::Null);
+                renames.push((
+                    format!("#/{}/{}", key, Self::pointer_token(&def_name)),
+                    format!("#/$defs/{}", Self::pointer_token(&new_name)),
+                ));
+                hoisted.push((new_name, def));
+            }
+        }
+        let root = format!("#/properties/{}", Self::pointer_token(name));
+        Self::rewrite_refs(schema, &renames, &root);
+        for (new_name, mut def) in hoisted {
+            Self::rewrite_refs(&mut def, &renames, &root);
+            defs.insert(new_name, def);
+        }
+    }
+
+    fn rewrite_refs(value: &mut Value, renames: &[(String, String)], root: &str) {
+        match value {
+            Value::Object(obj) => {
+                for (key, child) in obj.iter_mut() {
+                    match child {
+                        Value::String(reference) if key == "$ref" => {
+                            if let Some(rewritten) = Self::rewrite_ref(reference, renames, root) {
+                                *reference = rewritten;
+                            }
+                        }
+                        _ => Self::rewrite_refs(child, renames, root),
+                    }
+                }
+            }
+            Value::Array(arr) => {
+                for item in arr.iter_mut() {
+                    Self::rewrite_refs(item, renames, root);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // None for external refs (kept as is)
+    fn rewrite_ref(reference: &str, renames: &[(String, String)], root: &str) -> Option<String> {
+        let pointer = reference.strip_prefix('#')?;
+        for (from, to) in renames {
+            if let Some(rest) = reference.strip_prefix(from.as_str()) {
+                if rest.is_empty() || rest.starts_with('/') {
+                    return Some(format!("{}{}", to, rest));
+                }
+            }
+        }
+        Some(format!("{}{}", root, pointer))
+    }
+
+    /// Inline the internal refs of the combined schema, removing `$defs` if no ref is left
+    fn inline_all_refs(combined: &mut Map<String, Value>) {
+        let root = Value::Object(combined.clone());
+        let mut kept = false;
+        for (key, value) in combined.iter_mut() {
+            if key != "$defs" {
+                let location = format!("#/{}", Self::pointer_token(key));
+                Self::inline_refs(value, &root, &location, &mut Vec::new(), &mut kept);
+            }
+        }
+        if !kept {
+            combined.remove("$defs");
+        }
+    }
+
+    // `location`: pointer of the value in the combined schema (of its definition if inlined)
+    fn inline_refs(
+        value: &mut Value,
+        root: &Value,
+        location: &str,
+        stack: &mut Vec<String>,
+        kept: &mut bool,
+    ) {
+        match value {
+            Value::Object(obj) => {
+                if let Some(reference) = obj.get("$ref").and_then(|r| r.as_str()) {
+                    if let Some(pointer) = reference.strip_prefix('#') {
+                        let target = root.pointer(pointer);
+                        // recursive: refers to itself, an enclosing schema or a ref being inlined
+                        let recursive = location == reference
+                            || location.starts_with(&format!("{}/", reference))
+                            || stack.iter().any(|r| r == reference);
+                        if target.is_none() || recursive || stack.len() >= Self::MAX_INLINE_DEPTH {
+                            *kept = true;
+                            return;
+                        }
+                        let reference = reference.to_string();
+                        let mut inlined = target.cloned().unwrap_or_default();
+                        stack.push(reference.clone());
+                        Self::inline_refs(&mut inlined, root, &reference, stack, kept);
+                        stack.pop();
+                        // sibling keywords (e.g. description) take precedence
+                        if let Value::Object(inlined_obj) = &mut inlined {
+                            for (key, sibling) in obj.iter() {
+                                if key != "$ref" {
+                                    let mut sibling = sibling.clone();
+                                    let location =
+                                        format!("{}/{}", location, Self::pointer_token(key));
+                                    Self::inline_refs(&mut sibling, root, &location, stack, kept);
+                                    inlined_obj.insert(key.clone(), sibling);
+                                }
+                            }
+                        }
+                        *value = inlined;
+                        return;
+                    }
+                }
+                for (key, child) in obj.iter_mut() {
+                    let location = format!("{}/{}", location, Self::pointer_token(key));
+                    Self::inline_refs(child, root, &location, stack, kept);
+                }
+            }
+            Value::Array(arr) => {
+                for (index, item) in arr.iter_mut().enumerate() {
+                    let location = format!("{}/{}", location, index);
+                    Self::inline_refs(item, root, &location, stack, kept);
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// Save the combined schema to a file
     #[allow(dead_code)]
     pub fn save_combined_schema<P: AsRef<Path>>(&self, path: P) -> Result<()> {
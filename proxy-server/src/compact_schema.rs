@@ -0,0 +1,217 @@
+use rmcp::model::Tool;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+// keywords of annotations not needed to call the tool
+const ANNOTATION_KEYS: [&str; 2] = ["examples", "$comment"];
+// keywords of a single subschema, and of arrays and maps of subschemas
+const SUBSCHEMA_KEYS: [&str; 4] = ["items", "additionalProperties", "not", "contains"];
+const SUBSCHEMA_LIST_KEYS: [&str; 4] = ["oneOf", "anyOf", "allOf", "prefixItems"];
+const SUBSCHEMA_MAP_KEYS: [&str; 4] = ["properties", "patternProperties", "$defs", "definitions"];
+
+/// Compact conversion of the tool schemas for small context models (`compact_schema` of the
+/// config file)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CompactSchemaConfig {
+    pub enabled: bool,
+    // tool descriptions are cut at this length (characters)
+    pub max_description_chars: usize,
+    // field descriptions are cut at this length (0: removed)
+    pub max_field_description_chars: usize,
+    // budget of a tool (characters of the json of the tool; None: no limit)
+    pub max_tool_chars: Option<usize>,
+    // budget of all the converted tools (None: no limit)
+    pub max_total_chars: Option<usize>,
+}
+
+impl Default for CompactSchemaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_description_chars: 200,
+            max_field_description_chars: 80,
+            max_tool_chars: None,
+            max_total_chars: None,
+        }
+    }
+}
+
+/// Steps of shrinking a tool, applied in order while over the budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompactLevel {
+    // cut the descriptions and remove the annotations
+    Trim,
+    // optional fields are listed by the type only
+    CollapseOptional,
+    // first sentence of the tool description only, no field descriptions
+    Minimal,
+}
+
+const LEVELS: [CompactLevel; 3] = [
+    CompactLevel::Trim,
+    CompactLevel::CollapseOptional,
+    CompactLevel::Minimal,
+];
+
+/// Characters of the tool as listed (json)
+pub fn tool_chars(tool: &Tool) -> usize {
+    serde_json::to_string(tool)
+        .map(|s| s.chars().count())
+        .unwrap_or(0)
+}
+
+/// Shrink the tools within the budgets, and return the characters of the compacted tools
+///
+/// Tools still over the budgets after all the steps are listed as shrunk as possible.
+pub fn compact_tools(tools: &mut [Tool], config: &CompactSchemaConfig) -> usize {
+    for tool in tools.iter_mut() {
+        for level in LEVELS {
+            compact_tool(tool, level, config);
+            if config
+                .max_tool_chars
+                .is_none_or(|max| tool_chars(tool) <= max)
+            {
+                break;
+            }
+        }
+    }
+    let mut total = tools.iter().map(tool_chars).sum::<usize>();
+    let Some(max_total) = config.max_total_chars else {
+        return total;
+    };
+    // shrink the largest tools first
+    for level in &LEVELS[1..] {
+        let mut sizes = tools
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (tool_chars(t), i))
+            .collect::<Vec<_>>();
+        sizes.sort_by(|a, b| b.cmp(a));
+        for (size, i) in sizes {
+            if total <= max_total {
+                return total;
+            }
+            compact_tool(&mut tools[i], *level, config);
+            total = total - size + tool_chars(&tools[i]);
+        }
+    }
+    if total > max_total {
+        tracing::warn!(
+            "compacted tools ({} characters) exceed the budget of {} characters",
+            total,
+            max_total
+        );
+    }
+    total
+}
+
+fn compact_tool(tool: &mut Tool, level: CompactLevel, config: &CompactSchemaConfig) {
+    let mut schema = tool.input_schema.as_ref().clone();
+    match level {
+        CompactLevel::Trim => {
+            tool.description = truncate(&tool.description, config.max_description_chars).into();
+            trim_schema(&mut schema, config.max_field_description_chars);
+        }
+        CompactLevel::CollapseOptional => collapse_optional_fields(&mut schema),
+        CompactLevel::Minimal => {
+            let first = first_sentence(&tool.description);
+            tool.description = truncate(&first, config.max_description_chars).into();
+            trim_schema(&mut schema, 0);
+        }
+    }
+    tool.input_schema = Arc::new(schema);
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut cut = text
+        .chars()
+        .take(max_chars.saturating_sub(1))
+        .collect::<String>();
+    cut.push('…');
+    cut
+}
+
+fn first_sentence(text: &str) -> String {
+    let line = text.trim().lines().next().unwrap_or_default();
+    match line.find(". ") {
+        Some(end) => line[..=end].to_string(),
+        None => line.to_string(),
+    }
+}
+
+fn for_each_subschema(
+    schema: &mut Map<String, Value>,
+    f: &mut impl FnMut(&mut Map<String, Value>),
+) {
+    for key in SUBSCHEMA_KEYS {
+        match schema.get_mut(key) {
+            Some(Value::Object(sub)) => f(sub),
+            // tuple items
+            Some(Value::Array(list)) => list
+                .iter_mut()
+                .filter_map(|v| v.as_object_mut())
+                .for_each(&mut *f),
+            _ => {}
+        }
+    }
+    for key in SUBSCHEMA_LIST_KEYS {
+        if let Some(Value::Array(list)) = schema.get_mut(key) {
+            list.iter_mut()
+                .filter_map(|v| v.as_object_mut())
+                .for_each(&mut *f);
+        }
+    }
+    for key in SUBSCHEMA_MAP_KEYS {
+        if let Some(Value::Object(map)) = schema.get_mut(key) {
+            map.values_mut()
+                .filter_map(|v| v.as_object_mut())
+                .for_each(&mut *f);
+        }
+    }
+}
+
+// cut the descriptions (removed if 0) and remove the annotations
+fn trim_schema(schema: &mut Map<String, Value>, max_description_chars: usize) {
+    for key in ANNOTATION_KEYS {
+        schema.remove(key);
+    }
+    if let Some(Value::String(description)) = schema.remove("description") {
+        if max_description_chars > 0 {
+            let cut = truncate(&description, max_description_chars);
+            schema.insert("description".to_string(), Value::String(cut));
+        }
+    }
+    for_each_subschema(schema, &mut |sub| trim_schema(sub, max_description_chars));
+}
+
+// replace the schemas of the optional properties with their types
+fn collapse_optional_fields(schema: &mut Map<String, Value>) {
+    let required = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| {
+            r.iter()
+                .filter_map(|n| n.as_str())
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+        for (name, property) in properties.iter_mut() {
+            if required.contains(name) {
+                continue;
+            }
+            let mut collapsed = Map::new();
+            if let Some(t) = property.get("type") {
+                collapsed.insert("type".to_string(), t.clone());
+            }
+            *property = Value::Object(collapsed);
+        }
+    }
+    for_each_subschema(schema, &mut collapse_optional_fields);
+}
@@ -0,0 +1,84 @@
+use crate::compact_schema::CompactSchemaConfig;
+use crate::embedded_backend::EmbeddedBackendConfig;
+use crate::jobworkerp::artifact::ArtifactsConfig;
+use crate::jobworkerp::artifact_store::ArtifactStoreConfig;
+use crate::jobworkerp::error_reporter::ErrorReportingConfig;
+use crate::jobworkerp::file_staging::FileStagingConfig;
+use crate::jobworkerp::metadata::MetadataForwardingConfig;
+use crate::jobworkerp::result_projection::ResultProjectionsConfig;
+use crate::jobworkerp::server_info::ServerInfoConfig;
+use crate::jobworkerp::settings_presets::SettingsPresetsConfig;
+use crate::jobworkerp::tenant::TenantsConfig;
+use crate::jobworkerp::tool_defaults::ToolDefaultsConfig;
+use crate::jobworkerp::worker_options::WorkerOptionsConfig;
+use crate::logging::LoggingConfig;
+use crate::plugin::PluginsConfig;
+use crate::rbac::RbacConfig;
+use crate::scripting::ScriptingConfig;
+use crate::tool_conversion::ToolAlias;
+use crate::tool_prompt::ToolPromptConfig;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Settings loaded from the yaml config file specified by `MCP_CONFIG_FILE`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    pub rbac: Option<RbacConfig>,
+    pub metadata_forwarding: MetadataForwardingConfig,
+    pub logging: LoggingConfig,
+    pub server: ServerInfoConfig,
+    pub worker_options: WorkerOptionsConfig,
+    // alias -> tool listed with the alias
+    pub tool_aliases: BTreeMap<String, ToolAlias>,
+    // tool name -> default arguments merged under the arguments of the calls
+    pub tool_defaults: ToolDefaultsConfig,
+    // tool name -> JSONPath or jq expression extracting the relevant part of the job outputs
+    pub result_projections: ResultProjectionsConfig,
+    // runner name -> settings fields set by the server (hidden from the tool schemas)
+    pub settings_presets: SettingsPresetsConfig,
+    // upstream backends selected per sse connection
+    pub tenants: TenantsConfig,
+    // compact tool schemas within character budgets
+    pub compact_schema: CompactSchemaConfig,
+    // tool name -> caveats and example arguments of the `use-{tool}` prompt
+    pub tool_prompts: BTreeMap<String, ToolPromptConfig>,
+    // tool name -> arguments accepting files staged by the proxy
+    pub file_staging: FileStagingConfig,
+    // directories of the files produced by the jobs, linked from the tool results
+    pub artifacts: ArtifactsConfig,
+    // local directory or S3 compatible storage of oversized results and produced files
+    pub artifact_store: ArtifactStoreConfig,
+    // tool name -> wasm module rewriting the arguments and results (feature `wasm-plugins`)
+    pub plugins: PluginsConfig,
+    // rhai script routing the calls and transforming the results (feature `scripting`)
+    pub scripting: ScriptingConfig,
+    // jobworkerp embedded in the all-in-one binaries (storage, channels, runners)
+    pub embedded_backend: EmbeddedBackendConfig,
+    // webhook receiving the internal errors and panics of the tool calls
+    pub error_reporting: ErrorReportingConfig,
+}
+
+impl ProxyConfig {
+    pub const CONFIG_FILE_ENV: &str = "MCP_CONFIG_FILE";
+
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_yaml::from_str(yaml).context("Failed to parse config file")
+    }
+
+    /// Load the config file if `MCP_CONFIG_FILE` is set (default settings otherwise)
+    pub fn load() -> Result<Self> {
+        match std::env::var(Self::CONFIG_FILE_ENV) {
+            Ok(path) => Self::load_file(&path),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_file(path: &str) -> Result<Self> {
+        tracing::info!("loading config file: {}", path);
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path))?;
+        Self::from_yaml(&content)
+    }
+}
@@ -0,0 +1,196 @@
+use crate::config::ProxyConfig;
+use crate::jobworkerp::{JobworkerpRouter, JobworkerpRouterConfig};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+// directory of the bundled workflows registered at boot if `bootstrap_dir` is not set
+pub const DEFAULT_BOOTSTRAP_DIR: &str = "bootstrap";
+// wait for the embedded backend to accept connections before registering the workflows
+const BOOTSTRAP_WAIT_FOR_BACKEND_SEC: u64 = 30;
+
+/// Settings of the jobworkerp backend embedded in the all-in-one binaries (`embedded_backend` of
+/// the config file, overridden by the command line)
+///
+/// The embedded backend reads its settings from the environment: [`Self::apply_env`] sets the
+/// variables of the configured settings before it boots, so that a config file describes the
+/// whole deployment.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EmbeddedBackendConfig {
+    // gRPC address of the backend (also connected by the proxy)
+    pub grpc_addr: Option<String>,
+    // sqlite database file of the backend storage
+    pub sqlite_path: Option<PathBuf>,
+    pub sqlite_max_connections: Option<u32>,
+    // directory of the runner plugins
+    pub plugins_dir: Option<PathBuf>,
+    // number of jobs run in parallel by the default channel
+    pub worker_concurrency: Option<u32>,
+    // channel name -> number of jobs run in parallel by the channel
+    pub channels: BTreeMap<String, u32>,
+    // runners neither listed nor callable as tools
+    pub disabled_runners: Vec<String>,
+    // workflow definitions registered at boot (`bootstrap` if it exists)
+    pub bootstrap_dir: Option<PathBuf>,
+}
+
+impl EmbeddedBackendConfig {
+    /// Environment variables read by the backend for the configured settings
+    pub fn env_vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = Vec::new();
+        if let Some(addr) = self.grpc_addr.as_ref() {
+            vars.push(("GRPC_ADDR", addr.clone()));
+        }
+        if let Some(path) = self.sqlite_path.as_ref() {
+            vars.push((
+                "SQLITE_URL",
+                format!("sqlite://{}?mode=rwc", path.display()),
+            ));
+        }
+        if let Some(max) = self.sqlite_max_connections {
+            vars.push(("SQLITE_MAX_CONNECTIONS", max.to_string()));
+        }
+        if let Some(dir) = self.plugins_dir.as_ref() {
+            vars.push(("PLUGINS_RUNNER_DIR", dir.display().to_string()));
+        }
+        if let Some(concurrency) = self.worker_concurrency {
+            vars.push(("WORKER_DEFAULT_CONCURRENCY", concurrency.to_string()));
+        }
+        if !self.channels.is_empty() {
+            let (names, concurrencies): (Vec<_>, Vec<_>) = self
+                .channels
+                .iter()
+                .map(|(name, concurrency)| (name.clone(), concurrency.to_string()))
+                .unzip();
+            vars.push(("WORKER_CHANNELS", names.join(",")));
+            vars.push(("WORKER_CHANNEL_CONCURRENCIES", concurrencies.join(",")));
+        }
+        vars
+    }
+
+    /// Set the environment variables of the settings (before the backend and any other thread
+    /// is started)
+    pub fn apply_env(&self) {
+        for (key, value) in self.env_vars() {
+            tracing::debug!("embedded backend setting: {}={}", key, &value);
+            std::env::set_var(key, value);
+        }
+    }
+
+    /// Address of the backend for the proxy (`JOBWORKERP_ADDR`), if `grpc_addr` is set
+    pub fn jobworkerp_address(&self) -> Option<String> {
+        let addr = self.grpc_addr.as_ref()?;
+        // the backend listening on all interfaces is connected locally
+        let addr = match addr.rsplit_once(':') {
+            Some(("0.0.0.0", port)) | Some(("[::]", port)) => format!("127.0.0.1:{}", port),
+            _ => addr.clone(),
+        };
+        Some(format!("http://{}", addr))
+    }
+
+    /// Directory of the workflows registered at boot, if it exists
+    pub fn bootstrap_dir(&self) -> Option<PathBuf> {
+        let dir = self
+            .bootstrap_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_BOOTSTRAP_DIR));
+        dir.is_dir().then_some(dir)
+    }
+}
+
+/// Router config of the all-in-one binaries (connecting to `grpc_addr` of the embedded backend if
+/// set)
+pub fn router_config(proxy_config: ProxyConfig) -> JobworkerpRouterConfig {
+    let jobworkerp_address = proxy_config.embedded_backend.jobworkerp_address();
+    let mut config = JobworkerpRouterConfig::from_env(proxy_config);
+    if let Some(address) = jobworkerp_address {
+        config.jobworkerp_address = address;
+    }
+    config
+}
+
+/// Register the workflows of the bootstrap directory once the embedded backend is ready (errors
+/// are logged: the server is started without them)
+pub async fn register_bootstrap_workflows(proxy_config: &ProxyConfig) {
+    let Some(dir) = proxy_config.embedded_backend.bootstrap_dir() else {
+        return;
+    };
+    let config = JobworkerpRouterConfig {
+        wait_for_backend_sec: Some(BOOTSTRAP_WAIT_FOR_BACKEND_SEC),
+        ..router_config(proxy_config.clone())
+    };
+    let registered = match JobworkerpRouter::new(config).await {
+        Ok(router) => router.register_workflows(&dir).await,
+        Err(e) => Err(e),
+    };
+    match registered {
+        Ok(count) => tracing::info!("{} bootstrap workflows registered from {:?}", count, &dir),
+        Err(e) => tracing::error!("Failed to register bootstrap workflows: {:?}", e),
+    }
+}
+
+/// Command line options of the all-in-one binaries overriding `embedded_backend` of the config
+/// file
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct EmbeddedBackendArgs {
+    /// gRPC address of the embedded jobworkerp (e.g. 127.0.0.1:9010)
+    #[arg(long, value_name = "ADDR")]
+    pub grpc_addr: Option<String>,
+
+    /// Sqlite database file of the embedded jobworkerp
+    #[arg(long, value_name = "PATH")]
+    pub sqlite_path: Option<PathBuf>,
+
+    /// Directory of the runner plugins
+    #[arg(long, value_name = "DIR")]
+    pub plugins_dir: Option<PathBuf>,
+
+    /// Number of jobs run in parallel by the default channel
+    #[arg(long, value_name = "N")]
+    pub worker_concurrency: Option<u32>,
+
+    /// Channel and the number of jobs run in parallel by it (repeatable)
+    #[arg(long = "channel", value_name = "NAME=N", value_parser = parse_channel)]
+    pub channels: Vec<(String, u32)>,
+
+    /// Runner neither listed nor callable as a tool (repeatable)
+    #[arg(long = "disable-runner", value_name = "NAME")]
+    pub disabled_runners: Vec<String>,
+
+    /// Directory of the workflow definitions registered at boot (default: ./bootstrap)
+    #[arg(long, value_name = "DIR")]
+    pub bootstrap_dir: Option<PathBuf>,
+}
+
+impl EmbeddedBackendArgs {
+    pub fn apply(self, config: &mut EmbeddedBackendConfig) {
+        if self.grpc_addr.is_some() {
+            config.grpc_addr = self.grpc_addr;
+        }
+        if self.sqlite_path.is_some() {
+            config.sqlite_path = self.sqlite_path;
+        }
+        if self.plugins_dir.is_some() {
+            config.plugins_dir = self.plugins_dir;
+        }
+        if self.worker_concurrency.is_some() {
+            config.worker_concurrency = self.worker_concurrency;
+        }
+        if self.bootstrap_dir.is_some() {
+            config.bootstrap_dir = self.bootstrap_dir;
+        }
+        config.channels.extend(self.channels);
+        config.disabled_runners.extend(self.disabled_runners);
+    }
+}
+
+fn parse_channel(channel: &str) -> Result<(String, u32), String> {
+    let (name, concurrency) = channel
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=N: {}", channel))?;
+    let concurrency = concurrency
+        .parse::<u32>()
+        .map_err(|e| format!("invalid concurrency of {}: {}", name, e))?;
+    Ok((name.to_string(), concurrency))
+}
@@ -0,0 +1,194 @@
+use serde_json::{Map, Value};
+
+// keys of the symbolic names of integer enum values used by schema generators
+const ENUM_NAME_KEYS: [&str; 3] = ["enumNames", "x-enum-varnames", "x-enumNames"];
+const MAX_REF_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathSegment {
+    Key(String),
+    // every element of an array
+    Items,
+}
+
+/// Integer enum fields of a tool input schema rewritten to string enums
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnumMapping {
+    // field path -> (name, value)
+    fields: Vec<(Vec<PathSegment>, Vec<(String, i64)>)>,
+}
+
+impl EnumMapping {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Convert the symbolic names in the arguments back to the integer values
+    pub fn to_integers(&self, arguments: &mut Map<String, Value>) {
+        for (path, names) in self.fields.iter() {
+            if let Some((PathSegment::Key(first), rest)) = path.split_first() {
+                if let Some(value) = arguments.get_mut(first) {
+                    Self::convert(value, rest, names);
+                }
+            }
+        }
+    }
+
+    fn convert(value: &mut Value, path: &[PathSegment], names: &[(String, i64)]) {
+        match path.split_first() {
+            None => {
+                if let Some(number) = value
+                    .as_str()
+                    .and_then(|s| names.iter().find(|(n, _)| n == s))
+                    .map(|(_, v)| *v)
+                {
+                    *value = Value::from(number);
+                }
+            }
+            Some((PathSegment::Key(key), rest)) => {
+                if let Some(value) = value.get_mut(key.as_str()) {
+                    Self::convert(value, rest, names);
+                }
+            }
+            Some((PathSegment::Items, rest)) => {
+                if let Some(list) = value.as_array_mut() {
+                    for value in list.iter_mut() {
+                        Self::convert(value, rest, names);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Names and values of an integer enum schema (None if not an integer enum with names)
+fn enum_names(schema: &Map<String, Value>) -> Option<Vec<(String, i64)>> {
+    if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+        let names = ENUM_NAME_KEYS
+            .iter()
+            .find_map(|k| schema.get(*k))
+            .and_then(|n| n.as_array())?;
+        if names.len() != values.len() {
+            return None;
+        }
+        return names
+            .iter()
+            .zip(values)
+            .map(|(n, v)| Some((n.as_str()?.to_string(), v.as_i64()?)))
+            .collect();
+    }
+    // `oneOf` of `{"const": 1, "title": "NAME"}`
+    let variants = schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(|v| v.as_array())
+        .filter(|v| !v.is_empty())?;
+    variants
+        .iter()
+        .map(|v| {
+            let name = v.get("title").or_else(|| v.get("description"))?.as_str()?;
+            Some((name.to_string(), v.get("const")?.as_i64()?))
+        })
+        .collect()
+}
+
+fn string_enum_schema(schema: &Map<String, Value>, names: &[(String, i64)]) -> Value {
+    let mut rewritten = Map::new();
+    rewritten.insert("type".to_string(), Value::from("string"));
+    rewritten.insert(
+        "enum".to_string(),
+        Value::from(names.iter().map(|(n, _)| n.clone()).collect::<Vec<_>>()),
+    );
+    if let Some(description) = schema.get("description") {
+        rewritten.insert("description".to_string(), description.clone());
+    }
+    if let Some(default) = schema
+        .get("default")
+        .and_then(|d| d.as_i64())
+        .and_then(|d| names.iter().find(|(_, v)| *v == d))
+    {
+        rewritten.insert("default".to_string(), Value::from(default.0.clone()));
+    }
+    Value::Object(rewritten)
+}
+
+// local reference (`#/$defs/Name` or `#/definitions/Name`)
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    root.pointer(reference.strip_prefix('#')?)
+}
+
+fn collect(
+    schema: &Value,
+    root: &Value,
+    path: &mut Vec<PathSegment>,
+    depth: usize,
+    mapping: &mut EnumMapping,
+) {
+    let Some(object) = schema.as_object() else {
+        return;
+    };
+    if let Some(reference) = object.get("$ref").and_then(|r| r.as_str()) {
+        if depth < MAX_REF_DEPTH {
+            if let Some(resolved) = resolve_ref(root, reference) {
+                collect(resolved, root, path, depth + 1, mapping);
+            }
+        }
+        return;
+    }
+    if let Some(names) = enum_names(object) {
+        mapping.fields.push((path.clone(), names));
+        return;
+    }
+    if let Some(properties) = object.get("properties").and_then(|p| p.as_object()) {
+        for (key, property) in properties {
+            path.push(PathSegment::Key(key.clone()));
+            collect(property, root, path, depth, mapping);
+            path.pop();
+        }
+    }
+    if let Some(items) = object.get("items") {
+        path.push(PathSegment::Items);
+        collect(items, root, path, depth, mapping);
+        path.pop();
+    }
+    for key in ["allOf", "anyOf", "oneOf"] {
+        for member in object
+            .get(key)
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+        {
+            collect(member, root, path, depth, mapping);
+        }
+    }
+}
+
+fn rewrite(schema: &mut Value) {
+    match schema {
+        Value::Object(object) => {
+            if let Some(names) = enum_names(object) {
+                *schema = string_enum_schema(object, &names);
+                return;
+            }
+            for value in object.values_mut() {
+                rewrite(value);
+            }
+        }
+        Value::Array(list) => list.iter_mut().for_each(rewrite),
+        _ => {}
+    }
+}
+
+/// Rewrite integer enums with symbolic names into string enums, returning the mapping to convert
+/// the arguments back
+pub fn rewrite_integer_enums(schema: &mut Map<String, Value>) -> EnumMapping {
+    let root = Value::Object(schema.clone());
+    let mut mapping = EnumMapping::default();
+    collect(&root, &root, &mut vec![], 0, &mut mapping);
+    if !mapping.is_empty() {
+        for value in schema.values_mut() {
+            rewrite(value);
+        }
+    }
+    mapping
+}
@@ -0,0 +1,242 @@
+use crate::auth::{AuthIdentity, Authenticator, OAuthConfig};
+use crate::jobworkerp::session::{SessionRegistry, SessionStatus};
+use crate::jobworkerp::tool_metrics::ToolMetrics;
+use crate::logging::LoggingSettings;
+use crate::rbac::RbacConfig;
+use axum::{
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use tower_http::cors::{AllowHeaders, AllowOrigin, Any, CorsLayer};
+
+/// Join a base path prefix (e.g. `/mcp/`) and an endpoint path (e.g. `/sse`)
+pub fn join_path(base_path: &str, path: &str) -> String {
+    let base = base_path.trim_end_matches('/');
+    let path = path.trim_start_matches('/');
+    if base.is_empty() || base == "/" {
+        format!("/{}", path)
+    } else if base.starts_with('/') {
+        format!("{}/{}", base, path)
+    } else {
+        format!("/{}/{}", base, path)
+    }
+}
+
+/// Parse comma separated bind addresses (e.g. `127.0.0.1:8000,[::1]:8000`)
+pub fn parse_bind_addresses(addresses: &str) -> anyhow::Result<Vec<std::net::SocketAddr>> {
+    let binds = addresses
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse::<std::net::SocketAddr>()
+                .map_err(|e| anyhow::anyhow!("invalid bind address '{}': {}", s, e))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if binds.is_empty() {
+        Err(anyhow::anyhow!("no bind address specified"))
+    } else {
+        Ok(binds)
+    }
+}
+
+fn split_list(s: &str) -> Vec<&str> {
+    s.split(',')
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Build a CORS layer from comma separated allowed origins and headers (`*` for any)
+pub fn cors_layer(
+    allowed_origins: &str,
+    allowed_headers: Option<&str>,
+    allow_credentials: bool,
+) -> anyhow::Result<CorsLayer> {
+    let origins = split_list(allowed_origins);
+    // validated even with the wildcard not to ignore typos in the list
+    let origin_values = origins
+        .iter()
+        .filter(|o| **o != "*")
+        .map(|o| {
+            o.parse::<header::HeaderValue>()
+                .map_err(|e| anyhow::anyhow!("invalid cors origin '{}': {}", o, e))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    // wildcard cannot be used with credentials: reflect the request origin instead
+    let allow_origin = if origins.contains(&"*") {
+        if allow_credentials {
+            AllowOrigin::mirror_request()
+        } else {
+            AllowOrigin::from(Any)
+        }
+    } else {
+        AllowOrigin::list(origin_values)
+    };
+    let allow_headers = match allowed_headers.map(split_list) {
+        Some(headers) if !headers.contains(&"*") => AllowHeaders::list(
+            headers
+                .iter()
+                .map(|h| {
+                    h.parse::<header::HeaderName>()
+                        .map_err(|e| anyhow::anyhow!("invalid cors header '{}': {}", h, e))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        ),
+        _ if allow_credentials => AllowHeaders::mirror_request(),
+        _ => AllowHeaders::from(Any),
+    };
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_headers(allow_headers)
+        .allow_methods([
+            axum::http::Method::GET,
+            axum::http::Method::POST,
+            axum::http::Method::OPTIONS,
+        ])
+        .allow_credentials(allow_credentials))
+}
+
+/// Log method, path, session id, status and latency of requests (sampled by `sample_rate`: 0.0-1.0)
+///
+/// The latency of SSE requests is the time until the event stream is established.
+pub async fn access_log(sample_rate: f64, request: Request, next: Next) -> Response {
+    if sample_rate < 1.0 && rand::random::<f64>() >= sample_rate {
+        return next.run(request).await;
+    }
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let session_id = request
+        .uri()
+        .query()
+        .and_then(|q| {
+            q.split('&')
+                .find_map(|kv| kv.strip_prefix("sessionId="))
+                .map(|s| s.to_string())
+        })
+        .unwrap_or_default();
+    let start = std::time::Instant::now();
+    let response = next.run(request).await;
+    tracing::info!(
+        target: "access_log",
+        method = %method,
+        path = %path,
+        session_id = %session_id,
+        status = response.status().as_u16(),
+        latency_ms = start.elapsed().as_millis() as u64,
+        "{} {} {}",
+        method,
+        path,
+        response.status().as_u16()
+    );
+    response
+}
+
+pub fn bearer_token(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| {
+            v.strip_prefix("Bearer ")
+                .or_else(|| v.strip_prefix("bearer "))
+        })
+        .map(|v| v.trim())
+}
+
+/// Authenticate `Authorization: Bearer <token>` and attach the identity to the request extensions
+///
+/// Unauthenticated requests are rejected with 401 (pointing to the resource metadata when OAuth is enabled).
+pub async fn auth(authenticator: Arc<Authenticator>, mut request: Request, next: Next) -> Response {
+    let identity = match bearer_token(&request) {
+        Some(token) => authenticator
+            .authenticate(token)
+            .await
+            .inspect_err(|e| tracing::info!("authentication failed: {}", e))
+            .ok(),
+        None => None,
+    };
+    match identity {
+        Some(identity) => {
+            request.extensions_mut().insert(identity);
+            next.run(request).await
+        }
+        None => {
+            tracing::info!(
+                "unauthorized request: {} {}",
+                request.method(),
+                request.uri().path()
+            );
+            let challenge = match authenticator.oauth_config() {
+                Some(oauth) => format!(
+                    "Bearer resource_metadata=\"{}\"",
+                    oauth.resource_metadata_url()
+                ),
+                None => "Bearer".to_string(),
+            };
+            (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, challenge)],
+                "Unauthorized",
+            )
+                .into_response()
+        }
+    }
+}
+
+/// OAuth protected resource metadata endpoint
+pub async fn protected_resource_metadata(oauth: Arc<OAuthConfig>) -> Json<Value> {
+    Json(oauth.resource_metadata())
+}
+
+/// Per-tool metrics endpoint in the Prometheus text format
+pub async fn metrics(metrics: Arc<ToolMetrics>) -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.render_prometheus(),
+    )
+        .into_response()
+}
+
+/// Admin endpoint listing the active sessions (requires rbac and an admin role)
+pub async fn list_sessions(
+    sessions: Arc<SessionRegistry>,
+    rbac: Option<Arc<RbacConfig>>,
+    identity: Option<Extension<AuthIdentity>>,
+) -> Result<Json<Vec<SessionStatus>>, StatusCode> {
+    if !is_admin(rbac.as_deref(), identity.as_ref()) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(Json(sessions.list()))
+}
+
+/// Whether the request has an admin role (never without rbac)
+pub fn is_admin(rbac: Option<&RbacConfig>, identity: Option<&Extension<AuthIdentity>>) -> bool {
+    rbac.is_some_and(|rbac| {
+        rbac.permission_for(identity.map(|Extension(i)| i))
+            .is_admin()
+    })
+}
+
+/// Admin endpoint showing (GET) or changing (PUT) the log filter and the debug sample rate
+/// (requires rbac and an admin role)
+pub async fn logging_settings(
+    rbac: Option<Arc<RbacConfig>>,
+    identity: Option<Extension<AuthIdentity>>,
+    settings: Option<Json<LoggingSettings>>,
+) -> Result<Json<LoggingSettings>, (StatusCode, String)> {
+    if !is_admin(rbac.as_deref(), identity.as_ref()) {
+        return Err((StatusCode::FORBIDDEN, "admin role required".to_string()));
+    }
+    if let Some(Json(settings)) = settings {
+        settings
+            .apply()
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    }
+    Ok(Json(LoggingSettings::current()))
+}
@@ -0,0 +1,98 @@
+use futures::future::BoxFuture;
+use rmcp::model::CallToolResult;
+use rmcp::Error as McpError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+/// A runner or worker call seen by the interceptors
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    // called tool name (as listed)
+    pub tool_name: String,
+    // name of the runner or worker executing the call
+    pub target: String,
+    pub arguments: Map<String, Value>,
+    // grpc metadata sent to jobworkerp
+    pub metadata: HashMap<String, String>,
+    pub session_id: String,
+    pub subject: Option<String>,
+}
+
+/// Hooks around the runner and worker calls (e.g. custom auth, quota or billing of embedders)
+pub trait CallInterceptor: Send + Sync {
+    /// Called before the job is enqueued: the call can be modified, or rejected by an error
+    fn pre_call<'a>(&'a self, _call: &'a mut ToolCall) -> BoxFuture<'a, Result<(), McpError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Called with the result of the call (including tool errors), which can be modified
+    fn post_call<'a>(
+        &'a self,
+        _call: &'a ToolCall,
+        _result: &'a mut CallToolResult,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async {})
+    }
+
+    /// Called when the call failed (including rejections by `pre_call`)
+    fn on_error<'a>(&'a self, _call: &'a ToolCall, _error: &'a McpError) -> BoxFuture<'a, ()> {
+        Box::pin(async {})
+    }
+}
+
+/// Interceptors run in the registered order before the call and in the reverse order after it
+#[derive(Clone, Default)]
+pub struct CallInterceptors {
+    interceptors: Vec<Arc<dyn CallInterceptor>>,
+}
+
+impl CallInterceptors {
+    pub fn register(&mut self, interceptor: Arc<dyn CallInterceptor>) {
+        self.interceptors.push(interceptor);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.interceptors.is_empty()
+    }
+
+    pub async fn intercept<F, Fut>(
+        &self,
+        mut call: ToolCall,
+        run: F,
+    ) -> Result<CallToolResult, McpError>
+    where
+        F: FnOnce(ToolCall) -> Fut,
+        Fut: Future<Output = Result<CallToolResult, McpError>>,
+    {
+        if self.is_empty() {
+            return run(call).await;
+        }
+        let mut result = Ok(());
+        for interceptor in self.interceptors.iter() {
+            result = interceptor.pre_call(&mut call).await;
+            if result.is_err() {
+                break;
+            }
+        }
+        let result = match result {
+            Ok(()) => run(call.clone()).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(mut result) => {
+                for interceptor in self.interceptors.iter().rev() {
+                    interceptor.post_call(&call, &mut result).await;
+                }
+                Ok(result)
+            }
+            Err(e) => {
+                for interceptor in self.interceptors.iter().rev() {
+                    interceptor.on_error(&call, &e).await;
+                }
+                Err(e)
+            }
+        }
+    }
+}
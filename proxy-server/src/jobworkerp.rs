@@ -1,23 +1,117 @@
+pub mod artifact;
+pub mod artifact_store;
+pub mod channel;
+pub mod circuit_breaker;
+pub mod descriptor_cache;
+pub mod error_reporter;
+pub mod file_staging;
+pub mod idempotency;
+pub mod job_store;
+mod jobs;
+pub mod meta_tool;
+pub mod metadata;
+pub mod panic_guard;
 pub mod repository;
+pub mod result_projection;
+mod resume;
+pub mod runner_resource;
+pub mod scheduler;
+mod scheduling;
+pub mod server_info;
+pub mod session;
+pub mod settings_presets;
+pub mod tenant;
+pub mod tool_cache;
+pub mod tool_defaults;
+pub mod tool_metrics;
+pub mod worker_definition;
+pub mod worker_options;
+pub mod worker_reaper;
+pub mod workflow_validation;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use artifact::ArtifactRegistry;
+use artifact_store::{ArtifactStore, ArtifactStoreConfig};
+use base64::Engine;
+use channel::UpstreamChannelConfig;
+use chrono::Utc;
+use circuit_breaker::CircuitBreaker;
+use error_reporter::{ErrorReport, ErrorReporter};
+use file_staging::FileStagingConfig;
+use futures::StreamExt;
+use idempotency::{Claim, IdempotencyCache};
+use job_store::{JobEntry, JobFilter, JobStatus, JobStore, RunningJobGuard};
 use jobworkerp_client::{
     error,
-    jobworkerp::data::{Runner, RunnerData, RunnerId, RunnerType},
+    jobworkerp::{
+        data::{Runner, RunnerData, RunnerId, RunnerType, WorkerData},
+        function::data::FunctionSpecs,
+    },
 };
+use metadata::MetadataForwardingConfig;
 pub use repository::JobworkerpRepository;
+use repository::WorkerSpec;
+use result_projection::ResultProjections;
 use rmcp::{
     model::{
-        CallToolRequestMethod, CallToolRequestParam, CallToolResult, CancelledNotificationParam,
-        Content, Implementation, ListToolsResult, PaginatedRequestParam, ProtocolVersion,
-        ServerCapabilities, ServerInfo,
+        AnnotateAble, CallToolRequestMethod, CallToolRequestParam, CallToolResult,
+        CancelledNotificationParam, ClientInfo, Content, ErrorCode, GetPromptRequestParam,
+        GetPromptResult, Implementation, ListPromptsResult, ListResourceTemplatesResult,
+        ListResourcesResult, ListToolsResult, NumberOrString, PaginatedRequestParam,
+        ProtocolVersion, RawResource, RawResourceTemplate, ReadResourceRequestParam,
+        ReadResourceResult, Resource, ResourceContents, ResourceUpdatedNotificationParam,
+        ServerCapabilities, ServerInfo, SubscribeRequestParam, Tool, UnsubscribeRequestParam,
     },
-    service::RequestContext,
+    service::{Peer, RequestContext},
     Error as McpError, RoleServer, ServerHandler,
 };
-use std::{future::Future, sync::Arc};
+use runner_resource::RunnerResourceKind;
+use scheduler::{ScheduleSpec, ScheduleStatus, Scheduler};
+use session::{DisconnectPolicy, SessionClient, SessionInfo, SessionRegistry, SessionState};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    future::Future,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+use tenant::Tenants;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use tool_cache::ToolCache;
+use tool_defaults::ToolDefaultsConfig;
+use tool_metrics::ToolMetrics;
+use tracing::Instrument;
+use worker_definition::WorkerDefinition;
+use worker_reaper::EphemeralWorkers;
+use workflow_validation::WorkflowValidation;
+
+use crate::argument_errors;
+use crate::common::env;
+use crate::compact_schema::{self, CompactSchemaConfig};
+use crate::config::ProxyConfig;
+use crate::enum_schema::{self, EnumMapping};
+use crate::interceptor::{CallInterceptors, ToolCall};
+use crate::lenient_json;
+use crate::logging;
+use crate::plugin;
+use crate::rbac::{self, RbacConfig, ToolPermission};
+use crate::recording::Recorder;
+use crate::redaction;
+use crate::result_conversion::{
+    OutputFormat, ResultContext, ResultConverters, ResultPipeline, TableConverter,
+};
+use crate::schema_lint::{self, SchemaWarning};
+use crate::scripting::{self, Scripts};
+use crate::tool_conversion::{
+    RenamedTool, ToolAlias, ToolConversionOptions, ToolConverter, ToolDiagnostic, ToolKind,
+};
+use crate::tool_prompt::{self, ToolPromptConfig};
 
-use crate::tool_conversion::ToolConverter;
+pub const SERVER_INSTRUCTIONS: &str = "The system runs as an asynchronous job processing server that executes various functions in parallel. It supports general-purpose processing tasks like shell commands and HTTP/gRPC requests, while allowing users to create workflows through JSON-defined specifications. These workflows can compose multiple functions with defined input/output schemas, with all operations managed concurrently for efficient execution.";
 
 pub struct JobworkerpRouterConfig {
     pub jobworkerp_address: String,
@@ -25,6 +119,190 @@ pub struct JobworkerpRouterConfig {
     pub exclude_worker_as_tool: bool,
     pub exclude_runner_as_tool: bool,
     pub set_name: Option<String>,
+    pub proxy_config: ProxyConfig,
+    pub upstream: UpstreamChannelConfig,
+    // interval of refreshing the cached tool list in the background (None: no cache)
+    pub tool_refresh_interval_sec: Option<u64>,
+    // fetch and convert the tool list at startup, serving the first listings from memory
+    pub warm_up_tools: bool,
+    // retry connecting to jobworkerp until this deadline at startup (None: fail immediately)
+    pub wait_for_backend_sec: Option<u64>,
+    // list tools with broken schemas as stub tools explaining the problem
+    pub broken_tool_stubs: bool,
+    // inline $refs of the combined tool schemas (for clients which cannot resolve them)
+    pub inline_schema_refs: bool,
+    // append a "returns:" section of the result schema to tool descriptions (older clients)
+    pub describe_tool_results: bool,
+    // prefix of tools renamed for name collisions (None: `{kind}_`)
+    pub tool_name_collision_prefix: Option<String>,
+    // list tools with kind prefixes (`runner.`, `worker.`, `wf.`) and route calls by them
+    pub kind_prefixed_names: bool,
+    // list and allow the workflow creation tool (ReusableWorkflow runner)
+    pub allow_workflow_creation: bool,
+    // delete workers created by runner calls after being idle for this period (0: right after the call)
+    pub ephemeral_worker_ttl_sec: u64,
+    // maximum concurrent calls of a map_call request
+    pub map_call_max_parallelism: usize,
+    // period of returning the first result to calls with the same idempotency key (0: disabled)
+    pub idempotency_window_sec: u64,
+    // return a continuation token if the job does not finish within this period (None: wait for the job)
+    pub call_wait_timeout_sec: Option<u64>,
+    // results larger than this (characters of the json text) are returned in chunks (None: no limit)
+    pub max_result_chars: Option<usize>,
+    // calls with larger arguments (bytes of the json text) are rejected (None: no limit)
+    pub max_arguments_bytes: Option<usize>,
+    pub max_workflow_definition_bytes: Option<usize>,
+    // fail calls fast for a period after consecutive backend failures (0: disabled)
+    pub circuit_breaker_threshold: u32,
+    pub circuit_breaker_open_sec: u64,
+    // parse json text in the argument fields tolerating common formatting mistakes
+    pub lenient_arguments: bool,
+    // list integer enums of tool schemas as string enums of the symbolic names
+    pub string_enums: bool,
+    // list only the core tools (glob patterns) and the meta tools, the others are found by search_tools
+    pub tool_search_mode: bool,
+    pub core_tools: Vec<String>,
+    // forward the stdout of COMMAND runner calls to the job resources while running
+    pub stream_command_output: bool,
+    // cancel or let complete the jobs of calls in flight when the session is disconnected
+    pub disconnect_policy: DisconnectPolicy,
+    // add markdown tables of the list of objects outputs to the results
+    pub markdown_tables: bool,
+    // directory recording the tool calls and their responses (for the replay by `--replay`)
+    pub record_calls_dir: Option<String>,
+    // interval of logging the per-tool metrics summary (None: not logged)
+    pub metrics_summary_interval_sec: Option<u64>,
+    // wait for the running jobs of the stdio session before exiting when the client closes it
+    pub stdio_drain_sec: Option<u64>,
+    // exit code when the stdio client closes the connection (None: exit normally)
+    pub stdio_closed_exit_code: Option<i32>,
+}
+
+impl JobworkerpRouterConfig {
+    pub const DEFAULT_MAP_CALL_MAX_PARALLELISM: usize = 8;
+
+    /// Router settings from environment variables
+    pub fn from_env(proxy_config: ProxyConfig) -> Self {
+        let jobworkerp_address = std::env::var("JOBWORKERP_ADDR")
+            .unwrap_or_else(|_| "http://127.0.0.1:9000".to_string());
+        let request_timeout_sec = env::parse::<u32>("REQUEST_TIMEOUT_SEC");
+        let exclude_runner_as_tool =
+            env::parse::<bool>("EXCLUDE_RUNNER_AS_TOOL").unwrap_or_default();
+        let exclude_worker_as_tool =
+            env::parse::<bool>("EXCLUDE_WORKER_AS_TOOL").unwrap_or_default();
+        let set_name = env::parse::<String>("TOOL_SET_NAME");
+        let tool_refresh_interval_sec = env::parse::<u64>("TOOL_REFRESH_INTERVAL_SEC");
+        let warm_up_tools = env::parse::<bool>("WARM_UP_TOOLS").unwrap_or_default();
+        let broken_tool_stubs = env::parse::<bool>("BROKEN_TOOL_STUBS").unwrap_or_default();
+        let inline_schema_refs = env::parse::<bool>("INLINE_SCHEMA_REFS").unwrap_or_default();
+        let describe_tool_results = env::parse::<bool>("DESCRIBE_TOOL_RESULTS").unwrap_or_default();
+        let tool_name_collision_prefix = std::env::var("TOOL_NAME_COLLISION_PREFIX")
+            .ok()
+            .filter(|s| !s.is_empty());
+        let kind_prefixed_names = env::parse::<bool>("TOOL_NAME_KIND_PREFIX").unwrap_or_default();
+        let allow_workflow_creation = env::parse::<bool>("ALLOW_WORKFLOW_CREATION").unwrap_or(true);
+        let ephemeral_worker_ttl_sec = env::parse::<u64>("EPHEMERAL_WORKER_TTL_SEC")
+            .unwrap_or(EphemeralWorkers::DEFAULT_TTL.as_secs());
+        let map_call_max_parallelism = env::parse::<usize>("MAP_CALL_MAX_PARALLELISM")
+            .filter(|n| *n > 0)
+            .unwrap_or(Self::DEFAULT_MAP_CALL_MAX_PARALLELISM);
+        let idempotency_window_sec = env::parse::<u64>("IDEMPOTENCY_WINDOW_SEC")
+            .unwrap_or(IdempotencyCache::DEFAULT_WINDOW.as_secs());
+        let call_wait_timeout_sec = env::parse::<u64>("CALL_WAIT_TIMEOUT_SEC").filter(|s| *s > 0);
+        let max_result_chars = env::parse::<usize>("MAX_RESULT_CHARS").filter(|s| *s > 0);
+        let max_arguments_bytes = env::parse::<usize>("MAX_ARGUMENTS_BYTES").filter(|s| *s > 0);
+        let max_workflow_definition_bytes =
+            env::parse::<usize>("MAX_WORKFLOW_DEFINITION_BYTES").filter(|s| *s > 0);
+        let circuit_breaker_threshold = env::parse::<u32>("CIRCUIT_BREAKER_THRESHOLD").unwrap_or(5);
+        let circuit_breaker_open_sec = env::parse::<u64>("CIRCUIT_BREAKER_OPEN_SEC")
+            .filter(|s| *s > 0)
+            .unwrap_or(30);
+        let lenient_arguments = env::parse::<bool>("LENIENT_ARGUMENTS").unwrap_or(true);
+        let string_enums = env::parse::<bool>("STRING_ENUMS").unwrap_or(true);
+        let tool_search_mode = env::parse::<bool>("TOOL_SEARCH_MODE").unwrap_or_default();
+        let core_tools = env::list("CORE_TOOLS");
+        let stream_command_output = env::parse::<bool>("STREAM_COMMAND_OUTPUT").unwrap_or_default();
+        let disconnect_policy = std::env::var("ON_DISCONNECT")
+            .ok()
+            .and_then(|s| DisconnectPolicy::parse(&s))
+            .unwrap_or_default();
+        let markdown_tables = env::parse::<bool>("MARKDOWN_TABLES").unwrap_or_default();
+        let metrics_summary_interval_sec =
+            env::parse::<u64>("METRICS_SUMMARY_INTERVAL_SEC").filter(|s| *s > 0);
+        let stdio_drain_sec = env::parse::<u64>("STDIO_DRAIN_SEC").filter(|s| *s > 0);
+        let stdio_closed_exit_code = env::parse::<i32>("STDIO_CLOSED_EXIT_CODE");
+        let record_calls_dir = std::env::var("RECORD_CALLS_DIR")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        tracing::info!(
+            "Starting MCP server {}",
+            if let Some(set_name) = &set_name {
+                format!("with tool set name '{set_name}'")
+            } else {
+                format!(
+                    "{} {}",
+                    if exclude_runner_as_tool {
+                        "without runner"
+                    } else {
+                        "with runner"
+                    },
+                    if exclude_worker_as_tool {
+                        "without worker as tool"
+                    } else {
+                        "with worker as tool"
+                    }
+                )
+            }
+        );
+        Self {
+            jobworkerp_address,
+            request_timeout_sec,
+            exclude_runner_as_tool,
+            exclude_worker_as_tool,
+            set_name,
+            proxy_config,
+            upstream: UpstreamChannelConfig::from_env(),
+            tool_refresh_interval_sec,
+            warm_up_tools,
+            wait_for_backend_sec: None,
+            broken_tool_stubs,
+            inline_schema_refs,
+            describe_tool_results,
+            tool_name_collision_prefix,
+            kind_prefixed_names,
+            allow_workflow_creation,
+            ephemeral_worker_ttl_sec,
+            map_call_max_parallelism,
+            idempotency_window_sec,
+            call_wait_timeout_sec,
+            max_result_chars,
+            max_arguments_bytes,
+            max_workflow_definition_bytes,
+            circuit_breaker_threshold,
+            circuit_breaker_open_sec,
+            lenient_arguments,
+            string_enums,
+            tool_search_mode,
+            core_tools,
+            stream_command_output,
+            disconnect_policy,
+            markdown_tables,
+            record_calls_dir,
+            metrics_summary_interval_sec,
+            stdio_drain_sec,
+            stdio_closed_exit_code,
+        }
+    }
+}
+
+// options of a tool call given by `_meta`
+#[derive(Debug, Clone, Default)]
+struct CallOptions {
+    // channel of the worker created for a runner call
+    channel: Option<String>,
+    // rendering of the job output
+    output_format: Option<OutputFormat>,
 }
 
 #[derive(Clone)]
@@ -33,34 +311,1627 @@ pub struct JobworkerpRouter {
     pub exclude_worker_as_tool: bool,
     pub exclude_runner_as_tool: bool,
     pub set_name: Option<String>,
+    pub job_store: Arc<JobStore>,
+    pub session: Arc<SessionInfo>,
+    pub rbac: Option<Arc<RbacConfig>>,
+    // tools allowed for the current session
+    pub permission: Arc<ToolPermission>,
+    pub metadata_forwarding: Arc<MetadataForwardingConfig>,
+    // resource subscriptions of the current session (uri -> watcher task)
+    subscriptions: Arc<Mutex<HashMap<String, CancellationToken>>>,
+    // function list refreshed in the background (if enabled)
+    tool_cache: Option<Arc<ToolCache>>,
+    // tools converted from the function list at the last listing (reused while unchanged)
+    converted_tools: Arc<RwLock<Option<(Vec<FunctionSpecs>, ListToolsResult)>>>,
+    // whether the current session is notified of tool list changes
+    watching_tool_list: Arc<AtomicBool>,
+    pub broken_tool_stubs: bool,
+    pub inline_schema_refs: bool,
+    pub describe_tool_results: bool,
+    pub tool_name_collision_prefix: Option<String>,
+    pub kind_prefixed_names: bool,
+    pub allow_workflow_creation: bool,
+    // runners neither listed nor callable (`embedded_backend.disabled_runners`)
+    disabled_runners: Arc<HashSet<String>>,
+    // configured tool aliases, and the aliases applied at the last listing (alias -> tool name)
+    tool_aliases: Arc<BTreeMap<String, ToolAlias>>,
+    aliased_tools: Arc<RwLock<HashMap<String, String>>>,
+    // default arguments of the tools (by the name without aliases)
+    pub tool_defaults: Arc<ToolDefaultsConfig>,
+    // tools listed with other names for name collisions (by the listed name)
+    renamed_tools: Arc<RwLock<HashMap<String, RenamedTool>>>,
+    // functions which could not be converted to tools at the last listing
+    tool_diagnostics: Arc<RwLock<Vec<ToolDiagnostic>>>,
+    // metaschema violations of the function schemas at the last listing
+    schema_warnings: Arc<RwLock<Vec<SchemaWarning>>>,
+    // advertised server implementation and instructions
+    pub implementation: Implementation,
+    pub instructions: Arc<str>,
+    // active sessions of the server, and the state of the current session
+    pub sessions: Arc<SessionRegistry>,
+    session_state: SessionState,
+    pub map_call_max_parallelism: usize,
+    // scheduled tool calls (shared by all sessions)
+    pub scheduler: Arc<Scheduler>,
+    // results of calls with idempotency keys (shared by all sessions)
+    idempotency: Arc<IdempotencyCache>,
+    pub call_wait_timeout: Option<Duration>,
+    pub max_result_chars: Option<usize>,
+    pub max_arguments_bytes: Option<usize>,
+    pub max_workflow_definition_bytes: Option<usize>,
+    pub lenient_arguments: bool,
+    pub stream_command_output: bool,
+    pub disconnect_policy: DisconnectPolicy,
+    // parts of the job outputs converted to MCP content by tool name
+    result_projections: Arc<ResultProjections>,
+    // conversion of job outputs to MCP content
+    pub result_converters: ResultConverters,
+    // hooks around the runner and worker calls (registered by embedders)
+    pub interceptors: CallInterceptors,
+    // script routing the calls (also registered to the interceptors transforming the results)
+    scripts: Option<Arc<Scripts>>,
+    // records the tool calls of all sessions
+    recorder: Option<Arc<Recorder>>,
+    // per-tool latencies and errors (shared by all sessions)
+    pub tool_metrics: Arc<ToolMetrics>,
+    // sends the internal errors and panics of the calls to the webhook (shared by all sessions)
+    error_reporter: Option<Arc<ErrorReporter>>,
+    // list integer enums of tool schemas as string enums of the symbolic names
+    pub string_enums: bool,
+    enum_mappings: Arc<RwLock<HashMap<String, EnumMapping>>>,
+    // input schemas of the tools at the last listing (by the listed name)
+    tool_schemas: Arc<RwLock<HashMap<String, Arc<serde_json::Map<String, serde_json::Value>>>>>,
+    pub tool_search_mode: bool,
+    pub core_tools: Arc<Vec<String>>,
+    pub compact_schema: Arc<CompactSchemaConfig>,
+    tool_prompts: Arc<BTreeMap<String, ToolPromptConfig>>,
+    file_staging: Arc<FileStagingConfig>,
+    // files produced by the jobs (shared by the sessions)
+    artifacts: Arc<ArtifactRegistry>,
+    // oversized results and produced files are offloaded to the store
+    artifact_store: Option<Arc<dyn ArtifactStore>>,
+    artifact_store_config: Arc<ArtifactStoreConfig>,
+    // backends selected per connection (None: all sessions use the default backend)
+    tenants: Option<Arc<Tenants>>,
+    // tenant of the backend of the session (None: the default backend)
+    tenant: Option<String>,
 }
 
 impl JobworkerpRouter {
+    const DEFAULT_WARM_TOOL_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
     pub async fn new(config: JobworkerpRouterConfig) -> Result<Self> {
-        let repository =
-            JobworkerpRepository::new(&config.jobworkerp_address, config.request_timeout_sec)
-                .await?;
+        let repository = JobworkerpRepository::connect(
+            &config.jobworkerp_address,
+            config.request_timeout_sec,
+            &config.upstream,
+            config.wait_for_backend_sec.map(Duration::from_secs),
+        )
+        .await?
+        .with_ephemeral_worker_ttl(Duration::from_secs(config.ephemeral_worker_ttl_sec))
+        .with_worker_options(config.proxy_config.worker_options.clone())
+        .with_settings_presets(config.proxy_config.settings_presets.resolve()?)
+        .with_circuit_breaker(CircuitBreaker::new(
+            config.circuit_breaker_threshold,
+            Duration::from_secs(config.circuit_breaker_open_sec),
+        ));
+        let rbac = config.proxy_config.rbac.clone().map(Arc::new);
+        let tenants = if config.proxy_config.tenants.is_enabled() {
+            Some(Arc::new(
+                config.proxy_config.tenants.resolve(&config.upstream)?,
+            ))
+        } else {
+            None
+        };
+        let session = SessionInfo::stdio();
+        let permission = Self::resolve_permission(rbac.as_deref(), &session);
+        let server = &config.proxy_config.server;
+        let vars = HashMap::from([
+            ("jobworkerp_address", config.jobworkerp_address.clone()),
+            ("set_name", config.set_name.clone().unwrap_or_default()),
+            (
+                "exclude_runner_as_tool",
+                config.exclude_runner_as_tool.to_string(),
+            ),
+            (
+                "exclude_worker_as_tool",
+                config.exclude_worker_as_tool.to_string(),
+            ),
+            ("request_timeout_sec", repository.timeout_sec.to_string()),
+        ]);
+        let instructions = server.instructions(SERVER_INSTRUCTIONS, &vars)?;
+        let implementation = server.implementation();
 
-        Ok(Self {
+        // the warmed up tool list is refreshed by default (not to serve a stale list forever)
+        let tool_refresh_interval = match config.tool_refresh_interval_sec.filter(|s| *s > 0) {
+            Some(interval) => Some(Duration::from_secs(interval)),
+            None if config.warm_up_tools => Some(Self::DEFAULT_WARM_TOOL_REFRESH_INTERVAL),
+            None => None,
+        };
+        let mut interceptors = CallInterceptors::default();
+        if let Some(plugins) = plugin::build(&config.proxy_config.plugins)? {
+            interceptors.register(plugins);
+        }
+        let mut result_converters = ResultConverters::default();
+        if config.markdown_tables {
+            result_converters.register_common(Arc::new(TableConverter::default()));
+        }
+        let scripts = scripting::build(&config.proxy_config.scripting)?;
+        if let Some(scripts) = scripts.clone() {
+            interceptors.register(scripts);
+        }
+        let router = Self {
             repository: Arc::new(repository),
             exclude_worker_as_tool: config.exclude_worker_as_tool,
             exclude_runner_as_tool: config.exclude_runner_as_tool,
             set_name: config.set_name,
+            job_store: Arc::new(JobStore::default()),
+            session: Arc::new(session),
+            rbac,
+            permission: Arc::new(permission),
+            metadata_forwarding: Arc::new(config.proxy_config.metadata_forwarding),
+            subscriptions: Arc::new(Mutex::new(HashMap::new())),
+            tool_cache: tool_refresh_interval
+                .is_some()
+                .then(|| Arc::new(ToolCache::new())),
+            converted_tools: Arc::new(RwLock::new(None)),
+            watching_tool_list: Arc::new(AtomicBool::new(false)),
+            broken_tool_stubs: config.broken_tool_stubs,
+            inline_schema_refs: config.inline_schema_refs,
+            describe_tool_results: config.describe_tool_results,
+            tool_name_collision_prefix: config.tool_name_collision_prefix.clone(),
+            kind_prefixed_names: config.kind_prefixed_names,
+            allow_workflow_creation: config.allow_workflow_creation,
+            disabled_runners: Arc::new(
+                config
+                    .proxy_config
+                    .embedded_backend
+                    .disabled_runners
+                    .iter()
+                    .cloned()
+                    .collect(),
+            ),
+            tool_aliases: Arc::new(config.proxy_config.tool_aliases.clone()),
+            aliased_tools: Arc::new(RwLock::new(HashMap::new())),
+            tool_defaults: Arc::new(config.proxy_config.tool_defaults.clone()),
+            renamed_tools: Arc::new(RwLock::new(HashMap::new())),
+            tool_diagnostics: Arc::new(RwLock::new(Vec::new())),
+            schema_warnings: Arc::new(RwLock::new(Vec::new())),
+            implementation,
+            instructions: instructions.into(),
+            sessions: Arc::new(SessionRegistry::default()),
+            session_state: SessionState::default(),
+            map_call_max_parallelism: config.map_call_max_parallelism,
+            scheduler: Arc::new(Scheduler::default()),
+            idempotency: Arc::new(IdempotencyCache::new(Duration::from_secs(
+                config.idempotency_window_sec,
+            ))),
+            call_wait_timeout: config.call_wait_timeout_sec.map(Duration::from_secs),
+            max_result_chars: config.max_result_chars,
+            max_arguments_bytes: config.max_arguments_bytes,
+            max_workflow_definition_bytes: config.max_workflow_definition_bytes,
+            lenient_arguments: config.lenient_arguments,
+            stream_command_output: config.stream_command_output,
+            disconnect_policy: config.disconnect_policy,
+            result_projections: Arc::new(ResultProjections::new(
+                &config.proxy_config.result_projections,
+            )?),
+            result_converters,
+            interceptors,
+            scripts,
+            recorder: config
+                .record_calls_dir
+                .as_deref()
+                .map(Recorder::open)
+                .transpose()?
+                .map(Arc::new),
+            tool_metrics: Arc::new(ToolMetrics::default()),
+            error_reporter: ErrorReporter::new(&config.proxy_config.error_reporting)?.map(Arc::new),
+            string_enums: config.string_enums,
+            enum_mappings: Arc::new(RwLock::new(HashMap::new())),
+            tool_schemas: Arc::new(RwLock::new(HashMap::new())),
+            tool_search_mode: config.tool_search_mode,
+            core_tools: Arc::new(config.core_tools.clone()),
+            compact_schema: Arc::new(config.proxy_config.compact_schema.clone()),
+            tool_prompts: Arc::new(config.proxy_config.tool_prompts.clone()),
+            file_staging: Arc::new(config.proxy_config.file_staging.clone()),
+            artifacts: Arc::new(ArtifactRegistry::new(config.proxy_config.artifacts.clone())),
+            artifact_store: artifact_store::build(&config.proxy_config.artifact_store)?,
+            artifact_store_config: Arc::new(config.proxy_config.artifact_store.clone()),
+            tenants,
+            tenant: None,
+        };
+        router.repository.spawn_worker_reaper();
+        router.report_tool_diagnostics().await;
+        if let Some(interval) = tool_refresh_interval {
+            router.spawn_tool_refresher(interval);
+        }
+        if let Some(interval) = config.metrics_summary_interval_sec {
+            router.spawn_metrics_summary(Duration::from_secs(interval));
+        }
+        Ok(router)
+    }
+
+    // fetch the function list of the configured tool set (or all runners/workers)
+    async fn find_functions(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+    ) -> Result<Vec<FunctionSpecs>> {
+        if let Some(name) = self.set_name.as_ref() {
+            self.repository
+                .find_function_list_by_set(metadata, name.as_str())
+                .await
+        } else {
+            self.repository
+                .find_function_list(
+                    metadata,
+                    self.exclude_runner_as_tool,
+                    self.exclude_worker_as_tool,
+                )
+                .await
+        }
+    }
+
+    // function list from the cache (if enabled) or the backend
+    async fn load_functions(
+        &self,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<Vec<FunctionSpecs>, McpError> {
+        let mut functions = match self.tool_cache.as_ref().and_then(|c| c.get()) {
+            Some(functions) => functions.as_ref().clone(),
+            None => self
+                .find_functions(self.request_metadata(context))
+                .await
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to find tools: {}", e), None)
+                })?,
+        };
+        self.retain_listed_functions(&mut functions);
+        Ok(functions)
+    }
+
+    fn retain_listed_functions(&self, functions: &mut Vec<FunctionSpecs>) {
+        if !self.allow_workflow_creation {
+            functions.retain(|f| !Self::is_workflow_creation(f));
+        }
+        if !self.disabled_runners.is_empty() {
+            functions.retain(|f| f.worker_id.is_some() || !self.disabled_runners.contains(&f.name));
+        }
+    }
+
+    fn is_workflow_creation(function: &FunctionSpecs) -> bool {
+        function.worker_id.is_none() && function.runner_type == RunnerType::ReusableWorkflow as i32
+    }
+
+    // administrative tools (workflow creation) by the listed names
+    fn admin_tool_names(&self, functions: &[FunctionSpecs]) -> Vec<String> {
+        let mut names = functions
+            .iter()
+            .filter(|f| Self::is_workflow_creation(f))
+            .map(|f| {
+                if self.kind_prefixed_names {
+                    format!("{}{}", ToolConverter::kind_prefix(f), &f.name)
+                } else {
+                    f.name.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let aliases = self
+            .tool_aliases
+            .iter()
+            .filter(|(_, a)| names.contains(&a.tool))
+            .map(|(alias, _)| alias.clone())
+            .collect::<Vec<_>>();
+        names.extend(aliases);
+        names
+    }
+
+    fn is_tool_visible(&self, name: &str, admin_tools: &[String]) -> bool {
+        self.permission.is_allowed(name)
+            && (self.permission.can_use_admin_tools() || !admin_tools.iter().any(|n| n == name))
+    }
+
+    async fn help(&self, context: &RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let functions = self.load_functions(context).await?;
+        let admin_tools = self.admin_tool_names(&functions);
+        let unavailable = self
+            .tool_diagnostics
+            .read()
+            .map(|d| d.clone())
+            .unwrap_or_default();
+        let help = meta_tool::HelpInfo {
+            instructions: &self.instructions,
+            functions: &functions,
+            unavailable: &unavailable,
+            request_timeout_sec: self.repository.timeout_sec,
+            job_history_size: self.job_store.capacity(),
+        }
+        .render(|name| self.is_tool_visible(name, &admin_tools));
+        meta_tool::help_result(help)
+    }
+
+    async fn search_tools(
+        &self,
+        request: &CallToolRequestParam,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let (keywords, limit) = meta_tool::parse_search_tools(request.arguments.as_ref())?;
+        let functions = self.load_functions(context).await?;
+        let admin_tools = self.admin_tool_names(&functions);
+        let mut tools = self.convert_tools_cached(functions).tools;
+        tools.retain(|t| self.is_tool_visible(&t.name, &admin_tools));
+        meta_tool::search_tools_result(&tools, &keywords, limit)
+    }
+
+    // draft a workflow with the client llm (sampling), and create it if valid and requested
+    async fn author_workflow(
+        &self,
+        request: &CallToolRequestParam,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let (description, create, max_tokens) =
+            meta_tool::parse_author_workflow(request.arguments.as_ref())?;
+        if context.peer.peer_info().capabilities.sampling.is_none() {
+            return Err(McpError::invalid_request(
+                "The client does not support sampling".to_string(),
+                None,
+            ));
+        }
+        let functions = self.load_functions(context).await?;
+        let creation_tools = self.admin_tool_names(&functions);
+        let creation_tool = self
+            .visible_tools(context)
+            .await?
+            .into_iter()
+            .find(|t| creation_tools.contains(&t.name.to_string()))
+            .ok_or_else(|| {
+                McpError::invalid_request("Workflow creation is not available".to_string(), None)
+            })?;
+
+        let mut previous: Option<(String, Vec<argument_errors::FieldError>)> = None;
+        let mut draft = (None, vec![], None);
+        for _ in 0..meta_tool::MAX_AUTHORING_ATTEMPTS {
+            let params = meta_tool::workflow_authoring_request(
+                &description,
+                &creation_tool.input_schema,
+                previous.as_ref().map(|(t, e)| (t.as_str(), e.as_slice())),
+                max_tokens,
+            );
+            let result = context.peer.create_message(params).await.map_err(|e| {
+                McpError::internal_error(format!("Failed to sample a workflow: {}", e), None)
+            })?;
+            let text = result
+                .message
+                .content
+                .as_text()
+                .map(|t| t.text.clone())
+                .unwrap_or_default();
+            match meta_tool::extract_workflow_json(&text) {
+                Ok(workflow) => {
+                    let errors = argument_errors::field_errors(
+                        &creation_tool.input_schema,
+                        &serde_json::Value::Object(workflow.clone()),
+                    );
+                    let valid = errors.is_empty();
+                    previous = Some((text, errors.clone()));
+                    draft = (Some(workflow), errors, None);
+                    if valid {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    let error = argument_errors::FieldError {
+                        path: String::new(),
+                        message: e.clone(),
+                        received: serde_json::Value::Null,
+                    };
+                    previous = Some((text, vec![error]));
+                    draft = (None, vec![], Some(e));
+                }
+            }
+        }
+        let (workflow, errors, parse_error) = draft;
+        let valid = workflow.is_some() && errors.is_empty() && parse_error.is_none();
+        let mut created = false;
+        if create && valid {
+            let result = self
+                .dispatch_tool(
+                    CallToolRequestParam {
+                        name: creation_tool.name.clone(),
+                        arguments: workflow.clone(),
+                    },
+                    context,
+                )
+                .await?;
+            if result.is_error == Some(true) {
+                return Ok(result);
+            }
+            created = true;
+        }
+        meta_tool::author_workflow_result(
+            workflow.as_ref(),
+            &errors,
+            parse_error.as_deref(),
+            created,
+        )
+    }
+
+    // converted tools of jobworkerp visible to the session (with the full input schemas)
+    async fn visible_tools(
+        &self,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<Vec<Tool>, McpError> {
+        let functions = self.load_functions(context).await?;
+        let admin_tools = self.admin_tool_names(&functions);
+        let mut tools = self.convert_tools_cached(functions).tools;
+        tools.retain(|t| self.is_tool_visible(&t.name, &admin_tools));
+        if let Ok(schemas) = self.tool_schemas.read() {
+            for tool in tools.iter_mut() {
+                if let Some(schema) = schemas.get(tool.name.as_ref()) {
+                    tool.input_schema = schema.clone();
+                }
+            }
+        }
+        Ok(tools)
+    }
+
+    // tools listed to the session: converted functions and the meta tools
+    fn listed_tools(&self, functions: Vec<FunctionSpecs>) -> ListToolsResult {
+        let admin_tools = self.admin_tool_names(&functions);
+        let mut result = self.convert_tools_cached(functions);
+        if self.tool_search_mode {
+            result.tools.retain(|t| self.is_core_tool(&t.name));
+        }
+        result.tools.push(meta_tool::search_tools_tool());
+        result.tools.push(meta_tool::recent_results_tool());
+        result.tools.push(meta_tool::help_tool());
+        result.tools.push(meta_tool::tool_diagnostics_tool());
+        result.tools.push(meta_tool::batch_call_tool());
+        result
+            .tools
+            .push(meta_tool::map_call_tool(self.map_call_max_parallelism));
+        result.tools.push(meta_tool::schedule_job_tool());
+        result.tools.push(meta_tool::list_schedules_tool());
+        result.tools.push(meta_tool::cancel_schedule_tool());
+        result.tools.push(meta_tool::resume_call_tool());
+        result.tools.push(meta_tool::fetch_result_chunk_tool());
+        if self.allow_workflow_creation {
+            result.tools.push(meta_tool::author_workflow_tool());
+        }
+        result
+            .tools
+            .retain(|t| self.is_tool_visible(&t.name, &admin_tools));
+        result
+    }
+
+    /// The tool list of the backend as listed to the clients (without session metadata)
+    pub async fn export_tools(&self) -> Result<ListToolsResult> {
+        let mut functions = self.find_functions(Arc::new(HashMap::new())).await?;
+        self.retain_listed_functions(&mut functions);
+        Ok(self.listed_tools(functions))
+    }
+
+    /// Validate a workflow definition (the arguments of the workflow creation tool) against the
+    /// input schema of the tool and the runners and workers of the backend, without creating it
+    pub async fn validate_workflow(
+        &self,
+        definition: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<WorkflowValidation> {
+        let metadata = Arc::new(HashMap::new());
+        let functions = self.find_functions(metadata.clone()).await?;
+        let creation_tools = self.admin_tool_names(&functions);
+        self.convert_tools_cached(functions);
+        let schema = self
+            .tool_schemas
+            .read()
+            .ok()
+            .and_then(|s| creation_tools.iter().find_map(|name| s.get(name).cloned()))
+            .ok_or_else(|| anyhow::anyhow!("The workflow creation runner is not found"))?;
+        let definition = serde_json::Value::Object(
+            self.repository
+                .parse_arguments_for_reusable_workflow(definition)?,
+        );
+        let mut validation = WorkflowValidation {
+            errors: argument_errors::field_errors(&schema, &definition),
+            ..Default::default()
+        };
+        let (runners, workers) =
+            workflow_validation::referenced_functions(&definition["workflow_data"]);
+        for name in runners {
+            let found = self
+                .repository
+                .find_runner_by_name_with_mcp(metadata.clone(), &name)
+                .await?;
+            if found.is_none() {
+                validation.unknown_runners.push(name);
+            }
+        }
+        for name in workers {
+            let found = self
+                .repository
+                .find_worker_by_name_with_mcp(metadata.clone(), &name)
+                .await?;
+            if found.is_none() {
+                validation.unknown_workers.push(name);
+            }
+        }
+        Ok(validation)
+    }
+
+    /// Register the workflow definitions of the files (JSON or YAML) of the directory in the order
+    /// of the file names, as the workflow creation tool (workflows of existing names are kept as
+    /// they are). Invalid definitions are logged and skipped
+    pub async fn register_workflows(&self, dir: &Path) -> Result<usize> {
+        let mut files = std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read workflow directory: {:?}", dir))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "json" || ext == "yaml" || ext == "yml")
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        let metadata = Arc::new(HashMap::new());
+        let (runner_id, runner_data) = self
+            .find_runner(metadata.clone(), RunnerType::ReusableWorkflow.as_str_name())
+            .await?;
+        let mut registered = 0;
+        for file in files {
+            let definition = std::fs::read_to_string(&file)
+                .map_err(anyhow::Error::from)
+                .and_then(|text| workflow_validation::parse_definition(&text));
+            let created = match definition {
+                Ok(arguments) => {
+                    self.repository
+                        .create_workflow(
+                            metadata.clone(),
+                            runner_id,
+                            runner_data.clone(),
+                            Some(arguments),
+                        )
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            match created {
+                Ok(()) => registered += 1,
+                Err(e) => tracing::error!("Failed to register workflow {:?}: {:?}", &file, e),
+            }
+        }
+        Ok(registered)
+    }
+
+    async fn find_runner(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        name: &str,
+    ) -> Result<(RunnerId, RunnerData)> {
+        match self
+            .repository
+            .find_runner_by_name_with_mcp(metadata, name)
+            .await?
+        {
+            Some((
+                Runner {
+                    id: Some(runner_id),
+                    data: Some(runner_data),
+                },
+                _,
+            )) => Ok((runner_id, runner_data)),
+            _ => anyhow::bail!("runner not found: {}", name),
+        }
+    }
+
+    /// Create the worker of the definition (kept as it is if a worker of the name exists)
+    pub async fn create_worker(&self, definition: &WorkerDefinition) -> Result<()> {
+        let metadata = Arc::new(HashMap::new());
+        let runner_name = match definition.runner.as_deref() {
+            Some(runner) => runner,
+            None => RunnerType::ReusableWorkflow.as_str_name(),
+        };
+        let (runner_id, runner_data) = self.find_runner(metadata.clone(), runner_name).await?;
+        if let Some(workflow) = definition.workflow.as_ref() {
+            // the worker is named by the definition (and described if given)
+            let mut workflow = workflow.clone();
+            if let Some(document) = workflow.get_mut("document").and_then(|d| d.as_object_mut()) {
+                document.insert("name".to_string(), definition.name.clone().into());
+                if !definition.description.is_empty() {
+                    document.insert("summary".to_string(), definition.description.clone().into());
+                }
+            } else {
+                anyhow::bail!("{}: workflow requires a document", definition.name);
+            }
+            let arguments = serde_json::Map::from_iter([("workflow_data".to_string(), workflow)]);
+            return self
+                .repository
+                .create_workflow(metadata, runner_id, runner_data, Some(arguments))
+                .await;
+        }
+        let spec = WorkerSpec {
+            name: definition.name.clone(),
+            description: definition.description.clone(),
+            settings: definition.settings.clone(),
+            channel: definition.channel.clone(),
+            options: definition
+                .options
+                .merge(&self.repository.worker_options.for_runner(runner_name)),
+        };
+        self.repository
+            .create_worker(metadata, runner_id, &runner_data, spec)
+            .await
+    }
+
+    fn is_core_tool(&self, name: &str) -> bool {
+        self.core_tools.iter().any(|p| rbac::glob_match(p, name))
+    }
+
+    // convert functions to tools, keeping the conversion failures for `tool_diagnostics`
+    fn convert_tools(&self, functions: Vec<FunctionSpecs>) -> ListToolsResult {
+        let warnings = schema_lint::lint_functions(&functions);
+        if let Ok(mut w) = self.schema_warnings.write() {
+            // logged only when changed (the tools are converted at every listing)
+            if *w != warnings {
+                schema_lint::log_warnings(&warnings);
+                *w = warnings;
+            }
+        }
+        let converted = ToolConverter::convert_functions_with_options(
+            functions,
+            &ToolConversionOptions {
+                include_stubs: self.broken_tool_stubs,
+                inline_refs: self.inline_schema_refs,
+                describe_result: self.describe_tool_results,
+                collision_prefix: self.tool_name_collision_prefix.clone(),
+                kind_prefixes: self.kind_prefixed_names,
+                aliases: self.tool_aliases.as_ref().clone(),
+                preset_settings: self.repository.settings_presets.fields(),
+            },
+        );
+        let mut result = converted.result;
+        let redactor = redaction::redactor();
+        for tool in result.tools.iter() {
+            redactor.register_schema(&tool.input_schema);
+        }
+        if let Ok(mut d) = self.tool_diagnostics.write() {
+            *d = converted.diagnostics;
+        }
+        if let Ok(mut a) = self.aliased_tools.write() {
+            *a = converted.aliased;
+        }
+        if let Ok(mut r) = self.renamed_tools.write() {
+            *r = converted
+                .renamed
+                .into_iter()
+                .map(|t| (t.name.clone(), t))
+                .collect();
+        }
+        if self.string_enums {
+            let mut mappings = HashMap::new();
+            for tool in result.tools.iter_mut() {
+                let mut schema = tool.input_schema.as_ref().clone();
+                let mapping = enum_schema::rewrite_integer_enums(&mut schema);
+                if !mapping.is_empty() {
+                    tool.input_schema = Arc::new(schema);
+                    mappings.insert(tool.name.to_string(), mapping);
+                }
+            }
+            if let Ok(mut m) = self.enum_mappings.write() {
+                *m = mappings;
+            }
+        }
+        if self.file_staging.is_enabled() {
+            for tool in result.tools.iter_mut() {
+                let mut schema = tool.input_schema.as_ref().clone();
+                self.file_staging.rewrite_schema(&tool.name, &mut schema);
+                tool.input_schema = Arc::new(schema);
+            }
+        }
+        if let Ok(mut s) = self.tool_schemas.write() {
+            *s = result
+                .tools
+                .iter()
+                .map(|t| (t.name.to_string(), t.input_schema.clone()))
+                .collect();
+        }
+        // after recording the full schemas (validated and redacted by them)
+        if self.compact_schema.enabled {
+            let chars = compact_schema::compact_tools(&mut result.tools, &self.compact_schema);
+            tracing::debug!(
+                "compacted {} tools to {} characters",
+                result.tools.len(),
+                chars
+            );
+        }
+        result
+    }
+
+    // convert functions to tools, reusing the last conversion if the functions are unchanged
+    fn convert_tools_cached(&self, functions: Vec<FunctionSpecs>) -> ListToolsResult {
+        if let Ok(converted) = self.converted_tools.read() {
+            if let Some((_, result)) = converted.as_ref().filter(|(f, _)| *f == functions) {
+                return result.clone();
+            }
+        }
+        let result = self.convert_tools(functions.clone());
+        if let Ok(mut converted) = self.converted_tools.write() {
+            *converted = Some((functions, result.clone()));
+        }
+        result
+    }
+
+    fn find_tool_diagnostic(&self, name: &str) -> Option<ToolDiagnostic> {
+        self.tool_diagnostics
+            .read()
+            .ok()
+            .and_then(|d| d.iter().find(|d| d.name == name).cloned())
+    }
+
+    // startup summary of the tools (broken tools are reported), filling the tool cache if enabled
+    async fn report_tool_diagnostics(&self) {
+        let started = Instant::now();
+        match self.find_functions(Arc::new(HashMap::new())).await {
+            Ok(mut functions) => {
+                if let Some(cache) = self.tool_cache.as_ref() {
+                    cache.update(functions.clone());
+                }
+                self.retain_listed_functions(&mut functions);
+                let result = self.convert_tools_cached(functions);
+                let broken = self.tool_diagnostics.read().map(|d| d.len()).unwrap_or(0);
+                let warnings = self.schema_warnings.read().map(|w| w.len()).unwrap_or(0);
+                if warnings > 0 {
+                    tracing::warn!(
+                        "{} schema lint warnings (see `{}` tool)",
+                        warnings,
+                        meta_tool::TOOL_DIAGNOSTICS
+                    );
+                }
+                if broken > 0 {
+                    tracing::warn!(
+                        "{} tools loaded in {}ms, {} tools skipped for broken schemas (see `{}` tool)",
+                        result.tools.len(),
+                        started.elapsed().as_millis(),
+                        broken,
+                        meta_tool::TOOL_DIAGNOSTICS
+                    );
+                } else {
+                    tracing::info!(
+                        "{} tools loaded in {}ms",
+                        result.tools.len(),
+                        started.elapsed().as_millis()
+                    );
+                }
+            }
+            Err(e) => tracing::warn!(
+                "failed to load tools in {}ms: {:?}",
+                started.elapsed().as_millis(),
+                e
+            ),
+        }
+    }
+
+    // refresh the tool cache periodically (keeps the last list while the backend is unavailable)
+    //
+    // polled: jobworkerp does not expose a change stream of runners and workers to clients
+    fn spawn_tool_refresher(&self, interval: Duration) {
+        let Some(cache) = self.tool_cache.clone() else {
+            return;
+        };
+        let router = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                // refreshed as the server (not forwarding metadata of any session)
+                match router.find_functions(Arc::new(HashMap::new())).await {
+                    Ok(functions) => {
+                        if let Some(diff) = cache.update(functions) {
+                            tracing::info!("tool list changed: {}", diff);
+                        }
+                    }
+                    Err(e) => tracing::warn!("failed to refresh tool list: {:?}", e),
+                }
+            }
+        });
+    }
+
+    // log the per-tool metrics summary periodically
+    fn spawn_metrics_summary(&self, interval: Duration) {
+        let metrics = self.tool_metrics.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // the first tick completes immediately
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                metrics.log_summary();
+            }
+        });
+    }
+
+    // notify the peer whenever the cached tool list changes, until disconnected
+    fn watch_tool_list(&self, peer: Peer<RoleServer>) {
+        let Some(cache) = self.tool_cache.as_ref() else {
+            return;
+        };
+        if self.watching_tool_list.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let mut changes = cache.subscribe_changes();
+        tokio::spawn(async move {
+            loop {
+                match changes.recv().await {
+                    Ok(()) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+                if let Err(e) = peer.notify_tool_list_changed().await {
+                    tracing::info!("failed to notify tool list change (disconnected?): {:?}", e);
+                    break;
+                }
+            }
+        });
+    }
+
+    /// grpc metadata for jobworkerp requests of the current session
+    fn request_metadata(
+        &self,
+        context: &RequestContext<RoleServer>,
+    ) -> Arc<HashMap<String, String>> {
+        self.request_metadata_with_meta(context, None)
+    }
+
+    /// grpc metadata of the session with the allow-listed `_meta` fields of a tool call
+    fn request_metadata_with_meta(
+        &self,
+        context: &RequestContext<RoleServer>,
+        meta: Option<&serde_json::Map<String, serde_json::Value>>,
+    ) -> Arc<HashMap<String, String>> {
+        let client = SessionClient::from_peer_info(context.peer.peer_info());
+        let mut metadata = self.metadata_forwarding.build(&self.session, Some(&client));
+        if let Some(meta) = meta {
+            // session derived values take precedence over client supplied ones
+            for (k, v) in self.metadata_forwarding.build_meta(meta) {
+                metadata.entry(k).or_insert(v);
+            }
+        }
+        // credentials of the backend override forwarded values
+        metadata.extend(
+            self.repository
+                .backend_metadata
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone())),
+        );
+        Arc::new(metadata)
+    }
+
+    // `_meta` of the request (not a part of the tool arguments)
+    fn take_request_meta(
+        request: &mut CallToolRequestParam,
+    ) -> Option<serde_json::Map<String, serde_json::Value>> {
+        match request.arguments.as_mut()?.remove("_meta")? {
+            serde_json::Value::Object(meta) => Some(meta),
+            _ => None,
+        }
+    }
+
+    // id of the MCP request as a log field (numbers without the variant)
+    fn request_id_label(id: &NumberOrString) -> String {
+        match id {
+            NumberOrString::Number(n) => n.to_string(),
+            NumberOrString::String(s) => s.to_string(),
+        }
+    }
+
+    fn resolve_permission(rbac: Option<&RbacConfig>, session: &SessionInfo) -> ToolPermission {
+        match rbac {
+            Some(rbac) => {
+                let permission = rbac.permission_for(session.identity.as_ref());
+                tracing::debug!(
+                    "tool permission for session {} (subject: {:?}): {:?}",
+                    &session.id,
+                    session.subject(),
+                    &permission
+                );
+                permission
+            }
+            None => ToolPermission::allow_all(),
+        }
+    }
+
+    /// Share the session registry with others (e.g. the admin endpoint)
+    pub fn with_session_registry(self, sessions: Arc<SessionRegistry>) -> Self {
+        Self { sessions, ..self }
+    }
+
+    /// Registry of the sessions of the service (shared by its transports)
+    pub fn session_registry(&self) -> Arc<SessionRegistry> {
+        self.sessions.clone()
+    }
+
+    /// Clone the router for a new client session (shares the backend connection and job store)
+    ///
+    /// The session is registered until `on_disconnect` is called.
+    pub fn new_session(&self, session: SessionInfo) -> Self {
+        let permission = Self::resolve_permission(self.rbac.as_deref(), &session);
+        let session_state = self.sessions.register(&session);
+        tracing::debug!("session registered: {}", &session.id);
+        Self {
+            session_state,
+            session: Arc::new(session),
+            permission: Arc::new(permission),
+            subscriptions: Arc::new(Mutex::new(HashMap::new())),
+            watching_tool_list: Arc::new(AtomicBool::new(false)),
+            ..self.clone()
+        }
+    }
+
+    /// Clone the router for a new client session using the backend of the tenant selected by
+    /// the session (connected on the first session of the tenant)
+    ///
+    /// The tool listing state is not shared with the sessions of other backends.
+    pub async fn new_tenant_session(&self, session: SessionInfo) -> Result<Self> {
+        let Some(tenants) = self.tenants.clone() else {
+            return Ok(self.new_session(session));
+        };
+        let Some(tenant) = tenants.config.select(&session)? else {
+            return Ok(self.new_session(session));
+        };
+        let repository = tenants.repository(&tenant, &self.repository).await?;
+        tracing::debug!(
+            "session {} uses the backend of tenant {}",
+            &session.id,
+            &tenant
+        );
+        let router = Self {
+            repository,
+            tenant: Some(tenant.to_string()),
+            tool_cache: None,
+            converted_tools: Arc::new(RwLock::new(None)),
+            aliased_tools: Arc::new(RwLock::new(HashMap::new())),
+            renamed_tools: Arc::new(RwLock::new(HashMap::new())),
+            tool_diagnostics: Arc::new(RwLock::new(Vec::new())),
+            schema_warnings: Arc::new(RwLock::new(Vec::new())),
+            enum_mappings: Arc::new(RwLock::new(HashMap::new())),
+            tool_schemas: Arc::new(RwLock::new(HashMap::new())),
+            ..self.clone()
+        };
+        Ok(router.new_session(session))
+    }
+
+    /// Lifecycle hook called when the session is initialized by the client
+    pub fn on_initialized(&self, peer_info: &ClientInfo) {
+        let client = SessionClient::from_peer_info(peer_info);
+        tracing::info!(
+            client = %client.label(),
+            protocol_version = %client.protocol_version,
+            "session initialized: {}",
+            &self.session.id
+        );
+        let _ = self.session_state.client.set(client);
+    }
+
+    /// Lifecycle hook called when the session is disconnected: in-flight calls and resource
+    /// watchers of the session are cancelled
+    pub fn on_disconnect(&self) {
+        tracing::info!("session disconnected: {}", &self.session.id);
+        self.sessions.unregister(&self.session.id);
+        self.session_state.ct.cancel();
+        if let Ok(mut subscriptions) = self.subscriptions.lock() {
+            for (_, ct) in subscriptions.drain() {
+                ct.cancel();
+            }
+        }
+    }
+
+    // artifacts of the jobs visible to the session
+    fn visible_artifacts(&self) -> Vec<artifact::Artifact> {
+        let jobs = self
+            .visible_jobs(&JobFilter::default())
+            .into_iter()
+            .map(|j| j.id)
+            .collect::<Vec<_>>();
+        let mut artifacts = self.artifacts.list();
+        artifacts.retain(|a| jobs.contains(&a.job_id));
+        artifacts
+    }
+
+    fn read_artifact_resource(&self, uri: &str) -> Result<Option<ResourceContents>, McpError> {
+        let Some(artifact) = self
+            .artifacts
+            .get(uri)
+            .filter(|a| self.visible_artifacts().contains(a))
+        else {
+            return Ok(None);
+        };
+        self.artifacts
+            .read(&artifact)
+            .map(Some)
+            .map_err(|e| McpError::invalid_request(format!("{:#}", e), None))
+    }
+
+    // artifact of the store served by the proxy (of the jobs visible to the session)
+    async fn read_stored_artifact(&self, uri: &str) -> Result<Option<ResourceContents>, McpError> {
+        let (Some(store), Some(key)) = (
+            self.artifact_store.as_ref(),
+            uri.strip_prefix(artifact_store::STORE_URI_PREFIX),
+        ) else {
+            return Ok(None);
+        };
+        let visible = artifact_store::parse_job_key(key).is_some_and(|id| {
+            self.visible_jobs(&JobFilter::default())
+                .iter()
+                .any(|j| j.id == id)
+        });
+        if !visible {
+            return Ok(None);
+        }
+        let content = store
+            .get(key)
+            .await
+            .map_err(|e| McpError::internal_error(format!("{:#}", e), None))?;
+        Ok(content.map(|content| {
+            let mime_type = artifact::mime_type(std::path::Path::new(key)).to_string();
+            match String::from_utf8(content) {
+                Ok(text) => ResourceContents::TextResourceContents {
+                    uri: uri.to_string(),
+                    mime_type: Some(mime_type),
+                    text,
+                },
+                Err(e) => ResourceContents::BlobResourceContents {
+                    uri: uri.to_string(),
+                    mime_type: Some(mime_type),
+                    blob: base64::engine::general_purpose::STANDARD.encode(e.into_bytes()),
+                },
+            }
+        }))
+    }
+
+    // content of a job resource staged as a file
+    fn read_resource_bytes(&self, uri: &str) -> Option<Vec<u8>> {
+        match self.read_job_resource(uri)? {
+            ResourceContents::TextResourceContents { text, .. } => Some(text.into_bytes()),
+            _ => None,
+        }
+    }
+
+    // runner functions visible to the session (their definitions are the runner resources)
+    async fn runner_functions(
+        &self,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<Vec<FunctionSpecs>, McpError> {
+        let functions = self.load_functions(context).await?;
+        let admin_tools = self.admin_tool_names(&functions);
+        Ok(functions
+            .into_iter()
+            .filter(|f| f.worker_id.is_none() && self.is_tool_visible(&f.name, &admin_tools))
+            .collect())
+    }
+
+    fn runner_resources(function: &FunctionSpecs) -> Vec<Resource> {
+        RunnerResourceKind::ALL
+            .into_iter()
+            .filter(|kind| {
+                // the settings proto exists with the settings schema
+                let schema_kind = match kind {
+                    RunnerResourceKind::SettingsProto => RunnerResourceKind::SettingsSchema,
+                    kind => *kind,
+                };
+                runner_resource::schema_text(function, schema_kind).is_some()
+            })
+            .map(|kind| {
+                let mut resource = RawResource::new(
+                    runner_resource::uri(&function.name, kind),
+                    format!("{} {}", &function.name, kind.as_str()),
+                );
+                resource.description = Some(kind.description().to_string());
+                resource.mime_type = Some(kind.mime_type().to_string());
+                resource.no_annotation()
+            })
+            .collect()
+    }
+
+    async fn read_runner_resource(
+        &self,
+        uri: &str,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<Option<ResourceContents>, McpError> {
+        let Some((name, kind)) = runner_resource::parse_uri(uri) else {
+            return Ok(None);
+        };
+        let Some(function) = self
+            .runner_functions(context)
+            .await?
+            .into_iter()
+            .find(|f| f.name == name)
+        else {
+            return Ok(None);
+        };
+        let text = match kind {
+            RunnerResourceKind::SettingsProto => self
+                .repository
+                .find_runner_by_name_with_mcp(self.request_metadata(context), &name)
+                .await
+                .map_err(|e| {
+                    McpError::internal_error(format!("Failed to find runner: {}", e), None)
+                })?
+                .and_then(|(runner, _)| runner.data)
+                .and_then(|data| runner_resource::proto_text(&data, kind)),
+            kind => runner_resource::schema_text(&function, kind),
+        };
+        Ok(text.map(|text| ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(kind.mime_type().to_string()),
+            text,
+        }))
+    }
+
+    fn check_tool_permission(&self, name: &str) -> Result<(), McpError> {
+        if self.permission.is_allowed(name) {
+            return Ok(());
+        }
+        tracing::info!(
+            "tool not allowed: {} (subject: {:?})",
+            name,
+            self.session.subject()
+        );
+        Err(McpError::invalid_request(
+            format!("Tool not allowed: {}", name),
+            None,
+        ))
+    }
+
+    // call a tool of jobworkerp (or a meta tool other than batch_call)
+    async fn dispatch_tool(
+        &self,
+        mut request: CallToolRequestParam,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool_permission(&request.name)?;
+
+        if request.name == meta_tool::HELP {
+            return self.help(context).await;
+        }
+        if request.name == meta_tool::SEARCH_TOOLS {
+            return self.search_tools(&request, context).await;
+        }
+        if request.name == meta_tool::RECENT_RESULTS {
+            let (filter, limit, include_output) =
+                meta_tool::parse_recent_results(request.arguments.as_ref(), &self.session.id)?;
+            let jobs = self.visible_jobs(&filter);
+            return meta_tool::recent_results_result(&jobs, limit, include_output);
+        }
+        if request.name == meta_tool::TOOL_DIAGNOSTICS {
+            let diagnostics = self
+                .tool_diagnostics
+                .read()
+                .map(|d| d.clone())
+                .unwrap_or_default();
+            let warnings = self
+                .schema_warnings
+                .read()
+                .map(|w| w.clone())
+                .unwrap_or_default();
+            return meta_tool::tool_diagnostics_result(&diagnostics, &warnings);
+        }
+        if let Some(diagnostic) = self.find_tool_diagnostic(&request.name) {
+            return Ok(meta_tool::broken_tool_result(&diagnostic));
+        }
+
+        let meta = Self::take_request_meta(&mut request);
+        let mut fixes = vec![];
+        let schema = self
+            .tool_schemas
+            .read()
+            .ok()
+            .and_then(|s| s.get(request.name.as_ref()).cloned());
+        tracing::info_span!("convert_arguments").in_scope(|| {
+            if self.lenient_arguments {
+                fixes.extend(lenient_json::fix_arguments(&mut request.arguments));
+                if let Some(schema) = schema.as_ref() {
+                    fixes.extend(lenient_json::wrap_bare_arguments(
+                        schema,
+                        &mut request.arguments,
+                    ));
+                }
+            }
+            match request
+                .arguments
+                .as_mut()
+                .filter(|_| self.file_staging.is_enabled())
+            {
+                Some(arguments) => self
+                    .file_staging
+                    .stage_arguments(&request.name, arguments, |uri| {
+                        self.read_resource_bytes(uri)
+                    })
+                    .map_err(|e| McpError::invalid_params(format!("{:#}", e), None)),
+                None => Ok(()),
+            }
+        })?;
+        let metadata = self.request_metadata_with_meta(context, meta.as_ref());
+        let options = self.call_options(meta.as_ref())?;
+        // `run_after`, `interval_sec` or `cron` in `_meta` schedules the call
+        let schedule = meta
+            .as_ref()
+            .map(ScheduleSpec::parse)
+            .transpose()
+            .map_err(|e| McpError::invalid_params(e, None))?
+            .flatten();
+        if let Some(spec) = schedule {
+            return self.schedule_call(request, spec, metadata, options);
+        }
+        // arguments as listed (before the conversions) to explain the backend rejections
+        let listed_arguments = schema.as_ref().and(request.arguments.clone());
+        let result = match Self::take_idempotency_key(&mut request, meta.as_ref()) {
+            Some(key) if self.idempotency.is_enabled() => {
+                self.call_idempotent(&key, request, metadata, options).await
+            }
+            _ => self.call_backend(request, metadata, options).await,
+        };
+        result
+            .or_else(|e| Self::argument_error_result(e, schema.as_deref(), listed_arguments))
+            .map(|r| meta_tool::with_argument_fixes(r, &fixes))
+    }
+
+    // tool error with the fields mismatching the listed schema if the backend failed to run the
+    // call (e.g. the arguments could not be converted to the protobuf message)
+    fn argument_error_result(
+        error: McpError,
+        schema: Option<&serde_json::Map<String, serde_json::Value>>,
+        arguments: Option<serde_json::Map<String, serde_json::Value>>,
+    ) -> Result<CallToolResult, McpError> {
+        let (Some(schema), Some(arguments)) = (schema, arguments) else {
+            return Err(error);
+        };
+        if error.code != ErrorCode::INTERNAL_ERROR {
+            return Err(error);
+        }
+        let mut arguments = arguments;
+        arguments.remove("idempotency_key");
+        let errors = argument_errors::field_errors(schema, &serde_json::Value::Object(arguments));
+        if errors.is_empty() {
+            return Err(error);
+        }
+        Ok(argument_errors::field_errors_result(
+            &error.message,
+            &errors,
+        ))
+    }
+
+    // options of the call given by `_meta`
+    fn call_options(
+        &self,
+        meta: Option<&serde_json::Map<String, serde_json::Value>>,
+    ) -> Result<CallOptions, McpError> {
+        Ok(CallOptions {
+            channel: self.call_channel(meta)?,
+            output_format: OutputFormat::from_meta(meta)?,
         })
     }
 
-    // Router should not have any conversion logic
+    // `_meta.channel`: channel of the worker created for a runner call (allow-listed)
+    fn call_channel(
+        &self,
+        meta: Option<&serde_json::Map<String, serde_json::Value>>,
+    ) -> Result<Option<String>, McpError> {
+        let channel = match meta.and_then(|m| m.get("channel")) {
+            None | Some(serde_json::Value::Null) => return Ok(None),
+            Some(serde_json::Value::String(c)) if !c.is_empty() => c,
+            Some(_) => {
+                return Err(McpError::invalid_params(
+                    "_meta.channel must be a non-empty string",
+                    None,
+                ))
+            }
+        };
+        if !self.repository.worker_options.is_channel_allowed(channel) {
+            return Err(McpError::invalid_params(
+                format!("Channel not allowed: {}", channel),
+                None,
+            ));
+        }
+        Ok(Some(channel.clone()))
+    }
+
+    // `idempotency_key` of the arguments (not passed to the tool) or `_meta`
+    fn take_idempotency_key(
+        request: &mut CallToolRequestParam,
+        meta: Option<&serde_json::Map<String, serde_json::Value>>,
+    ) -> Option<String> {
+        let key = request
+            .arguments
+            .as_mut()
+            .and_then(|a| a.remove("idempotency_key"))
+            .or_else(|| meta.and_then(|m| m.get("idempotency_key")).cloned())?;
+        match key {
+            serde_json::Value::String(s) if !s.is_empty() => Some(s),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+    }
+
+    // run the call once per key (per tenant, owner and tool), returning the first result to
+    // duplicates
+    async fn call_idempotent(
+        &self,
+        key: &str,
+        request: CallToolRequestParam,
+        metadata: Arc<HashMap<String, String>>,
+        options: CallOptions,
+    ) -> Result<CallToolResult, McpError> {
+        let cache_key =
+            idempotency::cache_key(self.tenant.as_deref(), &self.session, &request.name, key);
+        match self.idempotency.claim(&cache_key) {
+            Claim::Run(guard) => {
+                guard.complete(self.call_backend(request, metadata, options).await)
+            }
+            Claim::Wait(rx) => {
+                tracing::info!(
+                    "waiting for the call with the same idempotency key: {}",
+                    key
+                );
+                idempotency::wait_result(rx).await
+            }
+            Claim::Done(result) => {
+                tracing::info!("duplicate call with idempotency key: {}", key);
+                Ok(result)
+            }
+        }
+    }
+
+    // call a runner, worker or workflow creation of jobworkerp
+    async fn call_backend(
+        &self,
+        mut request: CallToolRequestParam,
+        metadata: Arc<HashMap<String, String>>,
+        options: CallOptions,
+    ) -> Result<CallToolResult, McpError> {
+        // string enums of the listed schema back to the integer values
+        if let (Some(mapping), Some(arguments)) = (
+            self.enum_mappings
+                .read()
+                .ok()
+                .and_then(|m| m.get(request.name.as_ref()).cloned()),
+            request.arguments.as_mut(),
+        ) {
+            tracing::info_span!("convert_arguments").in_scope(|| mapping.to_integers(arguments));
+        }
+        let renamed = tracing::info_span!("resolve_tool", resolved = tracing::field::Empty)
+            .in_scope(|| {
+                if let Some(scripts) = self.scripts.as_ref() {
+                    // the routed tool must also be allowed
+                    if scripts.route(&mut request)? {
+                        self.check_tool_permission(&request.name)?;
+                    }
+                }
+                // aliases are called by the name of the aliased tool
+                let aliased = self
+                    .aliased_tools
+                    .read()
+                    .ok()
+                    .and_then(|a| a.get(request.name.as_ref()).cloned());
+                if let Some(name) = aliased {
+                    request.name = name.into();
+                    // the aliased tool must also be allowed
+                    self.check_tool_permission(&request.name)?;
+                }
+                self.tool_defaults
+                    .apply(&request.name, &mut request.arguments);
+                tracing::Span::current().record("resolved", request.name.as_ref());
+                Ok::<_, McpError>(
+                    self.renamed_tools
+                        .read()
+                        .ok()
+                        .and_then(|r| r.get(request.name.as_ref()).cloned()),
+                )
+            })?;
+        if let Some(renamed) = renamed {
+            return self
+                .call_renamed_tool(metadata, &request, renamed, options)
+                .await;
+        }
+        // kind prefixed names are routed by the prefix (the backend name follows it)
+        let prefixed = self
+            .kind_prefixed_names
+            .then(|| ToolConverter::strip_kind_prefix(&request.name))
+            .flatten();
+        // rules written against the backend names also apply to the prefixed names
+        if let Some((_, name)) = prefixed {
+            self.check_tool_permission(name)?;
+        }
+        match prefixed {
+            Some((ToolKind::Worker, name)) => {
+                self.handle_worker_call(metadata, &request, name, options)
+                    .await
+            }
+            Some((_, name)) => {
+                self.call_runner(metadata, &request, name, false, options)
+                    .await
+            }
+            None => {
+                self.call_runner(metadata, &request, &request.name, true, options)
+                    .await
+            }
+        }
+    }
+
+    // call the runner of the name (or the worker if not found with `worker_fallback`)
+    async fn call_runner(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        request: &CallToolRequestParam,
+        name: &str,
+        worker_fallback: bool,
+        options: CallOptions,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .repository
+            .find_runner_by_name_with_mcp(metadata.clone(), name)
+            .instrument(tracing::info_span!("resolve_tool", name))
+            .await
+        {
+            Ok(Some((runner, tool_name_opt))) => {
+                self.dispatch_runner(metadata, request, runner, tool_name_opt, options)
+                    .await
+            }
+            Ok(None) if worker_fallback => {
+                self.handle_worker_call(metadata, request, name, options)
+                    .await
+            }
+            Ok(None) => {
+                tracing::info!("runner not found: {}", name);
+                Err(McpError::method_not_found::<CallToolRequestMethod>())
+            }
+            Err(e) => {
+                if let Some(result) = meta_tool::backend_unavailable_result(&e) {
+                    return Ok(result);
+                }
+                tracing::error!("error: {:#?}", &e);
+                Err(McpError::method_not_found::<CallToolRequestMethod>())
+            }
+        }
+    }
+
+    // call the found runner (workflow creation by the reusable workflow runner requires an admin role)
+    async fn dispatch_runner(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        request: &CallToolRequestParam,
+        runner: Runner,
+        tool_name_opt: Option<String>,
+        options: CallOptions,
+    ) -> Result<CallToolResult, McpError> {
+        match runner {
+            Runner {
+                id: Some(rid),
+                data: Some(rdata),
+            } if rdata.runner_type == RunnerType::ReusableWorkflow as i32 => {
+                if !self.permission.can_use_admin_tools() {
+                    return Err(McpError::invalid_request(
+                        "Workflow creation requires an admin role".to_string(),
+                        None,
+                    ));
+                }
+                self.handle_reusable_workflow(metadata, request, rid, rdata)
+                    .await
+            }
+            runner => {
+                self.handle_runner_call(metadata, request, runner, tool_name_opt, options)
+                    .await
+            }
+        }
+    }
+
+    // a call of batch_call/map_call with its own span (errors are returned as the entry)
+    async fn call_item(
+        &self,
+        index: usize,
+        tool: String,
+        mut arguments: serde_json::Map<String, serde_json::Value>,
+        meta: Option<&serde_json::Map<String, serde_json::Value>>,
+        context: &RequestContext<RoleServer>,
+    ) -> serde_json::Value {
+        let span = tracing::info_span!(
+            "batch_item",
+            index,
+            tool_name = %tool,
+            job_id = tracing::field::Empty,
+        );
+        async move {
+            // `_meta` of the batch applies to the calls without their own
+            if let Some(meta) = meta {
+                arguments
+                    .entry("_meta")
+                    .or_insert_with(|| serde_json::Value::Object(meta.clone()));
+            }
+            let result = if meta_tool::COMPOSITE_TOOLS.contains(&tool.as_str()) {
+                Err(McpError::invalid_params(
+                    format!("{} cannot be nested", &tool),
+                    None,
+                ))
+            } else {
+                self.dispatch_tool(
+                    CallToolRequestParam {
+                        name: tool.clone().into(),
+                        arguments: Some(arguments),
+                    },
+                    context,
+                )
+                .await
+            };
+            meta_tool::batch_item_result(index, &tool, result)
+        }
+        .instrument(span)
+        .await
+    }
+
+    // run the calls concurrently, results in the order of the calls
+    async fn batch_call(
+        &self,
+        mut request: CallToolRequestParam,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let meta = Self::take_request_meta(&mut request);
+        let calls = meta_tool::parse_batch_calls(request.arguments.as_ref())?;
+        let results =
+            futures::future::join_all(calls.into_iter().enumerate().map(|(index, call)| {
+                self.call_item(
+                    index,
+                    call.tool,
+                    call.arguments.unwrap_or_default(),
+                    meta.as_ref(),
+                    context,
+                )
+            }))
+            .await;
+        meta_tool::batch_call_result(results)
+    }
+
+    // call the tool for each input with limited concurrency, results in the order of the inputs
+    async fn map_call(
+        &self,
+        mut request: CallToolRequestParam,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let meta = Self::take_request_meta(&mut request);
+        let call = meta_tool::MapCall::parse(request.arguments.as_ref())?;
+        let parallelism = call.parallelism(self.map_call_max_parallelism);
+        let tool = call.tool.clone();
+        let results = futures::stream::iter(call.inputs.into_iter().enumerate())
+            .map(|(index, arguments)| {
+                self.call_item(index, tool.clone(), arguments, meta.as_ref(), context)
+            })
+            .buffered(parallelism)
+            .collect::<Vec<_>>()
+            .await;
+        meta_tool::map_call_result(results)
+    }
 
     async fn handle_reusable_workflow(
         &self,
+        metadata: Arc<HashMap<String, String>>,
         request: &CallToolRequestParam,
         runner_id: RunnerId,
         runner_data: RunnerData,
     ) -> Result<CallToolResult, McpError> {
         tracing::debug!("found calling to reusable workflow: {:?}", &runner_data);
+        if !self.allow_workflow_creation {
+            return Err(McpError::invalid_request(
+                "Workflow creation is disabled by the server policy".to_string(),
+                None,
+            ));
+        }
+        if let Some(result) = meta_tool::check_payload_size(
+            "workflow definition",
+            request.arguments.as_ref(),
+            self.max_workflow_definition_bytes,
+        ) {
+            return Ok(result);
+        }
         match self
             .repository
-            .create_workflow(runner_id, runner_data, request.arguments.clone())
+            .create_workflow(metadata, runner_id, runner_data, request.arguments.clone())
             .await
         {
             Ok(_) => {
@@ -71,6 +1942,9 @@ impl JobworkerpRouter {
                 })
             }
             Err(e) => {
+                if let Some(unavailable) = meta_tool::backend_unavailable_result(&e) {
+                    return Ok(unavailable);
+                }
                 tracing::error!("Failed to create workflow: {}", e);
                 Err(McpError::internal_error(
                     format!("Failed to create workflow: {}", e),
@@ -82,46 +1956,187 @@ impl JobworkerpRouter {
 
     async fn handle_runner_call(
         &self,
+        metadata: Arc<HashMap<String, String>>,
         request: &CallToolRequestParam,
         runner: Runner,
         tool_name_opt: Option<String>,
+        options: CallOptions,
     ) -> Result<CallToolResult, McpError> {
         tracing::debug!("found runner: {:?}, tool: {:?}", &runner, &tool_name_opt);
-        let request_args = request.arguments.clone().unwrap_or_default();
-
-        let result = self
-            .repository
-            .setup_worker_and_enqueue_with_json(&runner, request_args, tool_name_opt)
+        if let Some(name) = runner
+            .data
+            .as_ref()
+            .map(|r| &r.name)
+            .filter(|name| self.disabled_runners.contains(*name))
+        {
+            return Err(McpError::invalid_request(
+                format!("Runner {} is disabled by the server policy", name),
+                None,
+            ));
+        }
+        if let Some(result) = meta_tool::check_payload_size(
+            "arguments",
+            request.arguments.as_ref(),
+            self.max_arguments_bytes,
+        ) {
+            return Ok(result);
+        }
+        let target = runner.data.as_ref().map(|r| r.name.clone());
+        let call = self.tool_call(request, target.unwrap_or_default(), &metadata);
+        self.interceptors
+            .intercept(call, |call| {
+                self.enqueue_runner_call(call, runner, tool_name_opt, options)
+            })
             .await
-            .map_err(|e| match e.downcast_ref() {
-                Some(error::ClientError::NotFound(m)) => {
-                    tracing::info!("Not found: {}", m);
-                    McpError::method_not_found::<CallToolRequestMethod>()
+    }
+
+    async fn enqueue_runner_call(
+        &self,
+        call: ToolCall,
+        runner: Runner,
+        tool_name_opt: Option<String>,
+        options: CallOptions,
+    ) -> Result<CallToolResult, McpError> {
+        let metadata = Arc::new(call.metadata);
+        let request_args = call.arguments;
+        let runner_type = runner.data.as_ref().map(|r| r.runner_type());
+        let repository = self.repository.clone();
+        if self.stream_command_output && runner_type == Some(RunnerType::Command) {
+            // stdout lines are appended to the running job (notified to the log subscribers)
+            let job_store = self.job_store.clone();
+            return self
+                .run_job(
+                    &call.tool_name,
+                    runner_type,
+                    options.output_format,
+                    move |job_id| async move {
+                        repository
+                            .setup_worker_and_enqueue_stream_with_json(
+                                metadata,
+                                &runner,
+                                request_args,
+                                tool_name_opt,
+                                options.channel.as_deref(),
+                                move |chunk| job_store.append_stdout(job_id, chunk),
+                            )
+                            .await
+                    },
+                )
+                .await;
+        }
+        let output_format = options.output_format;
+        self.run_job(
+            &call.tool_name,
+            runner_type,
+            output_format,
+            |_| async move {
+                repository
+                    .setup_worker_and_enqueue_with_json(
+                        metadata,
+                        &runner,
+                        request_args,
+                        tool_name_opt,
+                        options.channel.as_deref(),
+                    )
+                    .await
+            },
+        )
+        .await
+    }
+
+    // the call passed to the interceptors
+    fn tool_call(
+        &self,
+        request: &CallToolRequestParam,
+        target: String,
+        metadata: &HashMap<String, String>,
+    ) -> ToolCall {
+        ToolCall {
+            tool_name: request.name.to_string(),
+            target,
+            arguments: request.arguments.clone().unwrap_or_default(),
+            metadata: metadata.clone(),
+            session_id: self.session.id.clone(),
+            subject: self.session.subject().map(|s| s.to_string()),
+        }
+    }
+
+    // call the shadowed tool by its original name, skipping the lookups of the kinds resolved first
+    async fn call_renamed_tool(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        request: &CallToolRequestParam,
+        renamed: RenamedTool,
+        options: CallOptions,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::debug!("call renamed tool: {:?}", &renamed);
+        self.check_tool_permission(&renamed.original)?;
+        let not_found = |e: Option<anyhow::Error>| {
+            if let Some(e) = e {
+                tracing::error!("Failed to find renamed tool: {}", e);
+            }
+            McpError::method_not_found::<CallToolRequestMethod>()
+        };
+        let resolve_span = tracing::info_span!("resolve_tool", name = %renamed.original);
+        match renamed.kind {
+            ToolKind::Runner | ToolKind::McpTool => {
+                let (runner, tool_name_opt) = if renamed.kind == ToolKind::Runner {
+                    self.repository
+                        .find_runner_by_name_with_mcp(metadata.clone(), &renamed.original)
+                        .instrument(resolve_span)
+                        .await
+                } else {
+                    self.repository
+                        .find_mcp_runner(metadata.clone(), &renamed.original)
+                        .instrument(resolve_span)
+                        .await
                 }
-                Some(e) => {
-                    tracing::error!("Failed to enqueue job: {}", e);
-                    McpError::internal_error(format!("Failed to enqueue job: {}", e), None)
+                .map_err(|e| not_found(Some(e)))?
+                .ok_or_else(|| not_found(None))?;
+                self.dispatch_runner(metadata, request, runner, tool_name_opt, options)
+                    .await
+            }
+            ToolKind::Worker | ToolKind::WorkerMcpTool => {
+                let (worker_data, tool_name_opt) = if renamed.kind == ToolKind::Worker {
+                    self.repository
+                        .find_worker_by_name_with_mcp(metadata.clone(), &renamed.original)
+                        .instrument(resolve_span)
+                        .await
+                } else {
+                    self.repository
+                        .find_mcp_worker(metadata.clone(), &renamed.original)
+                        .instrument(resolve_span)
+                        .await
                 }
-                None => McpError::internal_error(format!("Failed to enqueue job: {}", e), None),
-            })?;
-
-        Ok(CallToolResult {
-            content: vec![Content::json(result)?],
-            is_error: None,
-        })
+                .map_err(|e| not_found(Some(e)))?
+                .ok_or_else(|| not_found(None))?;
+                self.run_worker(metadata, request, worker_data, tool_name_opt, options)
+                    .await
+            }
+        }
     }
 
     async fn handle_worker_call(
         &self,
+        metadata: Arc<HashMap<String, String>>,
         request: &CallToolRequestParam,
+        name: &str,
+        options: CallOptions,
     ) -> Result<CallToolResult, McpError> {
-        tracing::info!("runner not found, run as worker: {:?}", &request.name);
-        let request_args = request.arguments.clone().unwrap_or_default();
-
-        let (worker_data, tool_name_opt) = self
+        tracing::info!("run as worker: {:?}", name);
+        let found = self
             .repository
-            .find_worker_by_name_with_mcp(&request.name)
-            .await
+            .find_worker_by_name_with_mcp(metadata.clone(), name)
+            .instrument(tracing::info_span!("resolve_tool", name))
+            .await;
+        if let Some(unavailable) = found
+            .as_ref()
+            .err()
+            .and_then(meta_tool::backend_unavailable_result)
+        {
+            return Ok(unavailable);
+        }
+        let (worker_data, tool_name_opt) = found
             .map_err(|e| {
                 tracing::error!("Failed to find worker: {}", e);
                 McpError::method_not_found::<CallToolRequestMethod>()
@@ -130,105 +2145,504 @@ impl JobworkerpRouter {
                 tracing::info!("worker not found");
                 McpError::method_not_found::<CallToolRequestMethod>()
             })?;
+        self.run_worker(metadata, request, worker_data, tool_name_opt, options)
+            .await
+    }
 
-        let result = self
-            .repository
-            .enqueue_with_json(&worker_data, request_args, tool_name_opt)
+    async fn run_worker(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        request: &CallToolRequestParam,
+        worker_data: WorkerData,
+        tool_name_opt: Option<String>,
+        options: CallOptions,
+    ) -> Result<CallToolResult, McpError> {
+        // existing workers run on their own channel
+        if let Some(channel) = options
+            .channel
+            .as_ref()
+            .filter(|c| Some(*c) != worker_data.channel.as_ref())
+        {
+            return Err(McpError::invalid_params(
+                format!(
+                    "The channel of worker {} cannot be overridden: {}",
+                    &worker_data.name, channel
+                ),
+                None,
+            ));
+        }
+        if let Some(result) = meta_tool::check_payload_size(
+            "arguments",
+            request.arguments.as_ref(),
+            self.max_arguments_bytes,
+        ) {
+            return Ok(result);
+        }
+        let call = self.tool_call(request, worker_data.name.clone(), &metadata);
+        self.interceptors
+            .intercept(call, |call| async move {
+                let metadata = Arc::new(call.metadata);
+                // workers of mcp server runners are called with the tool name
+                let runner_type = tool_name_opt.as_ref().map(|_| RunnerType::McpServer);
+                let repository = self.repository.clone();
+                self.run_job(
+                    &call.tool_name,
+                    runner_type,
+                    options.output_format,
+                    |_| async move {
+                        repository
+                            .enqueue_with_json(
+                                metadata,
+                                &worker_data,
+                                call.arguments,
+                                tool_name_opt,
+                            )
+                            .await
+                    },
+                )
+                .await
+            })
             .await
-            .map_err(|e| match e.downcast_ref() {
-                Some(error::ClientError::NotFound(m)) => {
-                    tracing::info!("Not found: {}", m);
-                    McpError::method_not_found::<CallToolRequestMethod>()
-                }
-                Some(e) => {
-                    tracing::error!("Failed to enqueue job: {}", e);
-                    McpError::internal_error(format!("Failed to enqueue job: {}", e), None)
-                }
-                None => McpError::internal_error(format!("Failed to enqueue job: {}", e), None),
-            })?;
+    }
 
-        Ok(CallToolResult {
-            content: vec![Content::json(result)?],
-            is_error: None,
-        })
+    // report the call failing with an internal error or a panic (if error reporting is enabled)
+    async fn report_errors(
+        &self,
+        tool_name: &str,
+        arguments: Option<&serde_json::Map<String, serde_json::Value>>,
+        call: impl Future<Output = Result<CallToolResult, McpError>>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(reporter) = self.error_reporter.as_ref() else {
+            return call.await;
+        };
+        let (result, captured) = error_reporter::capture(call).await;
+        if let Some(report) =
+            ErrorReport::of_call(tool_name, &self.session.id, arguments, &result, captured)
+        {
+            reporter.report(report);
+        }
+        result
+    }
+
+    fn enqueue_error(e: anyhow::Error) -> McpError {
+        if !matches!(e.downcast_ref(), Some(error::ClientError::NotFound(_))) {
+            error_reporter::note_error(&e);
+        }
+        match e.downcast_ref() {
+            Some(error::ClientError::NotFound(m)) => {
+                tracing::info!("Not found: {}", m);
+                McpError::method_not_found::<CallToolRequestMethod>()
+            }
+            Some(e) => {
+                tracing::error!("Failed to enqueue job: {}", e);
+                McpError::internal_error(format!("Failed to enqueue job: {}", e), None)
+            }
+            None => McpError::internal_error(format!("Failed to enqueue job: {}", e), None),
+        }
     }
 }
 
 impl ServerHandler for JobworkerpRouter {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
-                protocol_version: ProtocolVersion::V_2024_11_05,
-                capabilities: ServerCapabilities::builder()
-                    .enable_tools()
-                    .build(),
-                server_info: Implementation::from_build_env(),
-                instructions: Some(
-                    "The system runs as an asynchronous job processing server that executes various functions in parallel. It supports general-purpose processing tasks like shell commands and HTTP/gRPC requests, while allowing users to create workflows through JSON-defined specifications. These workflows can compose multiple functions with defined input/output schemas, with all operations managed concurrently for efficient execution.".to_string(),
-                ),
-            }
+            protocol_version: ProtocolVersion::V_2024_11_05,
+            capabilities: ServerCapabilities::builder()
+                .enable_prompts()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .build(),
+            server_info: self.implementation.clone(),
+            instructions: Some(self.instructions.to_string()),
+        }
     }
     #[allow(clippy::manual_async_fn)]
     fn call_tool(
         &self,
         request: CallToolRequestParam,
-        _context: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send + '_ {
+        // fields of structured logs (job_id is recorded when the job is started), with the child
+        // spans resolve_tool, convert_arguments and enqueue for the timeline of the call
+        let span = tracing::info_span!(
+            "call_tool",
+            request_id = %Self::request_id_label(&context.id),
+            session_id = %self.session.id,
+            tool_name = %request.name,
+            job_id = tracing::field::Empty,
+            client = tracing::field::Empty,
+            protocol_version = tracing::field::Empty,
+        );
+        if let Some(client) = self.session_state.client.get() {
+            span.record("client", client.label().as_str());
+            span.record("protocol_version", client.protocol_version.as_str());
+        }
+        let tool_name = request.name.clone();
+        let arguments_hash = panic_guard::arguments_hash(request.arguments.as_ref());
+        let recorded_request = self.recorder.is_some().then(|| request.clone());
+        let reported_arguments = self
+            .error_reporter
+            .as_ref()
+            .filter(|r| r.include_arguments)
+            .and_then(|_| request.arguments.clone());
+        let call = async move {
+            if tracing::enabled!(tracing::Level::DEBUG) && logging::debug_sampled() {
+                tracing::debug!(
+                    "call_tool: {} {:?} (session: {}, subject: {:?})",
+                    &request.name,
+                    request
+                        .arguments
+                        .as_ref()
+                        .map(|a| redaction::redact(&serde_json::Value::Object(a.clone()))),
+                    &self.session.id,
+                    self.session.subject()
+                );
+            }
+            let name = request.name.clone();
+            if meta_tool::COMPOSITE_TOOLS.contains(&name.as_ref()) {
+                self.check_tool_permission(&name)?;
+            }
+            match name.as_ref() {
+                meta_tool::BATCH_CALL => self.batch_call(request, &context).await,
+                meta_tool::MAP_CALL => self.map_call(request, &context).await,
+                meta_tool::SCHEDULE_JOB => self.schedule_job(request, &context).await,
+                meta_tool::LIST_SCHEDULES => {
+                    meta_tool::list_schedules_result(&self.visible_schedules())
+                }
+                meta_tool::CANCEL_SCHEDULE => self.cancel_schedule(&request),
+                meta_tool::RESUME_CALL => self.resume_call(&request).await,
+                meta_tool::FETCH_RESULT_CHUNK => self.fetch_result_chunk(&request),
+                meta_tool::AUTHOR_WORKFLOW => self.author_workflow(&request, &context).await,
+                _ => self.dispatch_tool(request, &context).await,
+            }
+        }
+        .instrument(span);
         async move {
-            tracing::debug!("call_tool: {:?}", &request);
-
-            match self
-                .repository
-                .find_runner_by_name_with_mcp(&request.name)
-                .await
+            let _active_call = self.session_state.start_call();
+            let started = Instant::now();
+            // a panicking call fails alone instead of tearing down the service
+            let call = panic_guard::catch_panic(&tool_name, &arguments_hash, call);
+            let call = self.report_errors(&tool_name, reported_arguments.as_ref(), call);
+            let call = tool_metrics::scope_call(started, call);
+            // in-flight calls are aborted when the session is disconnected
+            let result = tokio::select! {
+                result = call => result,
+                _ = self.session_state.ct.cancelled() => Err(McpError::internal_error(
+                    "Session closed".to_string(),
+                    None,
+                )),
+            };
+            self.tool_metrics
+                .record_call(&tool_name, started.elapsed(), &result);
+            if let (Some(recorder), Some(request)) = (&self.recorder, &recorded_request) {
+                recorder.record(request, &result);
+            }
+            result
+        }
+    }
+    #[allow(clippy::manual_async_fn)]
+    fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ListToolsResult, McpError>> + Send + '_ {
+        async move {
+            self.watch_tool_list(context.peer.clone());
+            let functions = self.load_functions(&context).await?;
+            let result = self.listed_tools(functions);
+            if let Some(recorder) = self.recorder.as_ref() {
+                recorder.record_tools(&result);
+            }
+            Ok(result)
+        }
+    }
+    #[allow(clippy::manual_async_fn)]
+    fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ListPromptsResult, McpError>> + Send + '_ {
+        async move {
+            let prompts = self
+                .visible_tools(&context)
+                .await?
+                .iter()
+                .map(tool_prompt::prompt)
+                .collect();
+            Ok(ListPromptsResult {
+                prompts,
+                next_cursor: None,
+            })
+        }
+    }
+    #[allow(clippy::manual_async_fn)]
+    fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<GetPromptResult, McpError>> + Send + '_ {
+        async move {
+            let not_found =
+                || McpError::invalid_params(format!("Prompt not found: {}", &request.name), None);
+            let name = tool_prompt::tool_name(&request.name).ok_or_else(not_found)?;
+            let tool = self
+                .visible_tools(&context)
+                .await?
+                .into_iter()
+                .find(|t| t.name == name)
+                .ok_or_else(not_found)?;
+            // configured by the name without aliases
+            let original = self
+                .aliased_tools
+                .read()
+                .ok()
+                .and_then(|a| a.get(name).cloned());
+            let config = self
+                .tool_prompts
+                .get(name)
+                .or_else(|| original.and_then(|o| self.tool_prompts.get(&o)));
+            Ok(GetPromptResult {
+                description: tool_prompt::prompt(&tool).description,
+                messages: tool_prompt::prompt_messages(&tool, config),
+            })
+        }
+    }
+    #[allow(clippy::manual_async_fn)]
+    fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ListResourcesResult, McpError>> + Send + '_ {
+        async move {
+            let history = self.visible_jobs(&JobFilter::default());
+            let mut resources: Vec<Resource> = history
+                .iter()
+                .flat_map(|job| {
+                    let mut status = RawResource::new(
+                        JobStore::status_uri(job.id),
+                        format!("{} job {}", job.tool_name, job.id),
+                    );
+                    status.description = Some(format!("status: {}", job.status.as_str()));
+                    status.mime_type = Some("application/json".to_string());
+                    let mut log = RawResource::new(
+                        JobStore::log_uri(job.id),
+                        format!("{} job {} log", job.tool_name, job.id),
+                    );
+                    log.description = Some(format!("status: {}", job.status.as_str()));
+                    log.mime_type = Some("text/plain".to_string());
+                    [status.no_annotation(), log.no_annotation()]
+                })
+                .collect();
+            let mut tool_names = history
+                .iter()
+                .map(|j| j.tool_name.as_str())
+                .collect::<Vec<_>>();
+            tool_names.sort();
+            tool_names.dedup();
+            resources.extend(tool_names.into_iter().map(|name| {
+                let mut resource = RawResource::new(
+                    JobStore::tool_history_uri(name),
+                    format!("{} job history", name),
+                );
+                resource.mime_type = Some("application/json".to_string());
+                resource.no_annotation()
+            }));
+            if history
+                .iter()
+                .any(|j| j.session_id.as_ref() == Some(&self.session.id))
             {
-                Ok(Some((
-                    Runner {
-                        id: Some(rid),
-                        data: Some(rdata),
-                    },
-                    _,
-                ))) if rdata.runner_type == RunnerType::ReusableWorkflow as i32 => {
-                    self.handle_reusable_workflow(&request, rid, rdata).await
-                }
-                Ok(Some((runner, tool_name_opt))) => {
-                    self.handle_runner_call(&request, runner, tool_name_opt)
-                        .await
-                }
-                Ok(None) => self.handle_worker_call(&request).await,
-                Err(e) => {
-                    tracing::error!("error: {:#?}", &e);
-                    Err(McpError::method_not_found::<CallToolRequestMethod>())
+                let mut resource = RawResource::new(
+                    JobStore::session_history_uri(&self.session.id),
+                    "job history of this session".to_string(),
+                );
+                resource.mime_type = Some("application/json".to_string());
+                resources.push(resource.no_annotation());
+            }
+            resources.extend(self.visible_artifacts().into_iter().map(|a| {
+                let mut resource = RawResource::new(a.uri, a.name);
+                resource.description = Some(format!("file of job {}", a.job_id));
+                resource.mime_type = Some(a.mime_type);
+                resource.size = u32::try_from(a.size).ok();
+                resource.no_annotation()
+            }));
+            // the job resources are listed while the backend is unavailable
+            match self.runner_functions(&context).await {
+                Ok(functions) => {
+                    resources.extend(functions.iter().flat_map(Self::runner_resources))
                 }
+                Err(e) => tracing::warn!("failed to list runner resources: {:?}", e),
             }
+            Ok(ListResourcesResult {
+                resources,
+                next_cursor: None,
+            })
         }
     }
     #[allow(clippy::manual_async_fn)]
-    fn list_tools(
+    fn list_resource_templates(
         &self,
         _request: Option<PaginatedRequestParam>,
         _context: RequestContext<RoleServer>,
-    ) -> impl Future<Output = Result<ListToolsResult, McpError>> + Send + '_ {
+    ) -> impl Future<Output = Result<ListResourceTemplatesResult, McpError>> + Send + '_ {
         async move {
-            let functions = if let Some(name) = self.set_name.as_ref() {
-                self.repository
-                    .find_function_list_by_set(name.as_str())
-                    .await
-                    .map_err(|e| {
-                        McpError::internal_error(format!("Failed to find tools: {}", e), None)
-                    })
-            } else {
-                self.repository
-                    .find_function_list(self.exclude_runner_as_tool, self.exclude_worker_as_tool)
-                    .await
-                    .map_err(|e| {
-                        McpError::internal_error(format!("Failed to find tools: {}", e), None)
-                    })
-            }?;
-            ToolConverter::convert_functions_to_mcp_tools(functions).map_err(|e| {
-                McpError::internal_error(format!("Failed to convert tools: {}", e), None)
+            Ok(ListResourceTemplatesResult {
+                resource_templates: vec![
+                    RawResourceTemplate {
+                        uri_template: format!("{}{{id}}", JobStore::URI_SCHEME),
+                        name: "job status".to_string(),
+                        description: Some(
+                            "Status of a job executed through this server (subscribable)"
+                                .to_string(),
+                        ),
+                        mime_type: Some("application/json".to_string()),
+                    }
+                    .no_annotation(),
+                    RawResourceTemplate {
+                        uri_template: format!("{}{{id}}/log", JobStore::URI_SCHEME),
+                        name: "job log".to_string(),
+                        description: Some(
+                            "Output (stdout/stderr or result) of a job executed through this server"
+                                .to_string(),
+                        ),
+                        mime_type: Some("text/plain".to_string()),
+                    }
+                    .no_annotation(),
+                    RawResourceTemplate {
+                        uri_template: format!("{}tool/{{name}}", JobStore::URI_SCHEME),
+                        name: "tool job history".to_string(),
+                        description: Some(
+                            "Recent jobs of a tool executed through this server (newest first)"
+                                .to_string(),
+                        ),
+                        mime_type: Some("application/json".to_string()),
+                    }
+                    .no_annotation(),
+                    RawResourceTemplate {
+                        uri_template: format!("{}session/{{id}}", JobStore::URI_SCHEME),
+                        name: "session job history".to_string(),
+                        description: Some(
+                            "Recent jobs of a session executed through this server (newest first)"
+                                .to_string(),
+                        ),
+                        mime_type: Some("application/json".to_string()),
+                    }
+                    .no_annotation(),
+                    RawResourceTemplate {
+                        uri_template: format!("{}{{job_id}}/{{index}}/{{name}}", artifact::URI_SCHEME),
+                        name: "job artifact".to_string(),
+                        description: Some(
+                            "File produced by a job executed through this server".to_string(),
+                        ),
+                        mime_type: None,
+                    }
+                    .no_annotation(),
+                    RawResourceTemplate {
+                        uri_template: format!("{}{{name}}/{{kind}}", runner_resource::URI_SCHEME),
+                        name: "runner definition".to_string(),
+                        description: Some(format!(
+                            "Definition of a runner (kind: {})",
+                            RunnerResourceKind::ALL
+                                .iter()
+                                .map(|k| k.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )),
+                        mime_type: None,
+                    }
+                    .no_annotation(),
+                ],
+                next_cursor: None,
             })
         }
     }
+    #[allow(clippy::manual_async_fn)]
+    fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ReadResourceResult, McpError>> + Send + '_ {
+        async move {
+            let contents = match self.read_job_resource(&request.uri) {
+                Some(contents) => Some(contents),
+                None => match self.read_artifact_resource(&request.uri)? {
+                    Some(contents) => Some(contents),
+                    None => match self.read_stored_artifact(&request.uri).await? {
+                        Some(contents) => Some(contents),
+                        None => self.read_runner_resource(&request.uri, &context).await?,
+                    },
+                },
+            }
+            .ok_or_else(|| {
+                tracing::info!("resource not found: {}", &request.uri);
+                McpError::resource_not_found(format!("Resource not found: {}", &request.uri), None)
+            })?;
+            Ok(ReadResourceResult {
+                contents: vec![contents],
+            })
+        }
+    }
+    #[allow(clippy::manual_async_fn)]
+    fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        async move {
+            let job_id = JobStore::parse_job_uri(&request.uri)
+                .filter(|id| self.visible_job(*id).is_some())
+                .ok_or_else(|| {
+                    tracing::info!("subscribe: resource not found: {}", &request.uri);
+                    McpError::resource_not_found(
+                        format!("Resource not found: {}", &request.uri),
+                        None,
+                    )
+                })?;
+            tracing::debug!("subscribe: {}", &request.uri);
+            let updates = self.job_store.subscribe_updates();
+            if self
+                .job_store
+                .get(job_id)
+                .is_some_and(|j| j.status == job_store::JobStatus::Running)
+            {
+                self.watch_job_updates(request.uri, job_id, context.peer, updates);
+            } else {
+                // finished jobs will never be updated again: notify the finish at once
+                // (it may have happened after the client read the resource)
+                let peer = context.peer;
+                tokio::spawn(async move {
+                    if let Err(e) = peer
+                        .notify_resource_updated(ResourceUpdatedNotificationParam {
+                            uri: request.uri,
+                        })
+                        .await
+                    {
+                        tracing::info!("failed to notify resource update (disconnected?): {:?}", e);
+                    }
+                });
+            }
+            Ok(())
+        }
+    }
+    #[allow(clippy::manual_async_fn)]
+    fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<(), McpError>> + Send + '_ {
+        async move {
+            tracing::debug!("unsubscribe: {}", &request.uri);
+            if let Some(ct) = self
+                .subscriptions
+                .lock()
+                .ok()
+                .and_then(|mut s| s.remove(&request.uri))
+            {
+                ct.cancel();
+            }
+            Ok(())
+        }
+    }
     fn on_cancelled(
         &self,
         _notification: CancelledNotificationParam,
@@ -0,0 +1,271 @@
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use rmcp::model::{Content, ResourceContents};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+pub const URI_SCHEME: &str = "artifact://";
+
+/// Files produced by jobs, linked from the tool results and readable as resources
+/// (`artifacts` of the config file)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ArtifactsConfig {
+    // directories of the files which can be read through the proxy (shared with the workers)
+    pub dirs: Vec<String>,
+    // output fields holding the paths of the produced files
+    pub fields: Vec<String>,
+    // larger files are linked but cannot be read
+    pub max_read_bytes: u64,
+    // max number of artifacts kept (the oldest jobs first)
+    pub capacity: usize,
+}
+
+impl Default for ArtifactsConfig {
+    fn default() -> Self {
+        Self {
+            dirs: vec![],
+            fields: ["path", "file_path", "output_path", "output_file", "files"]
+                .map(String::from)
+                .to_vec(),
+            max_read_bytes: 10 * 1024 * 1024,
+            capacity: 1000,
+        }
+    }
+}
+
+impl ArtifactsConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.dirs.is_empty()
+    }
+}
+
+/// A file produced by a job
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artifact {
+    pub uri: String,
+    pub job_id: i64,
+    pub name: String,
+    pub path: PathBuf,
+    pub mime_type: String,
+    pub size: u64,
+}
+
+impl Artifact {
+    /// Link to the artifact resource
+    ///
+    /// rmcp does not have the resource_link content type yet: the link is returned as its json
+    /// text.
+    pub fn resource_link(&self) -> Content {
+        Content::text(
+            serde_json::json!({
+                "type": "resource_link",
+                "uri": &self.uri,
+                "name": &self.name,
+                "mimeType": &self.mime_type,
+                "size": self.size,
+            })
+            .to_string(),
+        )
+    }
+}
+
+/// Artifacts of the jobs executed through the proxy (by job id and index in the output)
+pub struct ArtifactRegistry {
+    config: ArtifactsConfig,
+    // canonical paths of the configured directories
+    dirs: Vec<PathBuf>,
+    artifacts: RwLock<BTreeMap<(i64, usize), Artifact>>,
+}
+
+impl ArtifactRegistry {
+    pub fn new(config: ArtifactsConfig) -> Self {
+        let dirs = config
+            .dirs
+            .iter()
+            .filter_map(|d| match std::fs::canonicalize(d) {
+                Ok(dir) => Some(dir),
+                Err(e) => {
+                    tracing::warn!("artifact directory not found: {}: {}", d, e);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            config,
+            dirs,
+            artifacts: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.is_enabled()
+    }
+
+    pub fn uri(job_id: i64, index: usize, name: &str) -> String {
+        format!("{}{}/{}/{}", URI_SCHEME, job_id, index, name)
+    }
+
+    /// Job id and index of an artifact uri
+    pub fn parse_uri(uri: &str) -> Option<(i64, usize)> {
+        let mut parts = uri.strip_prefix(URI_SCHEME)?.splitn(3, '/');
+        let job_id = parts.next()?.parse().ok()?;
+        let index = parts.next()?.parse().ok()?;
+        parts.next().filter(|n| !n.is_empty())?;
+        Some((job_id, index))
+    }
+
+    /// Register the files of the job output in the artifact directories
+    pub fn register_output(&self, job_id: i64, output: &Value) -> Vec<Artifact> {
+        if !self.is_enabled() {
+            return vec![];
+        }
+        let mut paths = vec![];
+        collect_paths(output, &self.config.fields, false, &mut paths);
+        let mut registered = vec![];
+        for path in paths {
+            let Some((path, size)) = self.artifact_path(&path) else {
+                continue;
+            };
+            if registered.iter().any(|a: &Artifact| a.path == path) {
+                continue;
+            }
+            let index = registered.len();
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            registered.push(Artifact {
+                uri: Self::uri(job_id, index, &name),
+                job_id,
+                mime_type: mime_type(&path).to_string(),
+                name,
+                path,
+                size,
+            });
+        }
+        if let Ok(mut artifacts) = self.artifacts.write() {
+            // the same entries if the output is converted again (e.g. resumed calls)
+            for (index, artifact) in registered.iter().enumerate() {
+                artifacts.insert((job_id, index), artifact.clone());
+            }
+            while artifacts.len() > self.config.capacity {
+                artifacts.pop_first();
+            }
+        }
+        registered
+    }
+
+    // canonical path and size of a file in the artifact directories
+    fn artifact_path(&self, path: &str) -> Option<(PathBuf, u64)> {
+        let path = Path::new(path);
+        if !path.is_absolute() {
+            return None;
+        }
+        let path = std::fs::canonicalize(path).ok()?;
+        if !self.dirs.iter().any(|d| path.starts_with(d)) {
+            tracing::debug!("not in the artifact directories: {}", path.display());
+            return None;
+        }
+        let metadata = std::fs::metadata(&path).ok().filter(|m| m.is_file())?;
+        Some((path, metadata.len()))
+    }
+
+    pub fn get(&self, uri: &str) -> Option<Artifact> {
+        let key = Self::parse_uri(uri)?;
+        let artifacts = self.artifacts.read().ok()?;
+        artifacts.get(&key).filter(|a| a.uri == uri).cloned()
+    }
+
+    pub fn list(&self) -> Vec<Artifact> {
+        self.artifacts
+            .read()
+            .map(|a| a.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Content of the artifact (text for text types, base64 blob otherwise)
+    pub fn read(&self, artifact: &Artifact) -> Result<ResourceContents> {
+        let size = std::fs::metadata(&artifact.path)
+            .with_context(|| format!("artifact not found: {}", &artifact.uri))?
+            .len();
+        if size > self.config.max_read_bytes {
+            bail!(
+                "artifact is too large to read: {} bytes (max: {})",
+                size,
+                self.config.max_read_bytes
+            );
+        }
+        let content = std::fs::read(&artifact.path)
+            .with_context(|| format!("Failed to read artifact: {}", &artifact.uri))?;
+        if is_text(&artifact.mime_type) {
+            if let Ok(text) = String::from_utf8(content.clone()) {
+                return Ok(ResourceContents::TextResourceContents {
+                    uri: artifact.uri.clone(),
+                    mime_type: Some(artifact.mime_type.clone()),
+                    text,
+                });
+            }
+        }
+        Ok(ResourceContents::BlobResourceContents {
+            uri: artifact.uri.clone(),
+            mime_type: Some(artifact.mime_type.clone()),
+            blob: base64::engine::general_purpose::STANDARD.encode(content),
+        })
+    }
+}
+
+// string values (and lists of them) of the path fields
+fn collect_paths(value: &Value, fields: &[String], in_field: bool, paths: &mut Vec<String>) {
+    match value {
+        Value::String(s) if in_field => paths.push(s.clone()),
+        Value::Array(list) => list
+            .iter()
+            .for_each(|v| collect_paths(v, fields, in_field, paths)),
+        Value::Object(object) => {
+            for (key, value) in object {
+                collect_paths(value, fields, fields.contains(key), paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_text(mime_type: &str) -> bool {
+    mime_type.starts_with("text/")
+        || mime_type.ends_with("json")
+        || mime_type.ends_with("xml")
+        || mime_type.ends_with("yaml")
+}
+
+/// Mime type by the file extension
+pub fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "wav" => "audio/wav",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,417 @@
+use super::artifact::Artifact;
+use super::settings_presets::resolve_string;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+/// Uris of the stored artifacts served by the proxy (`artifact://store/{key}`)
+pub const STORE_URI_PREFIX: &str = "artifact://store/";
+
+/// Storage of oversized results and produced files (`artifact_store` of the config file)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ArtifactStoreConfig {
+    // None: nothing is offloaded
+    pub backend: Option<ArtifactBackendConfig>,
+    // results larger than this (characters of the json) are stored and linked instead of returned
+    pub offload_result_chars: Option<usize>,
+    // upload the files produced by the jobs (`artifacts`) and link them by the store uris
+    pub upload_files: bool,
+    // larger files are not uploaded (linked as `artifact://` resources of the proxy)
+    pub max_upload_bytes: u64,
+}
+
+impl Default for ArtifactStoreConfig {
+    fn default() -> Self {
+        Self {
+            backend: None,
+            offload_result_chars: None,
+            upload_files: false,
+            max_upload_bytes: 100 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArtifactBackendConfig {
+    Local(LocalStoreConfig),
+    S3(S3StoreConfig),
+}
+
+/// Artifacts stored in a local directory
+#[derive(Debug, Clone, Deserialize)]
+pub struct LocalStoreConfig {
+    pub dir: String,
+    // url serving the directory (e.g. by a web server); read through the proxy if not set
+    pub base_url: Option<String>,
+}
+
+/// Artifacts stored in a bucket of an S3 compatible storage, linked by presigned urls
+#[derive(Debug, Clone, Deserialize)]
+pub struct S3StoreConfig {
+    // e.g. `https://s3.amazonaws.com`, `http://minio:9000`
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default = "S3StoreConfig::default_region")]
+    pub region: String,
+    // `${env:VAR}` and `${file:PATH}` are resolved
+    pub access_key: String,
+    pub secret_key: String,
+    // `{endpoint}/{bucket}/{key}` instead of `{bucket}.{endpoint host}/{key}` (e.g. minio)
+    #[serde(default)]
+    pub path_style: bool,
+    // key prefix of the artifacts
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default = "S3StoreConfig::default_presign_expiry_sec")]
+    pub presign_expiry_sec: u64,
+}
+
+impl S3StoreConfig {
+    fn default_region() -> String {
+        "us-east-1".to_string()
+    }
+
+    fn default_presign_expiry_sec() -> u64 {
+        3600
+    }
+}
+
+/// Storage of artifacts, returning the uris to read them
+pub trait ArtifactStore: Send + Sync {
+    /// Store the content under the key, and return its uri
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        content: Vec<u8>,
+        mime_type: &'a str,
+    ) -> BoxFuture<'a, Result<String>>;
+
+    /// Content of a key for the uris served by the proxy (None if not stored)
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>>>;
+}
+
+/// Store of the config (None if no backend is configured)
+pub fn build(config: &ArtifactStoreConfig) -> Result<Option<Arc<dyn ArtifactStore>>> {
+    let store: Arc<dyn ArtifactStore> = match config.backend.as_ref() {
+        None => return Ok(None),
+        Some(ArtifactBackendConfig::Local(local)) => Arc::new(LocalArtifactStore::new(local)),
+        Some(ArtifactBackendConfig::S3(s3)) => Arc::new(S3ArtifactStore::new(s3)?),
+    };
+    Ok(Some(store))
+}
+
+/// Key of an artifact of a job: `jobs/{job_id}/{name}`
+pub fn job_key(job_id: i64, name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '/') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    format!("jobs/{}/{}", job_id, name.trim_start_matches('/'))
+}
+
+/// Store the output larger than `offload_result_chars` and return its uri and size in bytes
+/// (None if not stored)
+pub async fn offload_output(
+    store: &dyn ArtifactStore,
+    config: &ArtifactStoreConfig,
+    job_id: i64,
+    output: &serde_json::Value,
+) -> Option<(String, usize)> {
+    let max_chars = config.offload_result_chars?;
+    let text = output.to_string();
+    text.chars().nth(max_chars)?;
+    let bytes = text.len();
+    let key = job_key(job_id, "output.json");
+    match store.put(&key, text.into_bytes(), "application/json").await {
+        Ok(uri) => Some((uri, bytes)),
+        Err(e) => {
+            // returned in chunks instead
+            tracing::warn!("Failed to offload the result of job {}: {:?}", job_id, e);
+            None
+        }
+    }
+}
+
+/// Upload a file produced by a job and return its uri (None if not uploaded)
+pub async fn upload_artifact(
+    store: &dyn ArtifactStore,
+    config: &ArtifactStoreConfig,
+    artifact: &Artifact,
+) -> Option<String> {
+    if !config.upload_files || artifact.size > config.max_upload_bytes {
+        return None;
+    }
+    let content = tokio::fs::read(&artifact.path)
+        .await
+        .inspect_err(|e| tracing::warn!("Failed to read artifact {}: {}", &artifact.uri, e))
+        .ok()?;
+    let key = job_key(artifact.job_id, &artifact.name);
+    store
+        .put(&key, content, &artifact.mime_type)
+        .await
+        .inspect_err(|e| tracing::warn!("Failed to upload artifact {}: {:?}", &artifact.uri, e))
+        .ok()
+}
+
+/// Job id of a key of `job_key`
+pub fn parse_job_key(key: &str) -> Option<i64> {
+    key.strip_prefix("jobs/")?.split('/').next()?.parse().ok()
+}
+
+// keys are relative paths without `..`
+fn check_key(key: &str) -> Result<()> {
+    let valid = !key.is_empty()
+        && Path::new(key)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+    if !valid {
+        bail!("invalid artifact key: {}", key);
+    }
+    Ok(())
+}
+
+pub struct LocalArtifactStore {
+    dir: PathBuf,
+    base_url: Option<String>,
+}
+
+impl LocalArtifactStore {
+    pub fn new(config: &LocalStoreConfig) -> Self {
+        Self {
+            dir: PathBuf::from(&config.dir),
+            base_url: config
+                .base_url
+                .as_ref()
+                .map(|u| u.trim_end_matches('/').to_string()),
+        }
+    }
+}
+
+impl ArtifactStore for LocalArtifactStore {
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        content: Vec<u8>,
+        _mime_type: &'a str,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            check_key(key)?;
+            let path = self.dir.join(key);
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await.with_context(|| {
+                    format!("Failed to create artifact directory: {}", parent.display())
+                })?;
+            }
+            tokio::fs::write(&path, content)
+                .await
+                .with_context(|| format!("Failed to store artifact: {}", path.display()))?;
+            Ok(match self.base_url.as_ref() {
+                Some(base_url) => format!("{}/{}", base_url, key),
+                None => format!("{}{}", STORE_URI_PREFIX, key),
+            })
+        })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>>> {
+        Box::pin(async move {
+            check_key(key)?;
+            match tokio::fs::read(self.dir.join(key)).await {
+                Ok(content) => Ok(Some(content)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(anyhow!("Failed to read artifact {}: {}", key, e)),
+            }
+        })
+    }
+}
+
+pub struct S3ArtifactStore {
+    config: S3StoreConfig,
+    // scheme and host[:port] of the endpoint
+    scheme: String,
+    host: String,
+    client: reqwest::Client,
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+impl S3ArtifactStore {
+    pub fn new(config: &S3StoreConfig) -> Result<Self> {
+        let url = reqwest::Url::parse(&config.endpoint)
+            .with_context(|| format!("invalid s3 endpoint: {}", &config.endpoint))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("invalid s3 endpoint: {}", &config.endpoint))?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        let mut config = config.clone();
+        config.access_key =
+            resolve_string(&config.access_key).context("Failed to resolve s3 access key")?;
+        config.secret_key =
+            resolve_string(&config.secret_key).context("Failed to resolve s3 secret key")?;
+        Ok(Self {
+            scheme: url.scheme().to_string(),
+            host: if config.path_style {
+                host
+            } else {
+                format!("{}.{}", &config.bucket, host)
+            },
+            config,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    // path of the object (`/{bucket}/{key}` in the path style)
+    fn object_path(&self, key: &str) -> String {
+        let key = format!("{}{}", &self.config.prefix, key);
+        let key = uri_encode(&key, false);
+        if self.config.path_style {
+            format!("/{}/{}", uri_encode(&self.config.bucket, true), key)
+        } else {
+            format!("/{}", key)
+        }
+    }
+
+    fn scope(&self, now: &DateTime<Utc>) -> String {
+        format!(
+            "{}/{}/s3/aws4_request",
+            now.format("%Y%m%d"),
+            &self.config.region
+        )
+    }
+
+    fn signature(&self, now: &DateTime<Utc>, canonical_request: &str) -> String {
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            now.format("%Y%m%dT%H%M%SZ"),
+            self.scope(now),
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let mut key = format!("AWS4{}", &self.config.secret_key).into_bytes();
+        for part in [
+            now.format("%Y%m%d").to_string().as_str(),
+            self.config.region.as_str(),
+            "s3",
+            "aws4_request",
+            string_to_sign.as_str(),
+        ] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        hex::encode(key)
+    }
+
+    /// Presigned url to get the object of the key (signature version 4)
+    pub fn presigned_url(&self, key: &str, now: DateTime<Utc>) -> String {
+        let path = self.object_path(key);
+        let credential = format!("{}/{}", &self.config.access_key, self.scope(&now));
+        let query = [
+            ("X-Amz-Algorithm", "AWS4-HMAC-SHA256".to_string()),
+            ("X-Amz-Credential", credential),
+            ("X-Amz-Date", now.format("%Y%m%dT%H%M%SZ").to_string()),
+            ("X-Amz-Expires", self.config.presign_expiry_sec.to_string()),
+            ("X-Amz-SignedHeaders", "host".to_string()),
+        ]
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, uri_encode(v, true)))
+        .collect::<Vec<_>>()
+        .join("&");
+        let canonical_request = format!(
+            "GET\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
+            &path, &query, &self.host
+        );
+        format!(
+            "{}://{}{}?{}&X-Amz-Signature={}",
+            &self.scheme,
+            &self.host,
+            &path,
+            &query,
+            self.signature(&now, &canonical_request)
+        )
+    }
+}
+
+impl ArtifactStore for S3ArtifactStore {
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        content: Vec<u8>,
+        mime_type: &'a str,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            check_key(key)?;
+            let now = Utc::now();
+            let path = self.object_path(key);
+            let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+            let payload_hash = hex::encode(Sha256::digest(&content));
+            let signed_headers = "content-type;host;x-amz-content-sha256;x-amz-date";
+            let canonical_request = format!(
+                "PUT\n{}\n\ncontent-type:{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+                &path, mime_type, &self.host, &payload_hash, &amz_date, signed_headers, &payload_hash
+            );
+            let authorization = format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                &self.config.access_key,
+                self.scope(&now),
+                signed_headers,
+                self.signature(&now, &canonical_request)
+            );
+            let response = self
+                .client
+                .put(format!("{}://{}{}", &self.scheme, &self.host, &path))
+                .header("content-type", mime_type)
+                .header("x-amz-content-sha256", &payload_hash)
+                .header("x-amz-date", &amz_date)
+                .header("authorization", authorization)
+                .body(content)
+                .send()
+                .await
+                .context("Failed to upload artifact")?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                bail!("Failed to upload artifact {}: {} {}", key, status, body);
+            }
+            Ok(self.presigned_url(key, now))
+        })
+    }
+
+    // linked by presigned urls (not read through the proxy)
+    fn get<'a>(&'a self, _key: &'a str) -> BoxFuture<'a, Result<Option<Vec<u8>>>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("hmac accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+// percent encoding of signature version 4 (unreserved characters are kept)
+fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut encoded = String::new();
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(b as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
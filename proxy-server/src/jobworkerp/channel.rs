@@ -0,0 +1,192 @@
+use crate::common::env;
+use anyhow::{Context, Result};
+use jobworkerp_client::jobworkerp::service::worker_service_client::WorkerServiceClient;
+use std::time::Duration;
+use tonic::codec::CompressionEncoding;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity};
+
+/// Connection settings of the upstream jobworkerp grpc channel
+#[derive(Debug, Clone, Default)]
+pub struct UpstreamChannelConfig {
+    // CA bundle (PEM) to verify the jobworkerp server certificate (system roots if not set)
+    pub tls_ca_path: Option<String>,
+    // client certificate and key (PEM) for mTLS
+    pub tls_client_cert_path: Option<String>,
+    pub tls_client_key_path: Option<String>,
+    // override of the server name (SNI) used to verify the server certificate
+    pub tls_domain: Option<String>,
+    // interval of http2 keepalive pings (also sent while idle), and the timeout of the ping ack
+    pub keepalive_interval: Option<Duration>,
+    pub keepalive_timeout: Option<Duration>,
+    // compression of the requests and accepted for the responses (gzip or zstd)
+    pub compression: Option<CompressionEncoding>,
+    // message size limits (bytes, tonic defaults if not set)
+    pub max_decoding_message_size: Option<usize>,
+    pub max_encoding_message_size: Option<usize>,
+}
+
+impl UpstreamChannelConfig {
+    pub fn from_env() -> Self {
+        let secs = |name: &str| {
+            env::parse::<u64>(name)
+                .filter(|s| *s > 0)
+                .map(Duration::from_secs)
+        };
+        Self {
+            tls_ca_path: std::env::var("JOBWORKERP_TLS_CA").ok(),
+            tls_client_cert_path: std::env::var("JOBWORKERP_TLS_CLIENT_CERT").ok(),
+            tls_client_key_path: std::env::var("JOBWORKERP_TLS_CLIENT_KEY").ok(),
+            tls_domain: std::env::var("JOBWORKERP_TLS_DOMAIN").ok(),
+            keepalive_interval: secs("JOBWORKERP_KEEPALIVE_INTERVAL_SEC"),
+            keepalive_timeout: secs("JOBWORKERP_KEEPALIVE_TIMEOUT_SEC"),
+            compression: std::env::var("JOBWORKERP_GRPC_COMPRESSION")
+                .ok()
+                .and_then(|s| match Self::parse_compression(&s) {
+                    Ok(c) => c,
+                    Err(e) => {
+                        tracing::warn!("{}", e);
+                        None
+                    }
+                }),
+            max_decoding_message_size: env::parse("JOBWORKERP_MAX_DECODING_MESSAGE_BYTES"),
+            max_encoding_message_size: env::parse("JOBWORKERP_MAX_ENCODING_MESSAGE_BYTES"),
+        }
+    }
+
+    /// `gzip`, `zstd` or `none`
+    pub fn parse_compression(name: &str) -> Result<Option<CompressionEncoding>> {
+        match name.trim().to_lowercase().as_str() {
+            "gzip" => Ok(Some(CompressionEncoding::Gzip)),
+            "zstd" => Ok(Some(CompressionEncoding::Zstd)),
+            "" | "none" => Ok(None),
+            other => Err(anyhow::anyhow!("unknown grpc compression: {}", other)),
+        }
+    }
+
+    // the channel is built by the proxy (not by jobworkerp-client) for TLS and keepalive
+    pub fn needs_custom_channel(&self, address: &str) -> bool {
+        self.is_tls(address) || self.keepalive_interval.is_some()
+    }
+
+    fn with_keepalive(&self, endpoint: Endpoint) -> Endpoint {
+        let Some(interval) = self.keepalive_interval else {
+            return endpoint;
+        };
+        let endpoint = endpoint
+            .http2_keep_alive_interval(interval)
+            .keep_alive_while_idle(true);
+        match self.keepalive_timeout {
+            Some(timeout) => endpoint.keep_alive_timeout(timeout),
+            None => endpoint,
+        }
+    }
+
+    /// Apply the compression and message size limits to a grpc client of the proxy
+    pub fn configure_worker_client(
+        &self,
+        mut client: WorkerServiceClient<Channel>,
+    ) -> WorkerServiceClient<Channel> {
+        if let Some(encoding) = self.compression {
+            client = client.send_compressed(encoding).accept_compressed(encoding);
+        }
+        if let Some(size) = self.max_decoding_message_size {
+            client = client.max_decoding_message_size(size);
+        }
+        if let Some(size) = self.max_encoding_message_size {
+            client = client.max_encoding_message_size(size);
+        }
+        client
+    }
+
+    pub fn is_tls(&self, address: &str) -> bool {
+        address.starts_with("https://")
+            || self.tls_ca_path.is_some()
+            || self.tls_client_cert_path.is_some()
+    }
+
+    fn tls_config(&self) -> Result<ClientTlsConfig> {
+        let mut tls = ClientTlsConfig::new().with_enabled_roots();
+        if let Some(ca_path) = self.tls_ca_path.as_ref() {
+            let ca = std::fs::read_to_string(ca_path)
+                .with_context(|| format!("Failed to read CA bundle: {}", ca_path))?;
+            tls = tls.ca_certificate(Certificate::from_pem(ca));
+        }
+        match (
+            self.tls_client_cert_path.as_ref(),
+            self.tls_client_key_path.as_ref(),
+        ) {
+            (Some(cert_path), Some(key_path)) => {
+                let cert = std::fs::read_to_string(cert_path)
+                    .with_context(|| format!("Failed to read client certificate: {}", cert_path))?;
+                let key = std::fs::read_to_string(key_path)
+                    .with_context(|| format!("Failed to read client key: {}", key_path))?;
+                tls = tls.identity(Identity::from_pem(cert, key));
+            }
+            (None, None) => {}
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "both client certificate and key are required for mTLS"
+                ))
+            }
+        }
+        if let Some(domain) = self.tls_domain.as_ref() {
+            tls = tls.domain_name(domain.clone());
+        }
+        Ok(tls)
+    }
+
+    pub fn endpoint(&self, address: &str) -> Result<Endpoint> {
+        // tls is negotiated by ClientTlsConfig: always use https scheme in that case
+        if !self.is_tls(address) {
+            let endpoint = Endpoint::from_shared(address.to_string())
+                .with_context(|| format!("Invalid jobworkerp address: {}", address))?;
+            return Ok(self.with_keepalive(endpoint));
+        }
+        let address = address.replacen("http://", "https://", 1);
+        let endpoint = Endpoint::from_shared(address.clone())
+            .with_context(|| format!("Invalid jobworkerp address: {}", &address))?
+            .tls_config(self.tls_config()?)?;
+        Ok(self.with_keepalive(endpoint))
+    }
+
+    /// Socket path of a `unix:///path/to/jobworkerp.sock` address
+    pub fn unix_socket_path(address: &str) -> Option<&str> {
+        address.strip_prefix("unix://").filter(|p| !p.is_empty())
+    }
+
+    /// Connect to jobworkerp listening on a unix domain socket (TLS is not used)
+    #[cfg(unix)]
+    pub async fn connect_unix(&self, path: &str) -> Result<Channel> {
+        let socket_path = path.to_string();
+        // the uri is only used for the request headers (the connector ignores it)
+        let channel = self
+            .with_keepalive(Endpoint::from_static("http://localhost"))
+            .connect_with_connector(tower::service_fn(move |_: tonic::transport::Uri| {
+                let socket_path = socket_path.clone();
+                async move {
+                    let stream = tokio::net::UnixStream::connect(socket_path).await?;
+                    Ok::<_, std::io::Error>(hyper_util::rt::TokioIo::new(stream))
+                }
+            }))
+            .await
+            .with_context(|| format!("Failed to connect to jobworkerp socket: {}", path))?;
+        Ok(channel)
+    }
+
+    #[cfg(not(unix))]
+    pub async fn connect_unix(&self, path: &str) -> Result<Channel> {
+        Err(anyhow::anyhow!(
+            "unix domain sockets are not supported on this platform: {}",
+            path
+        ))
+    }
+
+    pub async fn connect(&self, address: &str) -> Result<Channel> {
+        let channel = self
+            .endpoint(address)?
+            .connect()
+            .await
+            .with_context(|| format!("Failed to connect to jobworkerp: {}", address))?;
+        Ok(channel)
+    }
+}
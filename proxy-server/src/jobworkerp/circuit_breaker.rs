@@ -0,0 +1,143 @@
+use anyhow::Result;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Error of the calls rejected while the circuit is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackendUnavailable {
+    pub retry_after: Duration,
+}
+
+impl std::fmt::Display for BackendUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "jobworkerp backend is unavailable (retry after {} seconds)",
+            self.retry_after.as_secs().max(1)
+        )
+    }
+}
+
+impl std::error::Error for BackendUnavailable {}
+
+#[derive(Debug, Default)]
+struct CircuitState {
+    consecutive_failures: u32,
+    // calls fail fast until this time (None: closed)
+    open_until: Option<Instant>,
+    // a probe call has been let through after the open period
+    half_open: bool,
+}
+
+/// Fails calls fast after consecutive backend failures, letting a probe call through every
+/// open period until one succeeds
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    // consecutive failures opening the circuit (0: disabled)
+    threshold: u32,
+    open_duration: Duration,
+    state: Mutex<CircuitState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            threshold,
+            open_duration,
+            state: Mutex::new(CircuitState::default()),
+        }
+    }
+
+    /// A closed breaker with the same settings (for another backend)
+    pub fn with_same_settings(&self) -> Self {
+        Self::new(self.threshold, self.open_duration)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.threshold > 0
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state
+            .lock()
+            .is_ok_and(|s| s.open_until.is_some_and(|t| Instant::now() < t))
+    }
+
+    /// Admit a call (fails while open; a call after the open period is the probe)
+    pub fn acquire(&self) -> Result<(), BackendUnavailable> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let Ok(mut state) = self.state.lock() else {
+            return Ok(());
+        };
+        let Some(open_until) = state.open_until else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        if now < open_until {
+            return Err(BackendUnavailable {
+                retry_after: open_until - now,
+            });
+        }
+        // the other calls fail fast while probing (next probe after another period)
+        tracing::info!("probing jobworkerp backend");
+        state.open_until = Some(now + self.open_duration);
+        state.half_open = true;
+        Ok(())
+    }
+
+    pub fn record_success(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            if state.open_until.is_some() {
+                tracing::info!("jobworkerp backend recovered: circuit closed");
+            }
+            *state = CircuitState::default();
+        }
+    }
+
+    pub fn record_failure(&self) {
+        if !self.is_enabled() {
+            return;
+        }
+        if let Ok(mut state) = self.state.lock() {
+            state.consecutive_failures += 1;
+            if state.half_open || state.consecutive_failures >= self.threshold {
+                if state.open_until.is_none() {
+                    tracing::warn!(
+                        "jobworkerp backend failed {} times in a row: circuit opened for {}s",
+                        state.consecutive_failures,
+                        self.open_duration.as_secs()
+                    );
+                }
+                state.open_until = Some(Instant::now() + self.open_duration);
+                state.half_open = false;
+            }
+        }
+    }
+
+    /// Run the backend call through the circuit (only connection failures count as failures)
+    pub async fn call<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        self.acquire()?;
+        let result = call.await;
+        match result.as_ref() {
+            Err(e) if is_backend_failure(e) => self.record_failure(),
+            _ => self.record_success(),
+        }
+        result
+    }
+}
+
+/// Whether the error means the backend could not be reached
+///
+/// Timeouts (of the job itself, or the client deadline of a long job) and local errors (e.g. io
+/// errors of the file staging) are not failures of the backend.
+pub fn is_backend_failure(e: &anyhow::Error) -> bool {
+    e.chain().any(|cause| {
+        cause.is::<tonic::transport::Error>()
+            || cause
+                .downcast_ref::<tonic::Status>()
+                .is_some_and(|s| s.code() == tonic::Code::Unavailable)
+    })
+}
@@ -0,0 +1,52 @@
+use prost_reflect::MessageDescriptor;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::RwLock;
+
+/// Parsed runner settings descriptors by runner id and the hash of the settings proto
+/// (a runner updated with another proto is parsed again)
+#[derive(Debug)]
+pub struct DescriptorCache<D = MessageDescriptor> {
+    entries: RwLock<HashMap<(i64, u64), Option<D>>>,
+}
+
+impl<D> Default for DescriptorCache<D> {
+    fn default() -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl<D: Clone> DescriptorCache<D> {
+    /// The cached descriptor, or the result of `parse` (cached only if parsed successfully)
+    pub fn get_or_parse<E>(
+        &self,
+        runner_id: i64,
+        settings_proto: &str,
+        parse: impl FnOnce() -> Result<Option<D>, E>,
+    ) -> Result<Option<D>, E> {
+        let mut hasher = DefaultHasher::new();
+        settings_proto.hash(&mut hasher);
+        let key = (runner_id, hasher.finish());
+        if let Some(descriptor) = self.entries.read().ok().and_then(|e| e.get(&key).cloned()) {
+            return Ok(descriptor);
+        }
+        let descriptor = parse()?;
+        if let Ok(mut entries) = self.entries.write() {
+            // descriptors of the previous proto of the runner are no longer used
+            entries.retain(|(id, _), _| *id != runner_id);
+            entries.insert(key, descriptor.clone());
+        }
+        Ok(descriptor)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.read().map(|e| e.len()).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,224 @@
+use super::settings_presets::resolve_string;
+use crate::redaction;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use rmcp::model::{CallToolResult, ErrorCode};
+use rmcp::Error as McpError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+// reports waiting to be sent (further reports are dropped while the webhook is slow)
+const REPORT_QUEUE_SIZE: usize = 100;
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reporting of internal errors and panics of the tool calls to a webhook (`error_reporting` of
+/// the config file)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ErrorReportingConfig {
+    // None: errors are only logged. `${env:VAR}` and `${file:PATH}` are resolved
+    pub webhook_url: Option<String>,
+    // e.g. Authorization (values resolved as the url)
+    pub headers: BTreeMap<String, String>,
+    // sent as `environment` of the reports (e.g. production)
+    pub environment: Option<String>,
+    // send the arguments of the calls (redacted as the logs)
+    pub include_arguments: bool,
+    // reports of the same tool and message are sent once in this period
+    pub dedup_window_sec: u64,
+}
+
+impl Default for ErrorReportingConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            headers: BTreeMap::new(),
+            environment: None,
+            include_arguments: true,
+            dedup_window_sec: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Panic,
+    // errors of the backend or the proxy returned as internal_error
+    InternalError,
+}
+
+/// Error captured while running a call (see [`capture`])
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedError {
+    pub kind: ErrorKind,
+    // messages of the error and its causes
+    pub error_chain: Vec<String>,
+}
+
+tokio::task_local! {
+    // the error noted by the call served by the task
+    static CAPTURED: RefCell<Option<CapturedError>>;
+}
+
+/// Run the call keeping the error noted by [`note_error`] or [`note_panic`]
+pub async fn capture<F: Future>(call: F) -> (F::Output, Option<CapturedError>) {
+    CAPTURED
+        .scope(RefCell::new(None), async move {
+            let output = call.await;
+            let captured = CAPTURED.with(|c| c.borrow_mut().take());
+            (output, captured)
+        })
+        .await
+}
+
+/// Note the error chain of the call failing with an internal error (ignored outside of [`capture`])
+pub fn note_error(error: &anyhow::Error) {
+    note(CapturedError {
+        kind: ErrorKind::InternalError,
+        error_chain: error.chain().map(|e| e.to_string()).collect(),
+    });
+}
+
+/// Note the panic of the call (ignored outside of [`capture`])
+pub fn note_panic(message: &str) {
+    note(CapturedError {
+        kind: ErrorKind::Panic,
+        error_chain: vec![message.to_string()],
+    });
+}
+
+fn note(error: CapturedError) {
+    let _ = CAPTURED.try_with(|c| *c.borrow_mut() = Some(error));
+}
+
+/// Report sent to the webhook as json
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorReport {
+    pub kind: ErrorKind,
+    pub tool_name: String,
+    pub session_id: String,
+    // error returned to the client
+    pub message: String,
+    pub error_chain: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    pub server_version: String,
+    pub timestamp: String,
+}
+
+impl ErrorReport {
+    /// Report of the call result (None if the call did not fail with an internal error or panic)
+    pub fn of_call(
+        tool_name: &str,
+        session_id: &str,
+        arguments: Option<&Map<String, Value>>,
+        result: &Result<CallToolResult, McpError>,
+        captured: Option<CapturedError>,
+    ) -> Option<Self> {
+        let error = result.as_ref().err()?;
+        let (kind, error_chain) = match captured {
+            Some(captured) => (captured.kind, captured.error_chain),
+            None if error.code == ErrorCode::INTERNAL_ERROR => (ErrorKind::InternalError, vec![]),
+            None => return None,
+        };
+        Some(Self {
+            kind,
+            tool_name: tool_name.to_string(),
+            session_id: session_id.to_string(),
+            message: error.message.to_string(),
+            error_chain,
+            arguments: arguments.map(|a| redaction::redact(&Value::Object(a.clone()))),
+            environment: None,
+            server_version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        })
+    }
+}
+
+/// Sends the reports to the webhook in the background (shared by all sessions)
+#[derive(Debug)]
+pub struct ErrorReporter {
+    tx: mpsc::Sender<ErrorReport>,
+    environment: Option<String>,
+    pub include_arguments: bool,
+    dedup_window: Duration,
+    // tool and message -> last sent
+    sent: Mutex<HashMap<(String, String), Instant>>,
+}
+
+impl ErrorReporter {
+    /// Reporter of the config (None if no webhook is configured)
+    pub fn new(config: &ErrorReportingConfig) -> Result<Option<Self>> {
+        let Some(url) = config.webhook_url.as_deref() else {
+            return Ok(None);
+        };
+        let url = resolve_string(url).context("Failed to resolve error reporting webhook url")?;
+        let url = reqwest::Url::parse(&url).context("invalid error reporting webhook url")?;
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in config.headers.iter() {
+            let value = resolve_string(value)
+                .with_context(|| format!("Failed to resolve error reporting header: {}", name))?;
+            headers.insert(
+                reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                    .with_context(|| format!("invalid error reporting header: {}", name))?,
+                value
+                    .parse()
+                    .with_context(|| format!("invalid error reporting header value: {}", name))?,
+            );
+        }
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()?;
+        let (tx, mut rx) = mpsc::channel::<ErrorReport>(REPORT_QUEUE_SIZE);
+        tokio::spawn(async move {
+            while let Some(report) = rx.recv().await {
+                let sent = client
+                    .post(url.clone())
+                    .json(&report)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status());
+                if let Err(e) = sent {
+                    tracing::warn!("Failed to send error report: {}", e);
+                }
+            }
+        });
+        Ok(Some(Self {
+            tx,
+            environment: config.environment.clone(),
+            include_arguments: config.include_arguments,
+            dedup_window: Duration::from_secs(config.dedup_window_sec),
+            sent: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    /// Queue the report unless the same error of the tool was sent in the dedup window (never
+    /// blocks the call)
+    pub fn report(&self, mut report: ErrorReport) {
+        let key = (report.tool_name.clone(), report.message.clone());
+        {
+            let mut sent = self.sent.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            sent.retain(|_, at| now.duration_since(*at) < self.dedup_window);
+            if sent.contains_key(&key) {
+                tracing::debug!("duplicate error report of {} skipped", &key.0);
+                return;
+            }
+            sent.insert(key, now);
+        }
+        report.environment = self.environment.clone();
+        if let Err(e) = self.tx.try_send(report) {
+            tracing::warn!("error report dropped: {}", e);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use crate::rbac::glob_match;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const DEFAULT_DIR_NAME: &str = "jobworkerp-mcp-proxy-files";
+const DEFAULT_FILE_NAME: &str = "file";
+
+/// Files passed in the tool arguments, staged to a directory readable by the workers
+/// (`file_staging` of the config file)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FileStagingConfig {
+    // directory of the staged files (must be shared with the workers on other hosts;
+    // `jobworkerp-mcp-proxy-files` in the temp directory if not set)
+    pub dir: Option<String>,
+    // larger files are rejected
+    pub max_file_bytes: usize,
+    // staged files older than this are removed
+    pub ttl_sec: u64,
+    // tool name (`*` wildcards allowed) -> arguments accepting files (e.g. `input.path`)
+    pub tools: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for FileStagingConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            max_file_bytes: 10 * 1024 * 1024,
+            ttl_sec: 3600,
+            tools: BTreeMap::new(),
+        }
+    }
+}
+
+/// Schema of the file objects accepted in the file arguments
+pub fn file_schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "description": "file staged by the server (replaced with the path of the staged file)",
+        "properties": {
+            "name": {"type": "string", "description": "file name"},
+            "data": {"type": "string", "description": "base64 encoded content"},
+            "text": {"type": "string", "description": "text content"},
+            "uri": {"type": "string", "description": "`data:` uri or uri of a resource of this server"},
+        },
+    })
+}
+
+impl FileStagingConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.tools.is_empty()
+    }
+
+    /// File arguments of the tool (of all the matching patterns)
+    pub fn arguments_of(&self, tool: &str) -> Vec<&str> {
+        let mut arguments = self
+            .tools
+            .iter()
+            .filter(|(pattern, _)| glob_match(pattern, tool))
+            .flat_map(|(_, arguments)| arguments.iter().map(|a| a.as_str()))
+            .collect::<Vec<_>>();
+        arguments.sort_unstable();
+        arguments.dedup();
+        arguments
+    }
+
+    pub fn staging_dir(&self) -> PathBuf {
+        self.dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| std::env::temp_dir().join(DEFAULT_DIR_NAME))
+    }
+
+    /// Let the file arguments of the tool schema accept file objects too
+    pub fn rewrite_schema(&self, tool: &str, schema: &mut Map<String, Value>) {
+        for argument in self.arguments_of(tool) {
+            if let Some(property) = property_schema(schema, argument) {
+                let original = std::mem::take(property);
+                let mut any_of = Map::new();
+                if let Some(description) = original.get("description") {
+                    any_of.insert("description".to_string(), description.clone());
+                }
+                any_of.insert(
+                    "anyOf".to_string(),
+                    Value::Array(vec![Value::Object(original), file_schema()]),
+                );
+                *property = any_of;
+            }
+        }
+    }
+
+    /// Stage the file objects of the file arguments and substitute their paths
+    ///
+    /// `resolve` reads the content of the resource uris. Arguments which are not file objects
+    /// (e.g. paths) are kept as they are. Returns the staged files.
+    pub fn stage_arguments(
+        &self,
+        tool: &str,
+        arguments: &mut Map<String, Value>,
+        resolve: impl Fn(&str) -> Option<Vec<u8>>,
+    ) -> Result<Vec<PathBuf>> {
+        let mut staged = vec![];
+        for argument in self.arguments_of(tool) {
+            let Some(value) = argument_value(arguments, argument) else {
+                continue;
+            };
+            // a list of files
+            let values = match value {
+                Value::Array(values) => values.iter_mut().collect::<Vec<_>>(),
+                value => vec![value],
+            };
+            for value in values {
+                let Some((name, content)) = decode_file(value, &resolve)
+                    .with_context(|| format!("invalid file in `{}`", argument))?
+                else {
+                    continue;
+                };
+                if content.len() > self.max_file_bytes {
+                    bail!(
+                        "file in `{}` is too large: {} bytes (max: {})",
+                        argument,
+                        content.len(),
+                        self.max_file_bytes
+                    );
+                }
+                let path = self.write_file(&name, &content)?;
+                tracing::debug!("staged file of `{}`: {}", argument, path.display());
+                *value = Value::String(path.to_string_lossy().to_string());
+                staged.push(path);
+            }
+        }
+        if !staged.is_empty() {
+            self.remove_expired();
+        }
+        Ok(staged)
+    }
+
+    // each file in its own directory to keep the file name
+    fn write_file(&self, name: &str, content: &[u8]) -> Result<PathBuf> {
+        let dir = self
+            .staging_dir()
+            .join(format!("{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create staging directory: {}", dir.display()))?;
+        let path = dir.join(sanitize_file_name(name));
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to stage file: {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Remove the staged files older than the ttl
+    pub fn remove_expired(&self) {
+        let Ok(entries) = std::fs::read_dir(self.staging_dir()) else {
+            return;
+        };
+        let ttl = Duration::from_secs(self.ttl_sec);
+        for entry in entries.flatten() {
+            let expired = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| SystemTime::now().duration_since(t).ok())
+                .is_some_and(|age| age > ttl);
+            if expired {
+                if let Err(e) = std::fs::remove_dir_all(entry.path()) {
+                    tracing::warn!("Failed to remove staged files: {:?}: {}", entry.path(), e);
+                }
+            }
+        }
+    }
+}
+
+/// Name and content of a file object (None if the value is not a file object)
+pub fn decode_file(
+    value: &Value,
+    resolve: impl Fn(&str) -> Option<Vec<u8>>,
+) -> Result<Option<(String, Vec<u8>)>> {
+    let Value::Object(file) = value else {
+        return Ok(None);
+    };
+    let get = |key: &str| file.get(key).and_then(|v| v.as_str());
+    let name = get("name").unwrap_or(DEFAULT_FILE_NAME).to_string();
+    let content = if let Some(data) = get("data") {
+        decode_base64(data)?
+    } else if let Some(text) = get("text") {
+        text.as_bytes().to_vec()
+    } else if let Some(uri) = get("uri") {
+        match uri.strip_prefix("data:") {
+            Some(data_uri) => decode_data_uri(data_uri)?,
+            None => resolve(uri).ok_or_else(|| anyhow!("resource not found: {}", uri))?,
+        }
+    } else {
+        return Ok(None);
+    };
+    Ok(Some((name, content)))
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u8>> {
+    let data = data.split_whitespace().collect::<String>();
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .context("invalid base64 data")
+}
+
+// `[<mime type>][;base64],<data>`
+fn decode_data_uri(data_uri: &str) -> Result<Vec<u8>> {
+    let (header, data) = data_uri
+        .split_once(',')
+        .ok_or_else(|| anyhow!("invalid data uri"))?;
+    if header.ends_with(";base64") {
+        decode_base64(data)
+    } else {
+        Ok(data.as_bytes().to_vec())
+    }
+}
+
+// the last component of the name, without characters unsafe in file names
+fn sanitize_file_name(name: &str) -> String {
+    let name = Path::new(name)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if name.trim_matches('.').is_empty() {
+        DEFAULT_FILE_NAME.to_string()
+    } else {
+        name
+    }
+}
+
+// value of a dotted argument path (e.g. `input.path`)
+fn argument_value<'a>(arguments: &'a mut Map<String, Value>, path: &str) -> Option<&'a mut Value> {
+    let mut keys = path.split('.');
+    let mut value = arguments.get_mut(keys.next()?)?;
+    for key in keys {
+        value = value.as_object_mut()?.get_mut(key)?;
+    }
+    Some(value)
+}
+
+// schema of a dotted argument path (the item schema for arrays)
+fn property_schema<'a>(
+    schema: &'a mut Map<String, Value>,
+    path: &str,
+) -> Option<&'a mut Map<String, Value>> {
+    let mut current = schema;
+    for key in path.split('.') {
+        current = current
+            .get_mut("properties")?
+            .as_object_mut()?
+            .get_mut(key)?
+            .as_object_mut()?;
+    }
+    if current.get("type").and_then(|t| t.as_str()) == Some("array") {
+        return current.get_mut("items")?.as_object_mut();
+    }
+    Some(current)
+}
@@ -0,0 +1,168 @@
+use super::session::SessionInfo;
+use rmcp::model::CallToolResult;
+use rmcp::Error as McpError;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+pub type CallResult = Result<CallToolResult, McpError>;
+
+enum Entry {
+    // the first call is running (duplicates wait for its result)
+    Pending(watch::Receiver<Option<CallResult>>),
+    // successful result kept for the window
+    Done { result: CallToolResult, at: Instant },
+}
+
+/// Key of the cache: calls are deduplicated per backend (tenant), owner and tool, the owner being
+/// the authenticated subject or the session without authentication
+pub fn cache_key(
+    tenant: Option<&str>,
+    session: &SessionInfo,
+    tool_name: &str,
+    key: &str,
+) -> String {
+    let owner = match session.subject() {
+        Some(subject) => format!("subject:{}", subject),
+        None => format!("session:{}", &session.id),
+    };
+    format!(
+        "{}\0{}\0{}\0{}",
+        tenant.unwrap_or_default(),
+        owner,
+        tool_name,
+        key
+    )
+}
+
+/// What to do with a call with an idempotency key
+pub enum Claim {
+    // first call: run it and complete the guard with the result
+    Run(IdempotencyGuard),
+    // duplicate of a running call
+    Wait(watch::Receiver<Option<CallResult>>),
+    // duplicate of a finished call
+    Done(CallToolResult),
+}
+
+/// Results of tool calls by idempotency key, to avoid running retried calls twice
+pub struct IdempotencyCache {
+    window: Duration,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl IdempotencyCache {
+    pub const DEFAULT_WINDOW: Duration = Duration::from_secs(600);
+
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.window.is_zero()
+    }
+
+    pub fn claim(self: &Arc<Self>, key: &str) -> Claim {
+        let mut entries = match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(e) => e.into_inner(),
+        };
+        entries.retain(|_, e| match e {
+            Entry::Done { at, .. } => at.elapsed() < self.window,
+            Entry::Pending(_) => true,
+        });
+        match entries.get(key) {
+            Some(Entry::Done { result, .. }) => Claim::Done(result.clone()),
+            Some(Entry::Pending(rx)) => Claim::Wait(rx.clone()),
+            None => {
+                let (tx, rx) = watch::channel(None);
+                entries.insert(key.to_string(), Entry::Pending(rx));
+                Claim::Run(IdempotencyGuard {
+                    key: key.to_string(),
+                    tx,
+                    cache: self.clone(),
+                    completed: false,
+                })
+            }
+        }
+    }
+
+    // keep successful results only (failed calls can be retried)
+    fn complete(&self, key: &str, result: &CallResult) {
+        if let Ok(mut entries) = self.entries.lock() {
+            match result {
+                Ok(result) => {
+                    entries.insert(
+                        key.to_string(),
+                        Entry::Done {
+                            result: result.clone(),
+                            at: Instant::now(),
+                        },
+                    );
+                }
+                Err(_) => {
+                    entries.remove(key);
+                }
+            }
+        }
+    }
+
+    fn abandon(&self, key: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            if matches!(entries.get(key), Some(Entry::Pending(_))) {
+                entries.remove(key);
+            }
+        }
+    }
+}
+
+impl Default for IdempotencyCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_WINDOW)
+    }
+}
+
+/// The first call of a key (the key is released if dropped without completion)
+pub struct IdempotencyGuard {
+    key: String,
+    tx: watch::Sender<Option<CallResult>>,
+    cache: Arc<IdempotencyCache>,
+    completed: bool,
+}
+
+impl IdempotencyGuard {
+    pub fn complete(mut self, result: CallResult) -> CallResult {
+        self.cache.complete(&self.key, &result);
+        self.completed = true;
+        let _ = self.tx.send(Some(result.clone()));
+        result
+    }
+}
+
+impl Drop for IdempotencyGuard {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.cache.abandon(&self.key);
+        }
+    }
+}
+
+/// Result of the call a duplicate waited for
+pub async fn wait_result(mut rx: watch::Receiver<Option<CallResult>>) -> CallResult {
+    match rx.wait_for(|r| r.is_some()).await {
+        Ok(result) => result.clone().unwrap_or_else(|| {
+            Err(McpError::internal_error(
+                "the original call was aborted",
+                None,
+            ))
+        }),
+        Err(_) => Err(McpError::internal_error(
+            "the original call was aborted",
+            None,
+        )),
+    }
+}
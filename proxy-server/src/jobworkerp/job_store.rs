@@ -0,0 +1,464 @@
+use super::session::SessionInfo;
+use crate::result_conversion::OutputFormat;
+use jobworkerp_client::jobworkerp::data::RunnerType;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+use tokio::task::AbortHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "running" => Some(JobStatus::Running),
+            "succeeded" => Some(JobStatus::Succeeded),
+            "failed" => Some(JobStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JobEntry {
+    pub id: i64,
+    pub tool_name: String,
+    pub status: JobStatus,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+    pub output: Option<Value>,
+    pub error: Option<String>,
+    // session which called the tool, and its authenticated subject
+    pub session_id: Option<String>,
+    pub owner: Option<String>,
+    // stdout streamed while the job is running (the tail if too long)
+    pub live_stdout: String,
+    // rendering of the output when resumed by a continuation token
+    pub runner_type: Option<RunnerType>,
+    pub output_format: Option<OutputFormat>,
+}
+
+impl JobEntry {
+    /// Whether the session may read the job: jobs of the same subject, or of the same session
+    /// without authentication (admins read all jobs)
+    pub fn is_visible_to(&self, session: &SessionInfo) -> bool {
+        match session.subject() {
+            Some(subject) => self.owner.as_deref() == Some(subject),
+            None => self.session_id.as_deref() == Some(session.id.as_str()),
+        }
+    }
+
+    pub fn to_status_json(&self) -> Value {
+        serde_json::json!({
+            "id": self.id,
+            "tool_name": self.tool_name,
+            "status": self.status.as_str(),
+            "started_at": self.started_at,
+            "finished_at": self.finished_at,
+            "error": self.error,
+            "session_id": self.session_id,
+        })
+    }
+
+    /// Render the job output as a plain text log (stdout/stderr are separated if present)
+    pub fn render_log(&self) -> String {
+        let mut log = format!(
+            "job: {}\ntool: {}\nstatus: {}\n",
+            self.id,
+            self.tool_name,
+            self.status.as_str()
+        );
+        if let Some(output) = self.output.as_ref() {
+            match (
+                output.get("stdout").and_then(|s| s.as_str()),
+                output.get("stderr").and_then(|s| s.as_str()),
+            ) {
+                (None, None) => {
+                    log.push_str("--- output ---\n");
+                    log.push_str(
+                        &serde_json::to_string_pretty(output)
+                            .unwrap_or_else(|_| output.to_string()),
+                    );
+                    log.push('\n');
+                }
+                (stdout, stderr) => {
+                    if let Some(code) = output.get("exit_code") {
+                        log.push_str(&format!("exit_code: {}\n", code));
+                    }
+                    log.push_str("--- stdout ---\n");
+                    log.push_str(stdout.unwrap_or_default());
+                    log.push_str("\n--- stderr ---\n");
+                    log.push_str(stderr.unwrap_or_default());
+                    log.push('\n');
+                }
+            }
+        }
+        if self.output.is_none() && !self.live_stdout.is_empty() {
+            log.push_str("--- stdout (live) ---\n");
+            log.push_str(&self.live_stdout);
+            if !self.live_stdout.ends_with('\n') {
+                log.push('\n');
+            }
+        }
+        if let Some(error) = self.error.as_ref() {
+            log.push_str("--- error ---\n");
+            log.push_str(error);
+            log.push('\n');
+        }
+        log
+    }
+}
+
+/// Conditions of the jobs searched in the job history (all jobs if not set)
+#[derive(Debug, Clone, Default)]
+pub struct JobFilter {
+    // tool name pattern (`*` wildcards allowed)
+    pub tool_name: Option<String>,
+    pub status: Option<JobStatus>,
+    pub session_id: Option<String>,
+    // started at or after / before this time (unix epoch millis)
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+}
+
+impl JobFilter {
+    pub fn matches(&self, job: &JobEntry) -> bool {
+        self.tool_name
+            .as_ref()
+            .is_none_or(|p| crate::rbac::glob_match(p, &job.tool_name))
+            && self.status.is_none_or(|s| s == job.status)
+            && self
+                .session_id
+                .as_ref()
+                .is_none_or(|id| job.session_id.as_ref() == Some(id))
+            && self.since.is_none_or(|t| job.started_at >= t)
+            && self.until.is_none_or(|t| job.started_at < t)
+    }
+}
+
+/// Keeps track of jobs executed through this proxy so that their output can be read as resources
+pub struct JobStore {
+    next_id: AtomicI64,
+    capacity: usize,
+    jobs: RwLock<BTreeMap<i64, JobEntry>>,
+    updates: broadcast::Sender<i64>,
+}
+
+impl JobStore {
+    pub const DEFAULT_CAPACITY: usize = 100;
+    pub const URI_SCHEME: &str = "job://";
+    pub const MAX_LIVE_STDOUT_BYTES: usize = 1024 * 1024;
+
+    pub fn new(capacity: usize) -> Self {
+        let (updates, _) = broadcast::channel(capacity.max(16));
+        Self {
+            next_id: AtomicI64::new(1),
+            capacity,
+            jobs: RwLock::new(BTreeMap::new()),
+            updates,
+        }
+    }
+
+    /// Max number of jobs kept
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Receive ids of jobs whose state has changed
+    pub fn subscribe_updates(&self) -> broadcast::Receiver<i64> {
+        self.updates.subscribe()
+    }
+
+    fn now_millis() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default()
+    }
+
+    pub fn status_uri(id: i64) -> String {
+        format!("{}{}", Self::URI_SCHEME, id)
+    }
+
+    pub fn log_uri(id: i64) -> String {
+        format!("{}{}/log", Self::URI_SCHEME, id)
+    }
+
+    /// Parse `job://{id}/log` into a job id
+    pub fn parse_log_uri(uri: &str) -> Option<i64> {
+        uri.strip_prefix(Self::URI_SCHEME)
+            .and_then(|rest| rest.strip_suffix("/log"))
+            .and_then(|id| id.parse::<i64>().ok())
+    }
+
+    /// Parse `job://{id}` into a job id
+    pub fn parse_status_uri(uri: &str) -> Option<i64> {
+        uri.strip_prefix(Self::URI_SCHEME)
+            .and_then(|id| id.parse::<i64>().ok())
+    }
+
+    /// Job history of a tool (`job://tool/{name}`)
+    pub fn tool_history_uri(tool_name: &str) -> String {
+        format!("{}tool/{}", Self::URI_SCHEME, tool_name)
+    }
+
+    /// Job history of a session (`job://session/{id}`)
+    pub fn session_history_uri(session_id: &str) -> String {
+        format!("{}session/{}", Self::URI_SCHEME, session_id)
+    }
+
+    /// Parse a job history uri into the filter of the jobs
+    pub fn parse_history_uri(uri: &str) -> Option<JobFilter> {
+        let rest = uri.strip_prefix(Self::URI_SCHEME)?;
+        if let Some(tool_name) = rest.strip_prefix("tool/").filter(|n| !n.is_empty()) {
+            Some(JobFilter {
+                tool_name: Some(tool_name.to_string()),
+                ..Default::default()
+            })
+        } else {
+            rest.strip_prefix("session/")
+                .filter(|id| !id.is_empty())
+                .map(|id| JobFilter {
+                    session_id: Some(id.to_string()),
+                    ..Default::default()
+                })
+        }
+    }
+
+    /// Parse any job resource uri (`job://{id}` or `job://{id}/log`) into a job id
+    pub fn parse_job_uri(uri: &str) -> Option<i64> {
+        Self::parse_status_uri(uri).or_else(|| Self::parse_log_uri(uri))
+    }
+
+    pub fn start(&self, tool_name: &str) -> i64 {
+        self.start_in_session(tool_name, None, None)
+    }
+
+    /// Record a job called by the session (with its authenticated subject)
+    pub fn start_in_session(
+        &self,
+        tool_name: &str,
+        session_id: Option<&str>,
+        owner: Option<&str>,
+    ) -> i64 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let entry = JobEntry {
+            id,
+            tool_name: tool_name.to_string(),
+            status: JobStatus::Running,
+            started_at: Self::now_millis(),
+            finished_at: None,
+            output: None,
+            error: None,
+            session_id: session_id.map(|s| s.to_string()),
+            owner: owner.map(|s| s.to_string()),
+            live_stdout: String::new(),
+            runner_type: None,
+            output_format: None,
+        };
+        if let Ok(mut jobs) = self.jobs.write() {
+            jobs.insert(id, entry);
+            // drop the oldest finished jobs first
+            while jobs.len() > self.capacity {
+                let oldest = jobs
+                    .iter()
+                    .find(|(_, j)| j.status != JobStatus::Running)
+                    .or_else(|| jobs.iter().next())
+                    .map(|(id, _)| *id);
+                match oldest {
+                    Some(oldest) => {
+                        jobs.remove(&oldest);
+                    }
+                    None => break,
+                }
+            }
+        }
+        // no receivers is not an error
+        let _ = self.updates.send(id);
+        id
+    }
+
+    pub fn finish(&self, id: i64, result: Result<&Value, String>) {
+        if let Ok(mut jobs) = self.jobs.write() {
+            if let Some(entry) = jobs.get_mut(&id) {
+                entry.finished_at = Some(Self::now_millis());
+                match result {
+                    Ok(output) => {
+                        entry.status = JobStatus::Succeeded;
+                        entry.output = Some(output.clone());
+                        // the output has the whole stdout (kept for failed jobs)
+                        entry.live_stdout.clear();
+                    }
+                    Err(e) => {
+                        entry.status = JobStatus::Failed;
+                        entry.error = Some(e);
+                    }
+                }
+            }
+        }
+        let _ = self.updates.send(id);
+    }
+
+    /// Record how the output of the job is rendered (for `resume_call`)
+    pub fn set_output_options(
+        &self,
+        id: i64,
+        runner_type: Option<RunnerType>,
+        output_format: Option<OutputFormat>,
+    ) {
+        if let Ok(mut jobs) = self.jobs.write() {
+            if let Some(entry) = jobs.get_mut(&id) {
+                entry.runner_type = runner_type;
+                entry.output_format = output_format;
+            }
+        }
+    }
+
+    /// Append the stdout streamed by a running job (notified as an update of the job)
+    pub fn append_stdout(&self, id: i64, chunk: &str) {
+        if chunk.is_empty() {
+            return;
+        }
+        if let Ok(mut jobs) = self.jobs.write() {
+            let Some(entry) = jobs.get_mut(&id).filter(|j| j.status == JobStatus::Running) else {
+                return;
+            };
+            entry.live_stdout.push_str(chunk);
+            let excess = entry
+                .live_stdout
+                .len()
+                .saturating_sub(Self::MAX_LIVE_STDOUT_BYTES);
+            if excess > 0 {
+                let mut cut = excess;
+                while !entry.live_stdout.is_char_boundary(cut) {
+                    cut += 1;
+                }
+                entry.live_stdout.drain(..cut);
+            }
+        }
+        let _ = self.updates.send(id);
+    }
+
+    /// Fail the job if it is still running (e.g. the call was aborted)
+    pub fn cancel_if_running(&self, id: i64, reason: &str) {
+        let running = self.get(id).is_some_and(|j| j.status == JobStatus::Running);
+        if running {
+            self.finish(id, Err(format!("cancelled: {}", reason)));
+        }
+    }
+
+    pub fn get(&self, id: i64) -> Option<JobEntry> {
+        self.jobs
+            .read()
+            .ok()
+            .and_then(|jobs| jobs.get(&id).cloned())
+    }
+
+    /// Wait until the job finishes or the timeout elapses (None if the job is unknown)
+    pub async fn wait_finished(&self, id: i64, timeout: Duration) -> Option<JobEntry> {
+        let mut updates = self.subscribe_updates();
+        let wait = async {
+            loop {
+                match self.get(id) {
+                    Some(job) if job.status == JobStatus::Running => {}
+                    job => return job,
+                }
+                match updates.recv().await {
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return self.get(id),
+                }
+            }
+        };
+        match tokio::time::timeout(timeout, wait).await {
+            Ok(job) => job,
+            Err(_) => self.get(id),
+        }
+    }
+
+    /// Jobs ordered from newest to oldest
+    pub fn list(&self) -> Vec<JobEntry> {
+        self.jobs
+            .read()
+            .map(|jobs| jobs.values().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Jobs matching the filter, from newest to oldest
+    pub fn find(&self, filter: &JobFilter) -> Vec<JobEntry> {
+        self.jobs
+            .read()
+            .map(|jobs| {
+                jobs.values()
+                    .rev()
+                    .filter(|j| filter.matches(j))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Default for JobStore {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+/// Marks the job as cancelled when dropped before it finishes (the call future was dropped)
+pub struct RunningJobGuard {
+    pub id: i64,
+    store: Arc<JobStore>,
+    // task running the job, aborted together
+    abort: Option<AbortHandle>,
+    armed: bool,
+}
+
+impl RunningJobGuard {
+    pub fn new(store: Arc<JobStore>, id: i64) -> Self {
+        Self {
+            id,
+            store,
+            abort: None,
+            armed: true,
+        }
+    }
+
+    /// Also abort the task running the job when dropped
+    pub fn with_abort(mut self, abort: AbortHandle) -> Self {
+        self.abort = Some(abort);
+        self
+    }
+
+    /// Keep the job running after the guard is dropped (e.g. continued in the background)
+    pub fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for RunningJobGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        if let Some(abort) = self.abort.as_ref() {
+            abort.abort();
+        }
+        self.store.cancel_if_running(self.id, "call aborted");
+    }
+}
@@ -0,0 +1,237 @@
+use super::*;
+
+// jobs of the session recorded in the job store (`job://` resources)
+impl JobworkerpRouter {
+    /// Wait for the jobs of the session still running (let complete by the `retain` disconnect
+    /// policy) up to the timeout, returning the number of the jobs left running
+    pub async fn drain_jobs(&self, timeout: Duration) -> usize {
+        let filter = JobFilter {
+            status: Some(JobStatus::Running),
+            session_id: Some(self.session.id.clone()),
+            ..Default::default()
+        };
+        let mut updates = self.job_store.subscribe_updates();
+        let drained = tokio::time::timeout(timeout, async {
+            loop {
+                let running = self.job_store.find(&filter).len();
+                if running == 0 {
+                    break;
+                }
+                tracing::info!("waiting for {} running jobs", running);
+                if let Err(RecvError::Closed) = updates.recv().await {
+                    break;
+                }
+            }
+        })
+        .await;
+        let running = self.job_store.find(&filter);
+        if drained.is_err() {
+            for job in running.iter() {
+                tracing::warn!("job {} ({}) is left running", job.id, &job.tool_name);
+            }
+        }
+        running.len()
+    }
+
+    // jobs of the history visible to the session: those of the same subject (or the same
+    // session without authentication), or all jobs for admins
+    pub(super) fn visible_jobs(&self, filter: &JobFilter) -> Vec<JobEntry> {
+        self.job_store
+            .find(filter)
+            .into_iter()
+            .filter(|job| self.permission.is_admin() || job.is_visible_to(&self.session))
+            .collect()
+    }
+
+    // the job of the id if visible to the session (job ids are sequential: never read a job by
+    // its id without this check)
+    pub(super) fn visible_job(&self, job_id: i64) -> Option<JobEntry> {
+        self.job_store
+            .get(job_id)
+            .filter(|job| self.permission.is_admin() || job.is_visible_to(&self.session))
+    }
+
+    pub(super) fn read_job_resource(&self, uri: &str) -> Option<ResourceContents> {
+        if let Some(filter) = JobStore::parse_history_uri(uri) {
+            let history = meta_tool::job_history(&self.visible_jobs(&filter), true);
+            return Some(ResourceContents::TextResourceContents {
+                uri: uri.to_string(),
+                mime_type: Some("application/json".to_string()),
+                text: serde_json::Value::Array(history).to_string(),
+            });
+        }
+        if let Some(job) = JobStore::parse_log_uri(uri).and_then(|id| self.visible_job(id)) {
+            Some(ResourceContents::text(job.render_log(), uri))
+        } else if let Some(job) =
+            JobStore::parse_status_uri(uri).and_then(|id| self.visible_job(id))
+        {
+            Some(ResourceContents::TextResourceContents {
+                uri: uri.to_string(),
+                mime_type: Some("application/json".to_string()),
+                text: job.to_status_json().to_string(),
+            })
+        } else {
+            None
+        }
+    }
+
+    // notify the peer whenever the job of the subscribed uri is updated, until the job finishes
+    // (updates must be subscribed before checking the status of the job not to miss its finish)
+    pub(super) fn watch_job_updates(
+        &self,
+        uri: String,
+        job_id: i64,
+        peer: Peer<RoleServer>,
+        mut updates: tokio::sync::broadcast::Receiver<i64>,
+    ) {
+        let ct = CancellationToken::new();
+        if let Ok(mut subscriptions) = self.subscriptions.lock() {
+            if let Some(old) = subscriptions.insert(uri.clone(), ct.clone()) {
+                old.cancel();
+            }
+        }
+        let job_store = self.job_store.clone();
+        let subscriptions = self.subscriptions.clone();
+        tokio::spawn(async move {
+            loop {
+                let updated = tokio::select! {
+                    _ = ct.cancelled() => break,
+                    updated = updates.recv() => updated,
+                };
+                match updated {
+                    Ok(id) if id == job_id => {}
+                    // updates of this job may have been skipped (e.g. streamed output)
+                    Err(RecvError::Lagged(_)) => {}
+                    Ok(_) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+                if let Err(e) = peer
+                    .notify_resource_updated(ResourceUpdatedNotificationParam { uri: uri.clone() })
+                    .await
+                {
+                    tracing::info!("failed to notify resource update (disconnected?): {:?}", e);
+                    break;
+                }
+                if job_store
+                    .get(job_id)
+                    .is_none_or(|j| j.status != job_store::JobStatus::Running)
+                {
+                    break;
+                }
+            }
+            tracing::debug!("stop watching resource: {}", &uri);
+            ct.cancel();
+            if let Ok(mut subscriptions) = subscriptions.lock() {
+                // remove only if not replaced by another subscription
+                if subscriptions.get(&uri).is_some_and(|c| c.is_cancelled()) {
+                    subscriptions.remove(&uri);
+                }
+            }
+        });
+    }
+
+    // run the job recording it in the job store. With `call_wait_timeout`, a job running longer
+    // continues in the background and a continuation token is returned for `resume_call`
+    pub(super) async fn run_job<Fut>(
+        &self,
+        tool_name: &str,
+        runner_type: Option<RunnerType>,
+        output_format: Option<OutputFormat>,
+        job: impl FnOnce(i64) -> Fut,
+    ) -> Result<CallToolResult, McpError>
+    where
+        Fut: Future<Output = Result<serde_json::Value>> + Send + 'static,
+    {
+        let job_id = self.job_store.start_in_session(
+            tool_name,
+            Some(self.session.id.as_str()),
+            self.session.subject(),
+        );
+        self.job_store
+            .set_output_options(job_id, runner_type, output_format);
+        if let Some(latency) = tool_metrics::call_elapsed() {
+            self.tool_metrics.record_enqueue(tool_name, latency);
+        }
+        tracing::Span::current().record("job_id", job_id);
+        // enqueue and wait for the result
+        let job = job(job_id).instrument(tracing::info_span!("enqueue", job_id));
+        let result = match (self.call_wait_timeout, self.disconnect_policy) {
+            (None, DisconnectPolicy::Cancel) => {
+                // the job is marked as cancelled if the call is aborted
+                let _job_guard = RunningJobGuard::new(self.job_store.clone(), job_id);
+                job.await
+                    .inspect(|r| self.job_store.finish(job_id, Ok(r)))
+                    .inspect_err(|e| self.job_store.finish(job_id, Err(e.to_string())))
+            }
+            (wait, policy) => {
+                // the job runs in a task, not aborted with the call unless cancelled by the policy
+                let job_store = self.job_store.clone();
+                let handle = tokio::spawn(
+                    async move {
+                        job.await
+                            .inspect(|r| job_store.finish(job_id, Ok(r)))
+                            .inspect_err(|e| job_store.finish(job_id, Err(e.to_string())))
+                    }
+                    .in_current_span(),
+                );
+                let job_guard = (policy == DisconnectPolicy::Cancel).then(|| {
+                    RunningJobGuard::new(self.job_store.clone(), job_id)
+                        .with_abort(handle.abort_handle())
+                });
+                let joined = match wait {
+                    None => handle.await,
+                    Some(wait) => match tokio::time::timeout(wait, handle).await {
+                        Ok(joined) => joined,
+                        Err(_) => {
+                            tracing::info!(
+                                "job {} is still running, return continuation token",
+                                job_id
+                            );
+                            // resumed by the token even if the session is disconnected
+                            if let Some(job_guard) = job_guard {
+                                job_guard.disarm();
+                            }
+                            return meta_tool::continuation_result(job_id, tool_name);
+                        }
+                    },
+                };
+                joined.unwrap_or_else(|e| Err(anyhow::anyhow!("job task failed: {}", e)))
+            }
+        };
+        if let Some(unavailable) = result
+            .as_ref()
+            .err()
+            .and_then(meta_tool::backend_unavailable_result)
+        {
+            return Ok(unavailable);
+        }
+        let result = result.map_err(Self::enqueue_error)?;
+        self.job_output_result(job_id, tool_name, runner_type, output_format, result)
+            .await
+    }
+
+    // the job output as MCP content (see `ResultPipeline`)
+    pub(super) async fn job_output_result(
+        &self,
+        job_id: i64,
+        tool_name: &str,
+        runner_type: Option<RunnerType>,
+        output_format: Option<OutputFormat>,
+        output: serde_json::Value,
+    ) -> Result<CallToolResult, McpError> {
+        let pipeline = ResultPipeline {
+            artifacts: &self.artifacts,
+            artifact_store: self.artifact_store.as_deref(),
+            artifact_store_config: &self.artifact_store_config,
+            projections: &self.result_projections,
+            converters: &self.result_converters,
+            max_result_chars: self.max_result_chars,
+        };
+        let context = ResultContext {
+            job_id,
+            runner_type,
+            output_format,
+        };
+        pipeline.convert(&context, tool_name, output).await
+    }
+}
@@ -0,0 +1,959 @@
+use super::circuit_breaker::BackendUnavailable;
+use super::job_store::{JobEntry, JobFilter, JobStatus, JobStore};
+use super::scheduler::{self, ScheduleStatus};
+use crate::argument_errors::FieldError;
+use crate::lenient_json::ArgumentFix;
+use crate::schema_lint::SchemaWarning;
+use crate::tool_conversion::{ToolConverter, ToolDiagnostic};
+use jobworkerp_client::jobworkerp::{
+    data::RunnerType,
+    function::data::{function_specs, FunctionSpecs},
+};
+use rmcp::model::{
+    CallToolResult, Content, CreateMessageRequestParam, Role, SamplingMessage, Tool,
+};
+use rmcp::Error as McpError;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::time::Duration;
+
+/// Tools provided by this proxy itself (not by jobworkerp)
+pub const TOOL_DIAGNOSTICS: &str = "tool_diagnostics";
+pub const HELP: &str = "help";
+pub const BATCH_CALL: &str = "batch_call";
+pub const MAP_CALL: &str = "map_call";
+pub const SCHEDULE_JOB: &str = "schedule_job";
+pub const LIST_SCHEDULES: &str = "list_schedules";
+pub const CANCEL_SCHEDULE: &str = "cancel_schedule";
+pub const RESUME_CALL: &str = "resume_call";
+pub const FETCH_RESULT_CHUNK: &str = "fetch_result_chunk";
+pub const SEARCH_TOOLS: &str = "search_tools";
+pub const RECENT_RESULTS: &str = "recent_results";
+pub const AUTHOR_WORKFLOW: &str = "author_workflow";
+
+/// Default and max length (characters) of a result chunk
+pub const DEFAULT_CHUNK_LENGTH: usize = 65536;
+pub const MAX_CHUNK_LENGTH: usize = 1024 * 1024;
+
+/// Default wait of `resume_call` (without the call wait timeout)
+pub const DEFAULT_RESUME_WAIT: Duration = Duration::from_secs(60);
+
+/// Meta tools which cannot be called from batch_call, map_call or schedules
+pub const COMPOSITE_TOOLS: [&str; 7] = [
+    BATCH_CALL,
+    MAP_CALL,
+    SCHEDULE_JOB,
+    LIST_SCHEDULES,
+    CANCEL_SCHEDULE,
+    RESUME_CALL,
+    AUTHOR_WORKFLOW,
+];
+
+/// Maximum number of calls in a batch
+pub const MAX_BATCH_ITEMS: usize = 100;
+
+/// Default and max number of tools returned by `search_tools`
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+pub const MAX_SEARCH_LIMIT: usize = 50;
+
+/// Drafts requested from the client LLM by `author_workflow` (retried with the validation errors)
+pub const MAX_AUTHORING_ATTEMPTS: usize = 2;
+pub const DEFAULT_AUTHORING_MAX_TOKENS: u32 = 4096;
+
+/// Outputs of `recent_results` larger than this (characters of the json text) are omitted
+pub const MAX_RECENT_OUTPUT_CHARS: usize = 2000;
+
+fn object_schema(schema: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+    schema.as_object().cloned().unwrap_or_default()
+}
+
+pub fn tool_diagnostics_tool() -> Tool {
+    Tool::new(
+        TOOL_DIAGNOSTICS,
+        "Report the tools which could not be loaded from jobworkerp (e.g. broken input schema) and the reasons, and the schemas which do not conform to the JSON Schema metaschema.",
+        object_schema(serde_json::json!({"type": "object", "properties": {}})),
+    )
+}
+
+pub fn tool_diagnostics_result(
+    diagnostics: &[ToolDiagnostic],
+    warnings: &[SchemaWarning],
+) -> Result<CallToolResult, McpError> {
+    Ok(CallToolResult {
+        content: vec![Content::json(serde_json::json!({
+            "broken_tools": diagnostics,
+            "schema_warning_count": warnings.len(),
+            "schema_warnings": warnings,
+        }))?],
+        is_error: None,
+    })
+}
+
+/// Error result of calling a tool which could not be loaded
+pub fn broken_tool_result(diagnostic: &ToolDiagnostic) -> CallToolResult {
+    CallToolResult {
+        content: vec![Content::text(format!(
+            "Tool '{}' is unavailable because its schema is broken: {}",
+            &diagnostic.name, &diagnostic.message
+        ))],
+        is_error: Some(true),
+    }
+}
+
+/// Append the fixes of the lenient argument parsing (as `_meta` of the last content)
+pub fn with_argument_fixes(mut result: CallToolResult, fixes: &[ArgumentFix]) -> CallToolResult {
+    if fixes.is_empty() {
+        return result;
+    }
+    if let Ok(content) = Content::json(serde_json::json!({"_meta": {"argument_fixes": fixes}})) {
+        result.content.push(content);
+    }
+    result
+}
+
+/// Error result if the payload (bytes of the json text) is larger than the limit
+pub fn check_payload_size(
+    kind: &str,
+    payload: Option<&serde_json::Map<String, serde_json::Value>>,
+    limit: Option<usize>,
+) -> Option<CallToolResult> {
+    let limit = limit?;
+    let size = serde_json::to_vec(payload?).map(|v| v.len()).unwrap_or(0);
+    (size > limit).then(|| CallToolResult {
+        content: vec![Content::text(format!(
+            "The {} is too large: {} bytes (limit: {} bytes). Reduce the size of the input.",
+            kind, size, limit
+        ))],
+        is_error: Some(true),
+    })
+}
+
+/// Error result of the calls failed fast while the backend is unavailable (circuit open)
+pub fn backend_unavailable_result(error: &anyhow::Error) -> Option<CallToolResult> {
+    let unavailable = error.downcast_ref::<BackendUnavailable>()?;
+    Some(CallToolResult {
+        content: vec![Content::text(format!(
+            "The jobworkerp backend is unavailable after repeated failures. Retry after {} seconds.",
+            unavailable.retry_after.as_secs().max(1)
+        ))],
+        is_error: Some(true),
+    })
+}
+
+pub fn help_tool() -> Tool {
+    Tool::new(
+        HELP,
+        "Describe this server: how to use it, the available tools grouped by category, known limitations and example calls. Call this first when unsure how to use the tools.",
+        object_schema(serde_json::json!({"type": "object", "properties": {}})),
+    )
+}
+
+/// Live information of the server described by the help tool
+pub struct HelpInfo<'a> {
+    pub instructions: &'a str,
+    pub functions: &'a [FunctionSpecs],
+    pub unavailable: &'a [ToolDiagnostic],
+    pub request_timeout_sec: u32,
+    pub job_history_size: usize,
+}
+
+impl HelpInfo<'_> {
+    // tool names of the function with the category
+    fn categorized_tools(function: &FunctionSpecs) -> (&'static str, Vec<String>) {
+        if function.runner_type == RunnerType::McpServer as i32 {
+            let names = match function.schema.as_ref() {
+                Some(function_specs::Schema::McpTools(tools)) => tools
+                    .list
+                    .iter()
+                    .map(|t| ToolConverter::combine_names(&function.name, &t.name))
+                    .collect(),
+                _ => vec![],
+            };
+            ("mcp_server", names)
+        } else if function.worker_id.is_some() {
+            ("worker", vec![function.name.clone()])
+        } else if function.runner_type == RunnerType::ReusableWorkflow as i32 {
+            ("workflow_creation", vec![function.name.clone()])
+        } else {
+            ("runner", vec![function.name.clone()])
+        }
+    }
+
+    fn example(category: &str, name: &str) -> Value {
+        let (description, arguments) = match category {
+            "runner" => (
+                "runner: `settings` initializes the runner and `arguments` are passed to the job",
+                serde_json::json!({"settings": {}, "arguments": {}}),
+            ),
+            "worker" => (
+                "worker: the runner settings are preconfigured, pass only `arguments`",
+                serde_json::json!({"arguments": {}}),
+            ),
+            "mcp_server" => (
+                "mcp server tool (`<server>___<tool>`): pass the tool arguments as is",
+                serde_json::json!({}),
+            ),
+            _ => (
+                "workflow creation: pass a workflow definition conforming to the input schema",
+                serde_json::json!({}),
+            ),
+        };
+        serde_json::json!({
+            "description": description,
+            "call": {"name": name, "arguments": arguments},
+        })
+    }
+
+    /// Help document of the tools visible for `is_visible`
+    pub fn render(&self, is_visible: impl Fn(&str) -> bool) -> Value {
+        let mut tools = serde_json::Map::new();
+        let mut result_schemas = serde_json::Map::new();
+        let mut examples = Vec::new();
+        for function in self.functions {
+            let (category, names) = Self::categorized_tools(function);
+            let names = names
+                .into_iter()
+                .filter(|n| is_visible(n) && !self.unavailable.iter().any(|d| &d.name == n))
+                .collect::<Vec<_>>();
+            let Some(first) = names.first() else {
+                continue;
+            };
+            if let Some(schema) = ToolConverter::result_schema(function) {
+                result_schemas.insert(first.clone(), Value::Object(schema));
+            }
+            if !tools.contains_key(category) {
+                examples.push(Self::example(category, first));
+            }
+            if let Value::Array(list) = tools
+                .entry(category)
+                .or_insert_with(|| Value::Array(vec![]))
+            {
+                list.extend(names.into_iter().map(Value::String));
+            }
+        }
+        let meta_tools = [
+            HELP,
+            TOOL_DIAGNOSTICS,
+            BATCH_CALL,
+            MAP_CALL,
+            SCHEDULE_JOB,
+            LIST_SCHEDULES,
+            CANCEL_SCHEDULE,
+            RESUME_CALL,
+            FETCH_RESULT_CHUNK,
+        ]
+        .into_iter()
+        .filter(|n| is_visible(n))
+        .collect::<Vec<_>>();
+        tools.insert("meta".to_string(), serde_json::json!(meta_tools));
+        serde_json::json!({
+            "instructions": self.instructions,
+            "tools": tools,
+            "unavailable_tools": self.unavailable,
+            // schemas of the job results (`output` of the tool results) by tool name
+            "result_schemas": result_schemas,
+            "limitations": [
+                format!("A tool call waits for the job result up to {} seconds.", self.request_timeout_sec),
+                format!("Only the latest {} jobs of tool calls are kept as `job://{{id}}` and `job://{{id}}/log` resources.", self.job_history_size),
+                "Job results are returned as JSON text.",
+            ],
+            "examples": examples,
+        })
+    }
+}
+
+pub fn help_result(help: Value) -> Result<CallToolResult, McpError> {
+    Ok(CallToolResult {
+        content: vec![Content::json(help)?],
+        is_error: None,
+    })
+}
+
+pub fn batch_call_tool() -> Tool {
+    Tool::new(
+        BATCH_CALL,
+        "Call multiple tools concurrently in one request. Returns the results in the order of `calls`; a failed call does not affect the others.",
+        object_schema(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "calls": {
+                    "type": "array",
+                    "description": format!("tool calls to run concurrently (max {})", MAX_BATCH_ITEMS),
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "tool": {"type": "string", "description": "tool name"},
+                            "arguments": {"type": "object", "description": "tool arguments"},
+                        },
+                        "required": ["tool"],
+                    },
+                },
+            },
+            "required": ["calls"],
+        })),
+    )
+}
+
+/// A tool call in a batch
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchCallItem {
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: Option<Map<String, Value>>,
+}
+
+/// Calls of the `batch_call` arguments
+pub fn parse_batch_calls(
+    arguments: Option<&Map<String, Value>>,
+) -> Result<Vec<BatchCallItem>, McpError> {
+    let calls = arguments
+        .and_then(|a| a.get("calls"))
+        .cloned()
+        .ok_or_else(|| McpError::invalid_params("`calls` is required", None))?;
+    let calls = serde_json::from_value::<Vec<BatchCallItem>>(calls)
+        .map_err(|e| McpError::invalid_params(format!("invalid `calls`: {}", e), None))?;
+    if calls.is_empty() || calls.len() > MAX_BATCH_ITEMS {
+        return Err(McpError::invalid_params(
+            format!("`calls` must have 1 to {} items", MAX_BATCH_ITEMS),
+            None,
+        ));
+    }
+    Ok(calls)
+}
+
+/// Result entry of a call in a batch (errors are isolated to the entry)
+pub fn batch_item_result(
+    index: usize,
+    tool: &str,
+    result: Result<CallToolResult, McpError>,
+) -> Value {
+    match result {
+        Ok(result) => serde_json::json!({
+            "index": index,
+            "tool": tool,
+            "is_error": result.is_error.unwrap_or(false),
+            "content": result.content,
+        }),
+        Err(e) => serde_json::json!({
+            "index": index,
+            "tool": tool,
+            "is_error": true,
+            "error": e.message,
+        }),
+    }
+}
+
+pub fn batch_call_result(results: Vec<Value>) -> Result<CallToolResult, McpError> {
+    Ok(CallToolResult {
+        content: vec![Content::json(serde_json::json!({ "results": results }))?],
+        is_error: None,
+    })
+}
+
+pub fn map_call_tool(max_parallelism: usize) -> Tool {
+    Tool::new(
+        MAP_CALL,
+        "Call one tool for each of the argument objects in `inputs`, running up to `parallelism` calls at a time. Returns the result of each input in order with a summary of succeeded/failed calls.",
+        object_schema(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "tool": {"type": "string", "description": "tool name to call for each input"},
+                "inputs": {
+                    "type": "array",
+                    "description": format!("tool arguments of each call (max {})", MAX_BATCH_ITEMS),
+                    "items": {"type": "object"},
+                },
+                "parallelism": {
+                    "type": "integer",
+                    "description": format!("number of concurrent calls (default and max: {})", max_parallelism),
+                    "minimum": 1,
+                },
+            },
+            "required": ["tool", "inputs"],
+        })),
+    )
+}
+
+/// Arguments of `map_call`
+#[derive(Debug, Clone, Deserialize)]
+pub struct MapCall {
+    pub tool: String,
+    pub inputs: Vec<Map<String, Value>>,
+    #[serde(default)]
+    pub parallelism: Option<usize>,
+}
+
+impl MapCall {
+    pub fn parse(arguments: Option<&Map<String, Value>>) -> Result<Self, McpError> {
+        let arguments = arguments
+            .cloned()
+            .ok_or_else(|| McpError::invalid_params("`tool` and `inputs` are required", None))?;
+        let call = serde_json::from_value::<Self>(Value::Object(arguments))
+            .map_err(|e| McpError::invalid_params(format!("invalid arguments: {}", e), None))?;
+        if call.inputs.is_empty() || call.inputs.len() > MAX_BATCH_ITEMS {
+            return Err(McpError::invalid_params(
+                format!("`inputs` must have 1 to {} items", MAX_BATCH_ITEMS),
+                None,
+            ));
+        }
+        Ok(call)
+    }
+
+    /// Concurrency of the calls (capped by the server limit)
+    pub fn parallelism(&self, max_parallelism: usize) -> usize {
+        self.parallelism
+            .unwrap_or(max_parallelism)
+            .clamp(1, max_parallelism.max(1))
+    }
+}
+
+/// Results of `map_call` (entries of `batch_item_result`) with the summary
+pub fn map_call_result(results: Vec<Value>) -> Result<CallToolResult, McpError> {
+    let failed = results
+        .iter()
+        .filter(|r| r["is_error"].as_bool().unwrap_or(false))
+        .count();
+    Ok(CallToolResult {
+        content: vec![Content::json(serde_json::json!({
+            "summary": {
+                "total": results.len(),
+                "succeeded": results.len() - failed,
+                "failed": failed,
+            },
+            "results": results,
+        }))?],
+        is_error: None,
+    })
+}
+
+pub fn schedule_job_tool() -> Tool {
+    Tool::new(
+        SCHEDULE_JOB,
+        "Schedule a tool call to run later (`run_after`) or periodically (`interval_sec` or `cron`). Returns a schedule id for `list_schedules` and `cancel_schedule`. Results of the runs are available as `job://` resources. The same can be done by adding `run_after`, `interval_sec` or `cron` to `_meta` of a tool call.",
+        object_schema(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "tool": {"type": "string", "description": "tool name to call"},
+                "arguments": {"type": "object", "description": "tool arguments"},
+                "run_after": {
+                    "type": ["string", "integer"],
+                    "description": "run once at the time (RFC 3339 timestamp or unix epoch millis)",
+                },
+                "interval_sec": {
+                    "type": "integer",
+                    "description": "run repeatedly with the interval in seconds",
+                    "minimum": 1,
+                },
+                "cron": {
+                    "type": "string",
+                    "description": "run repeatedly at the times of the cron expression in UTC (`min hour day month weekday`, e.g. `0 9 * * *`)",
+                },
+            },
+            "required": ["tool"],
+        })),
+    )
+}
+
+pub fn list_schedules_tool() -> Tool {
+    Tool::new(
+        LIST_SCHEDULES,
+        "List the scheduled tool calls with their next run time (unix epoch millis), run count and last error.",
+        object_schema(serde_json::json!({"type": "object", "properties": {}})),
+    )
+}
+
+pub fn cancel_schedule_tool() -> Tool {
+    Tool::new(
+        CANCEL_SCHEDULE,
+        "Cancel a scheduled tool call.",
+        object_schema(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "schedule_id": {"type": "integer", "description": "schedule id returned by schedule_job"},
+            },
+            "required": ["schedule_id"],
+        })),
+    )
+}
+
+/// Target tool and arguments of `schedule_job`
+pub fn parse_schedule_job(
+    arguments: Option<&Map<String, Value>>,
+) -> Result<(String, Map<String, Value>), McpError> {
+    let tool = arguments
+        .and_then(|a| a.get("tool"))
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| McpError::invalid_params("`tool` is required", None))?;
+    let tool_arguments = match arguments.and_then(|a| a.get("arguments")) {
+        Some(Value::Object(a)) => a.clone(),
+        Some(Value::Null) | None => Map::new(),
+        Some(_) => {
+            return Err(McpError::invalid_params(
+                "`arguments` must be an object",
+                None,
+            ))
+        }
+    };
+    Ok((tool.to_string(), tool_arguments))
+}
+
+pub fn parse_schedule_id(arguments: Option<&Map<String, Value>>) -> Result<i64, McpError> {
+    arguments
+        .and_then(|a| a.get("schedule_id"))
+        .and_then(|id| id.as_i64())
+        .ok_or_else(|| McpError::invalid_params("`schedule_id` is required", None))
+}
+
+pub fn schedule_result(schedule: &ScheduleStatus) -> Result<CallToolResult, McpError> {
+    Ok(CallToolResult {
+        content: vec![Content::json(serde_json::json!({ "schedule": schedule }))?],
+        is_error: None,
+    })
+}
+
+pub fn list_schedules_result(schedules: &[ScheduleStatus]) -> Result<CallToolResult, McpError> {
+    Ok(CallToolResult {
+        content: vec![Content::json(
+            serde_json::json!({ "schedules": schedules }),
+        )?],
+        is_error: None,
+    })
+}
+
+pub fn resume_call_tool() -> Tool {
+    Tool::new(
+        RESUME_CALL,
+        "Wait for the result of a tool call which returned a `continuation_token` because the job was still running. Returns the result, or a new continuation token if the job is still running after `wait_sec`.",
+        object_schema(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "continuation_token": {"type": "string", "description": "continuation token of the tool call"},
+                "wait_sec": {"type": "integer", "description": "max seconds to wait for the result", "minimum": 0},
+            },
+            "required": ["continuation_token"],
+        })),
+    )
+}
+
+/// Result of a call whose job is still running (resumed by `resume_call`)
+pub fn continuation_result(job_id: i64, tool_name: &str) -> Result<CallToolResult, McpError> {
+    Ok(CallToolResult {
+        content: vec![Content::json(serde_json::json!({
+            "status": "running",
+            "tool": tool_name,
+            "continuation_token": job_id.to_string(),
+            "message": format!("The job is still running. Call `{}` with the continuation_token to get the result.", RESUME_CALL),
+        }))?],
+        is_error: None,
+    })
+}
+
+/// Job id and wait of the `resume_call` arguments (wait is capped by `max_wait`)
+pub fn parse_resume_call(
+    arguments: Option<&Map<String, Value>>,
+    max_wait: Duration,
+) -> Result<(i64, Duration), McpError> {
+    let job_id = match arguments.and_then(|a| a.get("continuation_token")) {
+        Some(Value::String(s)) => s.parse::<i64>().ok(),
+        Some(Value::Number(n)) => n.as_i64(),
+        _ => None,
+    }
+    .ok_or_else(|| McpError::invalid_params("valid `continuation_token` is required", None))?;
+    let wait = arguments
+        .and_then(|a| a.get("wait_sec"))
+        .and_then(|w| w.as_u64())
+        .map(|w| Duration::from_secs(w).min(max_wait))
+        .unwrap_or(max_wait);
+    Ok((job_id, wait))
+}
+
+pub fn fetch_result_chunk_tool() -> Tool {
+    Tool::new(
+        FETCH_RESULT_CHUNK,
+        "Read a part of the result (JSON text) of a job executed by a tool call. Use it to page through a result which was too large to be returned at once.",
+        object_schema(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "job_id": {"type": "integer", "description": "job id of the tool call"},
+                "offset": {"type": "integer", "description": "start position in characters (default: 0)", "minimum": 0},
+                "length": {
+                    "type": "integer",
+                    "description": format!("number of characters (default: {}, max: {})", DEFAULT_CHUNK_LENGTH, MAX_CHUNK_LENGTH),
+                    "minimum": 1,
+                },
+            },
+            "required": ["job_id"],
+        })),
+    )
+}
+
+/// Job id, offset and length of the `fetch_result_chunk` arguments
+pub fn parse_fetch_result_chunk(
+    arguments: Option<&Map<String, Value>>,
+) -> Result<(i64, usize, usize), McpError> {
+    let get = |key: &str| arguments.and_then(|a| a.get(key));
+    let job_id = get("job_id")
+        .and_then(|id| id.as_i64())
+        .ok_or_else(|| McpError::invalid_params("`job_id` is required", None))?;
+    let offset = get("offset").and_then(|o| o.as_u64()).unwrap_or(0) as usize;
+    let length = get("length")
+        .and_then(|l| l.as_u64())
+        .map(|l| (l as usize).clamp(1, MAX_CHUNK_LENGTH))
+        .unwrap_or(DEFAULT_CHUNK_LENGTH);
+    Ok((job_id, offset, length))
+}
+
+/// A part of the job output text with the position of the next chunk
+pub fn result_chunk(job_id: i64, text: &str, offset: usize, length: usize) -> Value {
+    let total_length = text.chars().count();
+    let data = text.chars().skip(offset).take(length).collect::<String>();
+    let end = offset.saturating_add(length).min(total_length);
+    serde_json::json!({
+        "job_id": job_id,
+        "offset": offset,
+        "length": end.saturating_sub(offset),
+        "total_length": total_length,
+        "next_offset": (end < total_length).then_some(end),
+        "data": data,
+    })
+}
+
+/// First chunk of the output if larger than `max_chars` (None if not oversized)
+pub fn oversized_result(
+    job_id: i64,
+    output: &Value,
+    max_chars: Option<usize>,
+) -> Option<Result<CallToolResult, McpError>> {
+    let max_chars = max_chars?;
+    let text = output.to_string();
+    text.chars().nth(max_chars)?;
+    let mut chunk = result_chunk(job_id, &text, 0, max_chars);
+    chunk["message"] = Value::String(format!(
+        "The result is too large and truncated. Call `{}` with job_id and next_offset to read the rest.",
+        FETCH_RESULT_CHUNK
+    ));
+    Some(Content::json(chunk).map(|content| CallToolResult {
+        content: vec![content],
+        is_error: None,
+    }))
+}
+
+/// Link to the output stored in the artifact store instead of the output
+pub fn offloaded_result(job_id: i64, uri: &str, bytes: usize) -> Result<CallToolResult, McpError> {
+    Ok(CallToolResult {
+        content: vec![Content::json(serde_json::json!({
+            "job_id": job_id,
+            "message": "The result is too large and stored as an artifact. Read it from the uri.",
+            "uri": uri,
+            "mime_type": "application/json",
+            "size": bytes,
+        }))?],
+        is_error: None,
+    })
+}
+
+pub fn search_tools_tool() -> Tool {
+    Tool::new(
+        SEARCH_TOOLS,
+        "Search the available tools by keywords in their names and descriptions, and return the matching tools with their input schemas. Use it to find a tool for the task when it is not in the tool list; the found tools can be called by name.",
+        object_schema(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {"type": "string", "description": "space separated keywords (all keywords must match, case insensitive)"},
+                "limit": {
+                    "type": "integer",
+                    "description": format!("max number of tools (default: {}, max: {})", DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT),
+                    "minimum": 1,
+                },
+            },
+            "required": ["query"],
+        })),
+    )
+}
+
+/// Keywords (lowercase) and limit of the `search_tools` arguments
+pub fn parse_search_tools(
+    arguments: Option<&Map<String, Value>>,
+) -> Result<(Vec<String>, usize), McpError> {
+    let keywords = arguments
+        .and_then(|a| a.get("query"))
+        .and_then(|q| q.as_str())
+        .map(|q| {
+            q.split_whitespace()
+                .map(|k| k.to_lowercase())
+                .collect::<Vec<_>>()
+        })
+        .filter(|k| !k.is_empty())
+        .ok_or_else(|| McpError::invalid_params("`query` is required", None))?;
+    let limit = arguments
+        .and_then(|a| a.get("limit"))
+        .and_then(|l| l.as_u64())
+        .map(|l| (l as usize).clamp(1, MAX_SEARCH_LIMIT))
+        .unwrap_or(DEFAULT_SEARCH_LIMIT);
+    Ok((keywords, limit))
+}
+
+/// Tools matching all the keywords, best matches (keywords in the name) first
+pub fn search_tools<'a>(tools: &'a [Tool], keywords: &[String]) -> Vec<&'a Tool> {
+    let mut matches = tools
+        .iter()
+        .filter_map(|tool| {
+            let name = tool.name.to_lowercase();
+            let description = tool.description.to_lowercase();
+            keywords
+                .iter()
+                .map(|k| {
+                    if name.contains(k.as_str()) {
+                        Some(2)
+                    } else if description.contains(k.as_str()) {
+                        Some(1)
+                    } else {
+                        None
+                    }
+                })
+                .sum::<Option<u32>>()
+                .map(|score| (score, tool))
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by(|(s1, t1), (s2, t2)| s2.cmp(s1).then_with(|| t1.name.cmp(&t2.name)));
+    matches.into_iter().map(|(_, tool)| tool).collect()
+}
+
+pub fn search_tools_result(
+    tools: &[Tool],
+    keywords: &[String],
+    limit: usize,
+) -> Result<CallToolResult, McpError> {
+    let matches = search_tools(tools, keywords);
+    Ok(CallToolResult {
+        content: vec![Content::json(serde_json::json!({
+            "total_matches": matches.len(),
+            "tools": matches.into_iter().take(limit).collect::<Vec<_>>(),
+        }))?],
+        is_error: None,
+    })
+}
+
+pub fn recent_results_tool() -> Tool {
+    Tool::new(
+        RECENT_RESULTS,
+        "List the recent tool calls executed through this server (newest first) with their status and results. Use it to refer back to the results of earlier calls.",
+        object_schema(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "tool": {"type": "string", "description": "tool name (`*` wildcards allowed)"},
+                "status": {"type": "string", "enum": ["running", "succeeded", "failed"]},
+                "since": {
+                    "type": ["string", "integer"],
+                    "description": "calls started at or after the time (RFC 3339 timestamp or unix epoch millis)",
+                },
+                "until": {
+                    "type": ["string", "integer"],
+                    "description": "calls started before the time (RFC 3339 timestamp or unix epoch millis)",
+                },
+                "current_session_only": {"type": "boolean", "description": "only the calls of this session (default: false)"},
+                "include_output": {"type": "boolean", "description": "include the job outputs (default: true)"},
+                "limit": {
+                    "type": "integer",
+                    "description": format!("max number of calls (default: {}, max: {})", DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT),
+                    "minimum": 1,
+                },
+            },
+        })),
+    )
+}
+
+/// Filter, limit and whether to include the outputs of the `recent_results` arguments
+pub fn parse_recent_results(
+    arguments: Option<&Map<String, Value>>,
+    session_id: &str,
+) -> Result<(JobFilter, usize, bool), McpError> {
+    let get = |key: &str| arguments.and_then(|a| a.get(key)).filter(|v| !v.is_null());
+    let time = |key: &str| {
+        get(key)
+            .map(|v| scheduler::parse_timestamp(key, v))
+            .transpose()
+            .map_err(|e| McpError::invalid_params(e, None))
+    };
+    let status = match get("status") {
+        Some(s) => Some(
+            s.as_str()
+                .and_then(JobStatus::parse)
+                .ok_or_else(|| McpError::invalid_params(format!("invalid status: {}", s), None))?,
+        ),
+        None => None,
+    };
+    let filter = JobFilter {
+        tool_name: get("tool").and_then(|t| t.as_str()).map(|t| t.to_string()),
+        status,
+        session_id: get("current_session_only")
+            .and_then(|c| c.as_bool())
+            .filter(|c| *c)
+            .map(|_| session_id.to_string()),
+        since: time("since")?,
+        until: time("until")?,
+    };
+    let limit = get("limit")
+        .and_then(|l| l.as_u64())
+        .map(|l| (l as usize).clamp(1, MAX_SEARCH_LIMIT))
+        .unwrap_or(DEFAULT_SEARCH_LIMIT);
+    let include_output = get("include_output")
+        .and_then(|i| i.as_bool())
+        .unwrap_or(true);
+    Ok((filter, limit, include_output))
+}
+
+/// Status (and output) of the jobs, with the uris of their logs
+pub fn job_history(jobs: &[JobEntry], include_output: bool) -> Vec<Value> {
+    jobs.iter()
+        .map(|job| {
+            let mut entry = job.to_status_json();
+            entry["log_uri"] = Value::String(JobStore::log_uri(job.id));
+            if let Some(output) = job.output.as_ref().filter(|_| include_output) {
+                if output.to_string().chars().count() <= MAX_RECENT_OUTPUT_CHARS {
+                    entry["output"] = output.clone();
+                } else {
+                    entry["output_omitted"] = Value::String(format!(
+                        "too large, read the log_uri resource or call `{}`",
+                        FETCH_RESULT_CHUNK
+                    ));
+                }
+            }
+            entry
+        })
+        .collect()
+}
+
+pub fn recent_results_result(
+    jobs: &[JobEntry],
+    limit: usize,
+    include_output: bool,
+) -> Result<CallToolResult, McpError> {
+    let shown = &jobs[..jobs.len().min(limit)];
+    Ok(CallToolResult {
+        content: vec![Content::json(serde_json::json!({
+            "total_matches": jobs.len(),
+            "calls": job_history(shown, include_output),
+        }))?],
+        is_error: None,
+    })
+}
+
+pub fn author_workflow_tool() -> Tool {
+    Tool::new(
+        AUTHOR_WORKFLOW,
+        "Draft a workflow definition from a natural-language description with the LLM of the client (sampling), validate it against the workflow schema, and optionally create it as a tool. Returns the draft and the validation errors.",
+        object_schema(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "description": {"type": "string", "description": "what the workflow does, its inputs and steps"},
+                "create": {"type": "boolean", "description": "create the workflow if the draft is valid (default: false)"},
+                "max_tokens": {
+                    "type": "integer",
+                    "description": format!("max tokens of the draft (default: {})", DEFAULT_AUTHORING_MAX_TOKENS),
+                    "minimum": 1,
+                },
+            },
+            "required": ["description"],
+        })),
+    )
+}
+
+/// Description, whether to create the workflow and max tokens of the `author_workflow` arguments
+pub fn parse_author_workflow(
+    arguments: Option<&Map<String, Value>>,
+) -> Result<(String, bool, u32), McpError> {
+    let get = |key: &str| arguments.and_then(|a| a.get(key));
+    let description = get("description")
+        .and_then(|d| d.as_str())
+        .filter(|d| !d.trim().is_empty())
+        .ok_or_else(|| McpError::invalid_params("`description` is required", None))?;
+    let create = get("create").and_then(|c| c.as_bool()).unwrap_or(false);
+    let max_tokens = get("max_tokens")
+        .and_then(|m| m.as_u64())
+        .map(|m| m.clamp(1, u32::MAX as u64) as u32)
+        .unwrap_or(DEFAULT_AUTHORING_MAX_TOKENS);
+    Ok((description.to_string(), create, max_tokens))
+}
+
+/// Sampling request drafting a workflow of the description (with the errors of the last draft)
+pub fn workflow_authoring_request(
+    description: &str,
+    schema: &Map<String, Value>,
+    previous: Option<(&str, &[FieldError])>,
+    max_tokens: u32,
+) -> CreateMessageRequestParam {
+    let system_prompt = format!(
+        "You write workflow definitions for jobworkerp. {}\n\nThe definition must conform to this JSON schema:\n{}\n\nReply with the JSON of the definition only.",
+        CREATION_TOOL_DESCRIPTION_SUMMARY,
+        Value::Object(schema.clone())
+    );
+    let mut messages = vec![SamplingMessage {
+        role: Role::User,
+        content: Content::text(description),
+    }];
+    if let Some((draft, errors)) = previous {
+        messages.push(SamplingMessage {
+            role: Role::Assistant,
+            content: Content::text(draft),
+        });
+        let errors = errors
+            .iter()
+            .map(|e| format!("- {}: {}", e.path, e.message))
+            .collect::<Vec<_>>()
+            .join("\n");
+        messages.push(SamplingMessage {
+            role: Role::User,
+            content: Content::text(format!(
+                "The definition does not conform to the schema:\n{}\nReply with the corrected JSON only.",
+                errors
+            )),
+        });
+    }
+    CreateMessageRequestParam {
+        messages,
+        model_preferences: None,
+        system_prompt: Some(system_prompt),
+        include_context: None,
+        temperature: None,
+        max_tokens,
+        stop_sequences: None,
+        metadata: None,
+    }
+}
+
+const CREATION_TOOL_DESCRIPTION_SUMMARY: &str = "A workflow has an input schema of the parameters it accepts as a tool, and steps calling the runners (functions) of the system.";
+
+/// The JSON object of a drafted workflow (in a code block or surrounded by text)
+pub fn extract_workflow_json(text: &str) -> Result<Map<String, Value>, String> {
+    let start = text.find('{').ok_or("no JSON object in the draft")?;
+    let end = text.rfind('}').ok_or("no JSON object in the draft")?;
+    if end < start {
+        return Err("no JSON object in the draft".to_string());
+    }
+    match serde_json::from_str(&text[start..=end]) {
+        Ok(Value::Object(workflow)) => Ok(workflow),
+        Ok(_) => Err("the draft is not a JSON object".to_string()),
+        Err(e) => Err(format!("invalid JSON in the draft: {}", e)),
+    }
+}
+
+/// Result of `author_workflow`: the draft, its validation errors and whether it was created
+pub fn author_workflow_result(
+    workflow: Option<&Map<String, Value>>,
+    errors: &[FieldError],
+    parse_error: Option<&str>,
+    created: bool,
+) -> Result<CallToolResult, McpError> {
+    let valid = workflow.is_some() && errors.is_empty() && parse_error.is_none();
+    Ok(CallToolResult {
+        content: vec![Content::json(serde_json::json!({
+            "valid": valid,
+            "created": created,
+            "workflow": workflow,
+            "errors": errors,
+            "parse_error": parse_error,
+        }))?],
+        is_error: (!valid).then_some(true),
+    })
+}
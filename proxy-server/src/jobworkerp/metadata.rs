@@ -0,0 +1,81 @@
+use super::session::{SessionClient, SessionInfo};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Mapping of session information forwarded to jobworkerp as grpc metadata
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MetadataForwardingConfig {
+    // metadata key of the authenticated subject
+    pub subject: Option<String>,
+    // metadata key of the token scopes (space separated)
+    pub scopes: Option<String>,
+    // metadata key of the client implementation ("name/version")
+    pub client_info: Option<String>,
+    // metadata key of the MCP protocol version of the client
+    pub protocol_version: Option<String>,
+    // http request header name -> metadata key
+    pub headers: HashMap<String, String>,
+    // allow-list of `_meta` keys of tool calls: `_meta` key -> metadata key
+    pub meta: HashMap<String, String>,
+}
+
+impl MetadataForwardingConfig {
+    pub fn build(
+        &self,
+        session: &SessionInfo,
+        client: Option<&SessionClient>,
+    ) -> HashMap<String, String> {
+        let mut metadata = HashMap::new();
+        if let (Some(key), Some(identity)) = (self.subject.as_ref(), session.identity.as_ref()) {
+            metadata.insert(key.to_lowercase(), identity.subject.clone());
+        }
+        if let (Some(key), Some(identity)) = (self.scopes.as_ref(), session.identity.as_ref()) {
+            metadata.insert(key.to_lowercase(), identity.scopes.join(" "));
+        }
+        if let (Some(key), Some(client)) = (self.client_info.as_ref(), client) {
+            metadata.insert(key.to_lowercase(), client.label());
+        }
+        if let (Some(key), Some(client)) = (self.protocol_version.as_ref(), client) {
+            metadata.insert(key.to_lowercase(), client.protocol_version.clone());
+        }
+        for (header, key) in self.headers.iter() {
+            if let Some(value) = session.headers.get(&header.to_lowercase()) {
+                metadata.insert(key.to_lowercase(), value.clone());
+            }
+        }
+        Self::retain_valid(&mut metadata);
+        metadata
+    }
+
+    /// Metadata from the `_meta` of a tool call (only allow-listed keys)
+    pub fn build_meta(&self, meta: &Map<String, Value>) -> HashMap<String, String> {
+        let mut metadata = self
+            .meta
+            .iter()
+            .filter_map(|(meta_key, key)| {
+                let value = match meta.get(meta_key)? {
+                    Value::Null => return None,
+                    Value::String(s) => s.clone(),
+                    // labels etc.: forwarded as json
+                    v => v.to_string(),
+                };
+                Some((key.to_lowercase(), value))
+            })
+            .collect::<HashMap<_, _>>();
+        Self::retain_valid(&mut metadata);
+        metadata
+    }
+
+    // grpc metadata values must be printable ascii
+    fn retain_valid(metadata: &mut HashMap<String, String>) {
+        metadata.retain(|k, v| {
+            let valid = v.chars().all(|c| c.is_ascii() && !c.is_ascii_control());
+            if !valid {
+                tracing::warn!("skip forwarding non-ascii metadata: {}", k);
+            }
+            valid
+        });
+    }
+}
@@ -0,0 +1,45 @@
+use futures::FutureExt;
+use rmcp::Error as McpError;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+
+/// Short hash of the call arguments, logged instead of the arguments themselves
+pub fn arguments_hash(arguments: Option<&Map<String, Value>>) -> String {
+    let text = arguments
+        .map(|a| Value::Object(a.clone()).to_string())
+        .unwrap_or_default();
+    hex::encode(&Sha256::digest(text.as_bytes())[..8])
+}
+
+/// Message of a caught panic payload
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Run a tool call returning internal_error if it panics, so that a bug in one call does not
+/// tear down the session
+pub async fn catch_panic<T>(
+    tool_name: &str,
+    arguments_hash: &str,
+    call: impl Future<Output = Result<T, McpError>>,
+) -> Result<T, McpError> {
+    match AssertUnwindSafe(call).catch_unwind().await {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            tracing::error!(tool_name, arguments_hash, "tool call panicked: {}", message);
+            super::error_reporter::note_panic(&message);
+            Err(McpError::internal_error(
+                format!("Tool call failed unexpectedly: {}", tool_name),
+                Some(serde_json::json!({"arguments_hash": arguments_hash})),
+            ))
+        }
+    }
+}
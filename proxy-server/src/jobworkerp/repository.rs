@@ -1,23 +1,61 @@
 use anyhow::Result;
+use futures::StreamExt;
 use jobworkerp_client::{
     client::{
         helper::UseJobworkerpClientHelper, wrapper::JobworkerpClientWrapper, JobworkerpClient,
     },
     jobworkerp::{
-        data::{ResponseType, Runner, RunnerData, RunnerId, RunnerType, WorkerData},
+        data::{Runner, RunnerData, RunnerId, RunnerType, WorkerData},
         function::data::FunctionSpecs,
     },
     proto::JobworkerpProto,
 };
 use serde_json::{Map, Value};
-use std::{collections::HashMap, sync::Arc};
-use tracing;
-
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{self, Instrument};
+
+use super::channel::UpstreamChannelConfig;
+use super::circuit_breaker::CircuitBreaker;
+use super::descriptor_cache::DescriptorCache;
+use super::settings_presets::SettingsPresets;
+use super::worker_options::{WorkerOptions, WorkerOptionsConfig};
+use super::worker_reaper::EphemeralWorkers;
+use crate::logging;
+use crate::redaction;
 use crate::tool_conversion::ToolConverter;
 
+/// Worker created by the proxy (workflows and provisioned workers)
+#[derive(Debug, Clone, Default)]
+pub struct WorkerSpec {
+    pub name: String,
+    pub description: String,
+    // json of the runner settings schema (None: no settings)
+    pub settings: Option<Value>,
+    pub channel: Option<String>,
+    pub options: WorkerOptions,
+}
+
 pub struct JobworkerpRepository {
     pub jobworkerp_client: Arc<JobworkerpClientWrapper>,
     pub timeout_sec: u32,
+    // workers created by runner calls (deleted after being idle for the ttl)
+    pub ephemeral_workers: Arc<EphemeralWorkers>,
+    pub worker_options: WorkerOptionsConfig,
+    // fixed runner settings set by the server
+    pub settings_presets: Arc<SettingsPresets>,
+    // grpc metadata sent with every request to the backend (e.g. the credentials of a tenant)
+    pub backend_metadata: Arc<HashMap<String, String>>,
+    // compression and message size limits of the grpc clients
+    pub upstream: UpstreamChannelConfig,
+    // fails the calls fast while the backend is down
+    pub circuit_breaker: Arc<CircuitBreaker>,
+    // parsed runner settings descriptors (workflow creation)
+    pub settings_descriptors: Arc<DescriptorCache>,
 }
 
 impl net_utils::trace::Tracing for JobworkerpRepository {}
@@ -32,16 +70,113 @@ impl jobworkerp_client::client::UseJobworkerpClient for JobworkerpRepository {
 
 impl JobworkerpRepository {
     const WORKFLOW_CHANNEL: Option<&str> = Some("workflow");
+    const MAX_CONNECT_BACKOFF: Duration = Duration::from_secs(5);
 
-    pub async fn new(jobworkerp_address: &str, request_timeout_sec: Option<u32>) -> Result<Self> {
+    pub async fn new(
+        jobworkerp_address: &str,
+        request_timeout_sec: Option<u32>,
+        upstream: &UpstreamChannelConfig,
+    ) -> Result<Self> {
         let jobworkerp_client =
-            JobworkerpClientWrapper::new(jobworkerp_address, request_timeout_sec).await?;
+            if let Some(path) = UpstreamChannelConfig::unix_socket_path(jobworkerp_address) {
+                tracing::info!("connecting to jobworkerp with unix socket: {}", path);
+                let channel = upstream.connect_unix(path).await?;
+                JobworkerpClientWrapper::new_with_channel(channel, request_timeout_sec)
+            } else if !upstream.needs_custom_channel(jobworkerp_address) {
+                JobworkerpClientWrapper::new(jobworkerp_address, request_timeout_sec).await?
+            } else {
+                if upstream.is_tls(jobworkerp_address) {
+                    tracing::info!("connecting to jobworkerp with TLS: {}", jobworkerp_address);
+                }
+                let channel = upstream.connect(jobworkerp_address).await?;
+                JobworkerpClientWrapper::new_with_channel(channel, request_timeout_sec)
+            };
         Ok(Self {
             jobworkerp_client: Arc::new(jobworkerp_client),
             timeout_sec: request_timeout_sec.unwrap_or(60 * 60),
+            ephemeral_workers: Arc::new(EphemeralWorkers::default()),
+            worker_options: WorkerOptionsConfig::default(),
+            settings_presets: Arc::new(SettingsPresets::default()),
+            backend_metadata: Arc::new(HashMap::new()),
+            upstream: upstream.clone(),
+            circuit_breaker: Arc::new(CircuitBreaker::default()),
+            settings_descriptors: Arc::new(DescriptorCache::default()),
         })
     }
 
+    pub fn with_worker_options(mut self, worker_options: WorkerOptionsConfig) -> Self {
+        self.worker_options = worker_options;
+        self
+    }
+
+    pub fn with_settings_presets(mut self, settings_presets: SettingsPresets) -> Self {
+        self.settings_presets = Arc::new(settings_presets);
+        self
+    }
+
+    pub fn with_backend_metadata(mut self, metadata: Arc<HashMap<String, String>>) -> Self {
+        self.backend_metadata = metadata;
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Arc::new(circuit_breaker);
+        self
+    }
+
+    pub fn with_ephemeral_worker_ttl(mut self, ttl: Duration) -> Self {
+        self.ephemeral_workers = Arc::new(EphemeralWorkers::new(ttl));
+        self
+    }
+
+    /// Connect to jobworkerp, retrying with backoff until `wait` elapses (no retry if None)
+    pub async fn connect(
+        jobworkerp_address: &str,
+        request_timeout_sec: Option<u32>,
+        upstream: &UpstreamChannelConfig,
+        wait: Option<Duration>,
+    ) -> Result<Self> {
+        let Some(wait) = wait else {
+            return Self::new(jobworkerp_address, request_timeout_sec, upstream).await;
+        };
+        let deadline = Instant::now() + wait;
+        let mut backoff = Duration::from_millis(500);
+        let mut attempt = 1;
+        loop {
+            match Self::new(jobworkerp_address, request_timeout_sec, upstream).await {
+                Ok(repository) => {
+                    if attempt > 1 {
+                        tracing::info!("connected to jobworkerp: {}", jobworkerp_address);
+                    }
+                    return Ok(repository);
+                }
+                Err(e) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        tracing::error!(
+                            "gave up waiting for jobworkerp after {} attempts: {}",
+                            attempt,
+                            jobworkerp_address
+                        );
+                        return Err(e);
+                    }
+                    let delay = backoff.min(remaining);
+                    tracing::info!(
+                        "waiting for jobworkerp {} (attempt {}, retry in {:.1}s, {}s left): {}",
+                        jobworkerp_address,
+                        attempt,
+                        delay.as_secs_f64(),
+                        remaining.as_secs(),
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    backoff = (backoff * 2).min(Self::MAX_CONNECT_BACKOFF);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
     pub fn parse_as_json_and_string_with_key_or_noop(
         &self,
         key: &str,
@@ -120,84 +255,115 @@ impl JobworkerpRepository {
 
     pub async fn find_runner_by_name_with_mcp(
         &self,
+        metadata: Arc<HashMap<String, String>>,
         name: &str,
     ) -> Result<Option<(Runner, Option<String>)>> {
         let empty_cx = None;
-        let empty = Arc::new(HashMap::new());
         match self
-            .jobworkerp_client
-            .find_runner_by_name(empty_cx, empty.clone(), name)
+            .circuit_breaker
+            .call(
+                self.jobworkerp_client
+                    .find_runner_by_name(empty_cx, metadata.clone(), name),
+            )
             .await
         {
             Ok(Some(runner)) => {
                 tracing::debug!("found runner: {:?}", &runner);
                 Ok(Some((runner, None)))
             }
-            Ok(None) => match ToolConverter::divide_names(name) {
-                Some((server_name, tool_name)) => {
-                    tracing::debug!(
-                        "found calling to mcp server: {}:{}",
-                        &server_name,
-                        &tool_name
-                    );
-                    self.jobworkerp_client
-                        .find_runner_by_name(empty_cx, empty.clone(), &server_name)
-                        .await
-                        .map(|res| res.map(|r| (r, Some(tool_name))))
-                }
-                None => Ok(None),
-            },
+            Ok(None) => self.find_mcp_runner(metadata, name).await,
             Err(e) => Err(e),
         }
     }
 
+    /// Runner of the mcp server of a combined tool name, with the tool name
+    pub async fn find_mcp_runner(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        name: &str,
+    ) -> Result<Option<(Runner, Option<String>)>> {
+        match ToolConverter::divide_names(name) {
+            Some((server_name, tool_name)) => {
+                tracing::debug!(
+                    "found calling to mcp server: {}:{}",
+                    &server_name,
+                    &tool_name
+                );
+                self.circuit_breaker
+                    .call(
+                        self.jobworkerp_client
+                            .find_runner_by_name(None, metadata, &server_name),
+                    )
+                    .await
+                    .map(|res| res.map(|r| (r, Some(tool_name))))
+            }
+            None => Ok(None),
+        }
+    }
+
     pub async fn find_worker_by_name_with_mcp(
         &self,
+        metadata: Arc<HashMap<String, String>>,
         name: &str,
     ) -> Result<Option<(WorkerData, Option<String>)>> {
         let empty_cx = None;
-        let empty = Arc::new(HashMap::new());
         match self
-            .jobworkerp_client
-            .find_worker_by_name(empty_cx, empty.clone(), name)
+            .circuit_breaker
+            .call(
+                self.jobworkerp_client
+                    .find_worker_by_name(empty_cx, metadata.clone(), name),
+            )
             .await
         {
             Ok(Some(worker)) => {
                 tracing::debug!("found worker: {:?}", &worker);
                 Ok(Some((worker.1, None)))
             }
-            Ok(None) => match ToolConverter::divide_names(name) {
-                Some((server_name, tool_name)) => {
-                    tracing::debug!(
-                        "found calling to mcp server: {}:{}",
-                        &server_name,
-                        &tool_name
-                    );
-                    self.jobworkerp_client
-                        .find_worker_by_name(empty_cx, empty.clone(), &server_name)
-                        .await
-                        .map(|res| res.map(|r| (r.1, Some(tool_name))))
-                }
-                None => Ok(None),
-            },
+            Ok(None) => self.find_mcp_worker(metadata, name).await,
             Err(e) => Err(e),
         }
     }
 
+    /// Worker of the mcp server of a combined tool name, with the tool name
+    pub async fn find_mcp_worker(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        name: &str,
+    ) -> Result<Option<(WorkerData, Option<String>)>> {
+        match ToolConverter::divide_names(name) {
+            Some((server_name, tool_name)) => {
+                tracing::debug!(
+                    "found calling to mcp server: {}:{}",
+                    &server_name,
+                    &tool_name
+                );
+                self.circuit_breaker
+                    .call(
+                        self.jobworkerp_client
+                            .find_worker_by_name(None, metadata, &server_name),
+                    )
+                    .await
+                    .map(|res| res.map(|r| (r.1, Some(tool_name))))
+            }
+            None => Ok(None),
+        }
+    }
+
     pub async fn create_workflow(
         &self,
+        metadata: Arc<HashMap<String, String>>,
         runner_id: RunnerId,
         runner_data: RunnerData,
         definition: Option<Map<String, Value>>,
     ) -> Result<()> {
-        let empty_cx = None;
-        let empty = Arc::new(HashMap::new());
-
         tracing::debug!("found calling to reusable workflow: {:?}", &runner_data);
         let arguments = definition.and_then(|a| self.parse_arguments_for_reusable_workflow(a).ok());
 
         if let Some(arguments) = arguments {
-            tracing::trace!("workflow_data: {:?}", &arguments);
+            tracing::trace!(
+                "workflow_data: {:?}",
+                redaction::redact(&Value::Object(arguments.clone()))
+            );
             let workflow_definition = serde_json::Value::Object(arguments);
             let document = workflow_definition.get("document").cloned();
             let workflow_name = document
@@ -214,49 +380,15 @@ impl JobworkerpRepository {
             let settings = serde_json::json!({
                 "json_data": workflow_definition.to_string()
             });
-            let runner_settings_descriptor =
-                JobworkerpProto::parse_runner_settings_schema_descriptor(&runner_data).map_err(
-                    |e| {
-                        anyhow::anyhow!(
-                            "Failed to parse runner_settings schema descriptor: {:#?}",
-                            e
-                        )
-                    },
-                )?;
-            let runner_settings = if let Some(ope_desc) = runner_settings_descriptor {
-                tracing::debug!("runner settings schema exists: {:#?}", &settings);
-                JobworkerpProto::json_value_to_message(ope_desc, &settings, true).map_err(|e| {
-                    anyhow::anyhow!("Failed to parse runner_settings schema: {:#?}", e)
-                })?
-            } else {
-                tracing::debug!("runner settings schema empty");
-                vec![]
-            };
-
-            let data = WorkerData {
-                name: workflow_name.to_string(),
-                description: workflow_description.to_string(),
-                runner_id: Some(runner_id),
-                runner_settings,
+            let spec = WorkerSpec {
+                name: workflow_name,
+                description: workflow_description,
+                settings: Some(settings),
                 channel: Self::WORKFLOW_CHANNEL.map(|s| s.to_string()),
-                response_type: ResponseType::Direct as i32,
-                broadcast_results: true,
-                ..Default::default()
+                options: self.worker_options.for_workflow(),
             };
-            let worker = self
-                .jobworkerp_client
-                .find_or_create_worker(empty_cx, empty, &data)
-                .await;
-            match worker {
-                Ok(worker) => {
-                    tracing::info!("Worker created: {:?}", worker);
-                    Ok(())
-                }
-                Err(e) => {
-                    tracing::error!("Failed to create worker: {}", e);
-                    Err(e)
-                }
-            }
+            self.create_worker(metadata, runner_id, &runner_data, spec)
+                .await
         } else {
             tracing::warn!("Workflow data is not found");
             Err(anyhow::anyhow!(
@@ -265,12 +397,78 @@ impl JobworkerpRepository {
         }
     }
 
+    /// Create a worker of the runner with the settings (json of the runner settings schema), or
+    /// find the worker of the same name
+    pub async fn create_worker(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        runner_id: RunnerId,
+        runner_data: &RunnerData,
+        spec: WorkerSpec,
+    ) -> Result<()> {
+        let empty_cx = None;
+        let runner_settings = match spec.settings.as_ref() {
+            Some(settings) => {
+                let runner_settings_descriptor = self
+                    .settings_descriptors
+                    .get_or_parse(runner_id.value, &runner_data.runner_settings_proto, || {
+                        JobworkerpProto::parse_runner_settings_schema_descriptor(runner_data)
+                    })
+                    .map_err(|e| {
+                        anyhow::anyhow!(
+                            "Failed to parse runner_settings schema descriptor: {:#?}",
+                            e
+                        )
+                    })?;
+                if let Some(ope_desc) = runner_settings_descriptor {
+                    tracing::debug!("runner settings schema exists: {:#?}", settings);
+                    JobworkerpProto::json_value_to_message(ope_desc, settings, true).map_err(
+                        |e| anyhow::anyhow!("Failed to parse runner_settings schema: {:#?}", e),
+                    )?
+                } else {
+                    tracing::debug!("runner settings schema empty");
+                    vec![]
+                }
+            }
+            None => vec![],
+        };
+
+        let data = WorkerData {
+            name: spec.name,
+            description: spec.description,
+            runner_id: Some(runner_id),
+            runner_settings,
+            channel: spec.channel,
+            response_type: spec.options.response_type() as i32,
+            broadcast_results: spec.options.broadcast_results.unwrap_or(true),
+            ..Default::default()
+        };
+        let worker = self
+            .circuit_breaker
+            .call(
+                self.jobworkerp_client
+                    .find_or_create_worker(empty_cx, metadata, &data),
+            )
+            .await;
+        match worker {
+            Ok(worker) => {
+                tracing::info!("Worker created: {:?}", worker);
+                Ok(())
+            }
+            Err(e) => {
+                tracing::error!("Failed to create worker: {}", e);
+                Err(e)
+            }
+        }
+    }
+
     pub async fn prepare_runner_call_arguments(
         request_args: Map<String, Value>,
         runner: &Runner,
         tool_name_opt: Option<String>,
+        settings_presets: &SettingsPresets,
     ) -> (Option<Value>, Value) {
-        let settings = request_args.get("settings").cloned();
+        let mut settings = request_args.get("settings").cloned();
         let arguments = if runner
             .data
             .as_ref()
@@ -297,38 +495,258 @@ impl JobworkerpRepository {
                 .unwrap_or(Value::Null)
         };
 
-        tracing::debug!(
-            "runner settings: {:#?}, arguments: {:#?}",
-            settings,
-            arguments
-        );
+        if tracing::enabled!(tracing::Level::DEBUG) && logging::debug_sampled() {
+            tracing::debug!(
+                "runner settings: {:#?}, arguments: {:#?}",
+                settings.as_ref().map(redaction::redact),
+                redaction::redact(&arguments)
+            );
+        }
 
+        // merged after logging (the presets may contain secrets)
+        if let Some(runner_data) = runner.data.as_ref() {
+            settings_presets.apply(&runner_data.name, &mut settings);
+        }
         (settings, arguments)
     }
 
     pub async fn setup_worker_and_enqueue_with_json(
         &self,
+        metadata: Arc<HashMap<String, String>>,
         runner: &Runner,
         request_args: Map<String, Value>,
         tool_name_opt: Option<String>,
+        channel: Option<&str>,
     ) -> Result<Value> {
-        let empty_cx = None;
-        let empty = Arc::new(HashMap::new());
+        self.call_runner_worker(
+            metadata,
+            runner,
+            request_args,
+            tool_name_opt,
+            channel,
+            |client, metadata, runner_name, settings, worker_params, arguments, timeout_sec| async move {
+                client
+                    .setup_worker_and_enqueue_with_json(
+                        None,
+                        metadata,
+                        &runner_name,
+                        settings,
+                        Some(worker_params),
+                        arguments,
+                        timeout_sec,
+                    )
+                    .await
+            },
+        )
+        .await
+    }
 
-        let (settings, arguments) =
-            Self::prepare_runner_call_arguments(request_args, &runner, tool_name_opt).await;
+    /// Enqueue a runner call streaming its output: the stdout of each output item is passed to
+    /// `on_stdout` as it arrives, and the last item (with the whole stdout) is returned
+    pub async fn setup_worker_and_enqueue_stream_with_json(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        runner: &Runner,
+        request_args: Map<String, Value>,
+        tool_name_opt: Option<String>,
+        channel: Option<&str>,
+        on_stdout: impl Fn(&str) + Send + 'static,
+    ) -> Result<Value> {
+        self.call_runner_worker(
+            metadata,
+            runner,
+            request_args,
+            tool_name_opt,
+            channel,
+            |client, metadata, runner_name, settings, worker_params, arguments, timeout_sec| async move {
+                let mut items = client
+                    .setup_worker_and_enqueue_stream_with_json(
+                        None,
+                        metadata,
+                        &runner_name,
+                        settings,
+                        Some(worker_params),
+                        arguments,
+                        timeout_sec,
+                    )
+                    .await?;
+                let mut stdout = String::new();
+                let mut last = Value::Null;
+                while let Some(item) = items.next().await {
+                    let item = item?;
+                    if let Some(chunk) = item.get("stdout").and_then(|s| s.as_str()) {
+                        on_stdout(chunk);
+                        stdout.push_str(chunk);
+                    }
+                    last = item;
+                }
+                // the last item may only hold its own chunk of the stdout
+                if let Some(output) = last.as_object_mut() {
+                    let last_len = output
+                        .get("stdout")
+                        .and_then(|s| s.as_str())
+                        .map_or(0, |s| s.len());
+                    if last_len < stdout.len() {
+                        output.insert("stdout".to_string(), Value::from(stdout));
+                    }
+                }
+                Ok(last)
+            },
+        )
+        .await
+    }
 
-        self.jobworkerp_client
-            .setup_worker_and_enqueue_with_json(
-                empty_cx,
-                empty,
-                runner.data.as_ref().map(|r| &r.name).unwrap().as_str(),
+    // call a runner by the (ephemeral) worker of its settings
+    async fn call_runner_worker<F, Fut>(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        runner: &Runner,
+        request_args: Map<String, Value>,
+        tool_name_opt: Option<String>,
+        channel: Option<&str>,
+        call: F,
+    ) -> Result<Value>
+    where
+        F: FnOnce(
+            Arc<JobworkerpClientWrapper>,
+            Arc<HashMap<String, String>>,
+            String,
+            Option<Value>,
+            Value,
+            Value,
+            u32,
+        ) -> Fut,
+        Fut: Future<Output = Result<Value>>,
+    {
+        let (settings, arguments) = Self::prepare_runner_call_arguments(
+            request_args,
+            runner,
+            tool_name_opt,
+            &self.settings_presets,
+        )
+        .instrument(tracing::info_span!("convert_arguments"))
+        .await;
+        let runner_name = runner.data.as_ref().map(|r| &r.name).unwrap().as_str();
+
+        // calls with the same settings share a named worker, deleted by the reaper when idle
+        let mut worker_name = EphemeralWorkers::worker_name(runner_name, settings.as_ref());
+        if let Some(channel) = channel {
+            worker_name = format!("{}-{}", worker_name, channel);
+        }
+        let lease = self.ephemeral_workers.acquire(&worker_name).await;
+        let mut worker_params = self
+            .worker_options
+            .for_runner(runner_name)
+            .worker_params(lease.name());
+        if let Some(channel) = channel {
+            worker_params["channel"] = Value::from(channel);
+        }
+        let result = self
+            .circuit_breaker
+            .call(call(
+                self.jobworkerp_client.clone(),
+                metadata.clone(),
+                runner_name.to_string(),
                 settings,
-                None,
+                worker_params,
                 arguments,
                 self.timeout_sec,
-            )
-            .await
+            ))
+            .await;
+        drop(lease);
+        if self.ephemeral_workers.ttl().is_zero() {
+            self.reap_ephemeral_workers(metadata).await;
+        }
+        result
+    }
+
+    /// Delete the worker by name (returns false if not found)
+    pub async fn delete_worker_by_name(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        name: &str,
+    ) -> Result<bool> {
+        let empty_cx = None;
+        let Some((worker_id, _)) = self
+            .jobworkerp_client
+            .find_worker_by_name(empty_cx, metadata.clone(), name)
+            .await?
+        else {
+            return Ok(false);
+        };
+        let mut request = tonic::Request::new(worker_id);
+        for (key, value) in metadata.iter() {
+            match (
+                tonic::metadata::MetadataKey::from_bytes(key.as_bytes()),
+                value.parse(),
+            ) {
+                (Ok(key), Ok(value)) => {
+                    request.metadata_mut().insert(key, value);
+                }
+                _ => tracing::warn!("invalid metadata for grpc request: {}", key),
+            }
+        }
+        let client = self
+            .jobworkerp_client
+            .jobworkerp_client
+            .worker_client()
+            .await;
+        let response = self
+            .upstream
+            .configure_worker_client(client)
+            .delete(request)
+            .await?;
+        Ok(response.into_inner().is_success)
+    }
+
+    /// Delete the ephemeral workers idle for the ttl
+    pub async fn reap_ephemeral_workers(&self, metadata: Arc<HashMap<String, String>>) {
+        let expired = self.ephemeral_workers.take_expired();
+        if expired.is_empty() {
+            return;
+        }
+        for name in expired {
+            let result = self.delete_worker_by_name(metadata.clone(), &name).await;
+            match result.as_ref() {
+                Ok(deleted) => {
+                    tracing::debug!("deleted ephemeral worker: {} ({})", &name, deleted)
+                }
+                Err(e) => tracing::warn!("failed to delete ephemeral worker {}: {:?}", &name, e),
+            }
+            self.ephemeral_workers.finish_delete(&name, &result);
+        }
+        let stats = self.ephemeral_workers.stats();
+        tracing::info!(
+            "ephemeral workers: created={}, deleted={}, delete_failed={}, active={}",
+            stats.created,
+            stats.deleted,
+            stats.delete_failed,
+            stats.active
+        );
+    }
+
+    /// Delete idle ephemeral workers periodically
+    pub fn spawn_worker_reaper(self: &Arc<Self>) {
+        let ttl = self.ephemeral_workers.ttl();
+        if ttl.is_zero() {
+            // deleted right after each call
+            return;
+        }
+        let interval = (ttl / 2).clamp(Duration::from_secs(1), Duration::from_secs(60));
+        let repository = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(repository) = repository.upgrade() else {
+                    break;
+                };
+                repository
+                    .reap_ephemeral_workers(repository.backend_metadata.clone())
+                    .await;
+            }
+        });
     }
 
     pub async fn prepare_worker_call_arguments(
@@ -362,45 +780,59 @@ impl JobworkerpRepository {
 
     pub async fn enqueue_with_json(
         &self,
+        metadata: Arc<HashMap<String, String>>,
         worker_data: &WorkerData,
         request_args: Map<String, Value>,
         tool_name_opt: Option<String>,
     ) -> Result<Value> {
         let empty_cx = None;
-        let empty = Arc::new(HashMap::new());
 
         let arguments =
-            Self::prepare_worker_call_arguments(request_args, &worker_data, tool_name_opt).await;
+            Self::prepare_worker_call_arguments(request_args, &worker_data, tool_name_opt)
+                .instrument(tracing::info_span!("convert_arguments"))
+                .await;
 
-        self.jobworkerp_client
-            .enqueue_with_json(empty_cx, empty, worker_data, arguments, self.timeout_sec)
+        self.circuit_breaker
+            .call(self.jobworkerp_client.enqueue_with_json(
+                empty_cx,
+                metadata,
+                worker_data,
+                arguments,
+                self.timeout_sec,
+            ))
             .await
     }
 
     pub async fn find_function_list(
         &self,
+        metadata: Arc<HashMap<String, String>>,
         exclude_runner_as_tool: bool,
         exclude_worker_as_tool: bool,
     ) -> Result<Vec<FunctionSpecs>> {
         let empty_cx = None;
-        let empty = Arc::new(HashMap::new());
 
-        self.jobworkerp_client
-            .find_function_list(
+        self.circuit_breaker
+            .call(self.jobworkerp_client.find_function_list(
                 empty_cx,
-                empty,
+                metadata,
                 exclude_runner_as_tool,
                 exclude_worker_as_tool,
-            )
+            ))
             .await
     }
 
-    pub async fn find_function_list_by_set(&self, name: &str) -> Result<Vec<FunctionSpecs>> {
+    pub async fn find_function_list_by_set(
+        &self,
+        metadata: Arc<HashMap<String, String>>,
+        name: &str,
+    ) -> Result<Vec<FunctionSpecs>> {
         let empty_cx = None;
-        let empty = Arc::new(HashMap::new());
 
-        self.jobworkerp_client
-            .find_function_list_by_set(empty_cx, empty, name)
+        self.circuit_breaker
+            .call(
+                self.jobworkerp_client
+                    .find_function_list_by_set(empty_cx, metadata, name),
+            )
             .await
     }
 }
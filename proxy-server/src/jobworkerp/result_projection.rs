@@ -0,0 +1,160 @@
+use crate::rbac::glob_match;
+use crate::redaction::{self, PathSegment};
+use anyhow::{anyhow, Result};
+use jaq_core::load::{Arena, File, Loader};
+use jaq_core::{Compiler, Ctx, Native, RcIter};
+use jaq_json::Val;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Expressions extracting the relevant part of the job outputs by tool name
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct ResultProjectionsConfig {
+    // tool name (`*` wildcards allowed) -> JSONPath (`$...`) or jq expression
+    pub tools: BTreeMap<String, String>,
+}
+
+/// A compiled projection expression
+#[derive(Clone)]
+pub enum Projection {
+    // the matched value, or the list of the matched values with wildcards
+    JsonPath(Vec<PathSegment>),
+    // the output value, or the list of the output values if not one
+    Jq(Arc<jaq_core::Filter<Native<Val>>>),
+}
+
+impl Projection {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expression = expression.trim();
+        if expression.starts_with('$') {
+            return Ok(Self::JsonPath(redaction::parse_path(expression)?));
+        }
+        let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+        let arena = Arena::default();
+        let modules = loader
+            .load(
+                &arena,
+                File {
+                    code: expression,
+                    path: (),
+                },
+            )
+            .map_err(|_| anyhow!("invalid jq expression: {}", expression))?;
+        let filter = Compiler::default()
+            .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+            .compile(modules)
+            .map_err(|_| anyhow!("invalid jq expression: {}", expression))?;
+        Ok(Self::Jq(Arc::new(filter)))
+    }
+
+    pub fn apply(&self, output: &Value) -> Result<Value> {
+        match self {
+            Self::JsonPath(path) => {
+                let mut found = Vec::new();
+                select_path(output, path, &mut found);
+                let wildcard = path.iter().any(|s| !matches!(s, PathSegment::Key(_)));
+                Ok(match found.len() {
+                    1 if !wildcard => found.remove(0),
+                    0 if !wildcard => Value::Null,
+                    _ => Value::Array(found),
+                })
+            }
+            Self::Jq(filter) => {
+                let inputs = RcIter::new(core::iter::empty());
+                let mut values = filter
+                    .run((Ctx::new([], &inputs), Val::from(output.clone())))
+                    .map(|v| v.map(Value::from).map_err(|e| anyhow!("jq: {}", e)))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(match values.len() {
+                    0 => Value::Null,
+                    1 => values.remove(0),
+                    _ => Value::Array(values),
+                })
+            }
+        }
+    }
+}
+
+fn select_path(value: &Value, path: &[PathSegment], found: &mut Vec<Value>) {
+    let Some((segment, rest)) = path.split_first() else {
+        found.push(value.clone());
+        return;
+    };
+    match segment {
+        PathSegment::Key(key) => {
+            let value = match value {
+                Value::Array(list) => key.parse::<usize>().ok().and_then(|i| list.get(i)),
+                _ => value.get(key.as_str()),
+            };
+            if let Some(value) = value {
+                select_path(value, rest, found);
+            }
+        }
+        PathSegment::Any => match value {
+            Value::Object(obj) => obj.values().for_each(|v| select_path(v, rest, found)),
+            Value::Array(list) => list.iter().for_each(|v| select_path(v, rest, found)),
+            _ => {}
+        },
+        PathSegment::Descendant(key) => {
+            if let Some(value) = value.get(key.as_str()) {
+                select_path(value, rest, found);
+            }
+            match value {
+                Value::Object(obj) => obj
+                    .iter()
+                    .filter(|(k, _)| *k != key)
+                    .for_each(|(_, v)| select_path(v, path, found)),
+                Value::Array(list) => list.iter().for_each(|v| select_path(v, path, found)),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The compiled projections of the config (checked at startup)
+#[derive(Clone, Default)]
+pub struct ResultProjections {
+    tools: BTreeMap<String, Projection>,
+}
+
+impl ResultProjections {
+    pub fn new(config: &ResultProjectionsConfig) -> Result<Self> {
+        let tools = config
+            .tools
+            .iter()
+            .map(|(tool, expression)| {
+                Projection::parse(expression)
+                    .map(|p| (tool.clone(), p))
+                    .map_err(|e| anyhow!("result projection of {}: {}", tool, e))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { tools })
+    }
+
+    /// Projection of the tool (exact name first, then the first matching pattern)
+    pub fn for_tool(&self, name: &str) -> Option<&Projection> {
+        self.tools.get(name).or_else(|| {
+            self.tools
+                .iter()
+                .find(|(pattern, _)| glob_match(pattern, name))
+                .map(|(_, projection)| projection)
+        })
+    }
+
+    /// The projected output of the tool (the output as it is if no projection or it fails)
+    pub fn apply(&self, name: &str, output: Value) -> Value {
+        let Some(projection) = self.for_tool(name) else {
+            return output;
+        };
+        match projection.apply(&output) {
+            Ok(projected) => projected,
+            Err(e) => {
+                tracing::warn!(tool_name = name, "Failed to project the result: {}", e);
+                output
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use super::*;
+
+// results of the jobs read after the call (continuation tokens and result chunks)
+impl JobworkerpRouter {
+    // a part of the output of a job in the job store
+    pub(super) fn fetch_result_chunk(
+        &self,
+        request: &CallToolRequestParam,
+    ) -> Result<CallToolResult, McpError> {
+        let (job_id, offset, length) =
+            meta_tool::parse_fetch_result_chunk(request.arguments.as_ref())?;
+        let job = self.visible_job(job_id).ok_or_else(|| {
+            McpError::invalid_params(format!("unknown or expired job: {}", job_id), None)
+        })?;
+        let output = job.output.ok_or_else(|| {
+            McpError::invalid_params(
+                format!("job {} has no result ({})", job_id, job.status.as_str()),
+                None,
+            )
+        })?;
+        Ok(CallToolResult {
+            content: vec![Content::json(meta_tool::result_chunk(
+                job_id,
+                &output.to_string(),
+                offset,
+                length,
+            ))?],
+            is_error: None,
+        })
+    }
+
+    // wait for the job of a continuation token
+    pub(super) async fn resume_call(
+        &self,
+        request: &CallToolRequestParam,
+    ) -> Result<CallToolResult, McpError> {
+        let (job_id, wait) = meta_tool::parse_resume_call(
+            request.arguments.as_ref(),
+            self.call_wait_timeout
+                .unwrap_or(meta_tool::DEFAULT_RESUME_WAIT),
+        )?;
+        let unknown = || {
+            McpError::invalid_params(
+                format!("unknown or expired continuation token: {}", job_id),
+                None,
+            )
+        };
+        self.visible_job(job_id).ok_or_else(unknown)?;
+        let job = self
+            .job_store
+            .wait_finished(job_id, wait)
+            .await
+            .ok_or_else(unknown)?;
+        match job.status {
+            job_store::JobStatus::Running => meta_tool::continuation_result(job_id, &job.tool_name),
+            job_store::JobStatus::Succeeded => {
+                self.job_output_result(
+                    job_id,
+                    &job.tool_name,
+                    job.runner_type,
+                    job.output_format,
+                    job.output.unwrap_or_default(),
+                )
+                .await
+            }
+            job_store::JobStatus::Failed => Err(McpError::internal_error(
+                format!("Failed to enqueue job: {}", job.error.unwrap_or_default()),
+                None,
+            )),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use jobworkerp_client::jobworkerp::data::RunnerData;
+use jobworkerp_client::jobworkerp::function::data::{function_specs, FunctionSpecs};
+
+pub const URI_SCHEME: &str = "runner://";
+
+/// Definitions of a runner readable as `runner://{name}/{kind}` resources
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunnerResourceKind {
+    SettingsSchema,
+    ArgumentsSchema,
+    OutputSchema,
+    // protobuf definition of the settings
+    SettingsProto,
+}
+
+impl RunnerResourceKind {
+    pub const ALL: [RunnerResourceKind; 4] = [
+        RunnerResourceKind::SettingsSchema,
+        RunnerResourceKind::ArgumentsSchema,
+        RunnerResourceKind::OutputSchema,
+        RunnerResourceKind::SettingsProto,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunnerResourceKind::SettingsSchema => "settings_schema",
+            RunnerResourceKind::ArgumentsSchema => "arguments_schema",
+            RunnerResourceKind::OutputSchema => "output_schema",
+            RunnerResourceKind::SettingsProto => "settings_proto",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            RunnerResourceKind::SettingsSchema => "JSON schema of the runner settings",
+            RunnerResourceKind::ArgumentsSchema => {
+                "JSON schema of the job arguments (of each tool for mcp servers)"
+            }
+            RunnerResourceKind::OutputSchema => "JSON schema of the job output",
+            RunnerResourceKind::SettingsProto => "protobuf definition of the runner settings",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            RunnerResourceKind::SettingsProto => "text/plain",
+            _ => "application/schema+json",
+        }
+    }
+}
+
+pub fn uri(runner: &str, kind: RunnerResourceKind) -> String {
+    format!("{}{}/{}", URI_SCHEME, runner, kind.as_str())
+}
+
+/// Parse `runner://{name}/{kind}` into the runner name and the kind
+pub fn parse_uri(uri: &str) -> Option<(String, RunnerResourceKind)> {
+    let (runner, kind) = uri.strip_prefix(URI_SCHEME)?.rsplit_once('/')?;
+    let kind = RunnerResourceKind::ALL
+        .into_iter()
+        .find(|k| k.as_str() == kind)?;
+    (!runner.is_empty()).then(|| (runner.to_string(), kind))
+}
+
+/// Schema of the runner function (None if the runner has no such schema)
+pub fn schema_text(function: &FunctionSpecs, kind: RunnerResourceKind) -> Option<String> {
+    let text = match (function.schema.as_ref()?, kind) {
+        (function_specs::Schema::SingleSchema(s), RunnerResourceKind::SettingsSchema) => {
+            s.settings.clone()?
+        }
+        (function_specs::Schema::SingleSchema(s), RunnerResourceKind::ArgumentsSchema) => {
+            s.arguments.clone()
+        }
+        (function_specs::Schema::SingleSchema(s), RunnerResourceKind::OutputSchema) => {
+            s.result_output_schema.clone()?
+        }
+        // input schemas by the tool name
+        (function_specs::Schema::McpTools(tools), RunnerResourceKind::ArgumentsSchema) => {
+            let schemas = tools
+                .list
+                .iter()
+                .map(|t| {
+                    let schema = serde_json::from_str(&t.input_schema)
+                        .unwrap_or(serde_json::Value::String(t.input_schema.clone()));
+                    (t.name.clone(), schema)
+                })
+                .collect::<serde_json::Map<_, _>>();
+            serde_json::to_string_pretty(&schemas).ok()?
+        }
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// Protobuf definition of the runner (None if the runner has no such definition)
+pub fn proto_text(runner: &RunnerData, kind: RunnerResourceKind) -> Option<String> {
+    match kind {
+        RunnerResourceKind::SettingsProto => {
+            Some(runner.runner_settings_proto.clone()).filter(|p| !p.trim().is_empty())
+        }
+        _ => None,
+    }
+}
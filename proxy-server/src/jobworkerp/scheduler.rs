@@ -0,0 +1,271 @@
+use super::session::SessionInfo;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Unix epoch millis of an RFC 3339 timestamp or epoch millis of the field
+pub fn parse_timestamp(field: &str, value: &Value) -> Result<i64, String> {
+    match value {
+        Value::Number(n) => n
+            .as_i64()
+            .ok_or_else(|| format!("invalid {}: {}", field, n)),
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .map(|t| t.timestamp_millis())
+            .map_err(|e| format!("invalid {} '{}': {}", field, s, e)),
+        _ => Err(format!(
+            "{} must be an RFC 3339 string or epoch millis",
+            field
+        )),
+    }
+}
+
+/// When a scheduled tool call runs
+#[derive(Debug, Clone)]
+pub enum ScheduleSpec {
+    // once at the time (unix epoch millis)
+    Once {
+        run_at: i64,
+    },
+    // repeatedly with the interval (first run after the interval)
+    Interval {
+        every: Duration,
+    },
+    // repeatedly at the times of the cron expression (UTC)
+    Cron {
+        expression: String,
+        schedule: Box<cron::Schedule>,
+    },
+}
+
+impl ScheduleSpec {
+    pub const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Schedule from `run_after`, `interval_sec` or `cron` of the map (None if none of them)
+    ///
+    /// `run_after` is an RFC 3339 timestamp or unix epoch millis.
+    /// `cron` accepts 5 fields (`min hour day month weekday`) or 6 fields with seconds.
+    pub fn parse(args: &Map<String, Value>) -> Result<Option<Self>, String> {
+        let specs = [
+            args.get("run_after"),
+            args.get("interval_sec"),
+            args.get("cron"),
+        ];
+        match specs.iter().filter(|s| s.is_some()).count() {
+            0 => return Ok(None),
+            1 => {}
+            _ => return Err("only one of run_after, interval_sec and cron can be set".to_string()),
+        }
+        match specs {
+            [Some(run_after), _, _] => Self::parse_run_after(run_after).map(Some),
+            [_, Some(interval), _] => {
+                let every = interval
+                    .as_u64()
+                    .map(Duration::from_secs)
+                    .filter(|d| *d >= Self::MIN_INTERVAL)
+                    .ok_or_else(|| "interval_sec must be a positive integer".to_string())?;
+                Ok(Some(Self::Interval { every }))
+            }
+            [_, _, Some(cron)] => {
+                let expression = cron
+                    .as_str()
+                    .ok_or_else(|| "cron must be a string".to_string())?;
+                Self::parse_cron(expression).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_run_after(value: &Value) -> Result<Self, String> {
+        let run_at = parse_timestamp("run_after", value)?;
+        Ok(Self::Once { run_at })
+    }
+
+    fn parse_cron(expression: &str) -> Result<Self, String> {
+        // the cron crate requires the seconds field
+        let normalized = if expression.split_whitespace().count() == 5 {
+            format!("0 {}", expression)
+        } else {
+            expression.to_string()
+        };
+        let schedule = cron::Schedule::from_str(&normalized)
+            .map_err(|e| format!("invalid cron '{}': {}", expression, e))?;
+        Ok(Self::Cron {
+            expression: expression.to_string(),
+            schedule: Box::new(schedule),
+        })
+    }
+
+    /// Next run time (epoch millis) after `now`, None if no more runs
+    pub fn next_run(&self, now: i64, last_run: Option<i64>) -> Option<i64> {
+        match self {
+            Self::Once { run_at } => last_run.is_none().then_some(*run_at),
+            Self::Interval { every } => Some(now + every.as_millis() as i64),
+            Self::Cron { schedule, .. } => {
+                let now = DateTime::<Utc>::from_timestamp_millis(now)?;
+                schedule.after(&now).next().map(|t| t.timestamp_millis())
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Once { run_at } => format!("once at {}", run_at),
+            Self::Interval { every } => format!("every {}s", every.as_secs()),
+            Self::Cron { expression, .. } => format!("cron '{}'", expression),
+        }
+    }
+}
+
+/// Summary of a schedule (returned by the schedule tools)
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleStatus {
+    pub id: i64,
+    pub tool_name: String,
+    pub schedule: String,
+    // authenticated subject of the session which scheduled the call, and the session
+    pub owner: Option<String>,
+    pub session_id: String,
+    pub next_run_at: Option<i64>,
+    pub runs: u64,
+    pub last_error: Option<String>,
+}
+
+impl ScheduleStatus {
+    /// Whether the session may see the schedule: schedules of the same subject, or of the same
+    /// session without authentication (admins see all schedules)
+    pub fn is_visible_to(&self, session: &SessionInfo) -> bool {
+        match session.subject() {
+            Some(subject) => self.owner.as_deref() == Some(subject),
+            None => self.owner.is_none() && self.session_id == session.id,
+        }
+    }
+}
+
+struct ScheduleEntry {
+    status: ScheduleStatus,
+    ct: CancellationToken,
+}
+
+/// Scheduled tool calls of the server (kept in memory, cancelled with the sessions which
+/// scheduled them)
+pub struct Scheduler {
+    next_id: AtomicI64,
+    capacity: usize,
+    // max schedules of an owner (the authenticated subject, or the session without authentication)
+    owner_capacity: usize,
+    schedules: RwLock<BTreeMap<i64, ScheduleEntry>>,
+}
+
+impl Scheduler {
+    pub const DEFAULT_CAPACITY: usize = 100;
+    pub const DEFAULT_OWNER_CAPACITY: usize = 10;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            next_id: AtomicI64::new(1),
+            capacity,
+            owner_capacity: Self::DEFAULT_OWNER_CAPACITY.min(capacity),
+            schedules: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn with_owner_capacity(self, owner_capacity: usize) -> Self {
+        Self {
+            owner_capacity,
+            ..self
+        }
+    }
+
+    /// Register a schedule, returning its id and the token cancelled by `cancel`
+    pub fn add(
+        &self,
+        tool_name: &str,
+        spec: &ScheduleSpec,
+        session: &SessionInfo,
+        next_run_at: Option<i64>,
+    ) -> Result<(i64, CancellationToken), String> {
+        let mut schedules = self
+            .schedules
+            .write()
+            .map_err(|_| "scheduler is unavailable".to_string())?;
+        if schedules.len() >= self.capacity {
+            return Err(format!("too many schedules (max {})", self.capacity));
+        }
+        let owned = schedules
+            .values()
+            .filter(|e| e.status.is_visible_to(session))
+            .count();
+        if owned >= self.owner_capacity {
+            return Err(format!(
+                "too many schedules of the owner (max {})",
+                self.owner_capacity
+            ));
+        }
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let ct = CancellationToken::new();
+        schedules.insert(
+            id,
+            ScheduleEntry {
+                status: ScheduleStatus {
+                    id,
+                    tool_name: tool_name.to_string(),
+                    schedule: spec.describe(),
+                    owner: session.subject().map(|s| s.to_string()),
+                    session_id: session.id.clone(),
+                    next_run_at,
+                    runs: 0,
+                    last_error: None,
+                },
+                ct: ct.clone(),
+            },
+        );
+        Ok((id, ct))
+    }
+
+    pub fn record_run(&self, id: i64, error: Option<String>, next_run_at: Option<i64>) {
+        if let Some(entry) = self
+            .schedules
+            .write()
+            .ok()
+            .as_mut()
+            .and_then(|s| s.get_mut(&id))
+        {
+            entry.status.runs += 1;
+            entry.status.last_error = error;
+            entry.status.next_run_at = next_run_at;
+        }
+    }
+
+    /// Remove the schedule (finished or cancelled)
+    pub fn remove(&self, id: i64) -> Option<ScheduleStatus> {
+        let entry = self.schedules.write().ok()?.remove(&id)?;
+        entry.ct.cancel();
+        Some(entry.status)
+    }
+
+    pub fn get(&self, id: i64) -> Option<ScheduleStatus> {
+        self.schedules
+            .read()
+            .ok()
+            .and_then(|s| s.get(&id).map(|e| e.status.clone()))
+    }
+
+    pub fn list(&self) -> Vec<ScheduleStatus> {
+        self.schedules
+            .read()
+            .map(|s| s.values().map(|e| e.status.clone()).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
@@ -0,0 +1,150 @@
+use super::*;
+
+// scheduled calls of the session (`schedule_job`, and `run_after`, `interval_sec` or `cron` in
+// `_meta`)
+impl JobworkerpRouter {
+    // run the call at the times of the schedule in the background until the session is disconnected
+    pub(super) fn schedule_call(
+        &self,
+        request: CallToolRequestParam,
+        spec: ScheduleSpec,
+        metadata: Arc<HashMap<String, String>>,
+        options: CallOptions,
+    ) -> Result<CallToolResult, McpError> {
+        let now = Utc::now().timestamp_millis();
+        let first_run = spec
+            .next_run(now, None)
+            .ok_or_else(|| McpError::invalid_params("the schedule has no future run", None))?;
+        let (id, ct) = self
+            .scheduler
+            .add(&request.name, &spec, &self.session, Some(first_run))
+            .map_err(|e| McpError::invalid_request(e, None))?;
+        tracing::info!("scheduled {}: {} ({})", id, &request.name, spec.describe());
+        let router = self.clone();
+        let session_ct = self.session_state.ct.clone();
+        tokio::spawn(async move {
+            let mut next_run = Some(first_run);
+            while let Some(run_at) = next_run {
+                let delay = (run_at - Utc::now().timestamp_millis()).max(0) as u64;
+                tokio::select! {
+                    _ = ct.cancelled() => break,
+                    _ = session_ct.cancelled() => {
+                        tracing::info!("schedule {} cancelled with the session", id);
+                        break;
+                    }
+                    _ = tokio::time::sleep(Duration::from_millis(delay)) => {}
+                }
+                // the tool must still be allowed at the time of the run
+                if !Self::resolve_permission(router.rbac.as_deref(), &router.session)
+                    .is_allowed(&request.name)
+                {
+                    tracing::warn!(
+                        "schedule {} cancelled: tool not allowed: {}",
+                        id,
+                        &request.name
+                    );
+                    break;
+                }
+                tracing::info!("run schedule {}: {}", id, &request.name);
+                let error = match router
+                    .call_backend(request.clone(), metadata.clone(), options.clone())
+                    .await
+                {
+                    Ok(result) if result.is_error == Some(true) => {
+                        Some("the tool returned an error".to_string())
+                    }
+                    Ok(_) => None,
+                    Err(e) => Some(e.message.to_string()),
+                };
+                next_run = spec.next_run(Utc::now().timestamp_millis(), Some(run_at));
+                router.scheduler.record_run(id, error, next_run);
+            }
+            router.scheduler.remove(id);
+        });
+        match self.scheduler.get(id) {
+            Some(schedule) => meta_tool::schedule_result(&schedule),
+            None => Err(McpError::internal_error("schedule not found", None)),
+        }
+    }
+
+    pub(super) async fn schedule_job(
+        &self,
+        request: CallToolRequestParam,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let (tool, mut arguments) = meta_tool::parse_schedule_job(request.arguments.as_ref())?;
+        let spec = request
+            .arguments
+            .as_ref()
+            .map(ScheduleSpec::parse)
+            .transpose()
+            .map_err(|e| McpError::invalid_params(e, None))?
+            .flatten()
+            .ok_or_else(|| {
+                McpError::invalid_params("one of run_after, interval_sec or cron is required", None)
+            })?;
+        self.check_tool_permission(&tool)?;
+        if [
+            meta_tool::HELP,
+            meta_tool::TOOL_DIAGNOSTICS,
+            meta_tool::SEARCH_TOOLS,
+            meta_tool::RECENT_RESULTS,
+        ]
+        .into_iter()
+        .chain(meta_tool::COMPOSITE_TOOLS)
+        .any(|n| n == tool)
+        {
+            return Err(McpError::invalid_params(
+                format!("{} cannot be scheduled", &tool),
+                None,
+            ));
+        }
+        let meta = match arguments.remove("_meta") {
+            Some(serde_json::Value::Object(meta)) => Some(meta),
+            _ => None,
+        };
+        let metadata = self.request_metadata_with_meta(context, meta.as_ref());
+        let options = self.call_options(meta.as_ref())?;
+        self.schedule_call(
+            CallToolRequestParam {
+                name: tool.into(),
+                arguments: Some(arguments),
+            },
+            spec,
+            metadata,
+            options,
+        )
+    }
+
+    // schedules visible for the session (admins see all)
+    pub(super) fn visible_schedules(&self) -> Vec<ScheduleStatus> {
+        self.scheduler
+            .list()
+            .into_iter()
+            .filter(|s| self.permission.is_admin() || s.is_visible_to(&self.session))
+            .collect()
+    }
+
+    pub(super) fn cancel_schedule(
+        &self,
+        request: &CallToolRequestParam,
+    ) -> Result<CallToolResult, McpError> {
+        let id = meta_tool::parse_schedule_id(request.arguments.as_ref())?;
+        if !self.visible_schedules().iter().any(|s| s.id == id) {
+            return Err(McpError::invalid_params(
+                format!("schedule not found: {}", id),
+                None,
+            ));
+        }
+        match self.scheduler.remove(id) {
+            Some(schedule) => {
+                tracing::info!("schedule cancelled: {}", id);
+                meta_tool::schedule_result(&schedule)
+            }
+            None => Err(McpError::invalid_params(
+                format!("schedule not found: {}", id),
+                None,
+            )),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{Context, Result};
+use rmcp::model::Implementation;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Server information advertised to clients (`server` of the config file)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ServerInfoConfig {
+    // server name and version (default: this crate)
+    pub name: Option<String>,
+    pub version: Option<String>,
+    // instructions text (template), or the file containing it
+    pub instructions: Option<String>,
+    pub instructions_file: Option<String>,
+}
+
+impl ServerInfoConfig {
+    pub fn implementation(&self) -> Implementation {
+        let mut implementation = Implementation::from_build_env();
+        if let Some(name) = self.name.as_ref() {
+            implementation.name = name.clone();
+        }
+        if let Some(version) = self.version.as_ref() {
+            implementation.version = version.clone();
+        }
+        implementation
+    }
+
+    /// Instructions with the template variables (`{{name}}`, `{{env.NAME}}`) replaced
+    pub fn instructions(&self, default: &str, vars: &HashMap<&str, String>) -> Result<String> {
+        let template = match (self.instructions.as_ref(), self.instructions_file.as_ref()) {
+            (Some(instructions), _) => instructions.clone(),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read instructions file: {}", path))?,
+            (None, None) => return Ok(default.to_string()),
+        };
+        Ok(render_template(&template, default, vars))
+    }
+}
+
+/// Replace `{{name}}` with the value of `vars`, `{{env.NAME}}` with the environment variable
+/// and `{{default_instructions}}` with the built-in instructions (unknown variables are kept)
+pub fn render_template(template: &str, default: &str, vars: &HashMap<&str, String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = rest[start + 2..start + end].trim();
+        let value = if name == "default_instructions" {
+            Some(default.to_string())
+        } else if let Some(env) = name.strip_prefix("env.") {
+            Some(std::env::var(env).unwrap_or_default())
+        } else {
+            vars.get(name).cloned()
+        };
+        match value {
+            Some(value) => rendered.push_str(&value),
+            None => rendered.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
@@ -0,0 +1,204 @@
+use crate::auth::AuthIdentity;
+use rmcp::model::{ClientInfo, ProtocolVersion};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio_util::sync::CancellationToken;
+
+/// Connection level information of an MCP client session
+#[derive(Debug, Clone, Default)]
+pub struct SessionInfo {
+    pub id: String,
+    pub identity: Option<AuthIdentity>,
+    // http request headers of the connection (lowercase names)
+    pub headers: HashMap<String, String>,
+    // tenant given by the path of the connection (if selected by the path)
+    pub tenant: Option<String>,
+}
+
+impl SessionInfo {
+    pub fn stdio() -> Self {
+        Self {
+            id: "stdio".to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn subject(&self) -> Option<&str> {
+        self.identity.as_ref().map(|i| i.subject.as_str())
+    }
+}
+
+/// What happens to the job of a call in flight when its session is disconnected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisconnectPolicy {
+    // abort the call (the job is marked as cancelled)
+    #[default]
+    Cancel,
+    // let the job complete, its result can be retrieved by `resume_call` with the job id
+    Retain,
+}
+
+impl DisconnectPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "cancel" => Some(DisconnectPolicy::Cancel),
+            "retain" => Some(DisconnectPolicy::Retain),
+            _ => None,
+        }
+    }
+}
+
+/// Client implementation and protocol version given by the client at initialize
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionClient {
+    pub name: String,
+    pub version: String,
+    pub protocol_version: String,
+}
+
+impl SessionClient {
+    pub fn from_peer_info(peer_info: &ClientInfo) -> Self {
+        Self {
+            name: peer_info.client_info.name.clone(),
+            version: peer_info.client_info.version.clone(),
+            protocol_version: protocol_version_str(&peer_info.protocol_version),
+        }
+    }
+
+    /// "name/version"
+    pub fn label(&self) -> String {
+        format!("{}/{}", &self.name, &self.version)
+    }
+}
+
+fn protocol_version_str(version: &ProtocolVersion) -> String {
+    serde_json::to_value(version)
+        .ok()
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .unwrap_or_default()
+}
+
+/// State of an active session shared with its router
+#[derive(Debug, Clone, Default)]
+pub struct SessionState {
+    // cancelled when the session is disconnected (aborts in-flight calls)
+    pub ct: CancellationToken,
+    pub active_calls: Arc<AtomicUsize>,
+    // set when the session is initialized
+    pub client: Arc<OnceLock<SessionClient>>,
+}
+
+impl SessionState {
+    /// Count an in-flight call until the returned guard is dropped
+    pub fn start_call(&self) -> ActiveCallGuard {
+        self.active_calls.fetch_add(1, Ordering::SeqCst);
+        ActiveCallGuard(self.active_calls.clone())
+    }
+}
+
+pub struct ActiveCallGuard(Arc<AtomicUsize>);
+
+impl Drop for ActiveCallGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Summary of an active session (admin endpoint)
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStatus {
+    pub id: String,
+    pub subject: Option<String>,
+    // client implementation ("name/version") and protocol version
+    pub client: Option<String>,
+    pub protocol_version: Option<String>,
+    // connected time (unix epoch millis)
+    pub connected_at: i64,
+    pub active_calls: usize,
+}
+
+struct RegisteredSession {
+    info: SessionInfo,
+    connected_at: i64,
+    state: SessionState,
+}
+
+/// Active sessions of the server
+#[derive(Default)]
+pub struct SessionRegistry {
+    sessions: RwLock<HashMap<String, RegisteredSession>>,
+}
+
+impl SessionRegistry {
+    pub fn register(&self, info: &SessionInfo) -> SessionState {
+        let state = SessionState::default();
+        let connected_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default();
+        if let Ok(mut sessions) = self.sessions.write() {
+            sessions.insert(
+                info.id.clone(),
+                RegisteredSession {
+                    info: info.clone(),
+                    connected_at,
+                    state: state.clone(),
+                },
+            );
+        }
+        state
+    }
+
+    /// Remove the session and cancel its in-flight calls
+    pub fn unregister(&self, id: &str) {
+        let removed = self.sessions.write().ok().and_then(|mut s| s.remove(id));
+        if let Some(session) = removed {
+            session.state.ct.cancel();
+        }
+    }
+
+    pub fn list(&self) -> Vec<SessionStatus> {
+        let mut list = self
+            .sessions
+            .read()
+            .map(|sessions| {
+                sessions
+                    .values()
+                    .map(|s| SessionStatus {
+                        id: s.info.id.clone(),
+                        subject: s.info.subject().map(|s| s.to_string()),
+                        client: s.state.client.get().map(|c| c.label()),
+                        protocol_version: s.state.client.get().map(|c| c.protocol_version.clone()),
+                        connected_at: s.connected_at,
+                        active_calls: s.state.active_calls.load(Ordering::SeqCst),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        list.sort_by_key(|s| s.connected_at);
+        list
+    }
+
+    /// Number of in-flight calls of the session (0 if unknown)
+    pub fn active_calls(&self, id: &str) -> usize {
+        self.sessions
+            .read()
+            .ok()
+            .and_then(|s| {
+                s.get(id)
+                    .map(|s| s.state.active_calls.load(Ordering::SeqCst))
+            })
+            .unwrap_or(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.read().map(|s| s.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Fixed runner settings set by the server (`${env:VAR}` and `${file:PATH}` are replaced with
+/// the value of the environment variable or the content of the file)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct SettingsPresetsConfig {
+    // runner name -> settings fields
+    runners: BTreeMap<String, Map<String, Value>>,
+}
+
+impl SettingsPresetsConfig {
+    /// Resolve the secret references (fails if a variable or file is not found)
+    pub fn resolve(&self) -> Result<SettingsPresets> {
+        let mut runners = HashMap::new();
+        for (runner, settings) in self.runners.iter() {
+            let mut settings = Value::Object(settings.clone());
+            resolve_references(&mut settings)
+                .with_context(|| format!("Failed to resolve settings preset of {}", runner))?;
+            if let Value::Object(settings) = settings {
+                runners.insert(runner.clone(), settings);
+            }
+        }
+        Ok(SettingsPresets { runners })
+    }
+}
+
+/// Resolved settings presets (Debug does not show the values)
+#[derive(Clone, Default)]
+pub struct SettingsPresets {
+    runners: HashMap<String, Map<String, Value>>,
+}
+
+impl std::fmt::Debug for SettingsPresets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.fields()).finish()
+    }
+}
+
+impl SettingsPresets {
+    pub fn for_runner(&self, runner_name: &str) -> Option<&Map<String, Value>> {
+        self.runners.get(runner_name)
+    }
+
+    /// Names of the preset fields by runner name (removed from the tool schemas)
+    pub fn fields(&self) -> HashMap<String, Vec<String>> {
+        self.runners
+            .iter()
+            .map(|(runner, settings)| (runner.clone(), settings.keys().cloned().collect()))
+            .collect()
+    }
+
+    /// Set the preset fields to the settings of the call (the presets take precedence)
+    pub fn apply(&self, runner_name: &str, settings: &mut Option<Value>) {
+        let Some(preset) = self.for_runner(runner_name) else {
+            return;
+        };
+        if !settings.as_ref().is_some_and(|s| s.is_object()) {
+            *settings = Some(Value::Object(Map::new()));
+        }
+        if let Some(Value::Object(settings)) = settings.as_mut() {
+            settings.extend(preset.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+    }
+}
+
+fn resolve_references(value: &mut Value) -> Result<()> {
+    match value {
+        Value::String(s) if s.contains("${") => *s = resolve_string(s)?,
+        Value::Object(obj) => obj.values_mut().try_for_each(resolve_references)?,
+        Value::Array(list) => list.iter_mut().try_for_each(resolve_references)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+// replace the `${env:VAR}` and `${file:PATH}` references in the string
+pub(crate) fn resolve_string(s: &str) -> Result<String> {
+    let mut resolved = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        resolved.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .map(|e| start + e)
+            .ok_or_else(|| anyhow!("unclosed reference: {}", &rest[start..]))?;
+        let reference = &rest[start + 2..end];
+        match reference.split_once(':') {
+            Some(("env", name)) => resolved.push_str(
+                &std::env::var(name)
+                    .with_context(|| format!("environment variable not found: {}", name))?,
+            ),
+            Some(("file", path)) => resolved.push_str(
+                std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read secret file: {}", path))?
+                    .trim_end_matches(['\r', '\n']),
+            ),
+            _ => return Err(anyhow!("unknown reference: ${{{}}}", reference)),
+        }
+        rest = &rest[end + 1..];
+    }
+    resolved.push_str(rest);
+    Ok(resolved)
+}
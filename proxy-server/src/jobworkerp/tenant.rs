@@ -0,0 +1,154 @@
+use super::channel::UpstreamChannelConfig;
+use super::repository::JobworkerpRepository;
+use super::session::SessionInfo;
+use super::settings_presets::resolve_string;
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Upstream backends selected per connection (`tenants` of the config file)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TenantsConfig {
+    // request header carrying the tenant name
+    pub header: Option<String>,
+    // select the tenant by the first path segment of the sse endpoint (`/{tenant}/sse`)
+    pub path_segment: bool,
+    // reject connections without a tenant (the default backend is used otherwise)
+    pub required: bool,
+    // tenant name -> backend
+    pub backends: BTreeMap<String, TenantBackend>,
+}
+
+/// Backend of a tenant (`${env:VAR}` and `${file:PATH}` in the metadata values are resolved)
+#[derive(Debug, Clone, Deserialize)]
+pub struct TenantBackend {
+    pub jobworkerp_address: String,
+    // grpc metadata sent with every request to the backend (e.g. credentials)
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    // TLS settings of the backend (those of the default backend if not set)
+    pub tls_ca: Option<String>,
+    pub tls_client_cert: Option<String>,
+    pub tls_client_key: Option<String>,
+    pub tls_domain: Option<String>,
+}
+
+impl TenantBackend {
+    pub fn upstream(&self, default: &UpstreamChannelConfig) -> UpstreamChannelConfig {
+        UpstreamChannelConfig {
+            tls_ca_path: self.tls_ca.clone().or(default.tls_ca_path.clone()),
+            tls_client_cert_path: self
+                .tls_client_cert
+                .clone()
+                .or(default.tls_client_cert_path.clone()),
+            tls_client_key_path: self
+                .tls_client_key
+                .clone()
+                .or(default.tls_client_key_path.clone()),
+            tls_domain: self.tls_domain.clone().or(default.tls_domain.clone()),
+            ..default.clone()
+        }
+    }
+}
+
+impl TenantsConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.backends.is_empty()
+    }
+
+    /// Tenant of the session (the path segment first, then the header)
+    ///
+    /// Fails for tenants not in the map, and for sessions without a tenant if required.
+    pub fn select(&self, session: &SessionInfo) -> Result<Option<String>> {
+        let requested = session.tenant.clone().or_else(|| {
+            self.header
+                .as_ref()
+                .and_then(|h| session.headers.get(&h.to_lowercase()).cloned())
+        });
+        match requested {
+            Some(tenant) if self.backends.contains_key(&tenant) => Ok(Some(tenant)),
+            Some(tenant) => Err(anyhow!("unknown tenant: {}", tenant)),
+            None if self.required => Err(anyhow!("tenant is required")),
+            None => Ok(None),
+        }
+    }
+
+    /// Resolve the secret references of the metadata (fails if a variable or file is not found)
+    pub fn resolve(&self, upstream: &UpstreamChannelConfig) -> Result<Tenants> {
+        let mut metadata = HashMap::new();
+        for (tenant, backend) in self.backends.iter() {
+            let resolved = backend
+                .metadata
+                .iter()
+                .map(|(k, v)| Ok((k.to_lowercase(), resolve_string(v)?)))
+                .collect::<Result<HashMap<_, _>>>()
+                .with_context(|| format!("Failed to resolve metadata of tenant {}", tenant))?;
+            metadata.insert(tenant.clone(), Arc::new(resolved));
+        }
+        Ok(Tenants {
+            config: self.clone(),
+            upstream: upstream.clone(),
+            metadata,
+            repositories: tokio::sync::Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+/// Tenant map with the backend connections (connected on the first session of each tenant)
+pub struct Tenants {
+    pub config: TenantsConfig,
+    upstream: UpstreamChannelConfig,
+    metadata: HashMap<String, Arc<HashMap<String, String>>>,
+    repositories: tokio::sync::Mutex<HashMap<String, Arc<JobworkerpRepository>>>,
+}
+
+impl std::fmt::Debug for Tenants {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.config.backends.keys()).finish()
+    }
+}
+
+impl Tenants {
+    pub fn metadata(&self, tenant: &str) -> Arc<HashMap<String, String>> {
+        self.metadata.get(tenant).cloned().unwrap_or_default()
+    }
+
+    /// Repository of the tenant, connected with the settings of the default repository
+    pub async fn repository(
+        &self,
+        tenant: &str,
+        default: &JobworkerpRepository,
+    ) -> Result<Arc<JobworkerpRepository>> {
+        let mut repositories = self.repositories.lock().await;
+        if let Some(repository) = repositories.get(tenant) {
+            return Ok(repository.clone());
+        }
+        let backend = self
+            .config
+            .backends
+            .get(tenant)
+            .ok_or_else(|| anyhow!("unknown tenant: {}", tenant))?;
+        tracing::info!(
+            "connecting to jobworkerp of tenant {}: {}",
+            tenant,
+            &backend.jobworkerp_address
+        );
+        let mut repository = JobworkerpRepository::new(
+            &backend.jobworkerp_address,
+            Some(default.timeout_sec),
+            &backend.upstream(&self.upstream),
+        )
+        .await?
+        .with_ephemeral_worker_ttl(default.ephemeral_workers.ttl())
+        .with_worker_options(default.worker_options.clone())
+        .with_backend_metadata(self.metadata(tenant))
+        .with_circuit_breaker(default.circuit_breaker.with_same_settings());
+        repository.settings_presets = default.settings_presets.clone();
+        let repository = Arc::new(repository);
+        repository.spawn_worker_reaper();
+        repositories.insert(tenant.to_string(), repository.clone());
+        Ok(repository)
+    }
+}
@@ -0,0 +1,103 @@
+use jobworkerp_client::jobworkerp::function::data::FunctionSpecs;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+
+/// Functions changed between two function lists (by name)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolListDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub updated: Vec<String>,
+}
+
+impl ToolListDiff {
+    pub fn new(old: &[FunctionSpecs], new: &[FunctionSpecs]) -> Self {
+        fn by_name(functions: &[FunctionSpecs]) -> BTreeMap<&str, Vec<&FunctionSpecs>> {
+            let mut map = BTreeMap::<&str, Vec<&FunctionSpecs>>::new();
+            for f in functions {
+                map.entry(f.name.as_str()).or_default().push(f);
+            }
+            map
+        }
+        let (old, new) = (by_name(old), by_name(new));
+        let mut diff = Self::default();
+        for (name, functions) in new.iter() {
+            match old.get(name) {
+                None => diff.added.push(name.to_string()),
+                Some(old_functions) if old_functions != functions => {
+                    diff.updated.push(name.to_string())
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .keys()
+            .filter(|name| !new.contains_key(*name))
+            .map(|name| name.to_string())
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+impl std::fmt::Display for ToolListDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "added: {:?}, removed: {:?}, updated: {:?}",
+            &self.added, &self.removed, &self.updated
+        )
+    }
+}
+
+/// Function list refreshed in the background and shared by all sessions
+pub struct ToolCache {
+    functions: RwLock<Option<Arc<Vec<FunctionSpecs>>>>,
+    // notified when the refreshed function list differs from the previous one
+    changes: broadcast::Sender<()>,
+}
+
+impl ToolCache {
+    pub fn new() -> Self {
+        let (changes, _) = broadcast::channel(16);
+        Self {
+            functions: RwLock::new(None),
+            changes,
+        }
+    }
+
+    pub fn get(&self) -> Option<Arc<Vec<FunctionSpecs>>> {
+        self.functions.read().ok().and_then(|f| f.clone())
+    }
+
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<()> {
+        self.changes.subscribe()
+    }
+
+    /// Swap the cached function list, and return the changes from the previous list
+    /// (None: unchanged, or the first list)
+    pub fn update(&self, functions: Vec<FunctionSpecs>) -> Option<ToolListDiff> {
+        let mut cached = self.functions.write().ok()?;
+        let diff = cached
+            .as_ref()
+            .map(|c| ToolListDiff::new(c, &functions))
+            .filter(|d| !d.is_empty());
+        *cached = Some(Arc::new(functions));
+        drop(cached);
+        if diff.is_some() {
+            // no receivers: no session is listening
+            let _ = self.changes.send(());
+        }
+        diff
+    }
+}
+
+impl Default for ToolCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,46 @@
+use crate::rbac::glob_match;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Default arguments of the tools, merged under the arguments of the calls
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct ToolDefaultsConfig {
+    // tool name (`*` wildcards allowed) -> default arguments
+    tools: BTreeMap<String, Map<String, Value>>,
+}
+
+impl ToolDefaultsConfig {
+    /// Defaults of the tool (exact name first, then the first matching pattern)
+    pub fn for_tool(&self, name: &str) -> Option<&Map<String, Value>> {
+        self.tools.get(name).or_else(|| {
+            self.tools
+                .iter()
+                .find(|(pattern, _)| glob_match(pattern, name))
+                .map(|(_, defaults)| defaults)
+        })
+    }
+
+    /// Merge the defaults of the tool under the arguments (the arguments take precedence)
+    pub fn apply(&self, name: &str, arguments: &mut Option<Map<String, Value>>) {
+        if let Some(defaults) = self.for_tool(name) {
+            merge_defaults(arguments.get_or_insert_with(Map::new), defaults);
+        }
+    }
+}
+
+/// Deep merge: objects are merged recursively, other values of the arguments are kept
+pub fn merge_defaults(arguments: &mut Map<String, Value>, defaults: &Map<String, Value>) {
+    for (key, default) in defaults {
+        match (arguments.get_mut(key), default) {
+            (Some(Value::Object(argument)), Value::Object(default)) => {
+                merge_defaults(argument, default)
+            }
+            (Some(_), _) => {}
+            (None, _) => {
+                arguments.insert(key.clone(), default.clone());
+            }
+        }
+    }
+}
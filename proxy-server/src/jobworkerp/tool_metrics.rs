@@ -0,0 +1,295 @@
+use rmcp::model::{CallToolResult, ErrorCode};
+use rmcp::Error as McpError;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// upper bounds (seconds) of the histogram buckets
+pub const BUCKETS: [f64; 15] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0,
+];
+
+tokio::task_local! {
+    // start of the tool call served by the task (for the enqueue latency of its jobs)
+    static CALL_STARTED: Instant;
+}
+
+/// Run the tool call with its start time available to [`call_elapsed`]
+pub fn scope_call<F: Future>(started: Instant, call: F) -> impl Future<Output = F::Output> {
+    CALL_STARTED.scope(started, call)
+}
+
+/// Time since the start of the tool call served by the current task (None outside of calls,
+/// e.g. scheduled jobs)
+pub fn call_elapsed() -> Option<Duration> {
+    CALL_STARTED.try_with(|started| started.elapsed()).ok()
+}
+
+/// Classes of failed tool calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ErrorClass {
+    // invalid arguments or requests, unknown tools
+    Validation,
+    // failures of the backend or the proxy
+    Backend,
+    Timeout,
+    // the tool returned an error result (e.g. non-zero exit codes)
+    Tool,
+}
+
+impl ErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorClass::Validation => "validation",
+            ErrorClass::Backend => "backend",
+            ErrorClass::Timeout => "timeout",
+            ErrorClass::Tool => "tool",
+        }
+    }
+
+    /// Class of the call result (None if succeeded)
+    pub fn of(result: &Result<CallToolResult, McpError>) -> Option<Self> {
+        match result {
+            Ok(r) if r.is_error == Some(true) => Some(ErrorClass::Tool),
+            Ok(_) => None,
+            Err(e)
+                if e.code == ErrorCode::INVALID_PARAMS
+                    || e.code == ErrorCode::INVALID_REQUEST
+                    || e.code == ErrorCode::METHOD_NOT_FOUND =>
+            {
+                Some(ErrorClass::Validation)
+            }
+            Err(e) if Self::is_timeout(&e.message) => Some(ErrorClass::Timeout),
+            Err(_) => Some(ErrorClass::Backend),
+        }
+    }
+
+    fn is_timeout(message: &str) -> bool {
+        let message = message.to_lowercase();
+        message.contains("timeout") || message.contains("timed out") || message.contains("deadline")
+    }
+}
+
+/// Histogram of durations over [`BUCKETS`]
+#[derive(Debug, Clone, Default)]
+pub struct Histogram {
+    // observations per bucket (the last one is over the largest bound)
+    counts: [u64; BUCKETS.len() + 1],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn observe(&mut self, duration: Duration) {
+        let secs = duration.as_secs_f64();
+        let index = BUCKETS
+            .iter()
+            .position(|bound| secs <= *bound)
+            .unwrap_or(BUCKETS.len());
+        self.counts[index] += 1;
+        self.sum += secs;
+        self.count += 1;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Cumulative counts of the buckets (`le` of the bounds, then +Inf)
+    pub fn cumulative(&self) -> Vec<u64> {
+        self.counts
+            .iter()
+            .scan(0, |total, count| {
+                *total += count;
+                Some(*total)
+            })
+            .collect()
+    }
+
+    /// Upper bound of the bucket containing the quantile (infinity over the largest bound)
+    pub fn quantile(&self, q: f64) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        let rank = ((q * self.count as f64).ceil() as u64).max(1);
+        let index = self
+            .cumulative()
+            .iter()
+            .position(|total| *total >= rank)
+            .unwrap_or(BUCKETS.len());
+        BUCKETS.get(index).copied().unwrap_or(f64::INFINITY)
+    }
+}
+
+/// Latencies and errors of a tool
+#[derive(Debug, Clone, Default)]
+pub struct ToolStats {
+    // from the tool call to the start of its job
+    pub enqueue: Histogram,
+    // whole tool call
+    pub duration: Histogram,
+    pub errors: BTreeMap<ErrorClass, u64>,
+}
+
+impl ToolStats {
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+}
+
+/// Per-tool latency histograms and error counters (shared by all sessions)
+#[derive(Debug, Default)]
+pub struct ToolMetrics {
+    tools: Mutex<BTreeMap<String, ToolStats>>,
+    // call counts at the last summary (tools without new calls are not logged)
+    summarized: Mutex<BTreeMap<String, u64>>,
+}
+
+impl ToolMetrics {
+    fn update(&self, tool: &str, f: impl FnOnce(&mut ToolStats)) {
+        let mut tools = self.tools.lock().unwrap_or_else(|e| e.into_inner());
+        match tools.get_mut(tool) {
+            Some(stats) => f(stats),
+            None => f(tools.entry(tool.to_string()).or_default()),
+        }
+    }
+
+    pub fn record_enqueue(&self, tool: &str, latency: Duration) {
+        self.update(tool, |stats| stats.enqueue.observe(latency));
+    }
+
+    pub fn record_call(
+        &self,
+        tool: &str,
+        duration: Duration,
+        result: &Result<CallToolResult, McpError>,
+    ) {
+        self.update(tool, |stats| {
+            stats.duration.observe(duration);
+            if let Some(class) = ErrorClass::of(result) {
+                *stats.errors.entry(class).or_default() += 1;
+            }
+        });
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, ToolStats> {
+        self.tools
+            .lock()
+            .map(|tools| tools.clone())
+            .unwrap_or_default()
+    }
+
+    /// Metrics in the Prometheus text format
+    pub fn render_prometheus(&self) -> String {
+        let tools = self.snapshot();
+        let mut text = String::new();
+        write_histograms(
+            &mut text,
+            "mcp_tool_enqueue_seconds",
+            "Time from the tool call to the start of its job",
+            tools.iter().map(|(tool, stats)| (tool, &stats.enqueue)),
+        );
+        write_histograms(
+            &mut text,
+            "mcp_tool_call_duration_seconds",
+            "Duration of the tool calls",
+            tools.iter().map(|(tool, stats)| (tool, &stats.duration)),
+        );
+        let _ = writeln!(
+            text,
+            "# HELP mcp_tool_errors_total Failed tool calls by the error class"
+        );
+        let _ = writeln!(text, "# TYPE mcp_tool_errors_total counter");
+        for (tool, stats) in tools.iter() {
+            for (class, count) in stats.errors.iter() {
+                let _ = writeln!(
+                    text,
+                    "mcp_tool_errors_total{{tool=\"{}\",class=\"{}\"}} {}",
+                    escape_label(tool),
+                    class.as_str(),
+                    count
+                );
+            }
+        }
+        text
+    }
+
+    /// Log a line per tool called since the last summary
+    pub fn log_summary(&self) {
+        let tools = self.snapshot();
+        let mut summarized = self.summarized.lock().unwrap_or_else(|e| e.into_inner());
+        for (tool, stats) in tools.iter() {
+            let calls = stats.duration.count();
+            if summarized.get(tool) == Some(&calls) {
+                continue;
+            }
+            summarized.insert(tool.clone(), calls);
+            let errors = stats
+                .errors
+                .iter()
+                .map(|(class, count)| format!("{}={}", class.as_str(), count))
+                .collect::<Vec<_>>();
+            tracing::info!(
+                tool_name = %tool,
+                "tool metrics: {} calls, {} errors [{}], duration p50 <= {}s p95 <= {}s, enqueue p95 <= {}s",
+                calls,
+                stats.error_count(),
+                errors.join(" "),
+                stats.duration.quantile(0.5),
+                stats.duration.quantile(0.95),
+                stats.enqueue.quantile(0.95),
+            );
+        }
+    }
+}
+
+fn write_histograms<'a>(
+    text: &mut String,
+    name: &str,
+    help: &str,
+    histograms: impl Iterator<Item = (&'a String, &'a Histogram)>,
+) {
+    let _ = writeln!(text, "# HELP {} {}", name, help);
+    let _ = writeln!(text, "# TYPE {} histogram", name);
+    for (tool, histogram) in histograms.filter(|(_, h)| h.count() > 0) {
+        let tool = escape_label(tool);
+        let bounds = BUCKETS
+            .iter()
+            .map(|b| b.to_string())
+            .chain(["+Inf".to_string()]);
+        for (le, total) in bounds.zip(histogram.cumulative()) {
+            let _ = writeln!(
+                text,
+                "{}_bucket{{tool=\"{}\",le=\"{}\"}} {}",
+                name, tool, le, total
+            );
+        }
+        let _ = writeln!(
+            text,
+            "{}_sum{{tool=\"{}\"}} {}",
+            name,
+            tool,
+            histogram.sum()
+        );
+        let _ = writeln!(
+            text,
+            "{}_count{{tool=\"{}\"}} {}",
+            name,
+            tool,
+            histogram.count()
+        );
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
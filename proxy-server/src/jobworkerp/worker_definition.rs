@@ -0,0 +1,55 @@
+use super::worker_options::WorkerOptions;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// A worker provisioned from a definition file (`create-worker`)
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkerDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    // runner of the worker (not set for workflows)
+    pub runner: Option<String>,
+    // json of the runner settings schema
+    pub settings: Option<Value>,
+    // definition of a reusable workflow (instead of `runner` and `settings`)
+    pub workflow: Option<Value>,
+    pub channel: Option<String>,
+    // response type and result broadcasting (merged with `worker_options` of the config file)
+    #[serde(flatten)]
+    pub options: WorkerOptions,
+}
+
+impl WorkerDefinition {
+    fn check(&self) -> Result<()> {
+        match (&self.runner, &self.workflow) {
+            (Some(_), Some(_)) => anyhow::bail!("{}: both runner and workflow are set", self.name),
+            (None, None) => anyhow::bail!("{}: either runner or workflow is required", self.name),
+            (None, Some(_)) if self.settings.is_some() => {
+                anyhow::bail!("{}: settings are not used by workflows", self.name)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parse the worker definitions of a file (JSON or YAML): a definition, a list of them, or
+/// `workers:` with the list
+pub fn parse_definitions(text: &str) -> Result<Vec<WorkerDefinition>> {
+    let value: Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(_) => serde_yaml::from_str(text).context("definition is neither JSON nor YAML")?,
+    };
+    let definitions: Vec<WorkerDefinition> = match value {
+        Value::Object(mut obj) if obj.contains_key("workers") => {
+            serde_json::from_value(obj.remove("workers").unwrap_or_default())?
+        }
+        Value::Array(_) => serde_json::from_value(value)?,
+        value => vec![serde_json::from_value(value)?],
+    };
+    for definition in definitions.iter() {
+        definition.check()?;
+    }
+    Ok(definitions)
+}
@@ -0,0 +1,101 @@
+use crate::rbac::glob_match;
+use jobworkerp_client::jobworkerp::data::ResponseType;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Response type of the workers created by the proxy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseTypeOption {
+    // the result is returned as the response of the enqueue request
+    Direct,
+    // the result is only delivered to listeners (requires broadcast_results)
+    NoResult,
+}
+
+impl ResponseTypeOption {
+    pub fn to_proto(self) -> ResponseType {
+        match self {
+            Self::Direct => ResponseType::Direct,
+            Self::NoResult => ResponseType::NoResult,
+        }
+    }
+}
+
+/// Options of the workers created by the proxy (unset fields are inherited)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct WorkerOptions {
+    pub response_type: Option<ResponseTypeOption>,
+    pub broadcast_results: Option<bool>,
+}
+
+impl WorkerOptions {
+    pub fn merge(&self, base: &WorkerOptions) -> WorkerOptions {
+        WorkerOptions {
+            response_type: self.response_type.or(base.response_type),
+            broadcast_results: self.broadcast_results.or(base.broadcast_results),
+        }
+    }
+
+    pub fn response_type(&self) -> ResponseType {
+        self.response_type
+            .unwrap_or(ResponseTypeOption::Direct)
+            .to_proto()
+    }
+
+    /// Worker parameters (json) of the worker created for a runner call
+    pub fn worker_params(&self, name: &str) -> serde_json::Value {
+        let mut params = serde_json::json!({ "name": name });
+        if let Some(response_type) = self.response_type {
+            params["response_type"] = serde_json::json!(response_type.to_proto() as i32);
+        }
+        if let Some(broadcast_results) = self.broadcast_results {
+            params["broadcast_results"] = serde_json::json!(broadcast_results);
+        }
+        params
+    }
+}
+
+/// Worker options for runner calls and workflow creation
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WorkerOptionsConfig {
+    // applied to all workers created by the proxy
+    pub default: WorkerOptions,
+    // workers created by workflow creation tools (default: direct response, broadcast results)
+    pub workflow: WorkerOptions,
+    // runner name (`*` wildcards allowed) -> options of the workers created for its calls
+    pub runners: BTreeMap<String, WorkerOptions>,
+    // channels allowed for `_meta.channel` of runner calls (`*` wildcards allowed)
+    pub channels: Vec<String>,
+}
+
+impl WorkerOptionsConfig {
+    /// Options for the runner (exact name first, then the first matching pattern)
+    pub fn for_runner(&self, runner_name: &str) -> WorkerOptions {
+        let runner = self.runners.get(runner_name).or_else(|| {
+            self.runners
+                .iter()
+                .find(|(pattern, _)| glob_match(pattern, runner_name))
+                .map(|(_, options)| options)
+        });
+        match runner {
+            Some(options) => options.merge(&self.default),
+            None => self.default.clone(),
+        }
+    }
+
+    pub fn is_channel_allowed(&self, channel: &str) -> bool {
+        self.channels
+            .iter()
+            .any(|pattern| glob_match(pattern, channel))
+    }
+
+    pub fn for_workflow(&self) -> WorkerOptions {
+        self.workflow.merge(&self.default).merge(&WorkerOptions {
+            response_type: Some(ResponseTypeOption::Direct),
+            broadcast_results: Some(true),
+        })
+    }
+}
@@ -0,0 +1,193 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// Counters of ephemeral workers created for runner calls
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EphemeralWorkerStats {
+    pub created: u64,
+    pub deleted: u64,
+    pub delete_failed: u64,
+    pub active: u64,
+}
+
+#[derive(Debug)]
+struct EphemeralWorker {
+    // in-flight calls using the worker
+    refcount: usize,
+    last_used: Instant,
+    // being deleted from jobworkerp (calls wait for the deletion before creating it again)
+    deleting: bool,
+}
+
+/// Tracks the workers created by runner calls so that idle ones can be deleted from jobworkerp
+#[derive(Debug)]
+pub struct EphemeralWorkers {
+    ttl: Duration,
+    workers: Mutex<HashMap<String, EphemeralWorker>>,
+    // notified when deletions finish
+    deleted_notify: Notify,
+    created: AtomicU64,
+    deleted: AtomicU64,
+    delete_failed: AtomicU64,
+}
+
+impl EphemeralWorkers {
+    pub const NAME_PREFIX: &str = "mcp-ephemeral-";
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            workers: Mutex::new(HashMap::new()),
+            deleted_notify: Notify::new(),
+            created: AtomicU64::new(0),
+            deleted: AtomicU64::new(0),
+            delete_failed: AtomicU64::new(0),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Worker name for the runner and settings (calls with the same settings share the worker)
+    pub fn worker_name(runner_name: &str, settings: Option<&Value>) -> String {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        settings.map(|s| s.to_string()).hash(&mut hasher);
+        format!(
+            "{}{}-{:016x}",
+            Self::NAME_PREFIX,
+            runner_name,
+            hasher.finish()
+        )
+    }
+
+    /// Mark the worker as used until the returned lease is dropped (waiting for the deletion of
+    /// the worker if it is being deleted, so that the call does not use the deleted worker)
+    pub async fn acquire(self: &Arc<Self>, name: &str) -> EphemeralWorkerLease {
+        loop {
+            let Ok(mut workers) = self.workers.lock() else {
+                break;
+            };
+            if workers.get(name).is_some_and(|w| w.deleting) {
+                // registered before the lock is released not to miss the notification
+                let deleted = self.deleted_notify.notified();
+                drop(workers);
+                deleted.await;
+                continue;
+            }
+            let worker = workers.entry(name.to_string()).or_insert_with(|| {
+                self.created.fetch_add(1, Ordering::Relaxed);
+                EphemeralWorker {
+                    refcount: 0,
+                    last_used: Instant::now(),
+                    deleting: false,
+                }
+            });
+            worker.refcount += 1;
+            worker.last_used = Instant::now();
+            break;
+        }
+        EphemeralWorkerLease {
+            name: name.to_string(),
+            workers: self.clone(),
+        }
+    }
+
+    fn release(&self, name: &str) {
+        if let Some(worker) = self
+            .workers
+            .lock()
+            .ok()
+            .as_mut()
+            .and_then(|w| w.get_mut(name))
+        {
+            worker.refcount = worker.refcount.saturating_sub(1);
+            worker.last_used = Instant::now();
+        }
+    }
+
+    /// Mark the workers unused for the ttl as being deleted and return them (to be deleted and
+    /// passed to [`Self::finish_delete`])
+    pub fn take_expired(&self) -> Vec<String> {
+        let Ok(mut workers) = self.workers.lock() else {
+            return vec![];
+        };
+        workers
+            .iter_mut()
+            .filter(|(_, w)| !w.deleting && w.refcount == 0 && w.last_used.elapsed() >= self.ttl)
+            .map(|(name, w)| {
+                w.deleting = true;
+                name.clone()
+            })
+            .collect()
+    }
+
+    /// Record the result of deleting the worker (whether it was found), waking the calls waiting
+    /// for it. Workers failed to delete are kept to be deleted again later.
+    pub fn finish_delete(&self, name: &str, result: &anyhow::Result<bool>) {
+        match result {
+            Ok(true) => {
+                self.deleted.fetch_add(1, Ordering::Relaxed);
+            }
+            // already deleted (e.g. by another proxy)
+            Ok(false) => {}
+            Err(_) => {
+                self.delete_failed.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        if let Ok(mut workers) = self.workers.lock() {
+            match result {
+                Ok(_) => {
+                    workers.remove(name);
+                }
+                Err(_) => {
+                    if let Some(worker) = workers.get_mut(name) {
+                        worker.deleting = false;
+                        worker.last_used = Instant::now();
+                    }
+                }
+            }
+        }
+        self.deleted_notify.notify_waiters();
+    }
+
+    pub fn stats(&self) -> EphemeralWorkerStats {
+        EphemeralWorkerStats {
+            created: self.created.load(Ordering::Relaxed),
+            deleted: self.deleted.load(Ordering::Relaxed),
+            delete_failed: self.delete_failed.load(Ordering::Relaxed),
+            active: self.workers.lock().map(|w| w.len() as u64).unwrap_or(0),
+        }
+    }
+}
+
+impl Default for EphemeralWorkers {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_TTL)
+    }
+}
+
+/// Releases the ephemeral worker when the call finishes (or is aborted)
+pub struct EphemeralWorkerLease {
+    name: String,
+    workers: Arc<EphemeralWorkers>,
+}
+
+impl EphemeralWorkerLease {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Drop for EphemeralWorkerLease {
+    fn drop(&mut self) {
+        self.workers.release(&self.name);
+    }
+}
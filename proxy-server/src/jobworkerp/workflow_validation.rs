@@ -0,0 +1,99 @@
+use crate::argument_errors::FieldError;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// Result of the validation of a workflow definition
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorkflowValidation {
+    // fields mismatching the input schema of the workflow creation tool
+    pub errors: Vec<FieldError>,
+    // names referenced by `run.runner` / `run.worker` tasks which are not found in jobworkerp
+    pub unknown_runners: Vec<String>,
+    pub unknown_workers: Vec<String>,
+}
+
+impl WorkflowValidation {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty() && self.unknown_runners.is_empty() && self.unknown_workers.is_empty()
+    }
+
+    /// Readable report of the errors (one per line)
+    pub fn report(&self) -> String {
+        let mut lines = self
+            .errors
+            .iter()
+            .map(|e| {
+                let path = if e.path.is_empty() {
+                    "(definition)"
+                } else {
+                    e.path.as_str()
+                };
+                format!("{}: {} (received: {})", path, e.message, e.received)
+            })
+            .collect::<Vec<_>>();
+        lines.extend(
+            self.unknown_runners
+                .iter()
+                .map(|name| format!("runner not found: {}", name)),
+        );
+        lines.extend(
+            self.unknown_workers
+                .iter()
+                .map(|name| format!("worker not found: {}", name)),
+        );
+        lines.join("\n")
+    }
+}
+
+/// Parse a workflow definition file (JSON or YAML) into the arguments of the workflow creation
+/// tool (a bare definition with `document` is put into `workflow_data`)
+pub fn parse_definition(text: &str) -> Result<Map<String, Value>> {
+    let value: Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(_) => serde_yaml::from_str(text).context("definition is neither JSON nor YAML")?,
+    };
+    let Value::Object(definition) = value else {
+        anyhow::bail!("definition must be an object");
+    };
+    if definition.contains_key("workflow_data") {
+        Ok(definition)
+    } else {
+        Ok(Map::from_iter([(
+            "workflow_data".to_string(),
+            Value::Object(definition),
+        )]))
+    }
+}
+
+/// Runner and worker names referenced by the tasks of the definition (`run.runner.name`,
+/// `run.worker.name`), in any nesting of the tasks
+pub fn referenced_functions(definition: &Value) -> (BTreeSet<String>, BTreeSet<String>) {
+    let mut runners = BTreeSet::new();
+    let mut workers = BTreeSet::new();
+    collect_references(definition, &mut runners, &mut workers);
+    (runners, workers)
+}
+
+fn collect_references(
+    value: &Value,
+    runners: &mut BTreeSet<String>,
+    workers: &mut BTreeSet<String>,
+) {
+    match value {
+        Value::Object(obj) => {
+            if let Some(run) = obj.get("run") {
+                let name = |kind: &str| run.get(kind)?.get("name")?.as_str().map(str::to_string);
+                runners.extend(name("runner"));
+                workers.extend(name("worker"));
+            }
+            obj.values()
+                .for_each(|v| collect_references(v, runners, workers));
+        }
+        Value::Array(list) => list
+            .iter()
+            .for_each(|v| collect_references(v, runners, workers)),
+        _ => {}
+    }
+}
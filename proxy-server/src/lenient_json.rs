@@ -0,0 +1,220 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+// argument fields which may be given as json text by LLMs
+const ENCODED_FIELDS: [&str; 3] = ["arguments", "settings", "workflow_data"];
+// fields of the call itself (not wrapped into the envelope)
+const CALL_FIELDS: [&str; 1] = ["idempotency_key"];
+
+/// A formatting mistake fixed by the lenient parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonFix {
+    // an object or array given as json text
+    EncodedString,
+    // markdown code fence around the json
+    CodeFence,
+    TrailingComma,
+    SingleQuote,
+    UnquotedKey,
+    // fields given at the top level instead of in the `arguments` (and `settings`) envelope
+    MissingEnvelope,
+}
+
+/// Fixes applied to a field of the tool arguments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArgumentFix {
+    pub field: String,
+    pub fixes: Vec<JsonFix>,
+}
+
+/// Parse json, fixing trailing commas, single quotes, unquoted keys and code fences if the
+/// strict parsing fails (the error is that of the strict parsing)
+pub fn parse(text: &str) -> Result<(Value, Vec<JsonFix>), serde_json::Error> {
+    let error = match serde_json::from_str(text) {
+        Ok(value) => return Ok((value, vec![])),
+        Err(e) => e,
+    };
+    let mut fixes = Vec::new();
+    let mut text = text.trim();
+    if let Some(inner) = strip_code_fence(text) {
+        fixes.push(JsonFix::CodeFence);
+        text = inner;
+        if let Ok(value) = serde_json::from_str(text) {
+            return Ok((value, fixes));
+        }
+    }
+    let repaired = repair(text, &mut fixes);
+    serde_json::from_str(&repaired)
+        .map(|value| (value, fixes))
+        .map_err(|_| error)
+}
+
+// the content of "```json\n...\n```"
+fn strip_code_fence(text: &str) -> Option<&str> {
+    let inner = text.strip_prefix("```")?.strip_suffix("```")?;
+    // language tag of the fence
+    let start = inner.find('\n').unwrap_or(0);
+    Some(inner[start..].trim())
+}
+
+fn repair(text: &str, fixes: &mut Vec<JsonFix>) -> String {
+    let mut fix = |f: JsonFix| {
+        if !fixes.contains(&f) {
+            fixes.push(f);
+        }
+    };
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut out = String::with_capacity(text.len());
+    // last significant character written outside strings
+    let mut last = ' ';
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '"' | '\'' => {
+                if c == '\'' {
+                    fix(JsonFix::SingleQuote);
+                }
+                out.push('"');
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    match chars[i] {
+                        '\\' if i + 1 < chars.len() => {
+                            // `\'` is not a json escape
+                            if chars[i + 1] != '\'' {
+                                out.push('\\');
+                            }
+                            out.push(chars[i + 1]);
+                            i += 1;
+                        }
+                        '"' => out.push_str("\\\""),
+                        other => out.push(other),
+                    }
+                    i += 1;
+                }
+                out.push('"');
+                last = '"';
+            }
+            ',' => {
+                let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+                if matches!(next, Some('}') | Some(']')) {
+                    fix(JsonFix::TrailingComma);
+                } else {
+                    out.push(c);
+                    last = c;
+                }
+            }
+            c if (c.is_alphabetic() || c == '_' || c == '$') && matches!(last, '{' | ',') => {
+                let start = i;
+                while i + 1 < chars.len()
+                    && (chars[i + 1].is_alphanumeric() || matches!(chars[i + 1], '_' | '$' | '-'))
+                {
+                    i += 1;
+                }
+                let key = chars[start..=i].iter().collect::<String>();
+                let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+                if next == Some(&':') {
+                    fix(JsonFix::UnquotedKey);
+                    out.push('"');
+                    out.push_str(&key);
+                    out.push('"');
+                } else {
+                    out.push_str(&key);
+                }
+                last = '"';
+            }
+            c => {
+                out.push(c);
+                if !c.is_whitespace() {
+                    last = c;
+                }
+            }
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Parse the objects (or arrays) given as json text in the argument fields (`arguments`,
+/// `settings` and `workflow_data`), returning the applied fixes
+pub fn fix_arguments(arguments: &mut Option<Map<String, Value>>) -> Vec<ArgumentFix> {
+    let Some(arguments) = arguments.as_mut() else {
+        return vec![];
+    };
+    let mut applied = Vec::new();
+    for field in ENCODED_FIELDS {
+        let Some(Value::String(text)) = arguments.get(field) else {
+            continue;
+        };
+        let trimmed = text.trim_start();
+        if !(trimmed.starts_with('{') || trimmed.starts_with('[') || trimmed.starts_with("```")) {
+            continue;
+        }
+        if let Ok((value, mut fixes)) = parse(text) {
+            if value.is_object() || value.is_array() {
+                fixes.insert(0, JsonFix::EncodedString);
+                arguments.insert(field.to_string(), value);
+                applied.push(ArgumentFix {
+                    field: field.to_string(),
+                    fixes,
+                });
+            }
+        }
+    }
+    applied
+}
+
+/// Wrap the fields given at the top level into the `arguments` (and `settings`) envelope of the
+/// combined schema if the wrapped arguments are valid and the bare ones are not (or have fields
+/// unknown to the schema), returning the applied fix
+pub fn wrap_bare_arguments(
+    schema: &Map<String, Value>,
+    arguments: &mut Option<Map<String, Value>>,
+) -> Option<ArgumentFix> {
+    let properties = schema.get("properties").and_then(|p| p.as_object())?;
+    let bare = arguments.as_ref().filter(|a| !a.is_empty())?;
+    if !properties.contains_key("arguments")
+        || bare.contains_key("arguments")
+        || bare.contains_key("settings")
+    {
+        return None;
+    }
+    let settings_fields = properties
+        .get("settings")
+        .and_then(|s| s.get("properties"))
+        .and_then(|p| p.as_object());
+    let (mut wrapped_settings, mut wrapped_arguments) = (Map::new(), Map::new());
+    let mut wrapped = Map::new();
+    for (key, value) in bare.iter() {
+        if CALL_FIELDS.contains(&key.as_str()) {
+            wrapped.insert(key.clone(), value.clone());
+        } else if settings_fields.is_some_and(|f| f.contains_key(key)) {
+            wrapped_settings.insert(key.clone(), value.clone());
+        } else {
+            wrapped_arguments.insert(key.clone(), value.clone());
+        }
+    }
+    wrapped.insert("arguments".to_string(), Value::Object(wrapped_arguments));
+    if !wrapped_settings.is_empty() {
+        wrapped.insert("settings".to_string(), Value::Object(wrapped_settings));
+    }
+    let without_call_fields = |a: &Map<String, Value>| {
+        let mut a = a.clone();
+        a.retain(|k, _| !CALL_FIELDS.contains(&k.as_str()));
+        Value::Object(a)
+    };
+    let validator = jsonschema::validator_for(&Value::Object(schema.clone())).ok()?;
+    let unknown_fields = bare
+        .keys()
+        .any(|k| !properties.contains_key(k) && !CALL_FIELDS.contains(&k.as_str()));
+    let bare_valid = validator.is_valid(&without_call_fields(bare));
+    if !validator.is_valid(&without_call_fields(&wrapped)) || (bare_valid && !unknown_fields) {
+        return None;
+    }
+    *arguments = Some(wrapped);
+    Some(ArgumentFix {
+        field: "arguments".to_string(),
+        fixes: vec![JsonFix::MissingEnvelope],
+    })
+}
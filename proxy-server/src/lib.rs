@@ -1,102 +1,659 @@
+use crate::auth::{Authenticator, OAuthConfig};
+use crate::common::env;
+use crate::config::ProxyConfig;
+use crate::jobworkerp::session::SessionInfo;
 use crate::jobworkerp::{JobworkerpRouter, JobworkerpRouterConfig};
-use anyhow::Result;
-use rmcp::{
-    transport::{sse_server::SseServerConfig, stdio, SseServer},
-    ServiceExt,
-};
+use crate::mock::{MockRouter, MockTools};
+use crate::sse::SseServerSettings;
+use crate::tls::TlsSettings;
+use anyhow::{Context, Result};
+use rmcp::{transport::stdio, ServiceExt};
+use std::{sync::Arc, time::Duration};
 use tokio_util::sync::CancellationToken;
 
+pub mod argument_errors;
+pub mod auth;
+pub mod client;
 mod common;
+pub mod compact_schema;
+pub mod config;
+pub mod embedded_backend;
+pub mod enum_schema;
+pub mod http;
+pub mod interceptor;
 pub mod jobworkerp;
+pub mod lenient_json;
+pub mod logging;
+pub mod mock;
+pub mod plugin;
+pub mod rbac;
+pub mod recording;
+pub mod redaction;
+pub mod rest;
+pub mod result_conversion;
+pub mod schema_lint;
+pub mod scripting;
+pub mod sse;
+pub mod tls;
 pub mod tool_conversion;
+pub mod tool_export;
+pub mod tool_prompt;
+pub mod web_ui;
+
+/// Builder of the proxy for applications embedding it (settings of the binaries by `from_env`)
+pub struct ProxyBuilder {
+    config: JobworkerpRouterConfig,
+    sse_config: SseConfig,
+}
+
+impl ProxyBuilder {
+    pub fn new(config: JobworkerpRouterConfig) -> Self {
+        Self {
+            config,
+            sse_config: SseConfig::default(),
+        }
+    }
+
+    pub fn from_env(proxy_config: ProxyConfig) -> Self {
+        Self {
+            config: JobworkerpRouterConfig::from_env(proxy_config),
+            sse_config: SseConfig::from_env(),
+        }
+    }
+
+    pub fn with_jobworkerp_address(mut self, address: impl Into<String>) -> Self {
+        self.config.jobworkerp_address = address.into();
+        self
+    }
+
+    pub fn with_request_timeout_sec(mut self, timeout_sec: u32) -> Self {
+        self.config.request_timeout_sec = Some(timeout_sec);
+        self
+    }
+
+    /// List only the tools of the function set
+    pub fn with_set_name(mut self, set_name: impl Into<String>) -> Self {
+        self.config.set_name = Some(set_name.into());
+        self
+    }
+
+    pub fn with_exclude_runner_as_tool(mut self, exclude: bool) -> Self {
+        self.config.exclude_runner_as_tool = exclude;
+        self
+    }
+
+    pub fn with_exclude_worker_as_tool(mut self, exclude: bool) -> Self {
+        self.config.exclude_worker_as_tool = exclude;
+        self
+    }
+
+    pub fn with_proxy_config(mut self, proxy_config: ProxyConfig) -> Self {
+        self.config.proxy_config = proxy_config;
+        self
+    }
+
+    /// Wait for the backend at startup for this period
+    pub fn with_wait_for_backend_sec(mut self, wait_sec: u64) -> Self {
+        self.config.wait_for_backend_sec = Some(wait_sec);
+        self
+    }
+
+    pub fn with_config(mut self, config: JobworkerpRouterConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_sse_config(mut self, sse_config: SseConfig) -> Self {
+        self.sse_config = sse_config;
+        self
+    }
+
+    /// Bind addresses of the SSE server (comma separated)
+    pub fn with_address(mut self, address: impl Into<String>) -> Self {
+        self.sse_config.address = address.into();
+        self
+    }
+
+    pub fn with_auth_tokens(mut self, tokens: Vec<String>) -> Self {
+        self.sse_config.auth_tokens = tokens;
+        self
+    }
+
+    pub fn config(&self) -> &JobworkerpRouterConfig {
+        &self.config
+    }
+
+    pub fn sse_config(&self) -> &SseConfig {
+        &self.sse_config
+    }
+
+    /// Connect to jobworkerp and create the MCP service (sessions are created by
+    /// `JobworkerpRouter::new_session`)
+    pub async fn build_router(self) -> Result<JobworkerpRouter> {
+        JobworkerpRouter::new(self.config).await
+    }
+
+    pub async fn serve_stdio(self) -> Result<()> {
+        boot_stdio_server(self.config).await
+    }
+
+    /// Routes of the SSE server for mounting into an existing axum app (see `sse_router`)
+    pub async fn build_axum_router(self) -> Result<(axum::Router, CancellationToken)> {
+        sse_router(self.config, &self.sse_config).await
+    }
+
+    pub async fn serve_sse(self) -> Result<()> {
+        boot_sse_server(self.config, self.sse_config).await
+    }
+
+    /// Serve a local client over stdio and remote clients over SSE from one service
+    pub async fn serve_stdio_sse(self) -> Result<()> {
+        boot_stdio_sse_server(self.config, self.sse_config).await
+    }
+}
 
 pub async fn boot_stdio_server(config: JobworkerpRouterConfig) -> Result<()> {
-    let job_service = JobworkerpRouter::new(config).await?;
+    let settings = StdioSettings::of(&config);
+    let service = JobworkerpRouter::new(config).await?;
+    serve_stdio_session(&service, settings).await
+}
+
+/// Behavior of the stdio server when the client closes the connection
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdioSettings {
+    pub drain: Option<Duration>,
+    pub closed_exit_code: Option<i32>,
+}
+
+impl StdioSettings {
+    pub fn of(config: &JobworkerpRouterConfig) -> Self {
+        Self {
+            drain: config.stdio_drain_sec.map(Duration::from_secs),
+            closed_exit_code: config.stdio_closed_exit_code,
+        }
+    }
+}
+
+/// Serve a session of the service over stdio until the client closes the connection
+pub async fn serve_stdio_session(
+    service: &JobworkerpRouter,
+    settings: StdioSettings,
+) -> Result<()> {
+    let job_service = service.new_session(SessionInfo::stdio());
 
     // Create an instance of our counter router
-    let service = job_service.serve(stdio()).await.inspect_err(|e| {
+    let service = job_service.clone().serve(stdio()).await.inspect_err(|e| {
         tracing::error!("serving error: {:?}", e);
+        job_service.on_disconnect();
     })?;
+    job_service.on_initialized(service.peer_info());
 
     tracing::debug!("Serving MCP server");
 
-    service.waiting().await?;
-    Ok(())
+    // ends when stdin is closed or stdout is broken
+    let result = service.waiting().await;
+    tracing::info!("stdio client closed the connection: {:?}", &result);
+    // calls in flight are aborted, their jobs cancelled or let complete by the disconnect policy
+    job_service.on_disconnect();
+    let running_jobs = match settings.drain {
+        Some(drain) => job_service.drain_jobs(drain).await,
+        None => 0,
+    };
+    result?;
+    match settings.closed_exit_code {
+        Some(exit_code) => Err(StdioClosed {
+            exit_code,
+            running_jobs,
+        }
+        .into()),
+        None => Ok(()),
+    }
 }
 
-pub async fn boot_sse_server() -> Result<()> {
-    let mcp_address = std::env::var("MCP_ADDR").unwrap_or_else(|_| "127.0.0.1:8000".to_string());
-
-    let jobworkerp_address =
-        std::env::var("JOBWORKERP_ADDR").unwrap_or_else(|_| "http://127.0.0.1:9000".to_string());
-    let request_timeout_sec = std::env::var("REQUEST_TIMEOUT_SEC")
-        .ok()
-        .and_then(|s| s.parse::<u32>().ok());
-    let exclude_runner_as_tool = std::env::var("EXCLUDE_RUNNER_AS_TOOL")
-        .ok()
-        .and_then(|s| s.parse::<bool>().ok())
-        .unwrap_or_default();
-    let exclude_worker_as_tool = std::env::var("EXCLUDE_WORKER_AS_TOOL")
-        .ok()
-        .and_then(|s| s.parse::<bool>().ok())
-        .unwrap_or_default();
-    let set_name = std::env::var("TOOL_SET_NAME")
-        .ok()
-        .and_then(|s| s.parse::<String>().ok());
+/// The stdio client closed the connection (returned by [`boot_stdio_server`] if
+/// `STDIO_CLOSED_EXIT_CODE` is set, for the binaries to exit with the code)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StdioClosed {
+    pub exit_code: i32,
+    // jobs left running after the drain
+    pub running_jobs: usize,
+}
 
-    tracing::info!(
-        "Starting MCP server {}",
-        if let Some(set_name) = &set_name {
-            format!("with tool set name '{set_name}'")
-        } else {
-            format!(
-                "{} {}",
-                if exclude_runner_as_tool {
-                    "without runner"
-                } else {
-                    "with runner"
-                },
-                if exclude_worker_as_tool {
-                    "without worker as tool"
-                } else {
-                    "with worker as tool"
-                }
-            )
+impl std::fmt::Display for StdioClosed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "stdio client closed the connection ({} jobs left running)",
+            self.running_jobs
+        )
+    }
+}
+
+impl std::error::Error for StdioClosed {}
+
+impl StdioClosed {
+    /// Exit code of the process for the result of a server (None if not closed by the client)
+    pub fn exit_code_of(result: &Result<()>) -> Option<i32> {
+        result
+            .as_ref()
+            .err()
+            .and_then(|e| e.downcast_ref::<StdioClosed>())
+            .map(|closed| closed.exit_code)
+    }
+}
+
+/// Settings of the SSE server
+#[derive(Debug, Clone)]
+pub struct SseConfig {
+    // bind addresses (comma separated)
+    pub address: String,
+    // keep-alive ping interval of sse connections (0: disabled)
+    pub keep_alive_sec: u64,
+    // reconnection delay hint sent to sse clients
+    pub retry_ms: Option<u64>,
+    // path prefix when mounted under a reverse proxy (e.g. `/mcp/`)
+    pub base_path: String,
+    pub sse_path: String,
+    pub post_path: String,
+    // limits of the sse sessions (None: no limit)
+    pub max_sessions: Option<usize>,
+    pub session_idle_timeout_sec: Option<u64>,
+    pub admin_api: bool,
+    // plain http api over the tools (`GET /tools`, `POST /tools/{name}/call`)
+    pub rest_api: bool,
+    // read-only page of the tools and the recent calls (`/ui`)
+    pub web_ui: bool,
+    // per-tool latencies and errors in the Prometheus text format (`/metrics`)
+    pub metrics: bool,
+    // static bearer tokens required for both sse and message endpoints
+    pub auth_tokens: Vec<String>,
+    pub oauth: Option<OAuthConfig>,
+    pub access_log: bool,
+    pub access_log_sample_rate: f64,
+    // CORS is enabled if the allowed origins are set
+    pub cors_allowed_origins: Option<String>,
+    pub cors_allowed_headers: Option<String>,
+    pub cors_allow_credentials: bool,
+    pub tls: Option<TlsSettings>,
+}
+
+impl Default for SseConfig {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:8000".to_string(),
+            keep_alive_sec: 15,
+            retry_ms: None,
+            base_path: String::new(),
+            sse_path: "/sse".to_string(),
+            post_path: "/message".to_string(),
+            max_sessions: None,
+            session_idle_timeout_sec: None,
+            admin_api: false,
+            rest_api: false,
+            web_ui: false,
+            metrics: false,
+            auth_tokens: vec![],
+            oauth: None,
+            access_log: false,
+            access_log_sample_rate: 1.0,
+            cors_allowed_origins: None,
+            cors_allowed_headers: None,
+            cors_allow_credentials: false,
+            tls: None,
         }
-    );
-    let config = JobworkerpRouterConfig {
-        jobworkerp_address,
-        request_timeout_sec,
-        exclude_runner_as_tool,
-        exclude_worker_as_tool,
-        set_name,
-    };
+    }
+}
+
+impl SseConfig {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let address = std::env::var("MCP_ADDR").unwrap_or(default.address);
+        let tls = TlsSettings::from_env();
+        Self {
+            oauth: OAuthConfig::from_env(&address, tls.is_some()),
+            address,
+            keep_alive_sec: env::parse::<u64>("SSE_KEEP_ALIVE_SEC")
+                .unwrap_or(default.keep_alive_sec),
+            retry_ms: env::parse::<u64>("SSE_RETRY_MS"),
+            base_path: std::env::var("MCP_BASE_PATH").unwrap_or_default(),
+            sse_path: std::env::var("MCP_SSE_PATH").unwrap_or(default.sse_path),
+            post_path: std::env::var("MCP_POST_PATH").unwrap_or(default.post_path),
+            max_sessions: env::parse::<usize>("MAX_SESSIONS").filter(|n| *n > 0),
+            session_idle_timeout_sec: env::parse::<u64>("SESSION_IDLE_TIMEOUT_SEC")
+                .filter(|s| *s > 0),
+            admin_api: env::parse::<bool>("ADMIN_API").unwrap_or_default(),
+            rest_api: env::parse::<bool>("REST_API").unwrap_or_default(),
+            web_ui: env::parse::<bool>("WEB_UI").unwrap_or_default(),
+            metrics: env::parse::<bool>("METRICS").unwrap_or_default(),
+            auth_tokens: env::list("MCP_AUTH_TOKENS"),
+            access_log: env::parse::<bool>("ACCESS_LOG").unwrap_or_default(),
+            access_log_sample_rate: env::parse::<f64>("ACCESS_LOG_SAMPLE_RATE")
+                .unwrap_or(default.access_log_sample_rate),
+            cors_allowed_origins: std::env::var("CORS_ALLOWED_ORIGINS").ok(),
+            cors_allowed_headers: std::env::var("CORS_ALLOWED_HEADERS").ok(),
+            cors_allow_credentials: env::parse::<bool>("CORS_ALLOW_CREDENTIALS")
+                .unwrap_or_default(),
+            tls,
+        }
+    }
+}
+
+/// Routes of the SSE server (with the auth, access log and CORS layers) to be mounted into an
+/// existing axum app, and the token cancelling the sessions served by them
+pub async fn sse_router(
+    config: JobworkerpRouterConfig,
+    sse_config: &SseConfig,
+) -> Result<(axum::Router, CancellationToken)> {
+    let proxy_config = config.proxy_config.clone();
+    let service = JobworkerpRouter::new(config).await?;
+    sse_router_with_service(service, &proxy_config, sse_config)
+}
 
-    let sse_config = SseServerConfig {
-        sse_keep_alive: None,
-        bind: mcp_address.parse()?,
-        sse_path: "/sse".to_string(),
-        post_path: "/message".to_string(),
-        ct: CancellationToken::new(),
+/// Routes of the SSE server serving sessions of an existing service, which shares the backend
+/// connection, caches and job history with the other sessions of the service (e.g. over stdio)
+pub fn sse_router_with_service(
+    service: JobworkerpRouter,
+    proxy_config: &ProxyConfig,
+    sse_config: &SseConfig,
+) -> Result<(axum::Router, CancellationToken)> {
+    let base_path = &sse_config.base_path;
+
+    let session_registry = service.session_registry();
+    let settings = SseServerSettings {
+        sse_path: http::join_path(base_path, &sse_config.sse_path),
+        post_path: http::join_path(base_path, &sse_config.post_path),
+        keep_alive: (sse_config.keep_alive_sec > 0)
+            .then(|| Duration::from_secs(sse_config.keep_alive_sec)),
+        retry: sse_config.retry_ms.map(Duration::from_millis),
+        tenants: proxy_config
+            .tenants
+            .is_enabled()
+            .then(|| Arc::new(proxy_config.tenants.clone())),
+        max_sessions: sse_config.max_sessions,
+        idle_timeout: sse_config.session_idle_timeout_sec.map(Duration::from_secs),
+        session_registry: Some(session_registry.clone()),
     };
+    tracing::info!(
+        "SSE endpoint: {}, message endpoint: {}",
+        &settings.sse_path,
+        &settings.post_path
+    );
+
+    let tenants = settings.tenants.clone();
+    let (mut router, mut sessions) = sse::router(settings);
+    if sse_config.admin_api {
+        let admin_path = http::join_path(base_path, "/admin/sessions");
+        tracing::info!("admin endpoint: {}", &admin_path);
+        let registry = session_registry.clone();
+        let rbac = proxy_config.rbac.clone().map(Arc::new);
+        router = router.route(
+            &admin_path,
+            axum::routing::get(move |identity| {
+                http::list_sessions(registry.clone(), rbac.clone(), identity)
+            }),
+        );
+        let logging_path = http::join_path(base_path, "/admin/logging");
+        tracing::info!("admin endpoint: {}", &logging_path);
+        let rbac = proxy_config.rbac.clone().map(Arc::new);
+        let get_rbac = rbac.clone();
+        router = router.route(
+            &logging_path,
+            axum::routing::get(move |identity| {
+                http::logging_settings(get_rbac.clone(), identity, None)
+            })
+            .put(move |identity, settings| {
+                http::logging_settings(rbac.clone(), identity, Some(settings))
+            }),
+        );
+    }
+    let rbac = proxy_config.rbac.clone().map(Arc::new);
+    if sse_config.rest_api {
+        tracing::info!("rest endpoint: {}", http::join_path(base_path, "/tools"));
+        router = router.merge(rest::router(service.clone(), base_path, tenants));
+    }
+    if sse_config.web_ui {
+        tracing::info!("web ui: {}", http::join_path(base_path, "/ui"));
+        router = router.merge(web_ui::router(service.clone(), base_path, rbac));
+    }
+    if sse_config.metrics {
+        let metrics_path = http::join_path(base_path, "/metrics");
+        tracing::info!("metrics endpoint: {}", &metrics_path);
+        let metrics = service.tool_metrics.clone();
+        router = router.route(
+            &metrics_path,
+            axum::routing::get(move || http::metrics(metrics.clone())),
+        );
+    }
+    let router = with_http_layers(router, sse_config)?;
+
+    let ct = CancellationToken::new();
+    let session_ct = ct.clone();
+    tokio::spawn(async move {
+        while let Some(session) = sessions.recv().await {
+            let service = service.clone();
+            let ct = session_ct.child_token();
+            tokio::spawn(async move {
+                let session_id = session.info.id.clone();
+                let service = match service.new_tenant_session(session.info).await {
+                    Ok(service) => service,
+                    Err(e) => {
+                        tracing::error!("failed to start session {}: {:?}", &session_id, e);
+                        return Err(e);
+                    }
+                };
+                let server = match service.clone().serve_with_ct(session.transport, ct).await {
+                    Ok(server) => server,
+                    Err(e) => {
+                        service.on_disconnect();
+                        return Err(e.into());
+                    }
+                };
+                service.on_initialized(server.peer_info());
+                let result = server.waiting().await;
+                service.on_disconnect();
+                result?;
+                anyhow::Ok(())
+            });
+        }
+    });
+    Ok((router, ct))
+}
 
-    let mut sse_server = SseServer::serve_with_config(sse_config).await?;
+// the auth, access log and CORS layers (and the OAuth metadata route) of the SSE server routes
+fn with_http_layers(mut router: axum::Router, sse_config: &SseConfig) -> Result<axum::Router> {
+    let authenticator = Arc::new(Authenticator::new(
+        sse_config.auth_tokens.clone(),
+        sse_config.oauth.clone(),
+    ));
+    if authenticator.is_enabled() {
+        tracing::info!("authentication enabled");
+        let auth = authenticator.clone();
+        router = router.layer(axum::middleware::from_fn(move |req, next| {
+            http::auth(auth.clone(), req, next)
+        }));
+    } else {
+        tracing::warn!("SSE server is running without authentication");
+    }
+    if let Some(oauth) = authenticator.oauth_config().cloned() {
+        tracing::info!("OAuth enabled with issuer: {}", &oauth.issuer);
+        let oauth = Arc::new(oauth);
+        router = router.route(
+            "/.well-known/oauth-protected-resource",
+            axum::routing::get(move || http::protected_resource_metadata(oauth.clone())),
+        );
+    }
+    if sse_config.access_log {
+        let sample_rate = sse_config.access_log_sample_rate;
+        router = router.layer(axum::middleware::from_fn(move |req, next| {
+            http::access_log(sample_rate, req, next)
+        }));
+    }
+    if let Some(allowed_origins) = sse_config.cors_allowed_origins.as_ref() {
+        tracing::info!("CORS enabled for origins: {}", allowed_origins);
+        router = router.layer(http::cors_layer(
+            allowed_origins,
+            sse_config.cors_allowed_headers.as_deref(),
+            sse_config.cors_allow_credentials,
+        )?);
+    }
+    Ok(router)
+}
+
+pub async fn boot_sse_server(config: JobworkerpRouterConfig, sse_config: SseConfig) -> Result<()> {
+    let (router, ct) = sse_router(config, &sse_config).await?;
+    serve_sse_router(router, ct, &sse_config).await
+}
+
+/// Serve the local stdio client and the SSE clients from one service (sharing the backend
+/// connection, caches and job history) until the stdio client closes the connection or ctrl-c
+pub async fn boot_stdio_sse_server(
+    config: JobworkerpRouterConfig,
+    sse_config: SseConfig,
+) -> Result<()> {
+    let settings = StdioSettings::of(&config);
+    let proxy_config = config.proxy_config.clone();
     let service = JobworkerpRouter::new(config).await?;
+    let (router, ct) = sse_router_with_service(service.clone(), &proxy_config, &sse_config)?;
+    bind_sse_router(router, &ct, &sse_config).await?;
+    let result = tokio::select! {
+        result = serve_stdio_session(&service, settings) => result,
+        signal = tokio::signal::ctrl_c() => signal.map_err(Into::into),
+    };
+    // the SSE sessions end with the stdio client
+    ct.cancel();
+    result
+}
 
-    let ct = sse_server.config.ct.clone();
+/// Serve the tools of the manifest over stdio without jobworkerp
+pub async fn boot_mock_stdio_server(tools: MockTools) -> Result<()> {
+    tracing::info!("serving {} mock tools", tools.tools.len());
+    let service = MockRouter::new(tools)
+        .serve(stdio())
+        .await
+        .inspect_err(|e| tracing::error!("serving error: {:?}", e))?;
+    service.waiting().await?;
+    Ok(())
+}
+
+/// Serve the tools of the manifest over SSE without jobworkerp
+pub async fn boot_mock_sse_server(tools: MockTools, sse_config: SseConfig) -> Result<()> {
+    tracing::info!("serving {} mock tools", tools.tools.len());
+    let (router, ct) = mock_sse_router(tools, &sse_config)?;
+    serve_sse_router(router, ct, &sse_config).await
+}
+
+/// Routes of the SSE server answering from the tool manifest (with the same auth, access log and
+/// CORS layers as the SSE server)
+pub fn mock_sse_router(
+    tools: MockTools,
+    sse_config: &SseConfig,
+) -> Result<(axum::Router, CancellationToken)> {
+    let settings = SseServerSettings {
+        sse_path: http::join_path(&sse_config.base_path, &sse_config.sse_path),
+        post_path: http::join_path(&sse_config.base_path, &sse_config.post_path),
+        keep_alive: (sse_config.keep_alive_sec > 0)
+            .then(|| Duration::from_secs(sse_config.keep_alive_sec)),
+        retry: sse_config.retry_ms.map(Duration::from_millis),
+        tenants: None,
+        max_sessions: sse_config.max_sessions,
+        idle_timeout: sse_config.session_idle_timeout_sec.map(Duration::from_secs),
+        session_registry: None,
+    };
+    let (router, mut sessions) = sse::router(settings);
+    let router = with_http_layers(router, sse_config)?;
+    let service = MockRouter::new(tools);
+    let ct = CancellationToken::new();
+    let session_ct = ct.clone();
     tokio::spawn(async move {
-        while let Some(transport) = sse_server.next_transport().await {
+        while let Some(session) = sessions.recv().await {
             let service = service.clone();
-            let ct = sse_server.config.ct.child_token();
+            let ct = session_ct.child_token();
             tokio::spawn(async move {
-                let server = service.serve_with_ct(transport, ct).await?;
-                server.waiting().await?;
-                tokio::io::Result::Ok(())
+                service
+                    .serve_with_ct(session.transport, ct)
+                    .await?
+                    .waiting()
+                    .await?;
+                anyhow::Ok(())
             });
         }
     });
+    Ok((router, ct))
+}
+
+async fn serve_sse_router(
+    router: axum::Router,
+    ct: CancellationToken,
+    sse_config: &SseConfig,
+) -> Result<()> {
+    bind_sse_router(router, &ct, sse_config).await?;
     tokio::signal::ctrl_c().await?;
     ct.cancel();
 
     Ok(())
 }
+
+/// Listen on the bind addresses in the background until the token is cancelled
+async fn bind_sse_router(
+    router: axum::Router,
+    ct: &CancellationToken,
+    sse_config: &SseConfig,
+) -> Result<()> {
+    let binds = http::parse_bind_addresses(&sse_config.address)?;
+    let tls_config = match sse_config.tls.as_ref() {
+        Some(tls) => {
+            tracing::info!(
+                "TLS enabled{}",
+                if tls.client_ca_path.is_some() {
+                    " with client certificate verification"
+                } else {
+                    ""
+                }
+            );
+            Some(axum_server::tls_rustls::RustlsConfig::from_config(
+                Arc::new(tls.server_config()?),
+            ))
+        }
+        None => None,
+    };
+    for bind in binds {
+        let router = router.clone();
+        let server_ct = ct.clone();
+        if let Some(tls_config) = tls_config.clone() {
+            // bound here to fail the startup (not only the task) if the address is unavailable
+            let listener = std::net::TcpListener::bind(bind)
+                .with_context(|| format!("Failed to bind {}", bind))?;
+            listener.set_nonblocking(true)?;
+            tracing::info!("listening on https://{}", bind);
+            let handle = axum_server::Handle::new();
+            let shutdown_handle = handle.clone();
+            tokio::spawn(async move {
+                server_ct.cancelled().await;
+                shutdown_handle.graceful_shutdown(Some(Duration::from_secs(5)));
+            });
+            tokio::spawn(async move {
+                if let Err(e) = axum_server::from_tcp_rustls(listener, tls_config)
+                    .handle(handle)
+                    .serve(router.into_make_service())
+                    .await
+                {
+                    tracing::error!("sse server error ({}): {:?}", bind, e);
+                }
+            });
+        } else {
+            let listener = tokio::net::TcpListener::bind(bind)
+                .await
+                .with_context(|| format!("Failed to bind {}", bind))?;
+            tracing::info!("listening on http://{}", bind);
+            tokio::spawn(async move {
+                if let Err(e) = axum::serve(listener, router)
+                    .with_graceful_shutdown(server_ct.cancelled_owned())
+                    .await
+                {
+                    tracing::error!("sse server error ({}): {:?}", bind, e);
+                }
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,339 @@
+use crate::common::env;
+use crate::redaction::RedactionConfig;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+};
+use tracing::Subscriber;
+use tracing_appender::{non_blocking::WorkerGuard, rolling};
+use tracing_subscriber::{
+    fmt::{format::FmtSpan, MakeWriter},
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    reload,
+    util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
+};
+
+// filter of the installed subscriber, replaced at runtime by the admin endpoint
+static LOG_FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+// rate of the per-call debug dumps (arguments, settings) logged (f64 bits, 1.0 by default)
+static DEBUG_SAMPLE_RATE: AtomicU64 = AtomicU64::new(0x3FF0_0000_0000_0000);
+
+/// Output format of logs (`LOG_FORMAT`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    // one json object per line with the fields of the current span and its parents (spans:
+    // call_tool with request_id, session_id, tool_name and job_id, then the phases of the call)
+    Json,
+}
+
+impl LogFormat {
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("LOG_FORMAT").as_deref() {
+            Ok("json") => Ok(Self::Json),
+            Ok("text") | Ok("") | Err(_) => Ok(Self::Text),
+            Ok(f) => Err(anyhow::anyhow!("Unknown LOG_FORMAT: {}", f)),
+        }
+    }
+}
+
+/// Log the close of spans with their busy and idle time (`LOG_SPAN_TIMINGS`), to follow the
+/// phases of tool calls (resolve_tool, convert_arguments, enqueue)
+pub fn span_events_from_env() -> FmtSpan {
+    let enabled = env::parse::<bool>("LOG_SPAN_TIMINGS").unwrap_or_default();
+    if enabled {
+        FmtSpan::CLOSE
+    } else {
+        FmtSpan::NONE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Minutely,
+    Hourly,
+    #[default]
+    Daily,
+    // rotate when the file exceeds `max_size` bytes
+    Size,
+    Never,
+}
+
+/// Log file settings (`logging.file` of the config file)
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogFileConfig {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub rotation: LogRotation,
+    // max bytes of a file for `size` rotation
+    #[serde(default = "LogFileConfig::default_max_size")]
+    pub max_size: u64,
+    // number of retained (rotated) files
+    pub max_files: Option<usize>,
+}
+
+impl LogFileConfig {
+    fn default_max_size() -> u64 {
+        10 * 1024 * 1024
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub file: Option<LogFileConfig>,
+    // sensitive values replaced in the logs of tool arguments and settings
+    pub redaction: RedactionConfig,
+}
+
+/// Log file writer rotated by size: `path` -> `path.1` -> ... -> `path.{max_files}`
+pub struct SizeRotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl SizeRotatingFile {
+    pub fn new(path: impl Into<PathBuf>, max_size: u64, max_files: usize) -> Result<Self> {
+        let path = path.into();
+        let file = Self::open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_size,
+            max_files,
+            file,
+            size,
+        })
+    }
+
+    fn open(path: &Path) -> Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file: {}", path.display()))
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            self.file.set_len(0)?;
+        } else {
+            let _ = std::fs::remove_file(self.rotated_path(self.max_files));
+            for i in (1..self.max_files).rev() {
+                let from = self.rotated_path(i);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(i + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = Self::open(&self.path).map_err(std::io::Error::other)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn file_writer(config: &LogFileConfig) -> Result<Box<dyn Write + Send>> {
+    if let Some(dir) = config.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create log directory: {}", dir.display()))?;
+    }
+    let rotation = match config.rotation {
+        LogRotation::Size => {
+            return Ok(Box::new(SizeRotatingFile::new(
+                &config.path,
+                config.max_size,
+                config.max_files.unwrap_or(5),
+            )?));
+        }
+        LogRotation::Minutely => rolling::Rotation::MINUTELY,
+        LogRotation::Hourly => rolling::Rotation::HOURLY,
+        LogRotation::Daily => rolling::Rotation::DAILY,
+        LogRotation::Never => rolling::Rotation::NEVER,
+    };
+    let file_name = config
+        .path
+        .file_name()
+        .and_then(|f| f.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid log file path: {}", config.path.display()))?;
+    let mut builder = rolling::Builder::new()
+        .rotation(rotation)
+        .filename_prefix(file_name);
+    if let Some(max_files) = config.max_files {
+        builder = builder.max_log_files(max_files);
+    }
+    let dir = config
+        .path
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    Ok(Box::new(builder.build(dir)?))
+}
+
+/// Log output layer of the format
+pub fn fmt_layer<S, W>(
+    format: LogFormat,
+    span_events: FmtSpan,
+    writer: W,
+) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer()
+            .with_span_events(span_events)
+            .with_writer(writer)
+            .with_ansi(false)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_span_events(span_events)
+            .with_writer(writer)
+            .boxed(),
+    }
+}
+
+/// Directives of the current log filter (None before [`init_tracing`])
+pub fn log_filter() -> Option<String> {
+    LOG_FILTER.get()?.with_current(|f| f.to_string()).ok()
+}
+
+/// Replace the log filter of the running process (`RUST_LOG` syntax, e.g.
+/// `info,[call_tool{tool_name=COMMAND}]=debug` for debug logs of the calls of one tool)
+pub fn set_log_filter(directives: &str) -> Result<()> {
+    let filter = EnvFilter::try_new(directives)
+        .with_context(|| format!("invalid log filter: {}", directives))?;
+    LOG_FILTER
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("log filter is not reloadable"))?
+        .reload(filter)
+        .context("Failed to reload log filter")?;
+    tracing::info!("log filter changed: {}", directives);
+    Ok(())
+}
+
+pub fn debug_sample_rate() -> f64 {
+    f64::from_bits(DEBUG_SAMPLE_RATE.load(Ordering::Relaxed))
+}
+
+/// Set the rate (0.0-1.0) of the per-call debug dumps logged (`DEBUG_LOG_SAMPLE_RATE`)
+pub fn set_debug_sample_rate(rate: f64) {
+    let rate = if rate.is_nan() {
+        1.0
+    } else {
+        rate.clamp(0.0, 1.0)
+    };
+    DEBUG_SAMPLE_RATE.store(rate.to_bits(), Ordering::Relaxed);
+}
+
+/// Whether to log a per-call debug dump (sampled by the debug sample rate)
+pub fn debug_sampled() -> bool {
+    let rate = debug_sample_rate();
+    rate >= 1.0 || rand::random::<f64>() < rate
+}
+
+/// Log filter and debug sampling of the running process (admin endpoint `/admin/logging`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LoggingSettings {
+    // `RUST_LOG` directives
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug_sample_rate: Option<f64>,
+}
+
+impl LoggingSettings {
+    pub fn current() -> Self {
+        Self {
+            filter: log_filter(),
+            debug_sample_rate: Some(debug_sample_rate()),
+        }
+    }
+
+    /// Apply the given settings (the others are kept)
+    pub fn apply(&self) -> Result<()> {
+        if let Some(filter) = self.filter.as_deref() {
+            set_log_filter(filter)?;
+        }
+        if let Some(rate) = self.debug_sample_rate {
+            set_debug_sample_rate(rate);
+            tracing::info!("debug log sample rate changed: {}", rate);
+        }
+        Ok(())
+    }
+}
+
+/// Initialize the tracing subscriber writing to stderr (stdout is used by the stdio transport)
+/// and to the log file if configured.
+///
+/// The returned guard must be held until exit to flush the buffered file logs.
+pub fn init_tracing(config: &LoggingConfig) -> Result<Option<WorkerGuard>> {
+    let format = LogFormat::from_env()?;
+    let span_events = span_events_from_env();
+    crate::redaction::init(&config.redaction)?;
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("debug"));
+    let (filter, filter_handle) = reload::Layer::new(filter);
+    if let Some(rate) = env::parse::<f64>("DEBUG_LOG_SAMPLE_RATE") {
+        set_debug_sample_rate(rate);
+    }
+    let (file_layer, guard) = match config.file.as_ref() {
+        Some(file) => {
+            let (writer, guard) = tracing_appender::non_blocking(file_writer(file)?);
+            (
+                Some(fmt_layer(format, span_events.clone(), writer)),
+                Some(guard),
+            )
+        }
+        None => (None, None),
+    };
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer(format, span_events, std::io::stderr))
+        .with(file_layer)
+        .try_init()?;
+    let _ = LOG_FILTER.set(filter_handle);
+    if let Some(file) = config.file.as_ref() {
+        tracing::info!("logging to file: {}", file.path.display());
+    }
+    Ok(guard)
+}
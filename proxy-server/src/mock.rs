@@ -0,0 +1,163 @@
+use crate::recording::{self, RecordedCall};
+use anyhow::{Context, Result};
+use rmcp::model::{
+    CallToolRequestParam, CallToolResult, Content, Implementation, ListToolsResult,
+    PaginatedRequestParam, ProtocolVersion, ServerCapabilities, ServerInfo, Tool,
+};
+use rmcp::service::RequestContext;
+use rmcp::{Error as McpError, RoleServer, ServerHandler};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Tool manifest served without a backend
+///
+/// Either the output of `jobworkerp-mcp-proxy list` (`{"tools": [..]}`), a list of the tools,
+/// or the tools with canned responses by tool name (`{"tools": [..], "responses": {..}}`).
+/// A response is a call result (`{"content": [..], "isError": ..}`) or any value returned as json.
+/// Tools without a response echo the call.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MockTools {
+    pub tools: Vec<Tool>,
+    #[serde(default)]
+    pub responses: HashMap<String, Value>,
+    // calls replayed from a recording (only the recorded calls are answered if set)
+    #[serde(skip)]
+    pub recorded: Option<Vec<RecordedCall>>,
+}
+
+impl MockTools {
+    pub fn load(path: &str) -> Result<Self> {
+        let manifest = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read tool manifest: {}", path))?;
+        Self::from_json(&manifest).with_context(|| format!("Invalid tool manifest: {}", path))
+    }
+
+    pub fn from_json(manifest: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(manifest)?;
+        let mock = match value {
+            Value::Array(tools) => Self {
+                tools: serde_json::from_value(Value::Array(tools))?,
+                responses: HashMap::new(),
+                recorded: None,
+            },
+            value => serde_json::from_value(value)?,
+        };
+        if let Some(unknown) = mock
+            .responses
+            .keys()
+            .find(|name| !mock.tools.iter().any(|t| t.name == name.as_str()))
+        {
+            anyhow::bail!("response of unknown tool: {}", unknown);
+        }
+        Ok(mock)
+    }
+
+    /// Replay the tools and the calls recorded to the directory (`RECORD_CALLS_DIR`)
+    pub fn load_recording(dir: &str) -> Result<Self> {
+        let tools = Path::new(dir).join(recording::TOOLS_FILE);
+        let mut mock = Self::load(&tools.to_string_lossy())?;
+        mock.recorded = Some(recording::load_calls(dir)?);
+        Ok(mock)
+    }
+
+    pub fn list_tools(&self) -> ListToolsResult {
+        ListToolsResult {
+            tools: self.tools.clone(),
+            next_cursor: None,
+        }
+    }
+
+    /// The canned response of the tool, or the echo of the call
+    pub fn call(&self, request: &CallToolRequestParam) -> Result<CallToolResult, McpError> {
+        if !self.tools.iter().any(|t| t.name == request.name) {
+            return Err(McpError::invalid_params(
+                format!("Tool not found: {}", &request.name),
+                None,
+            ));
+        }
+        if let Some(recorded) = self.recorded.as_ref() {
+            // the last recorded response of the same arguments
+            return match recorded.iter().rev().find(|call| call.matches(request)) {
+                Some(call) => call.response(),
+                None => Err(McpError::invalid_params(
+                    format!("No recorded call of {} with the arguments", &request.name),
+                    None,
+                )),
+            };
+        }
+        match self.responses.get(request.name.as_ref()) {
+            Some(response) if response.get("content").is_some_and(Value::is_array) => {
+                serde_json::from_value(response.clone()).map_err(|e| {
+                    McpError::internal_error(format!("Invalid canned response: {}", e), None)
+                })
+            }
+            Some(response) => Ok(CallToolResult {
+                content: vec![Content::json(response.clone())?],
+                is_error: None,
+            }),
+            None => {
+                let arguments = request.arguments.clone().unwrap_or_else(Map::new);
+                Ok(CallToolResult {
+                    content: vec![Content::json(
+                        json!({"tool": &request.name, "arguments": arguments}),
+                    )?],
+                    is_error: None,
+                })
+            }
+        }
+    }
+}
+
+/// MCP server answering from a tool manifest (for the client development without jobworkerp)
+#[derive(Debug, Clone)]
+pub struct MockRouter {
+    tools: Arc<MockTools>,
+}
+
+impl MockRouter {
+    pub fn new(tools: MockTools) -> Self {
+        Self {
+            tools: Arc::new(tools),
+        }
+    }
+}
+
+impl ServerHandler for MockRouter {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: ProtocolVersion::V_2024_11_05,
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation {
+                name: "jobworkerp-mcp-proxy (mock)".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            instructions: Some(
+                "Mock server: the tools are not executed and return canned or echoed results."
+                    .to_string(),
+            ),
+        }
+    }
+    #[allow(clippy::manual_async_fn)]
+    fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send + '_ {
+        async move {
+            tracing::debug!("mock call_tool: {}", &request.name);
+            self.tools.call(&request)
+        }
+    }
+    #[allow(clippy::manual_async_fn)]
+    fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> impl Future<Output = Result<ListToolsResult, McpError>> + Send + '_ {
+        async move { Ok(self.tools.list_tools()) }
+    }
+}
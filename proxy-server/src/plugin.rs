@@ -0,0 +1,229 @@
+use crate::interceptor::CallInterceptor;
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Per-tool WASM modules rewriting the arguments and the results of the calls
+///
+/// A module exports `memory`, `alloc(len: i32) -> i32` and at least one of
+/// `transform_arguments(ptr: i32, len: i32) -> i64` and `transform_result(ptr: i32, len: i32) -> i64`.
+/// The functions receive the JSON of the arguments object (or of the result) written to the memory
+/// allocated by `alloc`, and return `(ptr << 32) | len` of the rewritten JSON (len 0: unchanged).
+/// Modules are instantiated per call without imports.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PluginsConfig {
+    // directory of the wasm modules
+    pub dir: Option<String>,
+    // tool name -> module file in `dir`
+    pub tools: BTreeMap<String, String>,
+    // instructions executed by a plugin call (unlimited if not set)
+    pub fuel: Option<u64>,
+}
+
+impl PluginsConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.tools.is_empty()
+    }
+}
+
+pub const TRANSFORM_ARGUMENTS: &str = "transform_arguments";
+pub const TRANSFORM_RESULT: &str = "transform_result";
+
+/// Load the plugins of the config as an interceptor (None if no plugin is configured)
+pub fn build(config: &PluginsConfig) -> Result<Option<Arc<dyn CallInterceptor>>> {
+    if !config.is_enabled() {
+        return Ok(None);
+    }
+    #[cfg(feature = "wasm-plugins")]
+    {
+        Ok(Some(Arc::new(PluginFilters::load(config)?)))
+    }
+    #[cfg(not(feature = "wasm-plugins"))]
+    {
+        anyhow::bail!("plugins require proxy-server built with the `wasm-plugins` feature")
+    }
+}
+
+#[cfg(feature = "wasm-plugins")]
+pub use wasm::{PluginFilters, WasmPlugin};
+
+#[cfg(feature = "wasm-plugins")]
+mod wasm {
+    use super::*;
+    use crate::interceptor::ToolCall;
+    use anyhow::Context;
+    use futures::future::BoxFuture;
+    use rmcp::model::CallToolResult;
+    use rmcp::Error as McpError;
+    use serde_json::{Map, Value};
+    use std::collections::HashMap;
+    use std::path::Path;
+    use wasmtime::{Config, Engine, Instance, Module, Store};
+
+    /// A compiled plugin module
+    pub struct WasmPlugin {
+        engine: Engine,
+        module: Module,
+        fuel: Option<u64>,
+    }
+
+    impl WasmPlugin {
+        pub fn engine(fuel: Option<u64>) -> Result<Engine> {
+            let mut config = Config::new();
+            config.consume_fuel(fuel.is_some());
+            Engine::new(&config)
+        }
+
+        pub fn load(engine: &Engine, path: &Path, fuel: Option<u64>) -> Result<Self> {
+            let module = Module::from_file(engine, path)
+                .with_context(|| format!("Failed to load plugin: {}", path.display()))?;
+            if module.get_export(TRANSFORM_ARGUMENTS).is_none()
+                && module.get_export(TRANSFORM_RESULT).is_none()
+            {
+                anyhow::bail!(
+                    "plugin {} exports neither {} nor {}",
+                    path.display(),
+                    TRANSFORM_ARGUMENTS,
+                    TRANSFORM_RESULT
+                );
+            }
+            Ok(Self {
+                engine: engine.clone(),
+                module,
+                fuel,
+            })
+        }
+
+        pub fn exports(&self, function: &str) -> bool {
+            self.module.get_export(function).is_some()
+        }
+
+        /// Call the transform function with the input (None: unchanged)
+        pub fn call(&self, function: &str, input: &[u8]) -> Result<Option<Vec<u8>>> {
+            let mut store = Store::new(&self.engine, ());
+            if let Some(fuel) = self.fuel {
+                store.set_fuel(fuel)?;
+            }
+            let instance = Instance::new(&mut store, &self.module, &[])?;
+            let memory = instance
+                .get_memory(&mut store, "memory")
+                .context("plugin does not export memory")?;
+            let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+            let transform = instance.get_typed_func::<(i32, i32), i64>(&mut store, function)?;
+
+            let len = i32::try_from(input.len()).context("plugin input too large")?;
+            let ptr = alloc.call(&mut store, len)?;
+            memory.write(&mut store, ptr as u32 as usize, input)?;
+            let packed = transform.call(&mut store, (ptr, len))?;
+            let (out_ptr, out_len) = ((packed >> 32) as u32 as usize, packed as u32 as usize);
+            if out_len == 0 {
+                return Ok(None);
+            }
+            let mut output = vec![0; out_len];
+            memory.read(&store, out_ptr, &mut output)?;
+            Ok(Some(output))
+        }
+
+        async fn transform(
+            self: &Arc<Self>,
+            function: &'static str,
+            input: Vec<u8>,
+        ) -> Result<Option<Vec<u8>>> {
+            if !self.exports(function) {
+                return Ok(None);
+            }
+            let plugin = self.clone();
+            tokio::task::spawn_blocking(move || plugin.call(function, &input)).await?
+        }
+    }
+
+    /// Interceptor running the plugins of the called tools
+    pub struct PluginFilters {
+        // tool name -> plugin
+        plugins: HashMap<String, Arc<WasmPlugin>>,
+    }
+
+    impl PluginFilters {
+        pub fn load(config: &PluginsConfig) -> Result<Self> {
+            let engine = WasmPlugin::engine(config.fuel)?;
+            let dir = Path::new(config.dir.as_deref().unwrap_or("."));
+            // tools sharing a module use the same compiled module
+            let mut modules: HashMap<&str, Arc<WasmPlugin>> = HashMap::new();
+            let mut plugins = HashMap::new();
+            for (tool, file) in config.tools.iter() {
+                let plugin = match modules.get(file.as_str()) {
+                    Some(plugin) => plugin.clone(),
+                    None => {
+                        let plugin =
+                            Arc::new(WasmPlugin::load(&engine, &dir.join(file), config.fuel)?);
+                        modules.insert(file, plugin.clone());
+                        plugin
+                    }
+                };
+                tracing::info!("loaded plugin {} for tool {}", file, tool);
+                plugins.insert(tool.clone(), plugin);
+            }
+            Ok(Self { plugins })
+        }
+    }
+
+    impl CallInterceptor for PluginFilters {
+        fn pre_call<'a>(&'a self, call: &'a mut ToolCall) -> BoxFuture<'a, Result<(), McpError>> {
+            Box::pin(async move {
+                let Some(plugin) = self.plugins.get(&call.tool_name) else {
+                    return Ok(());
+                };
+                let input = serde_json::to_vec(&call.arguments)
+                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                let failed = |e: String| {
+                    tracing::error!(tool_name = %call.tool_name, "plugin failed: {}", e);
+                    McpError::internal_error(
+                        format!("Plugin of tool {} failed: {}", call.tool_name, e),
+                        None,
+                    )
+                };
+                let output = plugin
+                    .transform(TRANSFORM_ARGUMENTS, input)
+                    .await
+                    .map_err(|e| failed(e.to_string()))?;
+                if let Some(output) = output {
+                    call.arguments = serde_json::from_slice::<Map<String, Value>>(&output)
+                        .map_err(|e| failed(format!("invalid arguments: {}", e)))?;
+                }
+                Ok(())
+            })
+        }
+
+        fn post_call<'a>(
+            &'a self,
+            call: &'a ToolCall,
+            result: &'a mut CallToolResult,
+        ) -> BoxFuture<'a, ()> {
+            Box::pin(async move {
+                let Some(plugin) = self.plugins.get(&call.tool_name) else {
+                    return;
+                };
+                let transformed = match serde_json::to_vec(result) {
+                    Ok(input) => plugin.transform(TRANSFORM_RESULT, input).await,
+                    Err(e) => Err(e.into()),
+                }
+                .and_then(|output| {
+                    output
+                        .map(|o| serde_json::from_slice::<CallToolResult>(&o))
+                        .transpose()
+                        .map_err(|e| anyhow::anyhow!("invalid result: {}", e))
+                });
+                // the result is returned as it is if the plugin fails
+                match transformed {
+                    Ok(Some(transformed)) => *result = transformed,
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::error!(tool_name = %call.tool_name, "plugin failed: {}", e)
+                    }
+                }
+            })
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::auth::AuthIdentity;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RoleConfig {
+    // allowed tool name patterns (`*` matches any characters)
+    pub tools: Vec<String>,
+    // administrative tools (e.g. workflow creation), the jobs and schedules of all sessions and
+    // the admin endpoints
+    pub admin: bool,
+}
+
+/// Role based access control of tools
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RbacConfig {
+    // role granted to every session (including sessions without authentication)
+    pub default_role: Option<String>,
+    pub roles: HashMap<String, RoleConfig>,
+    // authenticated subject -> roles
+    pub subjects: HashMap<String, Vec<String>>,
+    // token scope -> roles
+    pub scopes: HashMap<String, Vec<String>>,
+}
+
+impl RbacConfig {
+    pub fn roles_for(&self, identity: Option<&AuthIdentity>) -> Vec<String> {
+        let mut roles: Vec<String> = self.default_role.iter().cloned().collect();
+        if let Some(identity) = identity {
+            if let Some(r) = self.subjects.get(&identity.subject) {
+                roles.extend(r.iter().cloned());
+            }
+            for scope in identity.scopes.iter() {
+                if let Some(r) = self.scopes.get(scope) {
+                    roles.extend(r.iter().cloned());
+                }
+            }
+        }
+        roles.sort();
+        roles.dedup();
+        roles
+    }
+
+    pub fn permission_for(&self, identity: Option<&AuthIdentity>) -> ToolPermission {
+        let roles = self.roles_for(identity);
+        let mut permission = ToolPermission {
+            patterns: vec![],
+            admin: false,
+            admin_tools: false,
+        };
+        for role in roles.iter() {
+            match self.roles.get(role) {
+                Some(r) => {
+                    permission.patterns.extend(r.tools.iter().cloned());
+                    permission.admin |= r.admin;
+                    permission.admin_tools |= r.admin;
+                }
+                None => tracing::warn!("undefined role: {}", role),
+            }
+        }
+        permission
+    }
+}
+
+/// Tools allowed for a session
+#[derive(Debug, Clone)]
+pub struct ToolPermission {
+    patterns: Vec<String>,
+    // granted by a role with `admin: true` (e.g. the jobs and schedules of all sessions)
+    admin: bool,
+    // allowed to use administrative tools (e.g. workflow creation)
+    admin_tools: bool,
+}
+
+impl ToolPermission {
+    /// All tools without an admin role (without rbac)
+    pub fn allow_all() -> Self {
+        Self {
+            patterns: vec!["*".to_string()],
+            admin: false,
+            admin_tools: true,
+        }
+    }
+
+    pub fn is_allowed(&self, tool_name: &str) -> bool {
+        self.patterns.iter().any(|p| glob_match(p, tool_name))
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.admin
+    }
+
+    pub fn can_use_admin_tools(&self) -> bool {
+        self.admin_tools
+    }
+}
+
+/// Match a name with a pattern which may contain `*` wildcards
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || !name.ends_with(last) || name.len() < first.len() + last.len() {
+        return false;
+    }
+    // on char boundaries as the name starts and ends with the parts
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
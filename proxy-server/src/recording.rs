@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use rmcp::model::{CallToolRequestParam, CallToolResult, ListToolsResult};
+use rmcp::Error as McpError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// files of a recording directory
+pub const CALLS_FILE: &str = "calls.jsonl";
+pub const TOOLS_FILE: &str = "tools.json";
+
+/// A tool call and its response (a line of `calls.jsonl`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub tool: String,
+    // arguments without `_meta`
+    #[serde(default)]
+    pub arguments: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<CallToolResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<McpError>,
+    // unix epoch millis
+    #[serde(default)]
+    pub recorded_at: i64,
+}
+
+impl RecordedCall {
+    pub fn matches(&self, request: &CallToolRequestParam) -> bool {
+        self.tool == request.name.as_ref() && self.arguments == call_arguments(request)
+    }
+
+    pub fn response(&self) -> Result<CallToolResult, McpError> {
+        match (&self.result, &self.error) {
+            (_, Some(error)) => Err(error.clone()),
+            (Some(result), None) => Ok(result.clone()),
+            (None, None) => Err(McpError::internal_error(
+                format!("Recorded call of {} has no response", &self.tool),
+                None,
+            )),
+        }
+    }
+}
+
+// arguments compared by the replay (`_meta` is not a part of the call)
+fn call_arguments(request: &CallToolRequestParam) -> Map<String, Value> {
+    let mut arguments = request.arguments.clone().unwrap_or_default();
+    arguments.remove("_meta");
+    arguments
+}
+
+/// Appends the tool calls and their responses to a recording directory
+#[derive(Debug)]
+pub struct Recorder {
+    dir: PathBuf,
+    calls: Mutex<File>,
+}
+
+impl Recorder {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create recording directory: {:?}", &dir))?;
+        let calls = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(CALLS_FILE))
+            .with_context(|| format!("Failed to open recording file in {:?}", &dir))?;
+        tracing::info!("recording tool calls to {:?}", &dir);
+        Ok(Self {
+            dir,
+            calls: Mutex::new(calls),
+        })
+    }
+
+    pub fn record(
+        &self,
+        request: &CallToolRequestParam,
+        response: &Result<CallToolResult, McpError>,
+    ) {
+        let call = RecordedCall {
+            tool: request.name.to_string(),
+            arguments: call_arguments(request),
+            result: response.as_ref().ok().cloned(),
+            error: response.as_ref().err().cloned(),
+            recorded_at: Utc::now().timestamp_millis(),
+        };
+        // a failed recording does not fail the call
+        let written = serde_json::to_string(&call)
+            .map_err(anyhow::Error::from)
+            .and_then(|line| {
+                let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+                writeln!(calls, "{}", line)?;
+                Ok(calls.flush()?)
+            });
+        if let Err(e) = written {
+            tracing::error!(tool_name = %request.name, "Failed to record the call: {}", e);
+        }
+    }
+
+    /// Keep the last listed tools (served by the replay)
+    pub fn record_tools(&self, tools: &ListToolsResult) {
+        let written = serde_json::to_vec_pretty(tools)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(self.dir.join(TOOLS_FILE), json)?));
+        if let Err(e) = written {
+            tracing::error!("Failed to record the tool list: {}", e);
+        }
+    }
+}
+
+/// Read the recorded calls of a recording directory (in the recorded order)
+pub fn load_calls(dir: impl AsRef<Path>) -> Result<Vec<RecordedCall>> {
+    let path = dir.as_ref().join(CALLS_FILE);
+    let file = File::open(&path).with_context(|| format!("Failed to open {:?}", &path))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(i, line)| {
+            serde_json::from_str(&line?)
+                .with_context(|| format!("Invalid recorded call at {:?}:{}", &path, i + 1))
+        })
+        .collect()
+}
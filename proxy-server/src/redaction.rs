@@ -0,0 +1,221 @@
+use crate::rbac::glob_match;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::sync::{OnceLock, RwLock};
+
+pub const REDACTED: &str = "[REDACTED]";
+
+// field names redacted by default (case insensitive, `*` wildcards allowed)
+const DEFAULT_FIELDS: [&str; 8] = [
+    "*password*",
+    "*secret*",
+    "*token*",
+    "api_key",
+    "apikey",
+    "authorization",
+    "cookie",
+    "private_key",
+];
+
+/// Redaction of sensitive values in logs (`logging.redaction` of the config file)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    // field names (case insensitive, `*` wildcards allowed)
+    pub fields: Vec<String>,
+    // JSONPath of the values (`$.a.b`, `$.a[*].b`, `$..b`)
+    pub paths: Vec<String>,
+    // redact the fields of `format: password` in the tool schemas
+    pub schema_password_fields: bool,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            fields: DEFAULT_FIELDS.iter().map(|f| f.to_string()).collect(),
+            paths: vec![],
+            schema_password_fields: true,
+        }
+    }
+}
+
+/// Segment of a JSONPath (`$.a.b`, `$.a[*].b`, `$..b`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),
+    // `*` or `[*]`: every field or element
+    Any,
+    // `..key`: the key at any depth
+    Descendant(String),
+}
+
+pub fn parse_path(path: &str) -> Result<Vec<PathSegment>> {
+    let rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| anyhow!("JSONPath must start with `$`: {}", path))?;
+    let rest = rest.replace("[*]", ".*");
+    let mut segments = Vec::new();
+    let mut parts = rest.split('.').peekable();
+    // the first part is empty (`$.a` -> ["", "a"])
+    if parts.next().is_some_and(|p| !p.is_empty()) {
+        return Err(anyhow!("invalid JSONPath: {}", path));
+    }
+    while let Some(part) = parts.next() {
+        match part {
+            // `..key`
+            "" => match parts.next() {
+                Some(key) if !key.is_empty() => segments.push(PathSegment::Descendant(key.into())),
+                _ => return Err(anyhow!("invalid JSONPath: {}", path)),
+            },
+            "*" => segments.push(PathSegment::Any),
+            key => segments.push(PathSegment::Key(key.to_string())),
+        }
+    }
+    Ok(segments)
+}
+
+/// Replaces the values of sensitive fields with REDACTED
+#[derive(Debug, Default)]
+pub struct Redactor {
+    fields: Vec<String>,
+    paths: Vec<Vec<PathSegment>>,
+    schema_password_fields: bool,
+    // fields of `format: password` found in the tool schemas
+    schema_fields: RwLock<BTreeSet<String>>,
+}
+
+impl Redactor {
+    pub fn new(config: &RedactionConfig) -> Result<Self> {
+        Ok(Self {
+            fields: config.fields.iter().map(|f| f.to_lowercase()).collect(),
+            paths: config
+                .paths
+                .iter()
+                .map(|p| parse_path(p))
+                .collect::<Result<_>>()?,
+            schema_password_fields: config.schema_password_fields,
+            schema_fields: RwLock::new(BTreeSet::new()),
+        })
+    }
+
+    fn is_sensitive(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.fields.iter().any(|f| glob_match(f, &key))
+            || self
+                .schema_fields
+                .read()
+                .is_ok_and(|fields| fields.contains(&key))
+    }
+
+    /// Register the fields of `format: password` in the schema (if enabled)
+    pub fn register_schema(&self, schema: &Map<String, Value>) {
+        if !self.schema_password_fields {
+            return;
+        }
+        let mut found = BTreeSet::new();
+        collect_password_fields(schema, &mut found);
+        if !found.is_empty() {
+            if let Ok(mut fields) = self.schema_fields.write() {
+                fields.extend(found);
+            }
+        }
+    }
+
+    /// Copy of the value with the sensitive values redacted
+    pub fn redact(&self, value: &Value) -> Value {
+        let mut value = value.clone();
+        self.redact_fields(&mut value);
+        for path in self.paths.iter() {
+            redact_path(&mut value, path);
+        }
+        value
+    }
+
+    fn redact_fields(&self, value: &mut Value) {
+        match value {
+            Value::Object(obj) => {
+                for (key, value) in obj.iter_mut() {
+                    if self.is_sensitive(key) {
+                        *value = Value::from(REDACTED);
+                    } else {
+                        self.redact_fields(value);
+                    }
+                }
+            }
+            Value::Array(list) => list.iter_mut().for_each(|v| self.redact_fields(v)),
+            _ => {}
+        }
+    }
+}
+
+fn collect_password_fields(schema: &Map<String, Value>, found: &mut BTreeSet<String>) {
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+        for (key, property) in properties {
+            if property.get("format").and_then(|f| f.as_str()) == Some("password") {
+                found.insert(key.to_lowercase());
+            }
+        }
+    }
+    for value in schema.values() {
+        match value {
+            Value::Object(obj) => collect_password_fields(obj, found),
+            Value::Array(list) => list
+                .iter()
+                .filter_map(|v| v.as_object())
+                .for_each(|obj| collect_password_fields(obj, found)),
+            _ => {}
+        }
+    }
+}
+
+fn redact_path(value: &mut Value, path: &[PathSegment]) {
+    let Some((segment, rest)) = path.split_first() else {
+        *value = Value::from(REDACTED);
+        return;
+    };
+    match segment {
+        PathSegment::Key(key) => {
+            if let Some(value) = value.get_mut(key.as_str()) {
+                redact_path(value, rest);
+            }
+        }
+        PathSegment::Any => match value {
+            Value::Object(obj) => obj.values_mut().for_each(|v| redact_path(v, rest)),
+            Value::Array(list) => list.iter_mut().for_each(|v| redact_path(v, rest)),
+            _ => {}
+        },
+        PathSegment::Descendant(key) => {
+            if let Some(value) = value.get_mut(key.as_str()) {
+                redact_path(value, rest);
+            }
+            match value {
+                Value::Object(obj) => obj
+                    .iter_mut()
+                    .filter(|(k, _)| *k != key)
+                    .for_each(|(_, v)| redact_path(v, path)),
+                Value::Array(list) => list.iter_mut().for_each(|v| redact_path(v, path)),
+                _ => {}
+            }
+        }
+    }
+}
+
+static REDACTOR: OnceLock<Redactor> = OnceLock::new();
+
+/// Install the redactor used by `redact` (the first call wins)
+pub fn init(config: &RedactionConfig) -> Result<()> {
+    let _ = REDACTOR.set(Redactor::new(config)?);
+    Ok(())
+}
+
+/// The installed redactor (the default settings if not installed)
+pub fn redactor() -> &'static Redactor {
+    REDACTOR.get_or_init(|| Redactor::new(&RedactionConfig::default()).unwrap_or_default())
+}
+
+/// Copy of the value for logging with the sensitive values redacted
+pub fn redact(value: &Value) -> Value {
+    redactor().redact(value)
+}
@@ -0,0 +1,244 @@
+use crate::auth::AuthIdentity;
+use crate::jobworkerp::session::SessionInfo;
+use crate::jobworkerp::tenant::TenantsConfig;
+use crate::jobworkerp::JobworkerpRouter;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use rmcp::model::{
+    CallToolRequestParam, ClientCapabilities, ClientInfo, ErrorCode, Implementation,
+};
+use rmcp::service::{RunningService, ServiceError};
+use rmcp::{Error as McpError, RoleClient, ServiceExt};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+// buffer of the in-process transport between the request and the router
+const TRANSPORT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Plain HTTP API over the tools of the router (for the clients not speaking MCP)
+///
+/// - `GET {base}/tools`: the tool list (`{"tools": [..]}` as `tools/list`)
+/// - `POST {base}/tools/{name}/call`: call the tool with the JSON object body as the arguments
+///   and return the call result (`{"content": [..], "isError": ..}`)
+///
+/// Each request is served by an MCP session of its own in the process, with the identity and
+/// the headers of the request, so that the validation, RBAC and conversions are the same as the
+/// MCP transports. Calls in flight are cancelled when the request is dropped.
+///
+/// With `tenants`, the tenant is selected as the sse transport (the path segment
+/// `/{tenant}{base}/tools..` if enabled, then the header) and requests of unknown tenants are
+/// rejected with 403.
+pub fn router(
+    service: JobworkerpRouter,
+    base_path: &str,
+    tenants: Option<Arc<TenantsConfig>>,
+) -> Router {
+    let tools_path = crate::http::join_path(base_path, "/tools");
+    let call_path = format!("{}/{{name}}/call", tools_path);
+    let mut router = Router::new()
+        .route(&tools_path, get(list_tools))
+        .route(&call_path, post(call_tool));
+    if tenants.as_ref().is_some_and(|t| t.path_segment) {
+        router = router
+            .route(
+                &format!("/{{tenant}}{}", tools_path),
+                get(tenant_list_tools),
+            )
+            .route(&format!("/{{tenant}}{}", call_path), post(tenant_call_tool));
+    }
+    router.with_state(RestApp { service, tenants })
+}
+
+#[derive(Clone)]
+struct RestApp {
+    service: JobworkerpRouter,
+    tenants: Option<Arc<TenantsConfig>>,
+}
+
+async fn list_tools(
+    State(app): State<RestApp>,
+    identity: Option<Extension<AuthIdentity>>,
+    headers: HeaderMap,
+) -> Response {
+    serve_list_tools(app, None, identity, headers).await
+}
+
+// tools endpoint prefixed with the tenant (`/{tenant}{base}/tools`)
+async fn tenant_list_tools(
+    State(app): State<RestApp>,
+    Path(tenant): Path<String>,
+    identity: Option<Extension<AuthIdentity>>,
+    headers: HeaderMap,
+) -> Response {
+    serve_list_tools(app, Some(tenant), identity, headers).await
+}
+
+async fn serve_list_tools(
+    app: RestApp,
+    tenant: Option<String>,
+    identity: Option<Extension<AuthIdentity>>,
+    headers: HeaderMap,
+) -> Response {
+    let client = match open_session(&app, tenant, identity, &headers).await {
+        Ok(client) => client,
+        Err(response) => return response,
+    };
+    let result = client.list_tools(Default::default()).await;
+    close_session(client).await;
+    match result {
+        Ok(tools) => Json(tools).into_response(),
+        Err(e) => service_error(e),
+    }
+}
+
+async fn call_tool(
+    State(app): State<RestApp>,
+    Path(name): Path<String>,
+    identity: Option<Extension<AuthIdentity>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Response {
+    serve_call_tool(app, None, name, identity, headers, body).await
+}
+
+// call endpoint prefixed with the tenant (`/{tenant}{base}/tools/{name}/call`)
+async fn tenant_call_tool(
+    State(app): State<RestApp>,
+    Path((tenant, name)): Path<(String, String)>,
+    identity: Option<Extension<AuthIdentity>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Response {
+    serve_call_tool(app, Some(tenant), name, identity, headers, body).await
+}
+
+async fn serve_call_tool(
+    app: RestApp,
+    tenant: Option<String>,
+    name: String,
+    identity: Option<Extension<AuthIdentity>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Response {
+    let arguments = match body.map(|Json(body)| body) {
+        None | Some(Value::Null) => Map::new(),
+        Some(Value::Object(arguments)) => arguments,
+        Some(_) => {
+            return error_response(McpError::invalid_params(
+                "The request body must be a JSON object of the arguments",
+                None,
+            ))
+        }
+    };
+    let client = match open_session(&app, tenant, identity, &headers).await {
+        Ok(client) => client,
+        Err(response) => return response,
+    };
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: name.into(),
+            arguments: Some(arguments),
+        })
+        .await;
+    close_session(client).await;
+    match result {
+        Ok(result) => Json(result).into_response(),
+        Err(e) => service_error(e),
+    }
+}
+
+// start a session of the router for the request and connect to it
+async fn open_session(
+    app: &RestApp,
+    tenant: Option<String>,
+    identity: Option<Extension<AuthIdentity>>,
+    headers: &HeaderMap,
+) -> Result<RunningService<RoleClient, ClientInfo>, Response> {
+    let info = SessionInfo {
+        id: format!("rest-{:032x}", rand::random::<u128>()),
+        identity: identity.map(|Extension(i)| i),
+        headers: headers
+            .iter()
+            .filter_map(|(k, v)| {
+                v.to_str()
+                    .ok()
+                    .map(|v| (k.as_str().to_lowercase(), v.to_string()))
+            })
+            .collect(),
+        tenant,
+    };
+    if let Some(tenants) = app.tenants.as_ref() {
+        if let Err(e) = tenants.select(&info) {
+            tracing::warn!("rest request rejected: {}", e);
+            let body = json!({"error": {
+                "code": ErrorCode::INVALID_REQUEST.0,
+                "message": e.to_string(),
+            }});
+            return Err((StatusCode::FORBIDDEN, Json(body)).into_response());
+        }
+    }
+    let service = app.service.new_tenant_session(info).await.map_err(|e| {
+        tracing::error!("failed to start rest session: {:?}", e);
+        error_response(McpError::internal_error(e.to_string(), None))
+    })?;
+    let (client_io, server_io) = tokio::io::duplex(TRANSPORT_BUFFER_SIZE);
+    tokio::spawn(async move {
+        let server = match service.clone().serve(tokio::io::split(server_io)).await {
+            Ok(server) => server,
+            Err(e) => {
+                service.on_disconnect();
+                return Err(e.into());
+            }
+        };
+        service.on_initialized(server.peer_info());
+        let result = server.waiting().await;
+        service.on_disconnect();
+        result?;
+        anyhow::Ok(())
+    });
+    let client_info = ClientInfo {
+        protocol_version: Default::default(),
+        capabilities: ClientCapabilities::default(),
+        client_info: Implementation {
+            name: "rest".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+    };
+    client_info
+        .serve(tokio::io::split(client_io))
+        .await
+        .map_err(|e| {
+            tracing::error!("failed to connect rest session: {:?}", e);
+            error_response(McpError::internal_error(e.to_string(), None))
+        })
+}
+
+async fn close_session(client: RunningService<RoleClient, ClientInfo>) {
+    if let Err(e) = client.cancel().await {
+        tracing::warn!("failed to close rest session: {:?}", e);
+    }
+}
+
+fn service_error(error: ServiceError) -> Response {
+    match error {
+        ServiceError::McpError(e) => error_response(e),
+        e => error_response(McpError::internal_error(e.to_string(), None)),
+    }
+}
+
+fn error_response(error: McpError) -> Response {
+    let status = match error.code {
+        ErrorCode::INVALID_PARAMS | ErrorCode::INVALID_REQUEST => StatusCode::BAD_REQUEST,
+        ErrorCode::METHOD_NOT_FOUND | ErrorCode::RESOURCE_NOT_FOUND => StatusCode::NOT_FOUND,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let body = json!({"error": {
+        "code": error.code.0,
+        "message": error.message,
+        "data": error.data,
+    }});
+    (status, Json(body)).into_response()
+}
@@ -0,0 +1,471 @@
+use crate::jobworkerp::artifact::ArtifactRegistry;
+use crate::jobworkerp::artifact_store::{self, ArtifactStore, ArtifactStoreConfig};
+use crate::jobworkerp::meta_tool;
+use crate::jobworkerp::result_projection::ResultProjections;
+use jobworkerp_client::jobworkerp::data::RunnerType;
+use rmcp::model::{CallToolResult, Content, ResourceContents};
+use rmcp::Error as McpError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Job of the output being converted
+#[derive(Debug, Clone, Copy)]
+pub struct ResultContext {
+    pub job_id: i64,
+    // None if unknown (e.g. resumed calls)
+    pub runner_type: Option<RunnerType>,
+    // rendering requested by the call (None: converted by the output type)
+    pub output_format: Option<OutputFormat>,
+}
+
+impl ResultContext {
+    pub fn output_uri(&self) -> String {
+        format!("job://{}/output", self.job_id)
+    }
+}
+
+/// Rendering of the job output selected by `_meta.output_format` of the call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    // pretty-printed json
+    Json,
+    // the text values of the output
+    Text,
+    // markdown (tables for lists of objects)
+    Markdown,
+    // the output as it is (compact json)
+    Raw,
+}
+
+impl OutputFormat {
+    pub const META_KEY: &str = "output_format";
+
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "text" => Some(Self::Text),
+            "markdown" | "md" => Some(Self::Markdown),
+            "raw" => Some(Self::Raw),
+            _ => None,
+        }
+    }
+
+    /// `_meta.output_format` of the call (None if not given)
+    pub fn from_meta(meta: Option<&Map<String, Value>>) -> Result<Option<Self>, McpError> {
+        match meta.and_then(|m| m.get(Self::META_KEY)) {
+            None | Some(Value::Null) => Ok(None),
+            Some(format) => format
+                .as_str()
+                .and_then(Self::parse)
+                .map(Some)
+                .ok_or_else(|| {
+                    McpError::invalid_params(
+                        "_meta.output_format must be one of json, text, markdown or raw",
+                        None,
+                    )
+                }),
+        }
+    }
+
+    pub fn render(&self, output: &Value) -> String {
+        match self {
+            Self::Json => serde_json::to_string_pretty(output).unwrap_or_default(),
+            Self::Text => text(output),
+            Self::Markdown => markdown(output),
+            Self::Raw => output.to_string(),
+        }
+    }
+}
+
+/// The string values of the output in order (the other scalars if no string), one per line
+pub fn text(output: &Value) -> String {
+    fn collect(value: &Value, strings_only: bool, found: &mut Vec<String>) {
+        match value {
+            Value::String(s) if !s.is_empty() => found.push(s.clone()),
+            Value::String(_) | Value::Null => {}
+            Value::Object(obj) => obj.values().for_each(|v| collect(v, strings_only, found)),
+            Value::Array(list) => list.iter().for_each(|v| collect(v, strings_only, found)),
+            scalar if !strings_only => found.push(scalar.to_string()),
+            _ => {}
+        }
+    }
+    let mut found = vec![];
+    collect(output, true, &mut found);
+    if found.is_empty() {
+        collect(output, false, &mut found);
+    }
+    found.join("\n")
+}
+
+/// Markdown of the output: lists of objects as tables, objects as `**key**: value` lines and
+/// other lists as bullet lists
+pub fn markdown(output: &Value) -> String {
+    match output {
+        Value::Array(list) => markdown_table(list).unwrap_or_else(|| {
+            list.iter()
+                .map(|v| format!("- {}", inline_value(v)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }),
+        Value::Object(obj) => obj
+            .iter()
+            .map(|(key, value)| match value {
+                Value::Object(_) | Value::Array(_) => {
+                    format!("**{}**:\n\n{}\n", key, markdown(value))
+                }
+                _ => format!("**{}**: {}", key, inline_value(value)),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => inline_value(output),
+    }
+}
+
+/// Markdown table of a list of objects (None if the list is empty or has other values).
+/// The columns are the keys of the objects in the order of appearance
+pub fn markdown_table(list: &[Value]) -> Option<String> {
+    if list.is_empty() {
+        return None;
+    }
+    let rows = list
+        .iter()
+        .map(|v| v.as_object())
+        .collect::<Option<Vec<_>>>()?;
+    let mut columns: Vec<&str> = vec![];
+    for key in rows.iter().flat_map(|row| row.keys()) {
+        if !columns.contains(&key.as_str()) {
+            columns.push(key);
+        }
+    }
+    if columns.is_empty() {
+        return None;
+    }
+    let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+    let mut lines = vec![
+        line(columns.iter().map(|c| table_cell(c)).collect()),
+        line(columns.iter().map(|_| "---".to_string()).collect()),
+    ];
+    lines.extend(rows.iter().map(|row| {
+        line(
+            columns
+                .iter()
+                .map(|c| {
+                    row.get(*c)
+                        .map(|v| table_cell(&inline_value(v)))
+                        .unwrap_or_default()
+                })
+                .collect(),
+        )
+    }));
+    Some(lines.join("\n"))
+}
+
+// strings as they are, nested values as compact json
+fn inline_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// Converts a job output into MCP content (returns None if not applicable to the output)
+pub trait ResultConverter: Send + Sync {
+    fn convert(&self, context: &ResultContext, output: &Value) -> Option<CallToolResult>;
+}
+
+/// Outputs described with a mime type: `{"mime_type" (or "content_type"): "...", "data": "<base64>"}`
+/// or `{"mime_type": "...", "text": "..."}`
+pub struct MediaConverter;
+
+impl MediaConverter {
+    fn content(
+        context: &ResultContext,
+        object: &serde_json::Map<String, Value>,
+    ) -> Option<Content> {
+        let mime_type = object
+            .get("mime_type")
+            .or_else(|| object.get("content_type"))?
+            .as_str()?
+            .to_string();
+        if let Some(text) = object.get("text").and_then(|t| t.as_str()) {
+            return Some(
+                if mime_type.starts_with("text/") || mime_type.ends_with("json") {
+                    Content::text(text)
+                } else {
+                    Content::resource(ResourceContents::TextResourceContents {
+                        uri: context.output_uri(),
+                        mime_type: Some(mime_type),
+                        text: text.to_string(),
+                    })
+                },
+            );
+        }
+        let data = object.get("data").and_then(|d| d.as_str())?.to_string();
+        Some(if mime_type.starts_with("image/") {
+            Content::image(data, mime_type)
+        } else {
+            // audio and other binaries as an embedded resource
+            Content::resource(ResourceContents::BlobResourceContents {
+                uri: context.output_uri(),
+                mime_type: Some(mime_type),
+                blob: data,
+            })
+        })
+    }
+}
+
+impl ResultConverter for MediaConverter {
+    fn convert(&self, context: &ResultContext, output: &Value) -> Option<CallToolResult> {
+        let content = match output {
+            Value::Object(object) => vec![Self::content(context, object)?],
+            // a list of media outputs
+            Value::Array(list) if !list.is_empty() => list
+                .iter()
+                .map(|o| o.as_object().and_then(|o| Self::content(context, o)))
+                .collect::<Option<Vec<_>>>()?,
+            _ => return None,
+        };
+        Some(CallToolResult {
+            content,
+            is_error: None,
+        })
+    }
+}
+
+/// Outputs of MCP server runners: the content list of the upstream tool result is passed through
+pub struct McpToolResultConverter;
+
+impl ResultConverter for McpToolResultConverter {
+    fn convert(&self, _context: &ResultContext, output: &Value) -> Option<CallToolResult> {
+        let content =
+            serde_json::from_value::<Vec<Content>>(output.get("content")?.clone()).ok()?;
+        let is_error = output
+            .get("is_error")
+            .or_else(|| output.get("isError"))
+            .and_then(|e| e.as_bool());
+        Some(CallToolResult { content, is_error })
+    }
+}
+
+/// Outputs of command runners (`{"exit_code": ..., "stdout": "...", "stderr": "..."}`): the exit
+/// code and the other fields as json, then stdout and stderr as separate text blocks. Non zero
+/// exit codes are tool errors
+pub struct CommandResultConverter;
+
+impl ResultConverter for CommandResultConverter {
+    fn convert(&self, _context: &ResultContext, output: &Value) -> Option<CallToolResult> {
+        let mut status = output.as_object()?.clone();
+        let exit_code = status.get("exit_code")?.as_i64()?;
+        let mut content = vec![];
+        let mut streams = vec![];
+        for stream in ["stdout", "stderr"] {
+            match status.remove(stream) {
+                Some(Value::String(text)) => streams.push((stream, text)),
+                Some(other) => {
+                    status.insert(stream.to_string(), other);
+                }
+                None => {}
+            }
+        }
+        content.push(Content::json(Value::Object(status)).ok()?);
+        content.extend(
+            streams
+                .into_iter()
+                .filter(|(_, text)| !text.is_empty())
+                .map(|(stream, text)| Content::text(format!("--- {} ---\n{}", stream, text))),
+        );
+        Some(CallToolResult {
+            content,
+            is_error: (exit_code != 0).then_some(true),
+        })
+    }
+}
+
+/// Lists of objects (e.g. rows of sql queries), also as a field of an object output: the json
+/// followed by a markdown table of the rows (up to `max_rows`)
+pub struct TableConverter {
+    pub max_rows: usize,
+}
+
+impl TableConverter {
+    pub const DEFAULT_MAX_ROWS: usize = 100;
+
+    // the rows and the field name if in an object (the only list of objects of the object)
+    fn rows(output: &Value) -> Option<(Option<&str>, &[Value])> {
+        let is_rows = |v: &Value| {
+            v.as_array()
+                .is_some_and(|l| !l.is_empty() && l.iter().all(|r| r.is_object()))
+        };
+        match output {
+            Value::Array(list) if is_rows(output) => Some((None, list.as_slice())),
+            Value::Object(object) => {
+                let mut lists = object.iter().filter(|(_, v)| is_rows(v));
+                let (field, rows) = lists.next()?;
+                if lists.next().is_some() {
+                    return None;
+                }
+                Some((Some(field.as_str()), rows.as_array()?.as_slice()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for TableConverter {
+    fn default() -> Self {
+        Self {
+            max_rows: Self::DEFAULT_MAX_ROWS,
+        }
+    }
+}
+
+impl ResultConverter for TableConverter {
+    fn convert(&self, _context: &ResultContext, output: &Value) -> Option<CallToolResult> {
+        let (field, rows) = Self::rows(output)?;
+        let shown = &rows[..rows.len().min(self.max_rows)];
+        let mut table = markdown_table(shown)?;
+        if rows.len() > shown.len() {
+            table.push_str(&format!("\n\n({} more rows)", rows.len() - shown.len()));
+        }
+        if let Some(field) = field {
+            table = format!("**{}**:\n\n{}", field, table);
+        }
+        Some(CallToolResult {
+            content: vec![Content::json(output.clone()).ok()?, Content::text(table)],
+            is_error: None,
+        })
+    }
+}
+
+/// Converters applied in order: those of the runner type, then the common ones, then json
+#[derive(Clone)]
+pub struct ResultConverters {
+    by_runner_type: HashMap<i32, Vec<Arc<dyn ResultConverter>>>,
+    common: Vec<Arc<dyn ResultConverter>>,
+}
+
+impl ResultConverters {
+    pub fn empty() -> Self {
+        Self {
+            by_runner_type: HashMap::new(),
+            common: vec![],
+        }
+    }
+
+    pub fn register(&mut self, runner_type: RunnerType, converter: Arc<dyn ResultConverter>) {
+        self.by_runner_type
+            .entry(runner_type as i32)
+            .or_default()
+            .push(converter);
+    }
+
+    pub fn register_common(&mut self, converter: Arc<dyn ResultConverter>) {
+        self.common.push(converter);
+    }
+
+    /// Convert the output (rendered in the format of the context if requested, keeping the
+    /// error status of the conversion by the output type)
+    pub fn convert(
+        &self,
+        context: &ResultContext,
+        output: Value,
+    ) -> Result<CallToolResult, McpError> {
+        let rendered = context.output_format.map(|f| f.render(&output));
+        let mut result = self.convert_by_type(context, output)?;
+        if let Some(rendered) = rendered {
+            result.content = vec![Content::text(rendered)];
+        }
+        Ok(result)
+    }
+
+    fn convert_by_type(
+        &self,
+        context: &ResultContext,
+        output: Value,
+    ) -> Result<CallToolResult, McpError> {
+        let by_runner_type = context
+            .runner_type
+            .and_then(|t| self.by_runner_type.get(&(t as i32)))
+            .into_iter()
+            .flatten();
+        for converter in by_runner_type.chain(self.common.iter()) {
+            if let Some(result) = converter.convert(context, &output) {
+                return Ok(result);
+            }
+        }
+        Ok(CallToolResult {
+            content: vec![Content::json(output)?],
+            is_error: None,
+        })
+    }
+}
+
+impl Default for ResultConverters {
+    fn default() -> Self {
+        let mut converters = Self::empty();
+        converters.register(RunnerType::McpServer, Arc::new(McpToolResultConverter));
+        converters.register(RunnerType::Command, Arc::new(CommandResultConverter));
+        converters.register_common(Arc::new(MediaConverter));
+        converters
+    }
+}
+
+/// Conversion of a job output to the MCP result of the call: the files produced by the job are
+/// registered (and uploaded to the store), the output is projected by the tool, then stored in
+/// the artifact store or returned in chunks if oversized, or converted by the converters
+pub struct ResultPipeline<'a> {
+    pub artifacts: &'a ArtifactRegistry,
+    pub artifact_store: Option<&'a dyn ArtifactStore>,
+    pub artifact_store_config: &'a ArtifactStoreConfig,
+    pub projections: &'a ResultProjections,
+    pub converters: &'a ResultConverters,
+    pub max_result_chars: Option<usize>,
+}
+
+impl ResultPipeline<'_> {
+    /// Result of the output with links to the files produced by the job
+    pub async fn convert(
+        &self,
+        context: &ResultContext,
+        tool_name: &str,
+        output: Value,
+    ) -> Result<CallToolResult, McpError> {
+        let job_id = context.job_id;
+        let mut artifacts = self.artifacts.register_output(job_id, &output);
+        if let Some(store) = self.artifact_store {
+            for artifact in artifacts.iter_mut() {
+                if let Some(uri) =
+                    artifact_store::upload_artifact(store, self.artifact_store_config, artifact)
+                        .await
+                {
+                    artifact.uri = uri;
+                }
+            }
+        }
+        let output = self.projections.apply(tool_name, output);
+        let offloaded = match self.artifact_store {
+            Some(store) => {
+                artifact_store::offload_output(store, self.artifact_store_config, job_id, &output)
+                    .await
+            }
+            None => None,
+        };
+        let mut result = match offloaded {
+            Some((uri, bytes)) => meta_tool::offloaded_result(job_id, &uri, bytes)?,
+            None => match meta_tool::oversized_result(job_id, &output, self.max_result_chars) {
+                Some(chunk) => chunk?,
+                None => self.converters.convert(context, output)?,
+            },
+        };
+        result
+            .content
+            .extend(artifacts.iter().map(|a| a.resource_link()));
+        Ok(result)
+    }
+}
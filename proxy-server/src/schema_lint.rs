@@ -0,0 +1,93 @@
+use crate::tool_conversion::ToolConverter;
+use jobworkerp_client::jobworkerp::function::data::{function_specs, FunctionSpecs};
+use serde::Serialize;
+use serde_json::Value;
+
+// `$schema` of the drafts known by the validator (others are validated as the default draft)
+const KNOWN_DRAFTS: [&str; 5] = ["draft-04", "draft-06", "draft-07", "2019-09", "2020-12"];
+
+/// A problem of a schema fetched from jobworkerp (the tool may still be listed)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaWarning {
+    pub tool: String,
+    // "settings", "arguments", "result" or "input" (mcp server tools)
+    pub schema: &'static str,
+    // json pointer of the problem in the schema
+    pub path: String,
+    pub message: String,
+}
+
+/// Validate a schema string against the JSON Schema metaschema (empty schemas are skipped)
+pub fn lint_schema(tool: &str, kind: &'static str, schema: &str) -> Option<SchemaWarning> {
+    if schema.trim().is_empty() {
+        return None;
+    }
+    let warning = |path: String, message: String| SchemaWarning {
+        tool: tool.to_string(),
+        schema: kind,
+        path,
+        message,
+    };
+    // workflow definition schemas may be yaml
+    let parsed = serde_json::from_str::<Value>(schema)
+        .or_else(|e| serde_yaml::from_str::<Value>(schema).map_err(|_| e));
+    let mut value = match parsed {
+        Ok(value) => value,
+        Err(e) => return Some(warning(String::new(), format!("invalid json: {}", e))),
+    };
+    if let Value::Object(obj) = &mut value {
+        let known = obj
+            .get("$schema")
+            .and_then(|s| s.as_str())
+            .is_some_and(|s| KNOWN_DRAFTS.iter().any(|d| s.contains(d)));
+        if !known {
+            obj.remove("$schema");
+        }
+    }
+    jsonschema::meta::validate(&value)
+        .err()
+        .map(|e| warning(e.instance_path.to_string(), e.to_string()))
+}
+
+/// Lint all the schemas of the functions
+pub fn lint_functions(functions: &[FunctionSpecs]) -> Vec<SchemaWarning> {
+    let mut warnings = Vec::new();
+    for function in functions {
+        match function.schema.as_ref() {
+            Some(function_specs::Schema::SingleSchema(schema)) => {
+                let schemas = [
+                    ("settings", schema.settings.as_deref()),
+                    ("arguments", Some(schema.arguments.as_str())),
+                    ("result", schema.result_output_schema.as_deref()),
+                ];
+                warnings.extend(
+                    schemas.into_iter().filter_map(|(kind, s)| {
+                        s.and_then(|s| lint_schema(&function.name, kind, s))
+                    }),
+                );
+            }
+            Some(function_specs::Schema::McpTools(tools)) => {
+                warnings.extend(tools.list.iter().filter_map(|t| {
+                    let name = ToolConverter::combine_names(&function.name, &t.name);
+                    lint_schema(&name, "input", &t.input_schema)
+                }));
+            }
+            None => {}
+        }
+    }
+    warnings
+}
+
+/// Log the warnings as structured fields
+pub fn log_warnings(warnings: &[SchemaWarning]) {
+    for w in warnings {
+        tracing::warn!(
+            tool = %w.tool,
+            schema = w.schema,
+            path = %w.path,
+            "schema lint: {}: {}",
+            &w.tool,
+            &w.message
+        );
+    }
+}
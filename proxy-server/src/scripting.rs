@@ -0,0 +1,220 @@
+use crate::interceptor::CallInterceptor;
+use anyhow::Result;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Rhai script of the deployment defining the hooks of the tool calls
+///
+/// - `route(tool, args)`: called before the tool is resolved. Returns `()` to call the tool as it
+///   is, a tool name to call another tool, or `#{tool: .., arguments: ..}` (both optional) to
+///   replace the tool and/or the arguments. Thrown values reject the call.
+/// - `transform_result(tool, result)`: called with the result of the runner and worker calls
+///   (`#{content: [..], isError: ..}`). Returns `()` to keep it or the replaced result.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ScriptingConfig {
+    // inline script
+    pub script: Option<String>,
+    // file of the script (instead of `script`)
+    pub script_file: Option<String>,
+    // operations executed by a hook call (unlimited if not set)
+    pub max_operations: Option<u64>,
+}
+
+impl ScriptingConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.script.is_some() || self.script_file.is_some()
+    }
+}
+
+pub const ROUTE: &str = "route";
+pub const TRANSFORM_RESULT: &str = "transform_result";
+
+/// Compile the script of the config (None if no script is configured)
+pub fn build(config: &ScriptingConfig) -> Result<Option<Arc<Scripts>>> {
+    if !config.is_enabled() {
+        return Ok(None);
+    }
+    #[cfg(feature = "scripting")]
+    {
+        Ok(Some(Arc::new(Scripts::load(config)?)))
+    }
+    #[cfg(not(feature = "scripting"))]
+    {
+        anyhow::bail!("scripting requires proxy-server built with the `scripting` feature")
+    }
+}
+
+#[cfg(feature = "scripting")]
+pub use engine::Scripts;
+
+/// Placeholder of the hooks without the `scripting` feature (never built)
+#[cfg(not(feature = "scripting"))]
+pub struct Scripts;
+
+#[cfg(not(feature = "scripting"))]
+impl Scripts {
+    pub fn route(
+        &self,
+        _request: &mut rmcp::model::CallToolRequestParam,
+    ) -> Result<bool, rmcp::Error> {
+        Ok(false)
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+impl CallInterceptor for Scripts {}
+
+#[cfg(feature = "scripting")]
+mod engine {
+    use super::*;
+    use crate::interceptor::ToolCall;
+    use anyhow::Context;
+    use futures::future::BoxFuture;
+    use rhai::{Dynamic, Engine, Scope, AST};
+    use rmcp::model::{CallToolRequestParam, CallToolResult};
+    use rmcp::Error as McpError;
+    use serde_json::Value;
+
+    /// The compiled script and the hooks defined by it
+    pub struct Scripts {
+        engine: Engine,
+        ast: AST,
+        has_route: bool,
+        has_transform_result: bool,
+    }
+
+    impl Scripts {
+        pub fn load(config: &ScriptingConfig) -> Result<Self> {
+            let script = match (&config.script, &config.script_file) {
+                (Some(script), _) => script.clone(),
+                (None, Some(path)) => std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read script file: {}", path))?,
+                (None, None) => String::new(),
+            };
+            Self::new(&script, config.max_operations)
+        }
+
+        pub fn new(script: &str, max_operations: Option<u64>) -> Result<Self> {
+            let mut engine = Engine::new();
+            if let Some(max_operations) = max_operations {
+                engine.set_max_operations(max_operations);
+            }
+            let ast = engine
+                .compile(script)
+                .map_err(|e| anyhow::anyhow!("Failed to compile script: {}", e))?;
+            let defined = |name: &str| {
+                ast.iter_functions()
+                    .any(|f| f.name == name && f.params.len() == 2)
+            };
+            let (has_route, has_transform_result) = (defined(ROUTE), defined(TRANSFORM_RESULT));
+            if !has_route && !has_transform_result {
+                anyhow::bail!("script defines neither {} nor {}", ROUTE, TRANSFORM_RESULT);
+            }
+            Ok(Self {
+                engine,
+                ast,
+                has_route,
+                has_transform_result,
+            })
+        }
+
+        fn call(&self, function: &str, tool: &str, value: Value) -> Result<Option<Value>, String> {
+            let value = rhai::serde::to_dynamic(value).map_err(|e| e.to_string())?;
+            let returned: Dynamic = self
+                .engine
+                .call_fn(
+                    &mut Scope::new(),
+                    &self.ast,
+                    function,
+                    (tool.to_string(), value),
+                )
+                .map_err(|e| match *e {
+                    rhai::EvalAltResult::ErrorRuntime(thrown, _) => thrown.to_string(),
+                    e => e.to_string(),
+                })?;
+            if returned.is_unit() {
+                return Ok(None);
+            }
+            rhai::serde::from_dynamic(&returned)
+                .map(Some)
+                .map_err(|e| e.to_string())
+        }
+
+        /// Apply `route` to the call (returns whether the tool is replaced)
+        pub fn route(&self, request: &mut CallToolRequestParam) -> Result<bool, McpError> {
+            if !self.has_route {
+                return Ok(false);
+            }
+            let arguments = Value::Object(request.arguments.clone().unwrap_or_default());
+            let routed = self.call(ROUTE, &request.name, arguments).map_err(|e| {
+                tracing::info!(tool_name = %request.name, "call rejected by route: {}", e);
+                McpError::invalid_request(e, None)
+            })?;
+            let (tool, arguments) = match routed {
+                None => return Ok(false),
+                Some(Value::String(tool)) => (Some(tool), None),
+                Some(Value::Object(mut routed)) => {
+                    let tool = match routed.remove("tool") {
+                        Some(Value::String(tool)) => Some(tool),
+                        None | Some(Value::Null) => None,
+                        Some(_) => return Err(invalid_route("tool must be a string")),
+                    };
+                    let arguments = match routed.remove("arguments") {
+                        Some(Value::Object(arguments)) => Some(arguments),
+                        None | Some(Value::Null) => None,
+                        Some(_) => return Err(invalid_route("arguments must be an object")),
+                    };
+                    (tool, arguments)
+                }
+                Some(_) => return Err(invalid_route("route must return (), a string or a map")),
+            };
+            if arguments.is_some() {
+                request.arguments = arguments;
+            }
+            match tool {
+                Some(tool) if tool != request.name.as_ref() => {
+                    tracing::debug!("routed {} to {}", &request.name, &tool);
+                    request.name = tool.into();
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        }
+    }
+
+    fn invalid_route(message: &str) -> McpError {
+        McpError::internal_error(format!("Invalid route of the script: {}", message), None)
+    }
+
+    impl CallInterceptor for Scripts {
+        fn post_call<'a>(
+            &'a self,
+            call: &'a ToolCall,
+            result: &'a mut CallToolResult,
+        ) -> BoxFuture<'a, ()> {
+            Box::pin(async move {
+                if !self.has_transform_result {
+                    return;
+                }
+                let transformed = serde_json::to_value(&*result)
+                    .map_err(|e| e.to_string())
+                    .and_then(|value| self.call(TRANSFORM_RESULT, &call.tool_name, value))
+                    .and_then(|value| {
+                        value
+                            .map(serde_json::from_value::<CallToolResult>)
+                            .transpose()
+                            .map_err(|e| format!("invalid result: {}", e))
+                    });
+                // the result is returned as it is if the script fails
+                match transformed {
+                    Ok(Some(transformed)) => *result = transformed,
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::error!(tool_name = %call.tool_name, "transform_result failed: {}", e)
+                    }
+                }
+            })
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use crate::auth::AuthIdentity;
+use crate::jobworkerp::session::{SessionInfo, SessionRegistry};
+use crate::jobworkerp::tenant::TenantsConfig;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use futures::{Stream, StreamExt};
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::{CancellationToken, PollSender};
+
+pub type SessionTransport = (
+    PollSender<ServerJsonRpcMessage>,
+    ReceiverStream<ClientJsonRpcMessage>,
+);
+
+/// A new sse connection to be served by the MCP service
+pub struct SseSession {
+    pub info: SessionInfo,
+    pub transport: SessionTransport,
+}
+
+pub struct SseServerSettings {
+    pub sse_path: String,
+    pub post_path: String,
+    pub keep_alive: Option<Duration>,
+    pub retry: Option<Duration>,
+    // tenants allowed to connect (connections of unknown tenants are rejected)
+    pub tenants: Option<Arc<TenantsConfig>>,
+    // connections over this number of sessions are rejected (None: no limit)
+    pub max_sessions: Option<usize>,
+    // sessions without messages for this period are closed (None: never)
+    pub idle_timeout: Option<Duration>,
+    // sessions with calls in flight are not idle
+    pub session_registry: Option<Arc<SessionRegistry>>,
+}
+
+struct SessionEntry {
+    tx: mpsc::Sender<ClientJsonRpcMessage>,
+    identity: Option<AuthIdentity>,
+    // time of the last message from or to the client (unix epoch millis)
+    last_active: Arc<AtomicI64>,
+    // ends the event stream of the session
+    close: CancellationToken,
+}
+
+#[derive(Clone)]
+struct SseApp {
+    sessions: Arc<RwLock<HashMap<String, SessionEntry>>>,
+    session_tx: mpsc::UnboundedSender<SseSession>,
+    post_path: Arc<str>,
+    keep_alive: Option<Duration>,
+    retry: Option<Duration>,
+    tenants: Option<Arc<TenantsConfig>>,
+    max_sessions: Option<usize>,
+    idle_timeout: Option<Duration>,
+}
+
+// remove the session when the event stream is dropped (client disconnected)
+struct SessionGuard {
+    id: String,
+    sessions: Arc<RwLock<HashMap<String, SessionEntry>>>,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        tracing::debug!("sse session closed: {}", &self.id);
+        if let Ok(mut sessions) = self.sessions.write() {
+            sessions.remove(&self.id);
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct PostEventQuery {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+}
+
+const CHANNEL_CAPACITY: usize = 64;
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+fn new_session_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+async fn sse_handler(
+    State(app): State<SseApp>,
+    identity: Option<Extension<AuthIdentity>>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    open_session(app, identity.map(|Extension(i)| i), headers, None)
+}
+
+// sse endpoint prefixed with the tenant (`/{tenant}/sse`)
+async fn tenant_sse_handler(
+    State(app): State<SseApp>,
+    Path(tenant): Path<String>,
+    identity: Option<Extension<AuthIdentity>>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    open_session(app, identity.map(|Extension(i)| i), headers, Some(tenant))
+}
+
+fn open_session(
+    app: SseApp,
+    identity: Option<AuthIdentity>,
+    headers: HeaderMap,
+    tenant: Option<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    let session_id = new_session_id();
+    let info = SessionInfo {
+        id: session_id.clone(),
+        identity: identity.clone(),
+        headers: headers
+            .iter()
+            .filter_map(|(k, v)| {
+                v.to_str()
+                    .ok()
+                    .map(|v| (k.as_str().to_lowercase(), v.to_string()))
+            })
+            .collect(),
+        tenant,
+    };
+    if let Some(tenants) = app.tenants.as_ref() {
+        if let Err(e) = tenants.select(&info) {
+            tracing::warn!("sse connection rejected: {}", e);
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
+    let (from_client_tx, from_client_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let (to_client_tx, to_client_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let last_active = Arc::new(AtomicI64::new(now_millis()));
+    let close = CancellationToken::new();
+    {
+        let mut sessions = app
+            .sessions
+            .write()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if app.max_sessions.is_some_and(|max| sessions.len() >= max) {
+            tracing::warn!(
+                "sse connection rejected: too many sessions ({})",
+                sessions.len()
+            );
+            return Err(StatusCode::SERVICE_UNAVAILABLE);
+        }
+        sessions.insert(
+            session_id.clone(),
+            SessionEntry {
+                tx: from_client_tx,
+                identity: identity.clone(),
+                last_active: last_active.clone(),
+                close: close.clone(),
+            },
+        );
+    }
+    tracing::info!(
+        "sse session connected: {} (subject: {:?})",
+        &session_id,
+        identity.as_ref().map(|i| &i.subject)
+    );
+    let guard = SessionGuard {
+        id: session_id.clone(),
+        sessions: app.sessions.clone(),
+    };
+
+    app.session_tx
+        .send(SseSession {
+            info,
+            transport: (
+                PollSender::new(to_client_tx),
+                ReceiverStream::new(from_client_rx),
+            ),
+        })
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+
+    let mut endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("{}?sessionId={}", &app.post_path, &session_id));
+    if let Some(retry) = app.retry {
+        endpoint = endpoint.retry(retry);
+    }
+    let messages = ReceiverStream::new(to_client_rx).map(move |message| {
+        let _guard = &guard;
+        last_active.store(now_millis(), Ordering::Relaxed);
+        Ok(match serde_json::to_string(&message) {
+            Ok(data) => Event::default().event("message").data(data),
+            Err(e) => {
+                tracing::error!("failed to serialize message: {}", e);
+                Event::default().comment("serialize error")
+            }
+        })
+    });
+    // tell the client why the stream ends when closed by the server
+    let idle_timeout = app.idle_timeout.unwrap_or_default();
+    let closed = close.clone();
+    let close_event = futures::stream::once(async move { closed.is_cancelled() }).filter_map(
+        move |closed| async move {
+            closed.then(|| {
+                Ok(Event::default().event("close").data(
+                    serde_json::json!({
+                        "reason": "idle_timeout",
+                        "idle_timeout_sec": idle_timeout.as_secs(),
+                    })
+                    .to_string(),
+                ))
+            })
+        },
+    );
+    let stream = futures::stream::once(async move { Ok(endpoint) })
+        .chain(messages.take_until(close.cancelled_owned()))
+        .chain(close_event);
+    let mut sse = Sse::new(stream);
+    if let Some(interval) = app.keep_alive {
+        sse = sse.keep_alive(KeepAlive::new().interval(interval));
+    }
+    Ok(sse)
+}
+
+async fn post_event_handler(
+    State(app): State<SseApp>,
+    Query(PostEventQuery { session_id }): Query<PostEventQuery>,
+    identity: Option<Extension<AuthIdentity>>,
+    Json(message): Json<ClientJsonRpcMessage>,
+) -> Result<StatusCode, StatusCode> {
+    let tx = {
+        let sessions = app
+            .sessions
+            .read()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let entry = sessions.get(&session_id).ok_or(StatusCode::NOT_FOUND)?;
+        entry.last_active.store(now_millis(), Ordering::Relaxed);
+        // a session can only be used by the identity which established it
+        if entry.identity.as_ref().map(|i| &i.subject)
+            != identity.as_ref().map(|Extension(i)| &i.subject)
+        {
+            tracing::warn!("identity mismatch for session: {}", &session_id);
+            return Err(StatusCode::FORBIDDEN);
+        }
+        entry.tx.clone()
+    };
+    tx.send(message).await.map_err(|_| StatusCode::GONE)?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+// close the sessions without messages nor calls in flight for the idle timeout
+async fn close_idle_sessions(
+    sessions: Arc<RwLock<HashMap<String, SessionEntry>>>,
+    idle_timeout: Duration,
+    session_registry: Option<Arc<SessionRegistry>>,
+) {
+    let mut interval = tokio::time::interval(
+        (idle_timeout / 2).clamp(Duration::from_millis(10), Duration::from_secs(30)),
+    );
+    loop {
+        interval.tick().await;
+        // stop when the router is dropped
+        if Arc::strong_count(&sessions) == 1 {
+            break;
+        }
+        let deadline = now_millis() - idle_timeout.as_millis() as i64;
+        let Ok(sessions) = sessions.read() else {
+            continue;
+        };
+        for (id, entry) in sessions.iter() {
+            let busy = session_registry
+                .as_ref()
+                .is_some_and(|r| r.active_calls(id) > 0);
+            if busy {
+                entry.last_active.store(now_millis(), Ordering::Relaxed);
+            } else if entry.last_active.load(Ordering::Relaxed) < deadline
+                && !entry.close.is_cancelled()
+            {
+                tracing::info!("closing idle sse session: {}", id);
+                entry.close.cancel();
+            }
+        }
+    }
+}
+
+/// Routes of the sse transport and the receiver of newly connected sessions
+pub fn router(settings: SseServerSettings) -> (Router, mpsc::UnboundedReceiver<SseSession>) {
+    let (session_tx, session_rx) = mpsc::unbounded_channel();
+    let sessions = Arc::new(RwLock::new(HashMap::new()));
+    if let Some(idle_timeout) = settings.idle_timeout {
+        tokio::spawn(close_idle_sessions(
+            sessions.clone(),
+            idle_timeout,
+            settings.session_registry.clone(),
+        ));
+    }
+    let app = SseApp {
+        sessions,
+        session_tx,
+        post_path: settings.post_path.clone().into(),
+        keep_alive: settings.keep_alive,
+        retry: settings.retry,
+        tenants: settings.tenants.clone(),
+        max_sessions: settings.max_sessions,
+        idle_timeout: settings.idle_timeout,
+    };
+    let mut router = Router::new()
+        .route(&settings.sse_path, get(sse_handler))
+        .route(&settings.post_path, post(post_event_handler));
+    if settings.tenants.as_ref().is_some_and(|t| t.path_segment) {
+        router = router.route(
+            &format!("/{{tenant}}{}", &settings.sse_path),
+            get(tenant_sse_handler),
+        );
+    }
+    let router = router.with_state(app);
+    (router, session_rx)
+}
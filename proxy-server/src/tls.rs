@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use rustls::{
+    pki_types::{CertificateDer, PrivateKeyDer},
+    server::WebPkiClientVerifier,
+    RootCertStore, ServerConfig,
+};
+use std::{fs::File, io::BufReader, path::Path, sync::Arc};
+
+/// TLS settings of the SSE server
+#[derive(Debug, Clone)]
+pub struct TlsSettings {
+    pub cert_path: String,
+    pub key_path: String,
+    // CA certificates to verify client certificates (mTLS)
+    pub client_ca_path: Option<String>,
+}
+
+impl TlsSettings {
+    pub fn from_env() -> Option<Self> {
+        match (
+            std::env::var("MCP_TLS_CERT").ok(),
+            std::env::var("MCP_TLS_KEY").ok(),
+        ) {
+            (Some(cert_path), Some(key_path)) => Some(Self {
+                cert_path,
+                key_path,
+                client_ca_path: std::env::var("MCP_TLS_CLIENT_CA").ok(),
+            }),
+            (None, None) => None,
+            _ => {
+                tracing::warn!("both MCP_TLS_CERT and MCP_TLS_KEY are required for TLS");
+                None
+            }
+        }
+    }
+
+    pub fn server_config(&self) -> Result<ServerConfig> {
+        let certs = load_certs(&self.cert_path)?;
+        let key = load_private_key(&self.key_path)?;
+        let builder = ServerConfig::builder();
+        let builder = match self.client_ca_path.as_ref() {
+            Some(ca_path) => {
+                let mut roots = RootCertStore::empty();
+                for cert in load_certs(ca_path)? {
+                    roots.add(cert)?;
+                }
+                let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+                    .build()
+                    .context("Failed to build client certificate verifier")?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+        let mut config = builder
+            .with_single_cert(certs, key)
+            .context("Failed to load server certificate")?;
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(config)
+    }
+}
+
+pub fn load_certs(path: impl AsRef<Path>) -> Result<Vec<CertificateDer<'static>>> {
+    let file = File::open(path.as_ref())
+        .with_context(|| format!("Failed to open certificate: {:?}", path.as_ref()))?;
+    rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to parse certificate: {:?}", path.as_ref()))
+}
+
+pub fn load_private_key(path: impl AsRef<Path>) -> Result<PrivateKeyDer<'static>> {
+    let file = File::open(path.as_ref())
+        .with_context(|| format!("Failed to open private key: {:?}", path.as_ref()))?;
+    rustls_pemfile::private_key(&mut BufReader::new(file))?
+        .ok_or_else(|| anyhow::anyhow!("No private key found in {:?}", path.as_ref()))
+}
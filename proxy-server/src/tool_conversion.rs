@@ -3,7 +3,9 @@ use jobworkerp_client::jobworkerp::data::RunnerType;
 use jobworkerp_client::jobworkerp::function::data::{function_specs, FunctionSpecs, McpToolList};
 use rmcp::model::{ListToolsResult, Tool};
 use rmcp::Error as McpError;
+use serde::{Deserialize, Serialize};
 use serde_json;
+use std::collections::{BTreeMap, HashMap};
 use tracing;
 pub const CREATION_TOOL_DESCRIPTION: &str =
     "Create Tools from workflow definitions provided as JSON. The workflow definition must:
@@ -13,175 +15,552 @@ pub const CREATION_TOOL_DESCRIPTION: &str =
 - When this workflow is executed as a Tool, it will receive parameters matching this input schema
 - Specify execution steps that utilize any available runner(function) in the system (except this creation Tool)";
 
+/// A function which could not be converted to a tool
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolDiagnostic {
+    pub name: String,
+    pub message: String,
+}
+
+impl ToolDiagnostic {
+    pub fn new(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Options of the conversion from functions to tools
+#[derive(Debug, Clone, Default)]
+pub struct ToolConversionOptions {
+    // include functions with broken schemas as stub tools
+    pub include_stubs: bool,
+    // inline `$ref`s of the combined settings and arguments schema
+    pub inline_refs: bool,
+    // append a "returns:" section describing the result schema to the description
+    pub describe_result: bool,
+    // prefix of tools renamed for name collisions (`{kind}` is replaced with the tool kind)
+    // (None: DEFAULT_COLLISION_PREFIX)
+    pub collision_prefix: Option<String>,
+    // prefix the tool names by the kind (RUNNER_PREFIX, WORKER_PREFIX, WORKFLOW_PREFIX)
+    pub kind_prefixes: bool,
+    // alias -> the tool listed with the alias instead of its name
+    pub aliases: BTreeMap<String, ToolAlias>,
+    // runner name -> settings fields set by the server (removed from the schema)
+    pub preset_settings: HashMap<String, Vec<String>>,
+}
+
+/// A friendlier name of a tool (configured by `tool_aliases` of the config file)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ToolAlias {
+    // name of the aliased tool (as listed without aliases)
+    pub tool: String,
+    // description replacing that of the tool
+    pub description: Option<String>,
+}
+
+pub const DEFAULT_COLLISION_PREFIX: &str = "{kind}_";
+
+/// Prefixes of the kind prefixed naming: runners (and their mcp tools), workers (and their mcp
+/// tools) and workflows (workers of reusable workflows)
+pub const RUNNER_PREFIX: &str = "runner.";
+pub const WORKER_PREFIX: &str = "worker.";
+pub const WORKFLOW_PREFIX: &str = "wf.";
+
+/// Kind of a tool, in the order call_tool resolves a name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolKind {
+    // runner (or workflow creation)
+    Runner,
+    // tool of an mcp server runner
+    McpTool,
+    Worker,
+    // tool of an mcp server worker
+    WorkerMcpTool,
+}
+
+impl ToolKind {
+    pub fn of(function: &FunctionSpecs) -> Self {
+        match (
+            function.runner_type == RunnerType::McpServer as i32,
+            function.worker_id.is_some(),
+        ) {
+            (false, false) => Self::Runner,
+            (true, false) => Self::McpTool,
+            (false, true) => Self::Worker,
+            (true, true) => Self::WorkerMcpTool,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Runner => "runner",
+            Self::McpTool => "mcp",
+            Self::Worker => "worker",
+            Self::WorkerMcpTool => "worker_mcp",
+        }
+    }
+}
+
+/// A tool listed with another name because its name is shadowed by a tool of the same name
+/// resolved first by call_tool
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RenamedTool {
+    pub name: String,
+    pub original: String,
+    pub kind: ToolKind,
+}
+
+/// Tools converted from functions
+#[derive(Debug, Clone)]
+pub struct ConvertedTools {
+    pub result: ListToolsResult,
+    pub diagnostics: Vec<ToolDiagnostic>,
+    pub renamed: Vec<RenamedTool>,
+    // alias -> name of the aliased tool (the aliases applied to the list)
+    pub aliased: HashMap<String, String>,
+}
 
 pub struct ToolConverter;
 
 impl ToolConverter {
     const DELIMITER: &str = "___";
+
+    // server names which cannot be split from the combined name by the first delimiter
+    fn needs_escape(server_name: &str) -> bool {
+        server_name.is_empty()
+            || server_name.contains(Self::DELIMITER)
+            || server_name.ends_with('_')
+    }
+
+    /// `{server}___{tool}`, or `___{length of server}_{server}___{tool}` if the server name
+    /// contains the delimiter or ends with `_` (the plain form never starts with the delimiter)
     pub fn combine_names(server_name: &str, tool_name: &str) -> String {
-        format!("{}{}{}", server_name, Self::DELIMITER, tool_name)
+        if Self::needs_escape(server_name) {
+            format!(
+                "{}{}_{}{}{}",
+                Self::DELIMITER,
+                server_name.len(),
+                server_name,
+                Self::DELIMITER,
+                tool_name
+            )
+        } else {
+            format!("{}{}{}", server_name, Self::DELIMITER, tool_name)
+        }
     }
 
     pub fn divide_names(combined: &str) -> Option<(String, String)> {
-        let delimiter = Self::DELIMITER;
-        let mut v: std::collections::VecDeque<&str> = combined.split(delimiter).collect();
-        match v.len().cmp(&2) {
-            std::cmp::Ordering::Less => {
+        let divided = match combined.strip_prefix(Self::DELIMITER) {
+            Some(escaped) => Self::divide_escaped_names(escaped),
+            // the tool name may contain the delimiter
+            None => combined.split_once(Self::DELIMITER),
+        };
+        match divided {
+            Some((server_name, tool_name)) => {
+                Some((server_name.to_string(), tool_name.to_string()))
+            }
+            None => {
                 tracing::error!("Failed to parse combined name: {:#?}", &combined);
                 None
             }
-            std::cmp::Ordering::Equal => Some((v[0].to_string(), v[1].to_string())),
-            std::cmp::Ordering::Greater => {
-                let server_name = v.pop_front();
-                Some((
-                    server_name.unwrap_or_default().to_string(),
-                    v.into_iter()
-                        .map(|s| s.to_string())
-                        .reduce(|acc, n| format!("{}{}{}", acc, delimiter, n))
-                        .unwrap_or_default(),
-                ))
-            }
-        }
-    }
-
-    pub fn convert_reusable_workflow(tool: &FunctionSpecs) -> Option<Tool> {
-        Some(Tool::new(
-            tool.name.clone(),
-            CREATION_TOOL_DESCRIPTION,
-            tool.schema
-                .as_ref()
-                .and_then(|s| match s {
-                    function_specs::Schema::SingleSchema(function) => {
-                        function.settings.as_ref().and_then(|f| {
-                            serde_json::from_str(f.as_str())
-                                .or_else(|e1| {
-                                    tracing::warn!("Failed to parse settings as json: {}", e1);
-                                    serde_yaml::from_str(f.as_str()).inspect_err(|e2| {
-                                        tracing::warn!("Failed to parse settings as yaml: {}", e2);
-                                    })
-                                })
-                                .ok()
-                        })
-                    }
-                    function_specs::Schema::McpTools(mcp) => {
-                        let mes = format!("error: expect workflow but got mcp: {:?}", mcp);
-                        tracing::error!(mes);
-                        None
-                    }
-                })
-                .unwrap_or(serde_json::json!({}))
-                .as_object()
-                .cloned()
-                .unwrap_or_default(),
-        ))
+        }
+    }
+
+    // `{length of server}_{server}___{tool}`
+    fn divide_escaped_names(escaped: &str) -> Option<(&str, &str)> {
+        let (length, rest) = escaped.split_once('_')?;
+        let length = length.parse::<usize>().ok()?;
+        let server_name = rest.get(..length)?;
+        let tool_name = rest.get(length..)?.strip_prefix(Self::DELIMITER)?;
+        Some((server_name, tool_name))
+    }
+
+    pub fn convert_reusable_workflow(tool: &FunctionSpecs) -> Result<Tool, String> {
+        let settings = match tool.schema.as_ref() {
+            Some(function_specs::Schema::SingleSchema(function)) => function.settings.as_ref(),
+            Some(function_specs::Schema::McpTools(_)) => {
+                return Err("expected workflow schema but got mcp tools".to_string());
+            }
+            None => None,
+        }
+        .ok_or_else(|| "no workflow definition schema".to_string())?;
+        let schema: serde_json::Value = serde_json::from_str(settings.as_str())
+            .or_else(|e1| {
+                tracing::debug!("Failed to parse settings as json: {}", e1);
+                serde_yaml::from_str(settings.as_str())
+            })
+            .map_err(|e| format!("invalid workflow definition schema: {}", e))?;
+        match schema {
+            serde_json::Value::Object(schema) => Ok(Tool::new(
+                tool.name.clone(),
+                CREATION_TOOL_DESCRIPTION,
+                schema,
+            )),
+            _ => Err("workflow definition schema is not an object".to_string()),
+        }
     }
 
-    pub fn convert_mcp_server(tool: &FunctionSpecs) -> Vec<Tool> {
+    pub fn convert_mcp_server(
+        tool: &FunctionSpecs,
+        diagnostics: &mut Vec<ToolDiagnostic>,
+    ) -> Vec<Tool> {
         let server_name = tool.name.as_str();
         match &tool.schema {
             Some(function_specs::Schema::McpTools(McpToolList { list })) => list
                 .iter()
-                .map(|tool| {
-                    Tool::new(
-                        Self::combine_names(server_name, tool.name.as_str()),
-                        tool.description.clone().unwrap_or_default(),
-                        serde_json::from_str(tool.input_schema.as_str())
-                            .unwrap_or(serde_json::json!({}))
-                            .as_object()
-                            .cloned()
-                            .unwrap_or_default(),
-                    )
+                .filter_map(|tool| {
+                    let name = Self::combine_names(server_name, tool.name.as_str());
+                    match serde_json::from_str(tool.input_schema.as_str()) {
+                        Ok(serde_json::Value::Object(schema)) => Some(Tool::new(
+                            name,
+                            tool.description.clone().unwrap_or_default(),
+                            schema,
+                        )),
+                        Ok(_) => {
+                            diagnostics
+                                .push(ToolDiagnostic::new(name, "input schema is not an object"));
+                            None
+                        }
+                        Err(e) => {
+                            diagnostics.push(ToolDiagnostic::new(
+                                name,
+                                format!("invalid input schema: {}", e),
+                            ));
+                            None
+                        }
+                    }
                 })
                 .collect(),
-            Some(function_specs::Schema::SingleSchema(function)) => {
-                tracing::error!("error: expect workflow but got function: {:?}", function);
+            Some(function_specs::Schema::SingleSchema(_)) => {
+                diagnostics.push(ToolDiagnostic::new(
+                    server_name,
+                    "expected mcp tools but got a function schema",
+                ));
                 vec![]
             }
             None => {
-                tracing::error!("error: expect workflow but got none: {:?}", &tool);
+                diagnostics.push(ToolDiagnostic::new(server_name, "no mcp tool list"));
                 vec![]
             }
         }
     }
 
-    pub fn convert_normal_function(tool: &FunctionSpecs) -> Option<Tool> {
-        let mut schema_combiner = SchemaCombiner::new();
-        tool.schema
-            .as_ref()
-            .and_then(|s| match s {
-                function_specs::Schema::SingleSchema(function) => function.settings.clone(),
-                function_specs::Schema::McpTools(_) => {
-                    tracing::error!("got mcp tool in not mcp tool runner type: {:#?}", &tool);
-                    None
-                }
-            })
-            .and_then(|s| {
-                schema_combiner
-                    .add_schema_from_string(
-                        "settings",
-                        s.as_str(),
-                        Some("Tool init settings".to_string()),
-                    )
-                    .inspect_err(|e| tracing::error!("Failed to parse schema: {}", e))
-                    .ok()
-            });
-        tool.schema
+    pub fn convert_normal_function(tool: &FunctionSpecs) -> Result<Tool, String> {
+        Self::convert_normal_function_with_options(tool, &ToolConversionOptions::default())
+    }
+
+    pub fn convert_normal_function_with_options(
+        tool: &FunctionSpecs,
+        options: &ToolConversionOptions,
+    ) -> Result<Tool, String> {
+        let function = match tool.schema.as_ref() {
+            Some(function_specs::Schema::SingleSchema(function)) => function,
+            Some(function_specs::Schema::McpTools(_)) => {
+                return Err("got mcp tools in not mcp tool runner type".to_string());
+            }
+            None => return Err("no schema".to_string()),
+        };
+        let mut schema_combiner = SchemaCombiner::new().with_inline_refs(options.inline_refs);
+        // empty schema: no settings (or arguments) required
+        if let Some(settings) = function.settings.as_ref().filter(|s| !s.trim().is_empty()) {
+            schema_combiner
+                .add_schema_from_string(
+                    "settings",
+                    settings.as_str(),
+                    Some("Tool init settings".to_string()),
+                )
+                .map_err(|e| format!("invalid settings schema: {:#}", e))?;
+        }
+        if !function.arguments.trim().is_empty() {
+            schema_combiner
+                .add_schema_from_string(
+                    "arguments",
+                    function.arguments.as_str(),
+                    Some("Tool arguments".to_string()),
+                )
+                .map_err(|e| format!("invalid arguments schema: {:#}", e))?;
+        }
+        // the result schema is only documented: a broken one does not make the tool unavailable
+        if let Some(result) = function
+            .result_output_schema
             .as_ref()
-            .map(|s| match s {
-                function_specs::Schema::SingleSchema(function) => function.arguments.clone(),
-                function_specs::Schema::McpTools(_) => {
-                    tracing::error!("got mcp tool in not mcp tool runner type: {:#?}", &tool);
-                    "".to_string()
+            .filter(|s| !s.trim().is_empty())
+        {
+            if let Err(e) = schema_combiner.add_result_schema_from_string(result.as_str()) {
+                tracing::warn!("ignore invalid result schema of {}: {:#}", &tool.name, e);
+            }
+        }
+        let description = match schema_combiner
+            .describe_result()
+            .filter(|_| options.describe_result)
+        {
+            Some(returns) => format!("{}\n\n{}", &tool.description, returns),
+            None => tool.description.clone(),
+        };
+        let mut schema = schema_combiner
+            .generate_combined_schema()
+            .map_err(|e| format!("failed to generate schema: {:#}", e))?;
+        if let Some(fields) = options
+            .preset_settings
+            .get(&tool.name)
+            .filter(|_| tool.worker_id.is_none())
+        {
+            Self::remove_settings_fields(&mut schema, fields);
+        }
+        Ok(Tool::new(tool.name.clone(), description, schema))
+    }
+
+    // remove the fields from the settings schema (and the settings if no field is left)
+    fn remove_settings_fields(
+        schema: &mut serde_json::Map<String, serde_json::Value>,
+        fields: &[String],
+    ) {
+        let Some(settings) = schema
+            .get_mut("properties")
+            .and_then(|p| p.get_mut("settings"))
+            .and_then(|s| s.as_object_mut())
+        else {
+            return;
+        };
+        if let Some(properties) = settings
+            .get_mut("properties")
+            .and_then(|p| p.as_object_mut())
+        {
+            properties.retain(|k, _| !fields.contains(k));
+        }
+        if let Some(required) = settings.get_mut("required").and_then(|r| r.as_array_mut()) {
+            required.retain(|r| !r.as_str().is_some_and(|r| fields.iter().any(|f| f == r)));
+        }
+        let empty = settings
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .is_some_and(|p| p.is_empty());
+        if empty {
+            if let Some(properties) = schema.get_mut("properties").and_then(|p| p.as_object_mut()) {
+                properties.remove("settings");
+            }
+            if let Some(required) = schema.get_mut("required").and_then(|r| r.as_array_mut()) {
+                required.retain(|r| r != "settings");
+            }
+        }
+    }
+
+    /// Result schema of the function (None if not provided or invalid)
+    pub fn result_schema(
+        tool: &FunctionSpecs,
+    ) -> Option<serde_json::Map<String, serde_json::Value>> {
+        let result = match tool.schema.as_ref() {
+            Some(function_specs::Schema::SingleSchema(function)) => {
+                function.result_output_schema.as_ref()?
+            }
+            _ => return None,
+        };
+        let mut schema_combiner = SchemaCombiner::new();
+        schema_combiner
+            .add_result_schema_from_string(result.as_str())
+            .ok()?;
+        schema_combiner.generate_result_schema()
+    }
+
+    // placeholder tool which explains why the function is unavailable
+    fn stub_tool(diagnostic: &ToolDiagnostic) -> Tool {
+        Tool::new(
+            diagnostic.name.clone(),
+            format!(
+                "[UNAVAILABLE] This tool could not be loaded because its schema is broken ({}). Calling it will fail until the schema is fixed in jobworkerp.",
+                &diagnostic.message
+            ),
+            serde_json::json!({"type": "object", "properties": {}})
+                .as_object()
+                .cloned()
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Convert functions to tools, collecting the functions which could not be converted
+    /// (included as stub tools if `include_stubs`)
+    pub fn convert_functions_with_diagnostics(
+        functions: Vec<FunctionSpecs>,
+        include_stubs: bool,
+    ) -> (ListToolsResult, Vec<ToolDiagnostic>) {
+        let converted = Self::convert_functions_with_options(
+            functions,
+            &ToolConversionOptions {
+                include_stubs,
+                ..Default::default()
+            },
+        );
+        (converted.result, converted.diagnostics)
+    }
+
+    pub fn convert_functions_with_options(
+        functions: Vec<FunctionSpecs>,
+        options: &ToolConversionOptions,
+    ) -> ConvertedTools {
+        let mut diagnostics = Vec::new();
+        let mut tool_list = Vec::new();
+        for tool in functions {
+            let kind = ToolKind::of(&tool);
+            let kind_prefix = if options.kind_prefixes {
+                Self::kind_prefix(&tool)
+            } else {
+                ""
+            };
+            let entry = |mut t: Tool| {
+                if !kind_prefix.is_empty() {
+                    t.name = format!("{}{}", kind_prefix, &t.name).into();
                 }
-            })
-            .and_then(|args| {
-                schema_combiner
-                    .add_schema_from_string(
-                        "arguments",
-                        args.as_str(),
-                        Some("Tool arguments".to_string()),
-                    )
-                    .inspect_err(|e| tracing::error!("Failed to parse schema: {}", e))
-                    .ok()
+                (kind, kind_prefix, t)
+            };
+            if tool.runner_type == RunnerType::McpServer as i32 {
+                tool_list.extend(
+                    Self::convert_mcp_server(&tool, &mut diagnostics)
+                        .into_iter()
+                        .map(entry),
+                );
+                continue;
+            }
+            let converted = if tool.worker_id.is_none()
+                && tool.runner_type == RunnerType::ReusableWorkflow as i32
+            {
+                Self::convert_reusable_workflow(&tool)
+            } else {
+                Self::convert_normal_function_with_options(&tool, options)
+            };
+            match converted {
+                Ok(t) => tool_list.push(entry(t)),
+                Err(message) => diagnostics.push(ToolDiagnostic::new(tool.name.clone(), message)),
+            }
+        }
+        for diagnostic in diagnostics.iter() {
+            tracing::warn!(
+                "skip tool with broken schema: {}: {}",
+                &diagnostic.name,
+                &diagnostic.message
+            );
+        }
+        let prefix = options
+            .collision_prefix
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .unwrap_or(DEFAULT_COLLISION_PREFIX);
+        let renamed = Self::disambiguate_names(&mut tool_list, prefix);
+        let mut tool_list = tool_list.into_iter().map(|(_, _, t)| t).collect::<Vec<_>>();
+        let aliased = Self::apply_aliases(&mut tool_list, &options.aliases);
+        if options.include_stubs {
+            tool_list.extend(diagnostics.iter().map(Self::stub_tool));
+        }
+        ConvertedTools {
+            result: ListToolsResult {
+                tools: tool_list,
+                next_cursor: None,
+            },
+            diagnostics,
+            renamed,
+            aliased,
+        }
+    }
+
+    /// List the tools with the aliases (aliases of unknown tools or taken names are ignored)
+    fn apply_aliases(
+        tools: &mut [Tool],
+        aliases: &BTreeMap<String, ToolAlias>,
+    ) -> HashMap<String, String> {
+        let mut aliased = HashMap::new();
+        for (alias, config) in aliases {
+            if tools.iter().any(|t| t.name == alias.as_str()) {
+                tracing::warn!("tool alias {} ignored: the name is taken", alias);
+                continue;
+            }
+            let Some(tool) = tools.iter_mut().find(|t| t.name == config.tool.as_str()) else {
+                tracing::warn!("tool alias {} ignored: {} not found", alias, &config.tool);
+                continue;
+            };
+            tool.name = alias.clone().into();
+            if let Some(description) = config.description.as_ref() {
+                tool.description = description.clone().into();
+            }
+            aliased.insert(alias.clone(), config.tool.clone());
+        }
+        aliased
+    }
+
+    /// Rename the tools whose names are shadowed by the tools resolved first by call_tool
+    /// (see ToolKind), prepending the prefix (after the kind prefix) until the name is unique
+    fn disambiguate_names(
+        tools: &mut [(ToolKind, &'static str, Tool)],
+        prefix: &str,
+    ) -> Vec<RenamedTool> {
+        let mut taken = std::collections::HashSet::new();
+        let mut order = (0..tools.len()).collect::<Vec<_>>();
+        // stable: the first listed of the same kind keeps the name
+        order.sort_by_key(|i| tools[*i].0);
+        let mut shadowed = Vec::new();
+        for i in order {
+            if !taken.insert(tools[i].2.name.to_string()) {
+                shadowed.push(i);
+            }
+        }
+        let mut renamed = Vec::new();
+        for i in shadowed {
+            let (kind, kind_prefix, tool) = &mut tools[i];
+            let listed = tool.name.to_string();
+            let original = listed
+                .strip_prefix(*kind_prefix)
+                .unwrap_or(&listed)
+                .to_string();
+            let prefix = prefix.replace("{kind}", kind.as_str());
+            let mut prefixed = format!("{}{}", prefix, original);
+            while !taken.insert(format!("{}{}", kind_prefix, prefixed)) {
+                prefixed = format!("{}{}", prefix, prefixed);
+            }
+            let name = format!("{}{}", kind_prefix, prefixed);
+            tracing::warn!(
+                "tool name collision: {} {} is listed as {}",
+                kind.as_str(),
+                &listed,
+                &name
+            );
+            tool.name = name.clone().into();
+            renamed.push(RenamedTool {
+                name,
+                original,
+                kind: *kind,
             });
-        match schema_combiner.generate_combined_schema() {
-            Ok(schema) => Some(Tool::new(
-                tool.name.clone(),
-                tool.description.clone(),
-                schema,
-            )),
-            Err(e) => {
-                tracing::error!("Failed to generate schema: {}", e);
-                None
+        }
+        renamed
+    }
+
+    /// Prefix of the tool names of the function in the kind prefixed naming
+    pub fn kind_prefix(function: &FunctionSpecs) -> &'static str {
+        match ToolKind::of(function) {
+            ToolKind::Runner | ToolKind::McpTool => RUNNER_PREFIX,
+            ToolKind::Worker if function.runner_type == RunnerType::ReusableWorkflow as i32 => {
+                WORKFLOW_PREFIX
             }
+            ToolKind::Worker | ToolKind::WorkerMcpTool => WORKER_PREFIX,
+        }
+    }
+
+    /// Kind (Runner or Worker) and backend name of a kind prefixed tool name
+    pub fn strip_kind_prefix(name: &str) -> Option<(ToolKind, &str)> {
+        if let Some(name) = name.strip_prefix(RUNNER_PREFIX) {
+            Some((ToolKind::Runner, name))
+        } else {
+            [WORKER_PREFIX, WORKFLOW_PREFIX]
+                .iter()
+                .find_map(|p| name.strip_prefix(p))
+                .map(|name| (ToolKind::Worker, name))
         }
     }
 
     pub fn convert_functions_to_mcp_tools(
         functions: Vec<FunctionSpecs>,
     ) -> Result<ListToolsResult, McpError> {
-        let tool_list = functions
-            .into_iter()
-            .flat_map(|tool| {
-                if tool.worker_id.is_none()
-                    && tool.runner_type == RunnerType::ReusableWorkflow as i32
-                {
-                    Self::convert_reusable_workflow(&tool)
-                        .into_iter()
-                        .collect::<Vec<_>>()
-                } else if tool.runner_type == RunnerType::McpServer as i32 {
-                    Self::convert_mcp_server(&tool)
-                } else {
-                    Self::convert_normal_function(&tool)
-                        .into_iter()
-                        .collect::<Vec<_>>()
-                }
-            })
-            .collect::<Vec<_>>();
-
-        Ok(ListToolsResult {
-            tools: tool_list,
-            next_cursor: None,
-        })
+        Ok(Self::convert_functions_with_diagnostics(functions, false).0)
     }
 }
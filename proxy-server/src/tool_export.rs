@@ -0,0 +1,79 @@
+use anyhow::Result;
+use rmcp::model::{ListToolsResult, Tool};
+use serde_json::{json, Map, Value};
+
+/// Formats of the exported tool catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    // the tool list as listed to the clients (`tools/list` result)
+    Json,
+    // OpenAPI 3.1 document with an operation per tool (`POST /tools/{name}`)
+    OpenApi,
+}
+
+impl ExportFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "openapi" => Some(Self::OpenApi),
+            _ => None,
+        }
+    }
+}
+
+/// Render the tool catalog sorted by name (stable for diffs)
+pub fn export(tools: &ListToolsResult, format: ExportFormat) -> Result<String> {
+    let mut tools = tools.tools.clone();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    let document = match format {
+        ExportFormat::Json => json!({ "tools": tools }),
+        ExportFormat::OpenApi => openapi(&tools),
+    };
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+/// OpenAPI document of the tools (the request body of an operation is the tool arguments)
+pub fn openapi(tools: &[Tool]) -> Value {
+    let paths = tools
+        .iter()
+        .map(|tool| {
+            let operation = json!({
+                "operationId": tool.name,
+                "summary": tool.description.lines().next().unwrap_or_default(),
+                "description": tool.description,
+                "requestBody": {
+                    "required": true,
+                    "content": {"application/json": {"schema": tool.input_schema.as_ref()}}
+                },
+                "responses": {
+                    "200": {
+                        "description": "result of the tool call",
+                        "content": {"application/json": {
+                            "schema": {"$ref": "#/components/schemas/CallToolResult"}
+                        }}
+                    }
+                }
+            });
+            (
+                format!("/tools/{}", tool.name),
+                json!({ "post": operation }),
+            )
+        })
+        .collect::<Map<_, _>>();
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "jobworkerp-mcp-proxy tools",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {"schemas": {"CallToolResult": {
+            "type": "object",
+            "properties": {
+                "content": {"type": "array", "items": {"type": "object"}},
+                "isError": {"type": "boolean"}
+            },
+            "required": ["content"]
+        }}}
+    })
+}
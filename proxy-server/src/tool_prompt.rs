@@ -0,0 +1,165 @@
+use rmcp::model::{Prompt, PromptMessage, PromptMessageRole, Tool};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Prompts are listed as `use-{tool}`
+pub const PROMPT_PREFIX: &str = "use-";
+// nesting of the generated example arguments
+const MAX_EXAMPLE_DEPTH: usize = 8;
+
+/// Additions to the generated prompt of a tool (`tool_prompts` of the config file)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ToolPromptConfig {
+    // notes on calling the tool
+    pub caveats: Vec<String>,
+    // example arguments replacing the generated ones
+    pub example: Option<Value>,
+}
+
+pub fn prompt_name(tool_name: &str) -> String {
+    format!("{}{}", PROMPT_PREFIX, tool_name)
+}
+
+/// Tool name of a prompt name (None if not a tool prompt)
+pub fn tool_name(prompt_name: &str) -> Option<&str> {
+    prompt_name
+        .strip_prefix(PROMPT_PREFIX)
+        .filter(|n| !n.is_empty())
+}
+
+pub fn prompt(tool: &Tool) -> Prompt {
+    Prompt {
+        name: prompt_name(&tool.name),
+        description: Some(format!(
+            "How to call the `{}` tool, with its input schema and example arguments",
+            &tool.name
+        )),
+        arguments: None,
+    }
+}
+
+/// Example arguments of the schema: the examples, defaults, constants and the first enum
+/// values, or placeholders of the types (optional fields are included if they have values)
+pub fn example_arguments(schema: &Map<String, Value>) -> Value {
+    example_value(schema, 0, true)
+}
+
+fn example_value(schema: &Map<String, Value>, depth: usize, all_fields: bool) -> Value {
+    if depth > MAX_EXAMPLE_DEPTH {
+        return Value::Null;
+    }
+    if let Some(example) = schema
+        .get("examples")
+        .and_then(|e| e.as_array())
+        .and_then(|e| e.first())
+    {
+        return example.clone();
+    }
+    for key in ["default", "const"] {
+        if let Some(value) = schema.get(key) {
+            return value.clone();
+        }
+    }
+    if let Some(value) = schema
+        .get("enum")
+        .and_then(|e| e.as_array())
+        .and_then(|e| e.first())
+    {
+        return value.clone();
+    }
+    for key in ["oneOf", "anyOf", "allOf"] {
+        if let Some(first) = schema
+            .get(key)
+            .and_then(|s| s.as_array())
+            .and_then(|s| s.first())
+            .and_then(|s| s.as_object())
+        {
+            return example_value(first, depth + 1, all_fields);
+        }
+    }
+    let schema_type = match schema.get("type") {
+        Some(Value::String(t)) => t.as_str(),
+        // the first non null type
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(|t| t.as_str())
+            .find(|t| *t != "null")
+            .unwrap_or("null"),
+        _ if schema.contains_key("properties") => "object",
+        _ => "",
+    };
+    match schema_type {
+        "object" => {
+            let required = schema
+                .get("required")
+                .and_then(|r| r.as_array())
+                .map(|r| r.iter().filter_map(|n| n.as_str()).collect::<Vec<_>>())
+                .unwrap_or_default();
+            let mut object = Map::new();
+            if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+                for (name, property) in properties {
+                    let Some(property) = property.as_object() else {
+                        continue;
+                    };
+                    let is_required = required.contains(&name.as_str());
+                    let value = example_value(property, depth + 1, is_required);
+                    if is_required || (all_fields && has_example(property)) {
+                        object.insert(name.clone(), value);
+                    }
+                }
+            }
+            Value::Object(object)
+        }
+        "array" => match schema.get("items").and_then(|i| i.as_object()) {
+            Some(items) => Value::Array(vec![example_value(items, depth + 1, all_fields)]),
+            None => Value::Array(vec![]),
+        },
+        "string" => Value::String(
+            schema
+                .get("format")
+                .and_then(|f| f.as_str())
+                .map(|f| format!("<{}>", f))
+                .unwrap_or_else(|| "<string>".to_string()),
+        ),
+        "integer" | "number" => Value::from(0),
+        "boolean" => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+fn has_example(schema: &Map<String, Value>) -> bool {
+    ["examples", "default", "const"]
+        .iter()
+        .any(|k| schema.contains_key(*k))
+}
+
+/// Prompt text of the tool: the description, the input schema, example arguments and caveats
+pub fn render(tool: &Tool, config: Option<&ToolPromptConfig>) -> String {
+    let pretty = |v: &Value| serde_json::to_string_pretty(v).unwrap_or_else(|_| v.to_string());
+    let example = config
+        .and_then(|c| c.example.clone())
+        .unwrap_or_else(|| example_arguments(&tool.input_schema));
+    let mut text = format!(
+        "Call the `{}` tool.\n\n{}\n\nInput schema:\n```json\n{}\n```\n\nExample arguments:\n```json\n{}\n```\n",
+        &tool.name,
+        &tool.description,
+        pretty(&Value::Object(tool.input_schema.as_ref().clone())),
+        pretty(&example)
+    );
+    let caveats = config.map(|c| c.caveats.as_slice()).unwrap_or_default();
+    if !caveats.is_empty() {
+        text.push_str("\nCaveats:\n");
+        for caveat in caveats {
+            text.push_str(&format!("- {}\n", caveat));
+        }
+    }
+    text
+}
+
+pub fn prompt_messages(tool: &Tool, config: Option<&ToolPromptConfig>) -> Vec<PromptMessage> {
+    vec![PromptMessage::new_text(
+        PromptMessageRole::User,
+        render(tool, config),
+    )]
+}
@@ -0,0 +1,96 @@
+use crate::auth::AuthIdentity;
+use crate::jobworkerp::meta_tool;
+use crate::jobworkerp::JobworkerpRouter;
+use crate::rbac::RbacConfig;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Extension, Json, Router};
+use serde_json::json;
+use std::sync::Arc;
+
+// recent calls shown by the page (newest first)
+const MAX_UI_CALLS: usize = 100;
+
+const INDEX_HTML: &str = include_str!("web_ui.html");
+
+#[derive(Clone)]
+struct UiState {
+    service: JobworkerpRouter,
+    rbac: Option<Arc<RbacConfig>>,
+}
+
+/// Read-only page of the tools and the recent calls of the server
+///
+/// - `GET {base}/ui`: the page
+/// - `GET {base}/ui/tools.json`: the tools as listed to the clients (sorted by name)
+/// - `GET {base}/ui/calls.json`: the recent calls of all sessions (without the outputs)
+///
+/// Admins only (requires RBAC).
+pub fn router(service: JobworkerpRouter, base_path: &str, rbac: Option<Arc<RbacConfig>>) -> Router {
+    let ui_path = crate::http::join_path(base_path, "/ui");
+    Router::new()
+        .route(&ui_path, get(index))
+        .route(&format!("{}/tools.json", ui_path), get(tools))
+        .route(&format!("{}/calls.json", ui_path), get(calls))
+        .with_state(UiState { service, rbac })
+}
+
+fn check_admin(
+    state: &UiState,
+    identity: Option<&Extension<AuthIdentity>>,
+) -> Result<(), StatusCode> {
+    if crate::http::is_admin(state.rbac.as_deref(), identity) {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+async fn index(
+    State(state): State<UiState>,
+    identity: Option<Extension<AuthIdentity>>,
+) -> Result<Html<&'static str>, StatusCode> {
+    check_admin(&state, identity.as_ref())?;
+    Ok(Html(INDEX_HTML))
+}
+
+async fn tools(
+    State(state): State<UiState>,
+    identity: Option<Extension<AuthIdentity>>,
+) -> Response {
+    if let Err(status) = check_admin(&state, identity.as_ref()) {
+        return status.into_response();
+    }
+    match state.service.export_tools().await {
+        Ok(mut tools) => {
+            tools.tools.sort_by(|a, b| a.name.cmp(&b.name));
+            Json(tools).into_response()
+        }
+        Err(e) => {
+            tracing::error!("failed to list tools for ui: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({"error": {"message": e.to_string()}})),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn calls(
+    State(state): State<UiState>,
+    identity: Option<Extension<AuthIdentity>>,
+) -> Response {
+    if let Err(status) = check_admin(&state, identity.as_ref()) {
+        return status.into_response();
+    }
+    let jobs = state.service.job_store.list();
+    let shown = &jobs[..jobs.len().min(MAX_UI_CALLS)];
+    Json(json!({
+        "total": jobs.len(),
+        "calls": meta_tool::job_history(shown, false),
+    }))
+    .into_response()
+}
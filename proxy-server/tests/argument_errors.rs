@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::argument_errors;
+    use serde_json::json;
+
+    #[test]
+    fn test_field_errors() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "arguments": {
+                    "type": "object",
+                    "properties": {
+                        "items": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {"count": {"type": "integer"}}
+                            }
+                        },
+                        "name": {"type": "string"}
+                    },
+                    "required": ["name"]
+                }
+            }
+        });
+        let schema = schema.as_object().unwrap();
+        let arguments = json!({"arguments": {"items": [{"count": "two"}], "name": "n1"}});
+        let errors = argument_errors::field_errors(schema, &arguments);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "arguments.items[0].count");
+        assert_eq!(errors[0].received, json!("two"));
+        assert!(errors[0].message.contains("integer"));
+
+        let result = argument_errors::field_errors_result("Failed to enqueue job", &errors);
+        assert_eq!(result.is_error, Some(true));
+        let text = serde_json::to_value(&result.content[0]).unwrap()["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(text.starts_with("Invalid arguments: Failed to enqueue job\n"));
+        assert!(text.contains("- arguments.items[0].count: "));
+        assert!(text.ends_with("(received: \"two\")"));
+
+        let errors = argument_errors::field_errors(schema, &json!({"arguments": {}}));
+        assert_eq!(errors[0].path, "arguments");
+        assert!(
+            argument_errors::field_errors(schema, &json!({"arguments": {"name": "n1"}})).is_empty()
+        );
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::jobworkerp::artifact::{self, ArtifactRegistry, ArtifactsConfig};
+    use rmcp::model::ResourceContents;
+    use std::path::Path;
+
+    fn new_registry(name: &str) -> (ArtifactRegistry, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("artifact-test-{}", name));
+        std::fs::create_dir_all(&dir).unwrap();
+        let registry = ArtifactRegistry::new(ArtifactsConfig {
+            dirs: vec![dir.to_string_lossy().to_string()],
+            max_read_bytes: 16,
+            ..Default::default()
+        });
+        (registry, std::fs::canonicalize(dir).unwrap())
+    }
+
+    #[test]
+    fn test_register_output() {
+        let (registry, dir) = new_registry("register");
+        std::fs::write(dir.join("report.csv"), "a,b\n1,2\n").unwrap();
+        std::fs::write(dir.join("image.png"), [0x89, 0x50]).unwrap();
+        let output = serde_json::json!({
+            "path": dir.join("report.csv"),
+            "files": [dir.join("image.png"), dir.join("missing.txt"), "/etc/hostname"],
+            // not a path field
+            "message": dir.join("report.csv"),
+            "nested": {"output_file": dir.join("report.csv")},
+        });
+        let artifacts = registry.register_output(7, &output);
+        assert_eq!(artifacts.len(), 2);
+        assert_eq!(artifacts[0].uri, "artifact://7/0/report.csv");
+        assert_eq!(artifacts[0].mime_type, "text/csv");
+        assert_eq!(artifacts[0].size, 8);
+        assert_eq!(artifacts[1].uri, "artifact://7/1/image.png");
+        assert_eq!(registry.list().len(), 2);
+        assert_eq!(
+            ArtifactRegistry::parse_uri("artifact://7/1/image.png"),
+            Some((7, 1))
+        );
+        assert!(registry.get("artifact://7/1/other.png").is_none());
+
+        let link = serde_json::to_value(artifacts[0].resource_link()).unwrap();
+        let link: serde_json::Value = serde_json::from_str(link["text"].as_str().unwrap()).unwrap();
+        assert_eq!(link["type"], "resource_link");
+        assert_eq!(link["mimeType"], "text/csv");
+
+        match registry.read(&artifacts[0]).unwrap() {
+            ResourceContents::TextResourceContents { text, .. } => assert_eq!(text, "a,b\n1,2\n"),
+            other => panic!("unexpected contents: {:?}", other),
+        }
+        match registry.read(&artifacts[1]).unwrap() {
+            ResourceContents::BlobResourceContents { blob, .. } => assert_eq!(blob, "iVA="),
+            other => panic!("unexpected contents: {:?}", other),
+        }
+        std::fs::write(dir.join("report.csv"), "larger than 16 bytes").unwrap();
+        assert!(registry.read(&artifacts[0]).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_disabled_and_outside_paths() {
+        let registry = ArtifactRegistry::new(ArtifactsConfig::default());
+        assert!(registry
+            .register_output(1, &serde_json::json!({"path": "/etc/hostname"}))
+            .is_empty());
+
+        let (registry, dir) = new_registry("outside");
+        // relative and escaping paths
+        let output = serde_json::json!({"path": ["report.csv", dir.join("../report.csv")]});
+        assert!(registry.register_output(1, &output).is_empty());
+        std::fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(
+            artifact::mime_type(Path::new("a/b.JSON")),
+            "application/json"
+        );
+        assert_eq!(
+            artifact::mime_type(Path::new("a/b")),
+            "application/octet-stream"
+        );
+    }
+}
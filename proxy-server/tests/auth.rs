@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod tests {
+    use axum::{routing::get, Json, Router};
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+    use proxy_server::auth::{AuthIdentity, Authenticator, JwtValidator, OAuthConfig};
+    use serde_json::{json, Value};
+
+    const ISSUER: &str = "https://auth.example.com";
+    const RESOURCE_URL: &str = "https://mcp.example.com";
+    // encoded the same in the standard and url safe base64 alphabets (no padding for 33 bytes)
+    const SECRET: &[u8] = b"secret-key-of-the-jwt-tests-33byt";
+
+    fn now() -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+
+    // serve the jwks of the secret with the key id
+    async fn serve_jwks(kid: &str) -> String {
+        use base64::Engine;
+        let jwks = json!({"keys": [{
+            "kty": "oct",
+            "kid": kid,
+            "alg": "HS256",
+            "k": base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(SECRET),
+        }]});
+        let router = Router::new().route("/jwks.json", get(move || async move { Json(jwks) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/jwks.json", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        url
+    }
+
+    async fn validator(audience: Option<&str>) -> JwtValidator {
+        JwtValidator::new(OAuthConfig {
+            issuer: ISSUER.to_string(),
+            jwks_url: serve_jwks("k1").await,
+            audience: audience.map(|a| a.to_string()),
+            resource_url: RESOURCE_URL.to_string(),
+            scopes_supported: vec![],
+        })
+    }
+
+    fn token(kid: &str, claims: Value) -> String {
+        let header = Header {
+            kid: Some(kid.to_string()),
+            ..Header::new(Algorithm::HS256)
+        };
+        encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn claims(overrides: Value) -> Value {
+        let mut claims = json!({
+            "iss": ISSUER,
+            "aud": RESOURCE_URL,
+            "sub": "alice",
+            "exp": now() + 600,
+        });
+        for (key, value) in overrides.as_object().unwrap() {
+            claims[key] = value.clone();
+        }
+        claims
+    }
+
+    #[tokio::test]
+    async fn test_validate_token() {
+        let validator = validator(Some(RESOURCE_URL)).await;
+
+        let identity = validator
+            .validate(&token("k1", claims(json!({"scope": "mcp:tools mcp:read"}))))
+            .await
+            .unwrap();
+        assert_eq!(
+            identity,
+            AuthIdentity {
+                subject: "alice".to_string(),
+                scopes: vec!["mcp:tools".to_string(), "mcp:read".to_string()],
+            }
+        );
+        // `scp` as an array
+        let identity = validator
+            .validate(&token("k1", claims(json!({"scp": ["mcp:tools"]}))))
+            .await
+            .unwrap();
+        assert_eq!(identity.scopes, vec!["mcp:tools"]);
+        let identity = validator
+            .validate(&token("k1", claims(json!({}))))
+            .await
+            .unwrap();
+        assert!(identity.scopes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reject_invalid_tokens() {
+        let validator = validator(Some(RESOURCE_URL)).await;
+        let invalid = [
+            token("k1", claims(json!({"iss": "https://other.example.com"}))),
+            // issued for another resource
+            token("k1", claims(json!({"aud": "https://other.example.com"}))),
+            token("k1", claims(json!({"exp": now() - 600}))),
+            // without a subject
+            token("k1", claims(json!({"sub": ""}))),
+            token("k1", claims(json!({"sub": null}))),
+            // unknown key id (not in the jwks even after refetching)
+            token("k2", claims(json!({}))),
+            "not a jwt".to_string(),
+            // not the algorithm of the key
+            encode(
+                &Header {
+                    kid: Some("k1".to_string()),
+                    ..Header::new(Algorithm::HS384)
+                },
+                &claims(json!({})),
+                &EncodingKey::from_secret(SECRET),
+            )
+            .unwrap(),
+        ];
+        for token in invalid.iter() {
+            assert!(validator.validate(token).await.is_err(), "{}", token);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audience_opt_out() {
+        let validator = validator(None).await;
+        let identity = validator
+            .validate(&token(
+                "k1",
+                claims(json!({"aud": "https://other.example.com"})),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(identity.subject, "alice");
+    }
+
+    #[test]
+    fn test_default_resource_url() {
+        assert_eq!(
+            OAuthConfig::default_resource_url("127.0.0.1:8000, [::1]:8000", false),
+            "http://127.0.0.1:8000"
+        );
+        assert_eq!(
+            OAuthConfig::default_resource_url("mcp.example.com:443", true),
+            "https://mcp.example.com:443"
+        );
+        assert_eq!(
+            OAuthConfig::default_resource_url("", false),
+            "http://127.0.0.1:8000"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_static_token_subjects() {
+        let authenticator = Authenticator::new(vec!["t1".to_string(), "t2".to_string()], None);
+        let alice = authenticator.authenticate("t1").await.unwrap();
+        let bob = authenticator.authenticate("t2").await.unwrap();
+        assert_eq!(alice.subject, Authenticator::static_token_subject("t1"));
+        assert_eq!(bob.subject, Authenticator::static_token_subject("t2"));
+        assert_ne!(alice.subject, bob.subject);
+        assert!(authenticator.authenticate("t3").await.is_err());
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::jobworkerp::JobworkerpRouterConfig;
+    use proxy_server::{ProxyBuilder, SseConfig};
+
+    #[test]
+    fn test_builder_settings() {
+        let builder = ProxyBuilder::new(JobworkerpRouterConfig::from_env(ProxyConfig::default()))
+            .with_jobworkerp_address("http://jobworkerp:9000")
+            .with_set_name("tools")
+            .with_exclude_worker_as_tool(true)
+            .with_address("0.0.0.0:8080")
+            .with_auth_tokens(vec!["token".to_string()]);
+        assert_eq!(
+            builder.config().jobworkerp_address,
+            "http://jobworkerp:9000"
+        );
+        assert_eq!(builder.config().set_name.as_deref(), Some("tools"));
+        assert!(builder.config().exclude_worker_as_tool);
+        assert_eq!(builder.sse_config().address, "0.0.0.0:8080");
+        assert_eq!(builder.sse_config().auth_tokens, vec!["token"]);
+        // not set by the builder
+        assert_eq!(builder.sse_config().sse_path, "/sse");
+        assert_eq!(builder.sse_config().keep_alive_sec, 15);
+    }
+
+    #[test]
+    fn test_sse_config_default() {
+        let config = SseConfig::default();
+        assert_eq!(config.address, "127.0.0.1:8000");
+        assert_eq!(config.post_path, "/message");
+        assert!(config.cors_allowed_origins.is_none());
+        assert!(config.tls.is_none());
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::jobworkerp::channel::UpstreamChannelConfig;
+    use std::time::Duration;
+    use tonic::codec::CompressionEncoding;
+
+    #[test]
+    fn test_unix_socket_path() {
+        assert_eq!(
+            UpstreamChannelConfig::unix_socket_path("unix:///run/jobworkerp.sock"),
+            Some("/run/jobworkerp.sock")
+        );
+        assert_eq!(UpstreamChannelConfig::unix_socket_path("unix://"), None);
+        assert_eq!(
+            UpstreamChannelConfig::unix_socket_path("http://127.0.0.1:9000"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_compression() {
+        assert_eq!(
+            UpstreamChannelConfig::parse_compression("gzip").unwrap(),
+            Some(CompressionEncoding::Gzip)
+        );
+        assert_eq!(
+            UpstreamChannelConfig::parse_compression("ZSTD").unwrap(),
+            Some(CompressionEncoding::Zstd)
+        );
+        assert_eq!(
+            UpstreamChannelConfig::parse_compression("none").unwrap(),
+            None
+        );
+        assert!(UpstreamChannelConfig::parse_compression("brotli").is_err());
+    }
+
+    #[test]
+    fn test_custom_channel_for_keepalive() {
+        let address = "http://127.0.0.1:9000";
+        assert!(!UpstreamChannelConfig::default().needs_custom_channel(address));
+        let upstream = UpstreamChannelConfig {
+            keepalive_interval: Some(Duration::from_secs(30)),
+            keepalive_timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        assert!(upstream.needs_custom_channel(address));
+        assert!(upstream.endpoint(address).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_connect_unix_without_socket() {
+        let path = std::env::temp_dir().join("channel_test_missing.sock");
+        let result = UpstreamChannelConfig::default()
+            .connect_unix(path.to_str().unwrap())
+            .await;
+        assert!(result.is_err());
+    }
+}
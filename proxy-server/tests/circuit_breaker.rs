@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::jobworkerp::circuit_breaker::{is_backend_failure, CircuitBreaker};
+    use proxy_server::jobworkerp::meta_tool;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn unavailable() -> anyhow::Result<()> {
+        Err(tonic::Status::unavailable("connection refused").into())
+    }
+
+    #[tokio::test]
+    async fn test_circuit_opens_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(100));
+        assert!(breaker.call(async { unavailable() }).await.is_err());
+        assert!(!breaker.is_open());
+        assert!(breaker.call(async { unavailable() }).await.is_err());
+        assert!(breaker.is_open());
+
+        // fails fast without calling the backend
+        let mut called = false;
+        let error = breaker
+            .call(async {
+                called = true;
+                Ok(())
+            })
+            .await
+            .unwrap_err();
+        assert!(!called);
+        let result = meta_tool::backend_unavailable_result(&error).unwrap();
+        assert_eq!(result.is_error, Some(true));
+
+        // a probe after the open period closes the circuit if it succeeds
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(breaker.call(async { Ok(()) }).await.is_ok());
+        assert!(!breaker.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_failed_probe_reopens_circuit() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+        assert!(breaker.call(async { unavailable() }).await.is_err());
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(breaker.acquire().is_ok());
+        // the other calls fail fast while probing
+        assert!(breaker.acquire().is_err());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_do_not_open_circuit() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(30));
+        let result: anyhow::Result<()> = breaker
+            .call(async { Err(tonic::Status::invalid_argument("bad arguments").into()) })
+            .await;
+        assert!(result.is_err());
+        assert!(!breaker.is_open());
+        assert!(meta_tool::backend_unavailable_result(&result.unwrap_err()).is_none());
+
+        // disabled
+        let breaker = CircuitBreaker::new(0, Duration::from_secs(30));
+        for _ in 0..3 {
+            assert!(breaker.call(async { unavailable() }).await.is_err());
+        }
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn test_is_backend_failure() {
+        assert!(is_backend_failure(
+            &unavailable().unwrap_err().context("call failed")
+        ));
+
+        // client deadline of a long job
+        let mut timeout = tonic::Status::deadline_exceeded("timeout");
+        timeout.set_source(Arc::new(std::io::Error::from(std::io::ErrorKind::TimedOut)));
+        assert!(!is_backend_failure(&anyhow::Error::from(timeout)));
+        // timeout of the job reported by the backend
+        assert!(!is_backend_failure(&anyhow::Error::from(
+            tonic::Status::deadline_exceeded("job timeout")
+        )));
+        // local io errors (e.g. file staging)
+        assert!(!is_backend_failure(&anyhow::Error::from(
+            std::io::Error::from(std::io::ErrorKind::NotFound)
+        )));
+        assert!(!is_backend_failure(&anyhow::anyhow!("not found")));
+    }
+}
@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::client::{
+        render_result, result_payload, sample_arguments, smoke_skip_reason, validate_arguments,
+        BenchOutcome, BenchReport, OutputFormat,
+    };
+    use rmcp::model::{CallToolResult, Content};
+    use serde_json::{json, Map, Value};
+    use std::time::Duration;
+
+    #[test]
+    fn test_bench_report() {
+        let samples = (1..=100)
+            .map(|i| {
+                let outcome = match i {
+                    1..=5 => BenchOutcome::ToolError,
+                    6..=7 => BenchOutcome::Error,
+                    _ => BenchOutcome::Success,
+                };
+                (Duration::from_millis(i), outcome)
+            })
+            .collect::<Vec<_>>();
+        let report = BenchReport::new(&samples, Duration::from_secs(2), 4);
+        assert_eq!(report.requests, 100);
+        assert_eq!(report.concurrency, 4);
+        assert_eq!(report.succeeded, 93);
+        assert_eq!(report.tool_errors, 5);
+        assert_eq!(report.errors, 2);
+        assert!((report.error_rate - 0.07).abs() < 1e-9);
+        assert!((report.requests_per_sec - 50.0).abs() < 1e-9);
+        let latency = &report.latency_ms;
+        assert!((latency.min - 1.0).abs() < 1e-9);
+        assert!((latency.p50 - 50.0).abs() < 1e-9);
+        assert!((latency.p90 - 90.0).abs() < 1e-9);
+        assert!((latency.p99 - 99.0).abs() < 1e-9);
+        assert!((latency.max - 100.0).abs() < 1e-9);
+        assert!((latency.mean - 50.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bench_report_empty() {
+        let report = BenchReport::new(&[], Duration::ZERO, 1);
+        assert_eq!(report.requests, 0);
+        assert_eq!(report.error_rate, 0.0);
+        assert_eq!(report.requests_per_sec, 0.0);
+        assert_eq!(report.latency_ms.max, 0.0);
+    }
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_sample_arguments() {
+        let schema = object(json!({
+            "type": "object",
+            "properties": {
+                "settings": {
+                    "type": "object",
+                    "properties": {"timeout": {"type": "integer", "default": 30}},
+                    "required": ["timeout"]
+                },
+                "arguments": {
+                    "type": "object",
+                    "properties": {
+                        "command": {"type": "string", "examples": ["echo"]},
+                        "args": {"type": "array", "items": {"type": "string"}, "minItems": 1},
+                        "mode": {"$ref": "#/$defs/Mode"},
+                        "count": {"type": ["null", "integer"], "minimum": 1},
+                        "name": {"type": "string", "minLength": 2},
+                        "optional": {"type": "string"}
+                    },
+                    "required": ["command", "args", "mode", "count", "name"]
+                }
+            },
+            "required": ["settings", "arguments"],
+            "$defs": {"Mode": {"type": "string", "enum": ["fast", "slow"]}}
+        }));
+        let arguments = sample_arguments(&schema);
+        assert_eq!(
+            arguments,
+            json!({
+                "settings": {"timeout": 30},
+                "arguments": {
+                    "command": "echo",
+                    "args": [""],
+                    "mode": "fast",
+                    "count": 1,
+                    "name": "xx"
+                }
+            })
+        );
+        assert!(validate_arguments(&schema, &arguments).is_ok());
+        assert!(validate_arguments(&schema, &json!({"settings": {}})).is_err());
+    }
+
+    #[test]
+    fn test_sample_arguments_without_required() {
+        let schema = object(json!({"type": "object", "properties": {"a": {"type": "string"}}}));
+        assert_eq!(sample_arguments(&schema), json!({}));
+        assert_eq!(sample_arguments(&Map::new()), json!({}));
+    }
+
+    #[test]
+    fn test_render_result() {
+        let result = CallToolResult {
+            content: vec![Content::text(r#"{"exit_code":0,"stdout":"hello"}"#)],
+            is_error: Some(false),
+        };
+        assert_eq!(
+            result_payload(&result),
+            json!({"exit_code": 0, "stdout": "hello"})
+        );
+        assert_eq!(
+            render_result(&result, OutputFormat::Yaml, true).unwrap(),
+            "exit_code: 0\nstdout: hello"
+        );
+        assert_eq!(
+            render_result(&result, OutputFormat::Text, false).unwrap(),
+            r#"{"exit_code":0,"stdout":"hello"}"#
+        );
+        let full: Value =
+            serde_json::from_str(&render_result(&result, OutputFormat::Json, false).unwrap())
+                .unwrap();
+        assert_eq!(full["isError"], json!(false));
+
+        let result = CallToolResult {
+            content: vec![Content::text("a"), Content::text("b")],
+            is_error: None,
+        };
+        assert_eq!(result_payload(&result), json!(["a", "b"]));
+        assert_eq!(
+            render_result(&result, OutputFormat::Text, true).unwrap(),
+            "a\nb"
+        );
+        assert_eq!(OutputFormat::parse("YAML"), Some(OutputFormat::Yaml));
+        assert_eq!(OutputFormat::parse("xml"), None);
+    }
+
+    #[test]
+    fn test_smoke_skip_reason() {
+        assert!(smoke_skip_reason("batch_call", true).is_some());
+        assert!(smoke_skip_reason("COMMAND", false).is_none());
+        // creation tools are validated but not called
+        for name in [
+            "author_workflow",
+            "REUSABLE_WORKFLOW",
+            "runner.REUSABLE_WORKFLOW",
+        ] {
+            assert!(smoke_skip_reason(name, true).is_none(), "{}", name);
+            assert!(smoke_skip_reason(name, false).is_some(), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::compact_schema::{compact_tools, tool_chars, CompactSchemaConfig};
+    use proxy_server::config::ProxyConfig;
+    use rmcp::model::Tool;
+    use serde_json::json;
+
+    fn tool() -> Tool {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "command to run in the shell of the worker host",
+                    "examples": ["ls -l"],
+                },
+                "env": {
+                    "type": "object",
+                    "description": "environment variables",
+                    "additionalProperties": {"type": "string"},
+                },
+            },
+            "required": ["command"],
+        });
+        Tool::new(
+            "COMMAND",
+            "Run a shell command. The output is returned as text.",
+            schema.as_object().cloned().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_trim_descriptions() {
+        let config = CompactSchemaConfig {
+            enabled: true,
+            max_description_chars: 20,
+            max_field_description_chars: 10,
+            ..Default::default()
+        };
+        let mut tools = vec![tool()];
+        compact_tools(&mut tools, &config);
+        assert_eq!(tools[0].description, "Run a shell command…");
+        let command = &tools[0].input_schema["properties"]["command"];
+        assert_eq!(command["description"], "command t…");
+        assert!(command.get("examples").is_none());
+        // optional fields are kept without budgets
+        assert_eq!(
+            tools[0].input_schema["properties"]["env"]["additionalProperties"],
+            json!({"type": "string"})
+        );
+    }
+
+    #[test]
+    fn test_tool_budget() {
+        let full = tool_chars(&tool());
+        let config = CompactSchemaConfig {
+            enabled: true,
+            max_tool_chars: Some(full - 100),
+            ..Default::default()
+        };
+        let mut tools = vec![tool()];
+        let total = compact_tools(&mut tools, &config);
+        assert!(total <= full - 100);
+        // the optional field is collapsed, the required field is kept
+        assert_eq!(
+            tools[0].input_schema["properties"]["env"],
+            json!({"type": "object"})
+        );
+        assert_eq!(
+            tools[0].input_schema["properties"]["command"]["type"],
+            "string"
+        );
+    }
+
+    #[test]
+    fn test_total_budget() {
+        let full = tool_chars(&tool());
+        let config = CompactSchemaConfig {
+            enabled: true,
+            max_total_chars: Some(full * 2 - 150),
+            ..Default::default()
+        };
+        let mut tools = vec![tool(), tool()];
+        let total = compact_tools(&mut tools, &config);
+        assert!(total <= full * 2 - 150);
+        assert_eq!(total, tools.iter().map(tool_chars).sum::<usize>());
+
+        let config = ProxyConfig::from_yaml(
+            r#"
+compact_schema:
+  enabled: true
+  max_total_chars: 10000
+"#,
+        )
+        .unwrap()
+        .compact_schema;
+        assert_eq!(config.max_total_chars, Some(10000));
+        assert_eq!(config.max_description_chars, 200);
+    }
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::jobworkerp::descriptor_cache::DescriptorCache;
+
+    #[test]
+    fn test_descriptor_parsed_once_per_proto() {
+        let cache = DescriptorCache::<String>::default();
+        let mut parsed = 0;
+        for _ in 0..3 {
+            let descriptor = cache
+                .get_or_parse(1, "message Settings {}", || {
+                    parsed += 1;
+                    Ok::<_, String>(Some("Settings".to_string()))
+                })
+                .unwrap();
+            assert_eq!(descriptor.as_deref(), Some("Settings"));
+        }
+        assert_eq!(parsed, 1);
+
+        // runners without settings are cached too
+        let descriptor = cache.get_or_parse(2, "", || Ok::<_, String>(None)).unwrap();
+        assert_eq!(descriptor, None);
+        let descriptor = cache
+            .get_or_parse(2, "", || Err("parsed again".to_string()))
+            .unwrap();
+        assert_eq!(descriptor, None);
+
+        // the runner is updated with another proto
+        let descriptor = cache
+            .get_or_parse(1, "message Settings2 {}", || {
+                Ok::<_, String>(Some("Settings2".to_string()))
+            })
+            .unwrap();
+        assert_eq!(descriptor.as_deref(), Some("Settings2"));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_errors_are_not_cached() {
+        let cache = DescriptorCache::<String>::default();
+        assert!(cache
+            .get_or_parse(1, "broken", || Err::<Option<String>, _>("invalid proto"))
+            .is_err());
+        assert!(cache.is_empty());
+    }
+}
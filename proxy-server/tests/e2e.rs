@@ -0,0 +1,486 @@
+// end-to-end tests against a jobworkerp backend in a container (requires docker):
+// cargo test -p proxy-server --features e2e --test e2e
+#[cfg(all(test, feature = "e2e"))]
+mod tests {
+    use proxy_server::client::McpClient;
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::jobworkerp::JobworkerpRouterConfig;
+    use proxy_server::{ProxyBuilder, SseConfig};
+    use rmcp::model::{
+        CallToolRequestParam, CallToolResult, ClientCapabilities, ClientInfo, Implementation,
+    };
+    use rmcp::ServiceExt;
+    use serde_json::{json, Map, Value};
+    use std::process::Stdio;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use testcontainers::core::{IntoContainerPort, WaitFor};
+    use testcontainers::runners::AsyncRunner;
+    use testcontainers::{ContainerAsync, GenericImage, ImageExt};
+    use tokio_util::sync::CancellationToken;
+
+    // image of the backend (overridden by `E2E_JOBWORKERP_IMAGE`)
+    const DEFAULT_IMAGE: &str = "ghcr.io/jobworkerp-rs/jobworkerp:latest";
+    const GRPC_PORT: u16 = 9000;
+
+    struct Backend {
+        // stopped when dropped
+        _container: ContainerAsync<GenericImage>,
+        address: String,
+    }
+
+    async fn start_backend() -> Backend {
+        let image = std::env::var("E2E_JOBWORKERP_IMAGE").unwrap_or(DEFAULT_IMAGE.to_string());
+        let (name, tag) = image.rsplit_once(':').unwrap_or((&image, "latest"));
+        let container = GenericImage::new(name, tag)
+            .with_exposed_port(GRPC_PORT.tcp())
+            .with_wait_for(WaitFor::seconds(3))
+            .with_env_var("GRPC_ADDR", format!("0.0.0.0:{}", GRPC_PORT))
+            .with_env_var("SQLITE_URL", "sqlite:///tmp/jobworkerp.sqlite3")
+            .start()
+            .await
+            .expect("Failed to start the jobworkerp container");
+        let host = container.get_host().await.unwrap();
+        let port = container.get_host_port_ipv4(GRPC_PORT).await.unwrap();
+        Backend {
+            _container: container,
+            address: format!("http://{}:{}", host, port),
+        }
+    }
+
+    // serve the sse routes on a free port, returning the sse url
+    async fn start_sse_server(backend: &Backend) -> (String, CancellationToken) {
+        start_server(backend, ProxyConfig::default(), SseConfig::default()).await
+    }
+
+    async fn start_server(
+        backend: &Backend,
+        proxy_config: ProxyConfig,
+        sse_config: SseConfig,
+    ) -> (String, CancellationToken) {
+        let builder = ProxyBuilder::from_env(proxy_config).with_sse_config(sse_config);
+        start_builder(backend, builder).await
+    }
+
+    async fn start_builder(
+        backend: &Backend,
+        builder: ProxyBuilder,
+    ) -> (String, CancellationToken) {
+        let (router, ct) = builder
+            .with_jobworkerp_address(&backend.address)
+            .with_wait_for_backend_sec(60)
+            .build_axum_router()
+            .await
+            .unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/sse", listener.local_addr().unwrap());
+        let shutdown = ct.clone();
+        tokio::spawn(async move {
+            axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown.cancelled_owned())
+                .await
+        });
+        (url, ct)
+    }
+
+    fn arguments(value: Value) -> Option<Map<String, Value>> {
+        value.as_object().cloned()
+    }
+
+    fn echo(text: &str) -> Option<Map<String, Value>> {
+        arguments(json!({"settings": {}, "arguments": {"command": "echo", "args": [text]}}))
+    }
+
+    fn texts(result: &CallToolResult) -> String {
+        serde_json::to_value(&result.content)
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|c| c["text"].as_str().map(|t| t.to_string()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[tokio::test]
+    async fn test_sse_server() {
+        let backend = start_backend().await;
+        let (url, ct) = start_sse_server(&backend).await;
+        let client = McpClient::connect(&url).await.unwrap();
+
+        let tools = client.list_tools().await.unwrap();
+        assert!(tools.tools.iter().any(|t| t.name == "COMMAND"));
+        let result = client
+            .call_tool("COMMAND", echo("hello sse"))
+            .await
+            .unwrap();
+        assert_ne!(result.is_error, Some(true));
+        assert!(texts(&result).contains("hello sse"));
+
+        client.close().await.unwrap();
+        ct.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_stdio_server() {
+        let backend = start_backend().await;
+        let mut child = tokio::process::Command::new(env!("CARGO_BIN_EXE_jobworkerp-mcp-proxy"))
+            .args(["serve", "stdio", "--wait-for-backend", "60"])
+            .env("JOBWORKERP_ADDR", &backend.address)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let transport = (child.stdout.take().unwrap(), child.stdin.take().unwrap());
+        let client = ClientInfo {
+            protocol_version: Default::default(),
+            capabilities: ClientCapabilities::default(),
+            client_info: Implementation {
+                name: "e2e".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+        }
+        .serve(transport)
+        .await
+        .unwrap();
+
+        let tools = client.list_tools(Default::default()).await.unwrap();
+        assert!(tools.tools.iter().any(|t| t.name == "COMMAND"));
+        let result = client
+            .call_tool(CallToolRequestParam {
+                name: "COMMAND".into(),
+                arguments: echo("hello stdio"),
+            })
+            .await
+            .unwrap();
+        assert!(texts(&result).contains("hello stdio"));
+
+        client.cancel().await.unwrap();
+        child.kill().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_workflow_creation() {
+        let backend = start_backend().await;
+        let (url, ct) = start_sse_server(&backend).await;
+        let client = McpClient::connect(&url).await.unwrap();
+
+        let definition = json!({
+            "workflow_data": {
+                "document": {
+                    "dsl": "1.0.0",
+                    "namespace": "e2e",
+                    "name": "e2e_echo",
+                    "version": "1.0.0",
+                    "summary": "echo created by the e2e test"
+                },
+                "input": {"schema": {"document": {"type": "object"}}},
+                "do": [{"echo": {"run": {"runner": {
+                    "name": "COMMAND",
+                    "arguments": {"command": "echo", "args": ["from workflow"]}
+                }}}}]
+            }
+        });
+        let result = client
+            .call_tool("REUSABLE_WORKFLOW", arguments(definition))
+            .await
+            .unwrap();
+        assert!(texts(&result).contains("ok"));
+        let tools = client.list_tools().await.unwrap();
+        assert!(tools.tools.iter().any(|t| t.name == "e2e_echo"));
+
+        client.close().await.unwrap();
+        ct.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_cancel_on_disconnect() {
+        let backend = start_backend().await;
+        let (url, ct) = start_sse_server(&backend).await;
+        let client = Arc::new(McpClient::connect(&url).await.unwrap());
+        let sleep =
+            arguments(json!({"settings": {}, "arguments": {"command": "sleep", "args": ["30"]}}));
+        let call = tokio::spawn({
+            let client = client.clone();
+            async move { client.call_tool("COMMAND", sleep).await }
+        });
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        // the call in flight is cancelled with the session (ON_DISCONNECT=cancel by default)
+        call.abort();
+        let _ = call.await;
+        let Ok(client) = Arc::try_unwrap(client) else {
+            panic!("client still shared");
+        };
+        client.close().await.unwrap();
+
+        let observer = McpClient::connect(&url).await.unwrap();
+        let mut cancelled = false;
+        for _ in 0..20 {
+            let result = observer
+                .call_tool(
+                    "recent_results",
+                    arguments(json!({"tool": "COMMAND", "status": "failed"})),
+                )
+                .await
+                .unwrap();
+            let history: Value = serde_json::from_str(&texts(&result)).unwrap();
+            cancelled = history["calls"].as_array().unwrap().iter().any(|c| {
+                c["error"]
+                    .as_str()
+                    .is_some_and(|e| e.starts_with("cancelled"))
+            });
+            if cancelled {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        assert!(cancelled);
+
+        observer.close().await.unwrap();
+        ct.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_rest_api() {
+        let backend = start_backend().await;
+        let sse_config = SseConfig {
+            rest_api: true,
+            ..Default::default()
+        };
+        let (url, ct) = start_server(&backend, ProxyConfig::default(), sse_config).await;
+        let base = url.trim_end_matches("/sse");
+        let http = reqwest::Client::new();
+
+        let tools: Value = http
+            .get(format!("{}/tools", base))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(tools["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|t| t["name"] == "COMMAND"));
+
+        let response = http
+            .post(format!("{}/tools/COMMAND/call", base))
+            .json(
+                &json!({"settings": {}, "arguments": {"command": "echo", "args": ["hello rest"]}}),
+            )
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let result: CallToolResult = response.json().await.unwrap();
+        assert!(texts(&result).contains("hello rest"));
+
+        let response = http
+            .post(format!("{}/tools/COMMAND/call", base))
+            .json(&json!(["not", "an", "object"]))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        ct.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_rest_api_tenants() {
+        let backend = start_backend().await;
+        let proxy_config = ProxyConfig::from_yaml(&format!(
+            "tenants:\n  header: X-Tenant\n  path_segment: true\n  backends:\n    acme:\n      jobworkerp_address: {}\n",
+            &backend.address
+        ))
+        .unwrap();
+        let sse_config = SseConfig {
+            rest_api: true,
+            ..Default::default()
+        };
+        let (url, ct) = start_server(&backend, proxy_config, sse_config).await;
+        let base = url.trim_end_matches("/sse");
+        let http = reqwest::Client::new();
+
+        let response = http
+            .get(format!("{}/acme/tools", base))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let response = http
+            .get(format!("{}/tools", base))
+            .header("X-Tenant", "acme")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        // unknown tenants are rejected as the sse connections
+        let response = http
+            .get(format!("{}/globex/tools", base))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+        let response = http
+            .post(format!("{}/tools/COMMAND/call", base))
+            .header("X-Tenant", "globex")
+            .json(&json!({}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+
+        ct.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_client_token() {
+        let backend = start_backend().await;
+        let sse_config = SseConfig {
+            auth_tokens: vec!["secret".to_string()],
+            ..Default::default()
+        };
+        let (url, ct) = start_server(&backend, ProxyConfig::default(), sse_config).await;
+
+        assert!(McpClient::connect(&url).await.is_err());
+        assert!(McpClient::connect_with_token(&url, Some("wrong"))
+            .await
+            .is_err());
+        let client = McpClient::connect_with_token(&url, Some("secret"))
+            .await
+            .unwrap();
+        assert!(!client.list_tools().await.unwrap().tools.is_empty());
+        client.close().await.unwrap();
+
+        ct.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_web_ui() {
+        let backend = start_backend().await;
+        let sse_config = SseConfig {
+            web_ui: true,
+            ..Default::default()
+        };
+        let proxy_config = ProxyConfig::from_yaml(
+            r#"
+rbac:
+  default_role: operator
+  roles:
+    operator:
+      tools: ["*"]
+      admin: true
+"#,
+        )
+        .unwrap();
+        let (url, ct) = start_server(&backend, proxy_config, sse_config).await;
+        let client = McpClient::connect(&url).await.unwrap();
+        let echo =
+            arguments(json!({"settings": {}, "arguments": {"command": "echo", "args": ["ui"]}}));
+        client.call_tool("COMMAND", echo).await.unwrap();
+        client.close().await.unwrap();
+
+        let base = url.trim_end_matches("/sse");
+        let http = reqwest::Client::new();
+        let page = http
+            .get(format!("{}/ui", base))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(page.contains("Recent calls"));
+        let tools: Value = http
+            .get(format!("{}/ui/tools.json", base))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(tools["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|t| t["name"] == "COMMAND"));
+        let calls: Value = http
+            .get(format!("{}/ui/calls.json", base))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(calls["calls"][0]["tool_name"], "COMMAND");
+        assert_eq!(calls["calls"][0]["status"], "succeeded");
+
+        ct.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_alias_permission() {
+        let backend = start_backend().await;
+        let proxy_config = ProxyConfig::from_yaml(
+            r#"
+tool_aliases:
+  run_shell:
+    tool: COMMAND
+rbac:
+  default_role: guest
+  roles:
+    guest:
+      tools: ["run_shell"]
+"#,
+        )
+        .unwrap();
+        let (url, ct) = start_server(&backend, proxy_config, SseConfig::default()).await;
+        let client = McpClient::connect(&url).await.unwrap();
+
+        // the alias does not grant the aliased tool
+        let error = client
+            .call_tool("run_shell", echo("via alias"))
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", error).contains("Tool not allowed: COMMAND"));
+
+        client.close().await.unwrap();
+        ct.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_kind_prefixed_name_permission() {
+        let backend = start_backend().await;
+        let proxy_config = ProxyConfig::from_yaml(
+            r#"
+rbac:
+  default_role: guest
+  roles:
+    guest:
+      tools: ["runner.*", "HTTP_REQUEST"]
+"#,
+        )
+        .unwrap();
+        let config = JobworkerpRouterConfig {
+            kind_prefixed_names: true,
+            ..JobworkerpRouterConfig::from_env(proxy_config)
+        };
+        let (url, ct) = start_builder(&backend, ProxyBuilder::new(config)).await;
+        let client = McpClient::connect(&url).await.unwrap();
+
+        // the backend name of the prefixed tool must also be allowed
+        let error = client
+            .call_tool("runner.COMMAND", echo("via prefix"))
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", error).contains("Tool not allowed: COMMAND"));
+
+        client.close().await.unwrap();
+        ct.cancel();
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::embedded_backend::{EmbeddedBackendArgs, EmbeddedBackendConfig};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_env_vars() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+embedded_backend:
+  grpc_addr: 0.0.0.0:9010
+  sqlite_path: /var/lib/jobworkerp/db.sqlite3
+  worker_concurrency: 8
+  channels:
+    io: 4
+    gpu: 1
+  disabled_runners: [DOCKER]
+"#,
+        )
+        .unwrap()
+        .embedded_backend;
+        assert_eq!(config.disabled_runners, vec!["DOCKER"]);
+        assert_eq!(
+            config.env_vars(),
+            vec![
+                ("GRPC_ADDR", "0.0.0.0:9010".to_string()),
+                (
+                    "SQLITE_URL",
+                    "sqlite:///var/lib/jobworkerp/db.sqlite3?mode=rwc".to_string()
+                ),
+                ("WORKER_DEFAULT_CONCURRENCY", "8".to_string()),
+                ("WORKER_CHANNELS", "gpu,io".to_string()),
+                ("WORKER_CHANNEL_CONCURRENCIES", "1,4".to_string()),
+            ]
+        );
+        assert_eq!(
+            config.jobworkerp_address().as_deref(),
+            Some("http://127.0.0.1:9010")
+        );
+        assert!(EmbeddedBackendConfig::default().env_vars().is_empty());
+        assert_eq!(EmbeddedBackendConfig::default().jobworkerp_address(), None);
+    }
+
+    #[test]
+    fn test_args_override_config() {
+        let mut config = EmbeddedBackendConfig {
+            grpc_addr: Some("127.0.0.1:9010".to_string()),
+            worker_concurrency: Some(8),
+            ..Default::default()
+        };
+        EmbeddedBackendArgs {
+            sqlite_path: Some(PathBuf::from("/tmp/jobworkerp.sqlite3")),
+            worker_concurrency: Some(2),
+            channels: vec![("io".to_string(), 4)],
+            disabled_runners: vec!["PYTHON_COMMAND".to_string()],
+            ..Default::default()
+        }
+        .apply(&mut config);
+        assert_eq!(config.grpc_addr.as_deref(), Some("127.0.0.1:9010"));
+        assert_eq!(
+            config.sqlite_path,
+            Some(PathBuf::from("/tmp/jobworkerp.sqlite3"))
+        );
+        assert_eq!(config.worker_concurrency, Some(2));
+        assert_eq!(config.channels.get("io"), Some(&4));
+        assert_eq!(config.disabled_runners, vec!["PYTHON_COMMAND"]);
+    }
+
+    #[test]
+    fn test_bootstrap_dir() {
+        let dir = std::env::temp_dir().join(format!("bootstrap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = EmbeddedBackendConfig {
+            bootstrap_dir: Some(dir.clone()),
+            ..Default::default()
+        };
+        assert_eq!(config.bootstrap_dir(), Some(dir.clone()));
+        std::fs::remove_dir(&dir).unwrap();
+        // a missing directory is not registered
+        assert_eq!(config.bootstrap_dir(), None);
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::enum_schema::rewrite_integer_enums;
+    use serde_json::{json, Map, Value};
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_rewrite_enum_with_names() {
+        let mut schema = object(json!({
+            "type": "object",
+            "properties": {
+                "method": {
+                    "type": "integer",
+                    "description": "http method",
+                    "enum": [0, 1],
+                    "enumNames": ["GET", "POST"],
+                    "default": 1
+                },
+                "url": {"type": "string"}
+            }
+        }));
+        let mapping = rewrite_integer_enums(&mut schema);
+        assert!(!mapping.is_empty());
+        assert_eq!(
+            schema["properties"]["method"],
+            json!({
+                "type": "string",
+                "enum": ["GET", "POST"],
+                "description": "http method",
+                "default": "POST"
+            })
+        );
+        assert_eq!(schema["properties"]["url"], json!({"type": "string"}));
+
+        let mut args = object(json!({"method": "POST", "url": "http://localhost"}));
+        mapping.to_integers(&mut args);
+        assert_eq!(args["method"], json!(1));
+        assert_eq!(args["url"], json!("http://localhost"));
+    }
+
+    #[test]
+    fn test_rewrite_one_of_const_in_ref_and_array() {
+        let mut schema = object(json!({
+            "type": "object",
+            "properties": {
+                "levels": {"type": "array", "items": {"$ref": "#/$defs/Level"}},
+                "nested": {
+                    "type": "object",
+                    "properties": {"level": {"$ref": "#/$defs/Level"}}
+                }
+            },
+            "$defs": {
+                "Level": {
+                    "oneOf": [
+                        {"const": 0, "title": "LOW"},
+                        {"const": 1, "title": "HIGH"}
+                    ]
+                }
+            }
+        }));
+        let mapping = rewrite_integer_enums(&mut schema);
+        assert_eq!(
+            schema["$defs"]["Level"],
+            json!({"type": "string", "enum": ["LOW", "HIGH"]})
+        );
+
+        let mut args = object(json!({
+            "levels": ["HIGH", "LOW", 1],
+            "nested": {"level": "HIGH"}
+        }));
+        mapping.to_integers(&mut args);
+        assert_eq!(args["levels"], json!([1, 0, 1]));
+        assert_eq!(args["nested"]["level"], json!(1));
+    }
+
+    #[test]
+    fn test_keep_schema_without_names() {
+        let original = json!({
+            "type": "object",
+            "properties": {
+                "code": {"type": "integer", "enum": [0, 1, 2]},
+                "mode": {"type": "string", "enum": ["a", "b"]},
+                "value": {"oneOf": [{"type": "string"}, {"type": "integer"}]}
+            }
+        });
+        let mut schema = object(original.clone());
+        let mapping = rewrite_integer_enums(&mut schema);
+        assert!(mapping.is_empty());
+        assert_eq!(Value::Object(schema), original);
+
+        // unknown names are passed through as is
+        let mut args = object(json!({"code": "UNKNOWN"}));
+        mapping.to_integers(&mut args);
+        assert_eq!(args["code"], json!("UNKNOWN"));
+    }
+}
@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use axum::{extract::State, http::HeaderMap, routing::post, Json, Router};
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::jobworkerp::error_reporter::{
+        self, CapturedError, ErrorKind, ErrorReport, ErrorReporter, ErrorReportingConfig,
+    };
+    use rmcp::model::{CallToolResult, Content};
+    use rmcp::Error as McpError;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_error_reporting_config() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+error_reporting:
+  webhook_url: https://hooks.example.com/errors
+  headers:
+    Authorization: Bearer ${env:ERROR_WEBHOOK_TOKEN}
+  environment: production
+  include_arguments: false
+"#,
+        )
+        .unwrap()
+        .error_reporting;
+        assert_eq!(
+            config.webhook_url.as_deref(),
+            Some("https://hooks.example.com/errors")
+        );
+        assert_eq!(config.headers.len(), 1);
+        assert_eq!(config.environment.as_deref(), Some("production"));
+        assert!(!config.include_arguments);
+        assert_eq!(config.dedup_window_sec, 60);
+
+        let config = ProxyConfig::from_yaml("{}").unwrap().error_reporting;
+        assert!(config.webhook_url.is_none());
+        assert!(config.include_arguments);
+        assert!(ErrorReporter::new(&config).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_report_of_call() {
+        let arguments = json!({"command": "ls"}).as_object().cloned();
+        let internal = Err(McpError::internal_error(
+            "Failed to enqueue job: boom",
+            None,
+        ));
+
+        let report =
+            ErrorReport::of_call("COMMAND", "s1", arguments.as_ref(), &internal, None).unwrap();
+        assert_eq!(report.kind, ErrorKind::InternalError);
+        assert_eq!(report.message, "Failed to enqueue job: boom");
+        assert_eq!(report.arguments, Some(json!({"command": "ls"})));
+        assert!(report.error_chain.is_empty());
+
+        let captured = CapturedError {
+            kind: ErrorKind::Panic,
+            error_chain: vec!["index out of bounds".to_string()],
+        };
+        let report =
+            ErrorReport::of_call("COMMAND", "s1", None, &internal, Some(captured)).unwrap();
+        assert_eq!(report.kind, ErrorKind::Panic);
+        assert_eq!(report.error_chain, vec!["index out of bounds"]);
+        assert!(report.arguments.is_none());
+
+        // invalid calls and error results are not reported
+        let invalid = Err(McpError::invalid_params("missing command", None));
+        assert!(ErrorReport::of_call("COMMAND", "s1", None, &invalid, None).is_none());
+        let tool_error = Ok(CallToolResult {
+            content: vec![Content::text("exit code 1")],
+            is_error: Some(true),
+        });
+        assert!(ErrorReport::of_call("COMMAND", "s1", None, &tool_error, None).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_capture() {
+        let (output, captured) = error_reporter::capture(async {
+            let error = anyhow::anyhow!("connection refused").context("Failed to enqueue job");
+            error_reporter::note_error(&error);
+            1
+        })
+        .await;
+        assert_eq!(output, 1);
+        let captured = captured.unwrap();
+        assert_eq!(captured.kind, ErrorKind::InternalError);
+        assert_eq!(
+            captured.error_chain,
+            vec!["Failed to enqueue job", "connection refused"]
+        );
+
+        let (_, captured) = error_reporter::capture(async {}).await;
+        assert!(captured.is_none());
+        // ignored outside of calls
+        error_reporter::note_panic("outside");
+    }
+
+    async fn webhook(
+        State(tx): State<mpsc::UnboundedSender<(Option<String>, Value)>>,
+        headers: HeaderMap,
+        Json(report): Json<Value>,
+    ) {
+        let authorization = headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        let _ = tx.send((authorization, report));
+    }
+
+    #[tokio::test]
+    async fn test_send_report() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let router = Router::new().route("/errors", post(webhook)).with_state(tx);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/errors", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let config = ErrorReportingConfig {
+            webhook_url: Some(url),
+            headers: [("Authorization".to_string(), "Bearer secret".to_string())].into(),
+            environment: Some("test".to_string()),
+            ..Default::default()
+        };
+        let reporter = ErrorReporter::new(&config).unwrap().unwrap();
+        let result = Err(McpError::internal_error(
+            "Tool call failed unexpectedly",
+            None,
+        ));
+        let report = |tool: &str| ErrorReport::of_call(tool, "s1", None, &result, None).unwrap();
+        reporter.report(report("COMMAND"));
+        // the same error of the tool is sent once in the dedup window
+        reporter.report(report("COMMAND"));
+        reporter.report(report("HTTP_REQUEST"));
+
+        let mut received = vec![];
+        for _ in 0..2 {
+            let (authorization, report) = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(authorization.as_deref(), Some("Bearer secret"));
+            assert_eq!(report["environment"], "test");
+            assert_eq!(report["kind"], "internal_error");
+            received.push(report["tool_name"].as_str().unwrap().to_string());
+        }
+        assert_eq!(received, vec!["COMMAND", "HTTP_REQUEST"]);
+        assert!(tokio::time::timeout(Duration::from_millis(300), rx.recv())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::jobworkerp::file_staging::{self, FileStagingConfig};
+    use std::collections::BTreeMap;
+
+    fn config(name: &str) -> FileStagingConfig {
+        let dir = std::env::temp_dir().join(format!("file-staging-test-{}", name));
+        FileStagingConfig {
+            dir: Some(dir.to_string_lossy().to_string()),
+            max_file_bytes: 16,
+            tools: BTreeMap::from([
+                ("COMMAND".to_string(), vec!["arguments.args".to_string()]),
+                ("*".to_string(), vec!["input".to_string()]),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stage_arguments() {
+        let config = config("stage");
+        assert_eq!(
+            config.arguments_of("COMMAND"),
+            vec!["arguments.args", "input"]
+        );
+        let mut arguments = serde_json::json!({
+            "arguments": {
+                "command": "cat",
+                "args": [
+                    "-n",
+                    {"name": "../hello.txt", "data": "aGVsbG8="},
+                    {"uri": "data:text/plain,world"},
+                ],
+            },
+            "input": {"text": "x", "name": "input.txt"},
+        });
+        let staged = config
+            .stage_arguments("COMMAND", arguments.as_object_mut().unwrap(), |_| None)
+            .unwrap();
+        assert_eq!(staged.len(), 3);
+        // paths are kept as they are
+        assert_eq!(arguments["arguments"]["args"][0], "-n");
+        let path = arguments["arguments"]["args"][1].as_str().unwrap();
+        assert!(path.starts_with(config.dir.as_ref().unwrap()));
+        assert!(path.ends_with("/hello.txt"));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "hello");
+        let path = arguments["arguments"]["args"][2].as_str().unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "world");
+        let path = arguments["input"].as_str().unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "x");
+        for path in staged {
+            std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_stage_arguments_errors() {
+        let config = config("errors");
+        let stage = |arguments: serde_json::Value| {
+            let mut arguments = arguments;
+            config.stage_arguments("TOOL", arguments.as_object_mut().unwrap(), |uri| {
+                (uri == "job://1/log").then(|| b"log".to_vec())
+            })
+        };
+        assert!(stage(serde_json::json!({"input": {"data": "not base64!"}})).is_err());
+        assert!(stage(serde_json::json!({"input": {"text": "more than 16 bytes"}})).is_err());
+        assert!(stage(serde_json::json!({"input": {"uri": "job://2/log"}})).is_err());
+        let staged = stage(serde_json::json!({"input": {"uri": "job://1/log"}})).unwrap();
+        assert_eq!(std::fs::read_to_string(&staged[0]).unwrap(), "log");
+        assert!(staged[0].ends_with("file"));
+        std::fs::remove_dir_all(staged[0].parent().unwrap()).unwrap();
+        // not a file object
+        assert!(stage(serde_json::json!({"input": {"path": "/tmp/a"}}))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_schema() {
+        let config = config("schema");
+        let mut schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "arguments": {
+                    "type": "object",
+                    "properties": {
+                        "args": {"type": "array", "items": {"type": "string"}, "description": "arguments"},
+                    },
+                },
+            },
+        });
+        config.rewrite_schema("COMMAND", schema.as_object_mut().unwrap());
+        let items = &schema["properties"]["arguments"]["properties"]["args"]["items"];
+        assert_eq!(items["anyOf"][0], serde_json::json!({"type": "string"}));
+        assert_eq!(items["anyOf"][1], file_staging::file_schema());
+        // no such argument
+        let mut schema = serde_json::json!({"type": "object", "properties": {}});
+        config.rewrite_schema("OTHER", schema.as_object_mut().unwrap());
+        assert_eq!(
+            schema,
+            serde_json::json!({"type": "object", "properties": {}})
+        );
+    }
+}
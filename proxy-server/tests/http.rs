@@ -0,0 +1,285 @@
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use axum::routing::{get, post};
+    use axum::{Extension, Router};
+    use proxy_server::auth::{AuthIdentity, Authenticator};
+    use proxy_server::http;
+    use proxy_server::jobworkerp::session::SessionRegistry;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tower::ServiceExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn ok_router(path: &str) -> Router {
+        Router::new().route(path, get(|| async { "ok" }).post(|| async { "ok" }))
+    }
+
+    async fn body_text(response: axum::response::Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_join_path() {
+        assert_eq!(http::join_path("", "/sse"), "/sse");
+        assert_eq!(http::join_path("/", "sse"), "/sse");
+        assert_eq!(http::join_path("/", "/"), "/");
+        assert_eq!(http::join_path("/mcp/", "/sse"), "/mcp/sse");
+        assert_eq!(http::join_path("/mcp//", "sse"), "/mcp/sse");
+        assert_eq!(http::join_path("mcp", "/message"), "/mcp/message");
+
+        let router = ok_router(&http::join_path("/mcp/", "/sse"));
+        let response = router
+            .clone()
+            .oneshot(Request::get("/mcp/sse").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = router
+            .oneshot(Request::get("/sse").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_parse_bind_addresses() {
+        let addresses = http::parse_bind_addresses("127.0.0.1:8000, [::1]:8001,").unwrap();
+        assert_eq!(
+            addresses,
+            vec![
+                "127.0.0.1:8000".parse().unwrap(),
+                "[::1]:8001".parse().unwrap()
+            ]
+        );
+        // host names are not resolved
+        assert!(http::parse_bind_addresses("127.0.0.1:8000,localhost:8000").is_err());
+        assert!(http::parse_bind_addresses("127.0.0.1").is_err());
+        assert!(http::parse_bind_addresses(" , ").is_err());
+
+        // the router is served on each address
+        let router = ok_router("/sse");
+        let mut served = vec![];
+        for address in http::parse_bind_addresses("127.0.0.1:0,127.0.0.1:0").unwrap() {
+            let listener = tokio::net::TcpListener::bind(address).await.unwrap();
+            served.push(listener.local_addr().unwrap());
+            let router = router.clone();
+            tokio::spawn(async move { axum::serve(listener, router).await });
+        }
+        assert_ne!(served[0], served[1]);
+        for address in served {
+            let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+            stream
+                .write_all(b"GET /sse HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        }
+    }
+
+    fn preflight(origin: &str) -> Request<Body> {
+        Request::options("/sse")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "authorization")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    fn allowed_origin(response: &axum::response::Response) -> Option<&str> {
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .map(|v| v.to_str().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_cors_layer() {
+        let cors = http::cors_layer("https://a.example, https://b.example", None, true).unwrap();
+        let router = ok_router("/sse").layer(cors);
+        let response = router
+            .clone()
+            .oneshot(preflight("https://b.example"))
+            .await
+            .unwrap();
+        assert_eq!(allowed_origin(&response), Some("https://b.example"));
+        assert_eq!(
+            response
+                .headers()
+                .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+                .unwrap(),
+            "true"
+        );
+        // request headers are reflected with credentials
+        assert_eq!(
+            response
+                .headers()
+                .get(header::ACCESS_CONTROL_ALLOW_HEADERS)
+                .unwrap(),
+            "authorization"
+        );
+        let response = router
+            .oneshot(preflight("https://c.example"))
+            .await
+            .unwrap();
+        assert_eq!(allowed_origin(&response), None);
+
+        // the wildcard reflects the origin with credentials
+        let router = ok_router("/sse").layer(http::cors_layer("*", None, true).unwrap());
+        let response = router
+            .oneshot(preflight("https://c.example"))
+            .await
+            .unwrap();
+        assert_eq!(allowed_origin(&response), Some("https://c.example"));
+        let router = ok_router("/sse").layer(http::cors_layer("*", None, false).unwrap());
+        let response = router
+            .oneshot(preflight("https://c.example"))
+            .await
+            .unwrap();
+        assert_eq!(allowed_origin(&response), Some("*"));
+
+        assert!(http::cors_layer("https://a.example\u{1}", None, false).is_err());
+        // invalid origins are not hidden by the wildcard
+        assert!(http::cors_layer("*, https://a.example\u{1}", None, true).is_err());
+        assert!(http::cors_layer("*", Some("x-ok, bad header"), false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_access_log() {
+        let buffer = SharedBuffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(move || writer.clone()),
+        );
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let router = Router::new()
+            .route("/message", post(|| async { StatusCode::ACCEPTED }))
+            .layer(axum::middleware::from_fn(|req, next| {
+                http::access_log(1.0, req, next)
+            }));
+        let response = router
+            .oneshot(
+                Request::post("/message?sessionId=s1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let logs = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("POST /message 202"), "{}", logs);
+        assert!(logs.contains("session_id=s1"), "{}", logs);
+
+        // not sampled
+        buffer.0.lock().unwrap().clear();
+        let router = ok_router("/sse").layer(axum::middleware::from_fn(|req, next| {
+            http::access_log(0.0, req, next)
+        }));
+        let response = router
+            .oneshot(Request::get("/sse").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(buffer.0.lock().unwrap().is_empty());
+    }
+
+    fn request(authorization: Option<&str>) -> Request<Body> {
+        let mut request = Request::get("/whoami");
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        request.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_auth() {
+        let authenticator = Arc::new(Authenticator::new(vec!["secret".to_string()], None));
+        let router = Router::new()
+            .route(
+                "/whoami",
+                get(|Extension(identity): Extension<AuthIdentity>| async move { identity.subject }),
+            )
+            .layer(axum::middleware::from_fn(move |req, next| {
+                http::auth(authenticator.clone(), req, next)
+            }));
+
+        for authorization in [
+            None,
+            Some("Bearer wrong"),
+            Some("Basic secret"),
+            Some("secret"),
+        ] {
+            let response = router
+                .clone()
+                .oneshot(request(authorization))
+                .await
+                .unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::UNAUTHORIZED,
+                "{:?}",
+                authorization
+            );
+            assert_eq!(
+                response.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+                "Bearer"
+            );
+        }
+        for authorization in ["Bearer secret", "bearer  secret "] {
+            let response = router
+                .clone()
+                .oneshot(request(Some(authorization)))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                body_text(response).await,
+                Authenticator::static_token_subject("secret")
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_without_rbac() {
+        let sessions = Arc::new(SessionRegistry::default());
+        let status = http::list_sessions(sessions, None, None).await.unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_logging_settings_without_rbac() {
+        let identity = AuthIdentity {
+            subject: "alice".to_string(),
+            scopes: vec!["admin".to_string()],
+        };
+        for identity in [None, Some(Extension(identity))] {
+            let (status, _) = http::logging_settings(None, identity, None)
+                .await
+                .unwrap_err();
+            assert_eq!(status, StatusCode::FORBIDDEN);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::auth::AuthIdentity;
+    use proxy_server::jobworkerp::idempotency::{self, Claim, IdempotencyCache};
+    use proxy_server::jobworkerp::session::SessionInfo;
+    use rmcp::model::{CallToolResult, Content};
+    use rmcp::Error as McpError;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn result(text: &str) -> CallToolResult {
+        CallToolResult {
+            content: vec![Content::text(text)],
+            is_error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_returns_first_result() {
+        let cache = Arc::new(IdempotencyCache::new(Duration::from_secs(60)));
+        let Claim::Run(guard) = cache.claim("k1") else {
+            panic!("first call must run");
+        };
+        let Claim::Wait(rx) = cache.claim("k1") else {
+            panic!("duplicate of a running call must wait");
+        };
+        let waiter = tokio::spawn(idempotency::wait_result(rx));
+        guard.complete(Ok(result("first"))).unwrap();
+
+        let waited = waiter.await.unwrap().unwrap();
+        assert_eq!(waited.content, result("first").content);
+        assert!(
+            matches!(cache.claim("k1"), Claim::Done(r) if r.content == result("first").content)
+        );
+        assert!(matches!(cache.claim("k2"), Claim::Run(_)));
+    }
+
+    #[tokio::test]
+    async fn test_failed_or_aborted_call_can_be_retried() {
+        let cache = Arc::new(IdempotencyCache::new(Duration::from_secs(60)));
+        let Claim::Run(guard) = cache.claim("k1") else {
+            panic!("first call must run");
+        };
+        assert!(guard
+            .complete(Err(McpError::internal_error("failed", None)))
+            .is_err());
+        let Claim::Run(guard) = cache.claim("k1") else {
+            panic!("failed call must be retried");
+        };
+        let Claim::Wait(rx) = cache.claim("k1") else {
+            panic!("duplicate of a running call must wait");
+        };
+        drop(guard);
+        assert!(idempotency::wait_result(rx).await.is_err());
+        assert!(matches!(cache.claim("k1"), Claim::Run(_)));
+    }
+
+    #[test]
+    fn test_zero_window_is_disabled() {
+        assert!(!IdempotencyCache::new(Duration::ZERO).is_enabled());
+        assert!(IdempotencyCache::default().is_enabled());
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let session = |id: &str, subject: Option<&str>| SessionInfo {
+            id: id.to_string(),
+            identity: subject.map(|s| AuthIdentity {
+                subject: s.to_string(),
+                scopes: vec![],
+            }),
+            ..Default::default()
+        };
+        let key = |tenant: Option<&str>, session: &SessionInfo| {
+            idempotency::cache_key(tenant, session, "COMMAND", "1")
+        };
+
+        // the same subject in any session
+        assert_eq!(
+            key(None, &session("s1", Some("alice"))),
+            key(None, &session("s2", Some("alice")))
+        );
+        // anonymous sessions do not share results
+        assert_ne!(
+            key(None, &session("s1", None)),
+            key(None, &session("s2", None))
+        );
+        assert_ne!(
+            key(None, &session("s1", None)),
+            key(None, &session("s2", Some("s1")))
+        );
+        // nor the tenants
+        assert_ne!(
+            key(Some("a"), &session("s1", Some("alice"))),
+            key(Some("b"), &session("s1", Some("alice")))
+        );
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use futures::future::BoxFuture;
+    use proxy_server::interceptor::{CallInterceptor, CallInterceptors, ToolCall};
+    use rmcp::model::{CallToolResult, Content};
+    use rmcp::Error as McpError;
+    use std::sync::{Arc, Mutex};
+
+    // records the hooks and rejects the calls of the tool `denied`
+    struct Recorder {
+        name: &'static str,
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Recorder {
+        fn record(&self, event: &str) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{}:{}", self.name, event));
+        }
+    }
+
+    impl CallInterceptor for Recorder {
+        fn pre_call<'a>(&'a self, call: &'a mut ToolCall) -> BoxFuture<'a, Result<(), McpError>> {
+            Box::pin(async move {
+                self.record("pre");
+                if call.tool_name == "denied" {
+                    return Err(McpError::invalid_request("quota exceeded", None));
+                }
+                call.metadata
+                    .insert(format!("x-{}", self.name), "1".to_string());
+                Ok(())
+            })
+        }
+
+        fn post_call<'a>(
+            &'a self,
+            _call: &'a ToolCall,
+            result: &'a mut CallToolResult,
+        ) -> BoxFuture<'a, ()> {
+            Box::pin(async move {
+                self.record("post");
+                result.content.push(Content::text(self.name));
+            })
+        }
+
+        fn on_error<'a>(&'a self, _call: &'a ToolCall, _error: &'a McpError) -> BoxFuture<'a, ()> {
+            Box::pin(async move { self.record("error") })
+        }
+    }
+
+    fn call(tool_name: &str) -> ToolCall {
+        ToolCall {
+            tool_name: tool_name.to_string(),
+            target: "COMMAND".to_string(),
+            arguments: Default::default(),
+            metadata: Default::default(),
+            session_id: "s1".to_string(),
+            subject: None,
+        }
+    }
+
+    fn result(content: Vec<Content>) -> CallToolResult {
+        CallToolResult {
+            content,
+            is_error: None,
+        }
+    }
+
+    fn interceptors(events: &Arc<Mutex<Vec<String>>>) -> CallInterceptors {
+        let mut interceptors = CallInterceptors::default();
+        for name in ["a", "b"] {
+            interceptors.register(Arc::new(Recorder {
+                name,
+                events: events.clone(),
+            }));
+        }
+        interceptors
+    }
+
+    #[tokio::test]
+    async fn test_intercept_call() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let result = interceptors(&events)
+            .intercept(call("COMMAND"), |call| async move {
+                // modified by pre_call
+                assert_eq!(call.metadata.len(), 2);
+                Ok(result(vec![]))
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.content.len(), 2);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["a:pre", "b:pre", "b:post", "a:post"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_intercept_rejected_and_failed_calls() {
+        let events = Arc::new(Mutex::new(vec![]));
+        let result = interceptors(&events)
+            .intercept(call("denied"), |_| async { Ok(result(vec![])) })
+            .await;
+        // rejected calls are not run
+        assert!(result.is_err());
+        assert_eq!(*events.lock().unwrap(), vec!["a:pre", "b:error", "a:error"]);
+
+        events.lock().unwrap().clear();
+        let result = interceptors(&events)
+            .intercept(call("COMMAND"), |_| async {
+                Err(McpError::internal_error("failed", None))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["a:pre", "b:pre", "b:error", "a:error"]
+        );
+    }
+}
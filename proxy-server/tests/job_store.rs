@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod tests {
+    use jobworkerp_client::jobworkerp::data::RunnerType;
+    use proxy_server::auth::AuthIdentity;
+    use proxy_server::jobworkerp::job_store::{JobFilter, JobStatus, JobStore, RunningJobGuard};
+    use proxy_server::jobworkerp::meta_tool;
+    use proxy_server::jobworkerp::session::SessionInfo;
+    use proxy_server::result_conversion::OutputFormat;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_log_uri_round_trip() {
+        let uri = JobStore::log_uri(42);
+        assert_eq!(uri, "job://42/log");
+        assert_eq!(JobStore::parse_log_uri(&uri), Some(42));
+        assert_eq!(JobStore::parse_log_uri("job://abc/log"), None);
+        assert_eq!(JobStore::parse_log_uri("job://42"), None);
+    }
+
+    #[test]
+    fn test_parse_job_uri() {
+        assert_eq!(JobStore::status_uri(7), "job://7");
+        assert_eq!(JobStore::parse_status_uri("job://7"), Some(7));
+        assert_eq!(JobStore::parse_status_uri("job://7/log"), None);
+        assert_eq!(JobStore::parse_job_uri("job://7"), Some(7));
+        assert_eq!(JobStore::parse_job_uri("job://7/log"), Some(7));
+        assert_eq!(JobStore::parse_job_uri("runner://7"), None);
+    }
+
+    #[tokio::test]
+    async fn test_updates_are_broadcast() {
+        let store = JobStore::default();
+        let mut updates = store.subscribe_updates();
+        let id = store.start("COMMAND");
+        store.finish(id, Ok(&serde_json::json!({})));
+        assert_eq!(updates.recv().await.unwrap(), id);
+        assert_eq!(updates.recv().await.unwrap(), id);
+    }
+
+    #[test]
+    fn test_finish_and_render_command_output() {
+        let store = JobStore::default();
+        let id = store.start("COMMAND");
+        assert_eq!(store.get(id).unwrap().status, JobStatus::Running);
+
+        let output = serde_json::json!({"exit_code": 0, "stdout": "hello", "stderr": ""});
+        store.finish(id, Ok(&output));
+        let job = store.get(id).unwrap();
+        assert_eq!(job.status, JobStatus::Succeeded);
+        let log = job.render_log();
+        assert!(log.contains("--- stdout ---\nhello"));
+        assert!(log.contains("exit_code: 0"));
+    }
+
+    #[tokio::test]
+    async fn test_append_stdout() {
+        let store = JobStore::default();
+        let id = store.start("COMMAND");
+        let mut updates = store.subscribe_updates();
+        store.append_stdout(id, "line 1\n");
+        store.append_stdout(id, "");
+        store.append_stdout(id, "line 2");
+        assert_eq!(updates.recv().await.unwrap(), id);
+        assert_eq!(updates.recv().await.unwrap(), id);
+        assert!(updates.try_recv().is_err());
+        let log = store.get(id).unwrap().render_log();
+        assert!(log.contains("--- stdout (live) ---\nline 1\nline 2\n"));
+
+        // the tail is kept
+        store.append_stdout(id, &"x".repeat(JobStore::MAX_LIVE_STDOUT_BYTES));
+        let job = store.get(id).unwrap();
+        assert_eq!(job.live_stdout.len(), JobStore::MAX_LIVE_STDOUT_BYTES);
+        assert!(job.live_stdout.starts_with('x'));
+
+        store.finish(id, Ok(&serde_json::json!({"stdout": "line 1\nline 2"})));
+        let job = store.get(id).unwrap();
+        assert!(job.live_stdout.is_empty());
+        assert!(!job.render_log().contains("(live)"));
+        // not appended after the job finished
+        store.append_stdout(id, "late");
+        assert!(store.get(id).unwrap().live_stdout.is_empty());
+    }
+
+    #[test]
+    fn test_capacity_evicts_oldest() {
+        let store = JobStore::new(2);
+        let first = store.start("a");
+        store.finish(first, Err("failed".to_string()));
+        let second = store.start("b");
+        let third = store.start("c");
+        assert!(store.get(first).is_none());
+        assert_eq!(
+            store.list().iter().map(|j| j.id).collect::<Vec<_>>(),
+            vec![third, second]
+        );
+    }
+
+    #[test]
+    fn test_running_job_guard() {
+        let store = Arc::new(JobStore::default());
+        let aborted = store.start("a");
+        drop(RunningJobGuard::new(store.clone(), aborted));
+        let job = store.get(aborted).unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.error.unwrap().starts_with("cancelled"));
+
+        let finished = store.start("b");
+        let guard = RunningJobGuard::new(store.clone(), finished);
+        store.finish(finished, Ok(&serde_json::json!({})));
+        drop(guard);
+        assert_eq!(store.get(finished).unwrap().status, JobStatus::Succeeded);
+    }
+
+    #[tokio::test]
+    async fn test_running_job_guard_abort() {
+        let store = Arc::new(JobStore::default());
+        let aborted = store.start("a");
+        let handle = tokio::spawn(std::future::pending::<()>());
+        drop(RunningJobGuard::new(store.clone(), aborted).with_abort(handle.abort_handle()));
+        assert!(handle.await.unwrap_err().is_cancelled());
+        assert_eq!(store.get(aborted).unwrap().status, JobStatus::Failed);
+
+        // disarmed guards keep the job running
+        let detached = store.start("b");
+        let handle = tokio::spawn(async { 1 });
+        RunningJobGuard::new(store.clone(), detached)
+            .with_abort(handle.abort_handle())
+            .disarm();
+        assert_eq!(handle.await.unwrap(), 1);
+        assert_eq!(store.get(detached).unwrap().status, JobStatus::Running);
+    }
+
+    #[tokio::test]
+    async fn test_wait_finished() {
+        let store = Arc::new(JobStore::default());
+        let id = store.start("a");
+        let running = store.wait_finished(id, Duration::from_millis(10)).await;
+        assert_eq!(running.unwrap().status, JobStatus::Running);
+
+        let finisher = store.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            finisher.finish(id, Ok(&serde_json::json!({"ok": true})));
+        });
+        let finished = store
+            .wait_finished(id, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(finished.status, JobStatus::Succeeded);
+        assert!(store
+            .wait_finished(id + 1, Duration::from_millis(10))
+            .await
+            .is_none());
+    }
+
+    #[test]
+    fn test_output_options() {
+        let store = JobStore::default();
+        let id = store.start("COMMAND");
+        assert_eq!(store.get(id).unwrap().output_format, None);
+        store.set_output_options(id, Some(RunnerType::Command), Some(OutputFormat::Markdown));
+        store.finish(id, Ok(&serde_json::json!({"stdout": "ok"})));
+        let job = store.get(id).unwrap();
+        assert_eq!(job.runner_type, Some(RunnerType::Command));
+        assert_eq!(job.output_format, Some(OutputFormat::Markdown));
+    }
+
+    #[test]
+    fn test_find_job_history() {
+        let store = JobStore::default();
+        let a = store.start_in_session("COMMAND", Some("s1"), Some("alice"));
+        store.finish(a, Ok(&serde_json::json!({"stdout": "ok"})));
+        let b = store.start_in_session("HTTP_REQUEST", Some("s2"), None);
+        store.finish(b, Err("failed".to_string()));
+        store.start_in_session("COMMAND", Some("s2"), None);
+
+        let commands = store.find(&JobFilter {
+            tool_name: Some("COMM*".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(commands.len(), 2);
+        // newest first
+        assert_eq!(commands[0].status, JobStatus::Running);
+        assert_eq!(commands[1].owner.as_deref(), Some("alice"));
+
+        let filter = JobStore::parse_history_uri(&JobStore::session_history_uri("s2")).unwrap();
+        assert_eq!(store.find(&filter).len(), 2);
+        assert!(JobStore::parse_history_uri("job://tool/").is_none());
+        assert!(JobStore::parse_status_uri(&JobStore::tool_history_uri("COMMAND")).is_none());
+
+        let (filter, limit, include_output) = meta_tool::parse_recent_results(
+            serde_json::json!({"status": "failed", "since": 0, "current_session_only": true})
+                .as_object(),
+            "s2",
+        )
+        .unwrap();
+        let failed = store.find(&filter);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].id, b);
+        assert_eq!(
+            (limit, include_output),
+            (meta_tool::DEFAULT_SEARCH_LIMIT, true)
+        );
+        assert!(meta_tool::parse_recent_results(
+            serde_json::json!({"status": "done"}).as_object(),
+            "s2"
+        )
+        .is_err());
+
+        let history = meta_tool::job_history(&store.find(&JobFilter::default()), true);
+        assert_eq!(history[2]["output"]["stdout"], "ok");
+        assert_eq!(history[2]["log_uri"], format!("job://{}/log", a));
+    }
+
+    #[test]
+    fn test_job_visibility() {
+        let store = JobStore::default();
+        let alices = store.start_in_session("COMMAND", Some("s1"), Some("alice"));
+        let anonymous = store.start_in_session("COMMAND", Some("s2"), None);
+        let session = |id: &str, subject: Option<&str>| SessionInfo {
+            id: id.to_string(),
+            identity: subject.map(|s| AuthIdentity {
+                subject: s.to_string(),
+                scopes: vec![],
+            }),
+            ..Default::default()
+        };
+        let visible = |session: &SessionInfo| {
+            [alices, anonymous]
+                .into_iter()
+                .filter(|id| store.get(*id).unwrap().is_visible_to(session))
+                .collect::<Vec<_>>()
+        };
+
+        // the jobs of the subject in any session
+        assert_eq!(visible(&session("s3", Some("alice"))), vec![alices]);
+        assert!(visible(&session("s2", Some("bob"))).is_empty());
+        // the jobs of the same session without authentication
+        assert_eq!(visible(&session("s2", None)), vec![anonymous]);
+        assert!(visible(&session("s3", None)).is_empty());
+    }
+}
@@ -17,6 +17,37 @@ mod tests {
             exclude_runner_as_tool: false,
             exclude_worker_as_tool: false,
             set_name: None,
+            proxy_config: Default::default(),
+            upstream: Default::default(),
+            tool_refresh_interval_sec: None,
+            warm_up_tools: false,
+            wait_for_backend_sec: None,
+            broken_tool_stubs: false,
+            inline_schema_refs: false,
+            describe_tool_results: false,
+            tool_name_collision_prefix: None,
+            kind_prefixed_names: false,
+            allow_workflow_creation: true,
+            ephemeral_worker_ttl_sec: 300,
+            map_call_max_parallelism: 8,
+            idempotency_window_sec: 600,
+            call_wait_timeout_sec: None,
+            max_result_chars: None,
+            max_arguments_bytes: None,
+            max_workflow_definition_bytes: None,
+            circuit_breaker_threshold: 0,
+            circuit_breaker_open_sec: 30,
+            lenient_arguments: true,
+            string_enums: true,
+            tool_search_mode: false,
+            core_tools: vec![],
+            stream_command_output: false,
+            disconnect_policy: Default::default(),
+            markdown_tables: false,
+            record_calls_dir: None,
+            metrics_summary_interval_sec: None,
+            stdio_drain_sec: None,
+            stdio_closed_exit_code: None,
         })
         .await
         .unwrap()
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::lenient_json::{self, ArgumentFix, JsonFix};
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_strict_json() {
+        let (value, fixes) = lenient_json::parse(r#"{"a": [1, 2], "b": "x'y"}"#).unwrap();
+        assert_eq!(value, json!({"a": [1, 2], "b": "x'y"}));
+        assert!(fixes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_common_mistakes() {
+        let text = "```json\n{command: 'echo', 'args': ['it\\'s', \"a \\\"b\\\"\",], env: {A_1: true,},}\n```";
+        let (value, fixes) = lenient_json::parse(text).unwrap();
+        assert_eq!(
+            value,
+            json!({"command": "echo", "args": ["it's", "a \"b\""], "env": {"A_1": true}})
+        );
+        assert_eq!(
+            fixes,
+            vec![
+                JsonFix::CodeFence,
+                JsonFix::UnquotedKey,
+                JsonFix::SingleQuote,
+                JsonFix::TrailingComma
+            ]
+        );
+
+        // values are not quoted, commas in strings are kept
+        let (value, _) = lenient_json::parse("{'a': null, b: [true, false], c: 'x,}'}").unwrap();
+        assert_eq!(value, json!({"a": null, "b": [true, false], "c": "x,}"}));
+
+        assert!(lenient_json::parse("{a: }").is_err());
+    }
+
+    #[test]
+    fn test_fix_arguments() {
+        let mut arguments = json!({
+            "settings": "{'model': 'm1',}",
+            "arguments": "{\"command\": \"ls\"}",
+            "text": "{not: 'an argument field'}"
+        })
+        .as_object()
+        .cloned();
+        let fixes = lenient_json::fix_arguments(&mut arguments);
+        assert_eq!(
+            arguments.map(serde_json::Value::Object),
+            Some(json!({
+                "settings": {"model": "m1"},
+                "arguments": {"command": "ls"},
+                "text": "{not: 'an argument field'}"
+            }))
+        );
+        assert_eq!(
+            fixes,
+            vec![
+                ArgumentFix {
+                    field: "arguments".to_string(),
+                    fixes: vec![JsonFix::EncodedString],
+                },
+                ArgumentFix {
+                    field: "settings".to_string(),
+                    fixes: vec![
+                        JsonFix::EncodedString,
+                        JsonFix::SingleQuote,
+                        JsonFix::TrailingComma
+                    ],
+                },
+            ]
+        );
+
+        // plain strings are kept
+        let mut arguments = json!({"arguments": "hello"}).as_object().cloned();
+        assert!(lenient_json::fix_arguments(&mut arguments).is_empty());
+        assert_eq!(arguments.unwrap()["arguments"], "hello");
+    }
+
+    fn combined_schema() -> serde_json::Map<String, serde_json::Value> {
+        json!({
+            "type": "object",
+            "properties": {
+                "settings": {
+                    "type": "object",
+                    "properties": {"model": {"type": "string"}}
+                },
+                "arguments": {
+                    "type": "object",
+                    "properties": {"prompt": {"type": "string"}, "max_tokens": {"type": "integer"}},
+                    "required": ["prompt"]
+                }
+            },
+            "required": ["arguments"]
+        })
+        .as_object()
+        .cloned()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_wrap_bare_arguments() {
+        let schema = combined_schema();
+        let mut arguments = json!({"prompt": "hi", "model": "m1", "idempotency_key": "k1"})
+            .as_object()
+            .cloned();
+        assert_eq!(
+            lenient_json::wrap_bare_arguments(&schema, &mut arguments),
+            Some(ArgumentFix {
+                field: "arguments".to_string(),
+                fixes: vec![JsonFix::MissingEnvelope],
+            })
+        );
+        assert_eq!(
+            arguments.map(serde_json::Value::Object),
+            Some(json!({
+                "settings": {"model": "m1"},
+                "arguments": {"prompt": "hi"},
+                "idempotency_key": "k1"
+            }))
+        );
+
+        // the envelope is given
+        let mut arguments = json!({"arguments": {"prompt": "hi"}}).as_object().cloned();
+        assert!(lenient_json::wrap_bare_arguments(&schema, &mut arguments).is_none());
+
+        // invalid also when wrapped
+        let mut arguments = json!({"max_tokens": 10}).as_object().cloned();
+        assert!(lenient_json::wrap_bare_arguments(&schema, &mut arguments).is_none());
+        assert_eq!(arguments.unwrap()["max_tokens"], 10);
+
+        // not a combined schema
+        let schema = json!({"type": "object", "properties": {"prompt": {"type": "string"}}});
+        let mut arguments = json!({"prompt": "hi"}).as_object().cloned();
+        assert!(
+            lenient_json::wrap_bare_arguments(schema.as_object().unwrap(), &mut arguments)
+                .is_none()
+        );
+    }
+}
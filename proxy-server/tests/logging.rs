@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::logging::{self, LogFormat, LogRotation, LoggingSettings, SizeRotatingFile};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::format::FmtSpan;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_logging_config() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+logging:
+  file:
+    path: /tmp/proxy.log
+    rotation: size
+    max_size: 1024
+    max_files: 3
+"#,
+        )
+        .unwrap();
+        let file = config.logging.file.unwrap();
+        assert_eq!(file.rotation, LogRotation::Size);
+        assert_eq!(file.max_size, 1024);
+        assert_eq!(file.max_files, Some(3));
+
+        let config = ProxyConfig::from_yaml("logging:\n  file:\n    path: proxy.log\n").unwrap();
+        assert_eq!(config.logging.file.unwrap().rotation, LogRotation::Daily);
+        assert!(ProxyConfig::from_yaml("{}").unwrap().logging.file.is_none());
+    }
+
+    #[test]
+    fn test_size_rotating_file() {
+        let dir = std::env::temp_dir().join(format!("proxy-log-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("proxy.log");
+        let mut writer = SizeRotatingFile::new(&path, 10, 2).unwrap();
+        for line in ["aaaaaaaa\n", "bbbbbbbb\n", "cccccccc\n", "dddddddd\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "dddddddd\n");
+        assert_eq!(
+            std::fs::read_to_string(dir.join("proxy.log.1")).unwrap(),
+            "cccccccc\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("proxy.log.2")).unwrap(),
+            "bbbbbbbb\n"
+        );
+        assert!(!dir.join("proxy.log.3").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_json_span_list() {
+        let buffer = SharedBuffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry().with(logging::fmt_layer(
+            LogFormat::Json,
+            FmtSpan::CLOSE,
+            move || writer.clone(),
+        ));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!(
+                "call_tool",
+                request_id = "7",
+                session_id = "s1",
+                tool_name = "COMMAND",
+                job_id = tracing::field::Empty,
+            );
+            let _call = span.enter();
+            span.record("job_id", 42);
+            tracing::info_span!("enqueue", job_id = 42).in_scope(|| tracing::info!("enqueued"));
+        });
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines = output
+            .lines()
+            .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap())
+            .collect::<Vec<_>>();
+
+        let event = lines.iter().find(|l| l["message"] == "enqueued").unwrap();
+        assert_eq!(event["span"]["name"], "enqueue");
+        assert_eq!(event["spans"][0]["name"], "call_tool");
+        assert_eq!(event["spans"][0]["request_id"], "7");
+        assert_eq!(event["spans"][0]["job_id"], 42);
+        assert_eq!(event["spans"][1]["name"], "enqueue");
+        // the closed spans with their timings
+        let closed = lines
+            .iter()
+            .filter(|l| l["message"] == "close")
+            .map(|l| l["span"]["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(closed, vec!["enqueue", "call_tool"]);
+        assert!(lines.iter().any(|l| l.get("time.busy").is_some()));
+    }
+
+    #[test]
+    fn test_logging_settings() {
+        logging::set_debug_sample_rate(0.0);
+        assert!((0..100).all(|_| !logging::debug_sampled()));
+        logging::set_debug_sample_rate(2.0);
+        assert_eq!(logging::debug_sample_rate(), 1.0);
+        assert!(logging::debug_sampled());
+
+        let settings = LoggingSettings {
+            filter: None,
+            debug_sample_rate: Some(0.25),
+        };
+        settings.apply().unwrap();
+        assert_eq!(LoggingSettings::current().debug_sample_rate, Some(0.25));
+        // the subscriber is not installed by the tests
+        assert!(logging::log_filter().is_none());
+        let invalid = LoggingSettings {
+            filter: Some("info,[call_tool{tool_name=COMMAND}]=verbose".to_string()),
+            debug_sample_rate: None,
+        };
+        assert!(invalid.apply().is_err());
+        let unreloadable = LoggingSettings {
+            filter: Some("info,[call_tool{tool_name=COMMAND}]=debug".to_string()),
+            debug_sample_rate: None,
+        };
+        assert!(unreloadable.apply().is_err());
+        logging::set_debug_sample_rate(1.0);
+    }
+}
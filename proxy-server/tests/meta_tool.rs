@@ -0,0 +1,312 @@
+#[cfg(test)]
+mod tests {
+    use jobworkerp_client::jobworkerp::data::RunnerType;
+    use jobworkerp_client::jobworkerp::function::data::{
+        function_specs, FunctionSchema, FunctionSpecs, McpTool, McpToolList,
+    };
+    use proxy_server::jobworkerp::meta_tool::{self, HelpInfo};
+    use proxy_server::tool_conversion::ToolDiagnostic;
+    use rmcp::model::{CallToolResult, Content};
+    use rmcp::Error as McpError;
+
+    fn function(name: &str, runner_type: RunnerType) -> FunctionSpecs {
+        FunctionSpecs {
+            runner_type: runner_type as i32,
+            runner_id: Some(Default::default()),
+            worker_id: None,
+            name: name.to_string(),
+            description: "desc".to_string(),
+            output_type: 0,
+            schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+                settings: None,
+                arguments: "{\"type\":\"object\"}".to_string(),
+                result_output_schema: None,
+            })),
+        }
+    }
+
+    #[test]
+    fn test_help_groups_visible_tools() {
+        let mcp_server = FunctionSpecs {
+            schema: Some(function_specs::Schema::McpTools(McpToolList {
+                list: vec![McpTool {
+                    name: "toolA".to_string(),
+                    description: None,
+                    input_schema: "{\"type\":\"object\"}".to_string(),
+                    annotations: None,
+                }],
+            })),
+            ..function("server1", RunnerType::McpServer)
+        };
+        let command = FunctionSpecs {
+            schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+                settings: None,
+                arguments: "{\"type\":\"object\"}".to_string(),
+                result_output_schema: Some("{\"type\":\"object\"}".to_string()),
+            })),
+            ..function("COMMAND", RunnerType::Command)
+        };
+        let functions = vec![
+            command,
+            function("HTTP_REQUEST", RunnerType::HttpRequest),
+            function("broken", RunnerType::Command),
+            mcp_server,
+        ];
+        let unavailable = vec![ToolDiagnostic::new("broken", "invalid arguments schema")];
+        let help = HelpInfo {
+            instructions: "instructions",
+            functions: &functions,
+            unavailable: &unavailable,
+            request_timeout_sec: 60,
+            job_history_size: 100,
+        }
+        .render(|name| name != "HTTP_REQUEST");
+
+        assert_eq!(help["instructions"], "instructions");
+        assert_eq!(help["tools"]["runner"], serde_json::json!(["COMMAND"]));
+        assert_eq!(
+            help["tools"]["mcp_server"],
+            serde_json::json!(["server1___toolA"])
+        );
+        assert_eq!(help["unavailable_tools"][0]["name"], "broken");
+        assert_eq!(
+            help["result_schemas"],
+            serde_json::json!({"COMMAND": {"type": "object"}})
+        );
+        assert_eq!(help["examples"].as_array().unwrap().len(), 2);
+        assert_eq!(help["examples"][0]["call"]["name"], "COMMAND");
+    }
+
+    #[test]
+    fn test_parse_batch_calls() {
+        let args = serde_json::json!({"calls": [
+            {"tool": "COMMAND", "arguments": {"arguments": {"command": "ls"}}},
+            {"tool": "worker1"},
+        ]});
+        let calls = meta_tool::parse_batch_calls(args.as_object()).unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].tool, "COMMAND");
+        assert!(calls[1].arguments.is_none());
+
+        assert!(meta_tool::parse_batch_calls(None).is_err());
+        let empty = serde_json::json!({"calls": []});
+        assert!(meta_tool::parse_batch_calls(empty.as_object()).is_err());
+        let invalid = serde_json::json!({"calls": [{"arguments": {}}]});
+        assert!(meta_tool::parse_batch_calls(invalid.as_object()).is_err());
+    }
+
+    #[test]
+    fn test_batch_item_result_isolates_errors() {
+        let ok = meta_tool::batch_item_result(
+            0,
+            "COMMAND",
+            Ok(CallToolResult {
+                content: vec![Content::text("done")],
+                is_error: None,
+            }),
+        );
+        assert_eq!(ok["index"], 0);
+        assert_eq!(ok["is_error"], false);
+        assert_eq!(ok["content"][0]["text"], "done");
+
+        let err = meta_tool::batch_item_result(
+            1,
+            "missing",
+            Err(McpError::invalid_params("not found", None)),
+        );
+        assert_eq!(err["tool"], "missing");
+        assert_eq!(err["is_error"], true);
+        assert_eq!(err["error"], "not found");
+    }
+
+    #[test]
+    fn test_map_call_parallelism_and_summary() {
+        let args = serde_json::json!({
+            "tool": "COMMAND",
+            "inputs": [{"arguments": {"command": "ls"}}, {"arguments": {"command": "pwd"}}],
+            "parallelism": 20,
+        });
+        let call = meta_tool::MapCall::parse(args.as_object()).unwrap();
+        assert_eq!(call.inputs.len(), 2);
+        assert_eq!(call.parallelism(8), 8);
+        assert_eq!(
+            meta_tool::MapCall {
+                parallelism: None,
+                ..call.clone()
+            }
+            .parallelism(4),
+            4
+        );
+        let no_inputs = serde_json::json!({"tool": "COMMAND", "inputs": []});
+        assert!(meta_tool::MapCall::parse(no_inputs.as_object()).is_err());
+
+        let results = vec![
+            serde_json::json!({"index": 0, "is_error": false}),
+            serde_json::json!({"index": 1, "is_error": true}),
+        ];
+        let result = meta_tool::map_call_result(results).unwrap();
+        let value = serde_json::to_value(&result.content[0]).unwrap();
+        let body: serde_json::Value =
+            serde_json::from_str(value["text"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            body["summary"],
+            serde_json::json!({"total": 2, "succeeded": 1, "failed": 1})
+        );
+    }
+
+    #[test]
+    fn test_resume_call_arguments() {
+        let max_wait = std::time::Duration::from_secs(30);
+        let args = serde_json::json!({"continuation_token": "12", "wait_sec": 100});
+        let (job_id, wait) = meta_tool::parse_resume_call(args.as_object(), max_wait).unwrap();
+        assert_eq!(job_id, 12);
+        assert_eq!(wait, max_wait);
+        let args = serde_json::json!({"continuation_token": 3, "wait_sec": 5});
+        let (job_id, wait) = meta_tool::parse_resume_call(args.as_object(), max_wait).unwrap();
+        assert_eq!((job_id, wait.as_secs()), (3, 5));
+        let args = serde_json::json!({"continuation_token": "abc"});
+        assert!(meta_tool::parse_resume_call(args.as_object(), max_wait).is_err());
+
+        let result = meta_tool::continuation_result(12, "COMMAND").unwrap();
+        let value = serde_json::to_value(&result.content[0]).unwrap();
+        let body: serde_json::Value =
+            serde_json::from_str(value["text"].as_str().unwrap()).unwrap();
+        assert_eq!(body["status"], "running");
+        assert_eq!(body["continuation_token"], "12");
+    }
+
+    #[test]
+    fn test_result_chunks() {
+        let text = "あいうえおabcde";
+        let first = meta_tool::result_chunk(1, text, 0, 4);
+        assert_eq!(first["data"], "あいうえ");
+        assert_eq!(first["total_length"], 10);
+        assert_eq!(first["next_offset"], 4);
+        let last = meta_tool::result_chunk(1, text, 8, 4);
+        assert_eq!(last["data"], "de");
+        assert_eq!(last["length"], 2);
+        assert!(last["next_offset"].is_null());
+
+        let args = serde_json::json!({"job_id": 1, "length": 0});
+        assert_eq!(
+            meta_tool::parse_fetch_result_chunk(args.as_object()).unwrap(),
+            (1, 0, 1)
+        );
+    }
+
+    #[test]
+    fn test_oversized_job_output() {
+        let output = serde_json::json!({"stdout": "x".repeat(100)});
+        assert!(meta_tool::oversized_result(1, &output, None).is_none());
+        assert!(meta_tool::oversized_result(1, &output, Some(1000)).is_none());
+
+        let large = meta_tool::oversized_result(1, &output, Some(10))
+            .unwrap()
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_str(
+            serde_json::to_value(&large.content[0]).unwrap()["text"]
+                .as_str()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(body["job_id"], 1);
+        assert_eq!(body["next_offset"], 10);
+        assert_eq!(body["data"].as_str().unwrap().chars().count(), 10);
+    }
+
+    #[test]
+    fn test_check_payload_size() {
+        let args = serde_json::json!({"text": "x".repeat(100)});
+        assert!(meta_tool::check_payload_size("arguments", args.as_object(), None).is_none());
+        assert!(meta_tool::check_payload_size("arguments", args.as_object(), Some(200)).is_none());
+        assert!(meta_tool::check_payload_size("arguments", None, Some(1)).is_none());
+
+        let error = meta_tool::check_payload_size("arguments", args.as_object(), Some(50)).unwrap();
+        assert_eq!(error.is_error, Some(true));
+        let text = serde_json::to_value(&error.content[0]).unwrap()["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(text.contains("111 bytes (limit: 50 bytes)"));
+    }
+
+    #[test]
+    fn test_search_tools() {
+        let tool = |name: &str, description: &str| {
+            rmcp::model::Tool::new(
+                name.to_string(),
+                description.to_string(),
+                serde_json::Map::new(),
+            )
+        };
+        let tools = vec![
+            tool("COMMAND", "run a shell command"),
+            tool("HTTP_REQUEST", "send a http request"),
+            tool("fetch_page", "fetch a web page by http"),
+        ];
+        let (keywords, limit) = meta_tool::parse_search_tools(
+            serde_json::json!({"query": "HTTP", "limit": 1000}).as_object(),
+        )
+        .unwrap();
+        assert_eq!(limit, meta_tool::MAX_SEARCH_LIMIT);
+        // matches in the name first
+        let names = meta_tool::search_tools(&tools, &keywords)
+            .iter()
+            .map(|t| t.name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["HTTP_REQUEST", "fetch_page"]);
+        // all keywords must match
+        let keywords = vec!["http".to_string(), "page".to_string()];
+        assert_eq!(meta_tool::search_tools(&tools, &keywords).len(), 1);
+        assert!(
+            meta_tool::parse_search_tools(serde_json::json!({"query": " "}).as_object()).is_err()
+        );
+
+        let result = meta_tool::search_tools_result(&tools, &["command".to_string()], 10).unwrap();
+        let body: serde_json::Value = serde_json::from_str(
+            serde_json::to_value(&result.content[0]).unwrap()["text"]
+                .as_str()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(body["total_matches"], 1);
+        assert_eq!(body["tools"][0]["name"], "COMMAND");
+        assert!(body["tools"][0]["inputSchema"].is_object());
+    }
+
+    #[test]
+    fn test_author_workflow_draft() {
+        let (description, create, max_tokens) = meta_tool::parse_author_workflow(
+            serde_json::json!({"description": "fetch a page and summarize it"}).as_object(),
+        )
+        .unwrap();
+        assert_eq!(description, "fetch a page and summarize it");
+        assert!(!create);
+        assert_eq!(max_tokens, meta_tool::DEFAULT_AUTHORING_MAX_TOKENS);
+        assert!(meta_tool::parse_author_workflow(None).is_err());
+
+        // in a code block surrounded by text
+        let workflow = meta_tool::extract_workflow_json(
+            "Here is the workflow:\n```json\n{\"document\": {\"name\": \"summarize\"}, \"do\": []}\n```\nDone.",
+        )
+        .unwrap();
+        assert_eq!(workflow["document"]["name"], "summarize");
+        assert!(meta_tool::extract_workflow_json("no workflow").is_err());
+        assert!(meta_tool::extract_workflow_json("{\"do\": [}").is_err());
+
+        let schema = serde_json::json!({"type": "object", "required": ["document"]});
+        let request = meta_tool::workflow_authoring_request(
+            &description,
+            schema.as_object().unwrap(),
+            None,
+            max_tokens,
+        );
+        assert_eq!(request.messages.len(), 1);
+        assert!(request.system_prompt.unwrap().contains("\"required\""));
+
+        let result = meta_tool::author_workflow_result(Some(&workflow), &[], None, false).unwrap();
+        assert_eq!(result.is_error, None);
+        let result = meta_tool::author_workflow_result(None, &[], Some("no JSON"), false).unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::jobworkerp::session::{SessionClient, SessionInfo};
+
+    #[test]
+    fn test_build_meta_allow_list() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+metadata_forwarding:
+  meta:
+    run_id: x-mcp-run-id
+    labels: X-MCP-Labels
+"#,
+        )
+        .unwrap();
+        let meta = serde_json::json!({
+            "run_id": "run-1",
+            "labels": {"team": "a"},
+            "injected": "value",
+        });
+        let metadata = config
+            .metadata_forwarding
+            .build_meta(meta.as_object().unwrap());
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata.get("x-mcp-run-id").unwrap(), "run-1");
+        assert_eq!(metadata.get("x-mcp-labels").unwrap(), r#"{"team":"a"}"#);
+    }
+
+    #[test]
+    fn test_build_meta_skip_invalid_value() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+metadata_forwarding:
+  meta:
+    user_id: x-mcp-user-id
+    run_id: x-mcp-run-id
+"#,
+        )
+        .unwrap();
+        let meta = serde_json::json!({
+            "user_id": "ユーザー",
+            "run_id": null,
+        });
+        let metadata = config
+            .metadata_forwarding
+            .build_meta(meta.as_object().unwrap());
+        assert!(metadata.is_empty());
+    }
+
+    #[test]
+    fn test_build_client_metadata() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+metadata_forwarding:
+  client_info: x-mcp-client
+  protocol_version: X-MCP-Protocol-Version
+"#,
+        )
+        .unwrap();
+        let client = SessionClient {
+            name: "client".to_string(),
+            version: "1.0".to_string(),
+            protocol_version: "2024-11-05".to_string(),
+        };
+        let metadata = config
+            .metadata_forwarding
+            .build(&SessionInfo::stdio(), Some(&client));
+        assert_eq!(metadata.get("x-mcp-client").unwrap(), "client/1.0");
+        assert_eq!(
+            metadata.get("x-mcp-protocol-version").unwrap(),
+            "2024-11-05"
+        );
+        assert!(config
+            .metadata_forwarding
+            .build(&SessionInfo::stdio(), None)
+            .is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::mock::MockTools;
+    use rmcp::model::CallToolRequestParam;
+    use serde_json::json;
+
+    const MANIFEST: &str = r#"{
+  "tools": [
+    {"name": "COMMAND", "description": "run a command", "inputSchema": {"type": "object"}},
+    {"name": "greet", "description": "greet", "inputSchema": {"type": "object"}},
+    {"name": "fail", "description": "fail", "inputSchema": {"type": "object"}}
+  ],
+  "responses": {
+    "greet": {"message": "hello"},
+    "fail": {"content": [{"type": "text", "text": "broken"}], "isError": true}
+  }
+}"#;
+
+    fn request(name: &'static str, arguments: serde_json::Value) -> CallToolRequestParam {
+        CallToolRequestParam {
+            name: name.into(),
+            arguments: arguments.as_object().cloned(),
+        }
+    }
+
+    fn content(result: &rmcp::model::CallToolResult) -> serde_json::Value {
+        serde_json::to_value(&result.content).unwrap()
+    }
+
+    #[test]
+    fn test_mock_calls() {
+        let mock = MockTools::from_json(MANIFEST).unwrap();
+        assert_eq!(mock.list_tools().tools.len(), 3);
+
+        let echo = mock
+            .call(&request("COMMAND", json!({"command": "echo"})))
+            .unwrap();
+        let echoed: serde_json::Value =
+            serde_json::from_str(content(&echo)[0]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            echoed,
+            json!({"tool": "COMMAND", "arguments": {"command": "echo"}})
+        );
+
+        let greet = mock.call(&request("greet", json!({}))).unwrap();
+        assert!(content(&greet)[0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("hello"));
+
+        let fail = mock.call(&request("fail", json!({}))).unwrap();
+        assert_eq!(fail.is_error, Some(true));
+        assert_eq!(content(&fail)[0]["text"], "broken");
+
+        assert!(mock.call(&request("unknown", json!({}))).is_err());
+    }
+
+    #[test]
+    fn test_manifest_formats() {
+        // a plain list of the tools
+        let mock = MockTools::from_json(
+            r#"[{"name": "COMMAND", "description": "run", "inputSchema": {"type": "object"}}]"#,
+        )
+        .unwrap();
+        assert_eq!(mock.list_tools().tools[0].name, "COMMAND");
+        assert!(mock.responses.is_empty());
+
+        // responses of unlisted tools are rejected
+        assert!(MockTools::from_json(r#"{"tools": [], "responses": {"x": 1}}"#).is_err());
+        assert!(MockTools::from_json("{").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_sse_router_layers() {
+        use axum::body::Body;
+        use axum::http::{header, Request, StatusCode};
+        use tower::ServiceExt;
+
+        let sse_config = proxy_server::SseConfig {
+            auth_tokens: vec!["secret".to_string()],
+            cors_allowed_origins: Some("https://app.example.com".to_string()),
+            ..Default::default()
+        };
+        let (router, ct) =
+            proxy_server::mock_sse_router(MockTools::from_json(MANIFEST).unwrap(), &sse_config)
+                .unwrap();
+        let response = router
+            .oneshot(
+                Request::get("/sse")
+                    .header(header::ORIGIN, "https://app.example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response
+                .headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .unwrap(),
+            "https://app.example.com"
+        );
+        ct.cancel();
+    }
+}
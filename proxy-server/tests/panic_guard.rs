@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::jobworkerp::panic_guard;
+
+    #[tokio::test]
+    async fn test_catch_panic() {
+        let result: Result<i32, _> = panic_guard::catch_panic("ok", "0", async { Ok(1) }).await;
+        assert_eq!(result.unwrap(), 1);
+
+        let result: Result<i32, _> = panic_guard::catch_panic("broken", "0123", async {
+            panic!("boom");
+        })
+        .await;
+        let error = result.unwrap_err();
+        assert!(error.message.contains("broken"));
+        assert_eq!(
+            error.data,
+            Some(serde_json::json!({"arguments_hash": "0123"}))
+        );
+    }
+
+    #[test]
+    fn test_arguments_hash_and_message() {
+        let a = serde_json::json!({"command": "ls"});
+        let b = serde_json::json!({"command": "rm"});
+        let hash = panic_guard::arguments_hash(a.as_object());
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, panic_guard::arguments_hash(a.as_object()));
+        assert_ne!(hash, panic_guard::arguments_hash(b.as_object()));
+
+        let payload: Box<dyn std::any::Any + Send> = Box::new(format!("index {}", 3));
+        assert_eq!(panic_guard::panic_message(payload.as_ref()), "index 3");
+        let payload: Box<dyn std::any::Any + Send> = Box::new(1);
+        assert_eq!(
+            panic_guard::panic_message(payload.as_ref()),
+            "unknown panic"
+        );
+    }
+}
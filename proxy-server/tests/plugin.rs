@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::plugin::{self, PluginsConfig};
+
+    #[test]
+    fn test_config() {
+        let config: PluginsConfig = serde_yaml::from_str(
+            "dir: /etc/mcp/plugins\ntools:\n  COMMAND: command.wasm\nfuel: 1000000\n",
+        )
+        .unwrap();
+        assert!(config.is_enabled());
+        assert_eq!(config.tools["COMMAND"], "command.wasm");
+        assert!(plugin::build(&PluginsConfig::default()).unwrap().is_none());
+    }
+
+    #[cfg(not(feature = "wasm-plugins"))]
+    #[test]
+    fn test_plugins_without_feature() {
+        let config: PluginsConfig = serde_yaml::from_str("tools:\n  COMMAND: a.wasm\n").unwrap();
+        assert!(plugin::build(&config).is_err());
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[tokio::test]
+    async fn test_transform_arguments() {
+        use proxy_server::interceptor::ToolCall;
+        use rmcp::model::{CallToolResult, Content};
+
+        // returns fixed arguments, the result is unchanged
+        const PLUGIN: &str = r#"(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "{\"unit\":\"m\",\"value\":1000}")
+  (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+  (func (export "transform_arguments") (param i32 i32) (result i64) (i64.const 25))
+  (func (export "transform_result") (param i32 i32) (result i64) (i64.const 0)))"#;
+        // never returns
+        const LOOP: &str = r#"(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32) (i32.const 0))
+  (func (export "transform_arguments") (param i32 i32) (result i64) (loop (br 0)) (i64.const 0)))"#;
+        let dir = std::env::temp_dir().join("plugin-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("convert.wat"), PLUGIN).unwrap();
+        std::fs::write(dir.join("loop.wat"), LOOP).unwrap();
+        let config: PluginsConfig = serde_yaml::from_str(&format!(
+            "dir: {}\ntools:\n  CONVERT: convert.wat\n  LOOP: loop.wat\nfuel: 100000\n",
+            dir.display()
+        ))
+        .unwrap();
+        let plugins = plugin::build(&config).unwrap().unwrap();
+
+        let call = |tool_name: &str| ToolCall {
+            tool_name: tool_name.to_string(),
+            target: "COMMAND".to_string(),
+            arguments: serde_json::from_str(r#"{"unit":"km","value":1}"#).unwrap(),
+            metadata: Default::default(),
+            session_id: "s1".to_string(),
+            subject: None,
+        };
+        let mut converted = call("CONVERT");
+        plugins.pre_call(&mut converted).await.unwrap();
+        assert_eq!(
+            serde_json::Value::Object(converted.arguments.clone()),
+            serde_json::json!({"unit": "m", "value": 1000})
+        );
+        let mut result = CallToolResult {
+            content: vec![Content::text("ok")],
+            is_error: None,
+        };
+        plugins.post_call(&converted, &mut result).await;
+        assert_eq!(result.content.len(), 1);
+
+        // tools without plugins are not changed, and plugins running out of fuel fail
+        let mut other = call("OTHER");
+        plugins.pre_call(&mut other).await.unwrap();
+        assert_eq!(other.arguments["unit"], "km");
+        assert!(plugins.pre_call(&mut call("LOOP")).await.is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::auth::AuthIdentity;
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::rbac::{glob_match, ToolPermission};
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", "COMMAND"));
+        assert!(glob_match("COMMAND", "COMMAND"));
+        assert!(!glob_match("COMMAND", "COMMAND2"));
+        assert!(glob_match("server___*", "server___toolA"));
+        assert!(!glob_match("server___*", "other___toolA"));
+        assert!(glob_match("*_REQUEST", "HTTP_REQUEST"));
+        assert!(glob_match("a*b*c", "aXXbYYc"));
+        assert!(!glob_match("a*b*c", "aXXcYYb"));
+        assert!(!glob_match("ab*ba", "aba"));
+        // names of workers may contain non-ascii characters
+        assert!(!glob_match("a*b", "aé"));
+        assert!(!glob_match("é*é", "é"));
+        assert!(glob_match("*é*", "aéb"));
+        assert!(glob_match("ツール*", "ツール一覧"));
+    }
+
+    #[test]
+    fn test_permission_from_config() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+rbac:
+  default_role: guest
+  roles:
+    guest:
+      tools: ["echo"]
+    developer:
+      tools: ["COMMAND", "server___*"]
+    admin:
+      tools: ["*"]
+      admin: true
+  subjects:
+    alice: [admin]
+  scopes:
+    "mcp:tools": [developer]
+"#,
+        )
+        .unwrap();
+        let rbac = config.rbac.unwrap();
+
+        let anonymous = rbac.permission_for(None);
+        assert!(anonymous.is_allowed("echo"));
+        assert!(!anonymous.is_allowed("COMMAND"));
+        assert!(!anonymous.is_admin());
+
+        let bob = AuthIdentity {
+            subject: "bob".to_string(),
+            scopes: vec!["mcp:tools".to_string()],
+        };
+        let permission = rbac.permission_for(Some(&bob));
+        assert!(permission.is_allowed("COMMAND"));
+        assert!(permission.is_allowed("server___toolA"));
+        assert!(permission.is_allowed("echo"));
+        assert!(!permission.is_allowed("HTTP_REQUEST"));
+        assert!(!permission.is_admin());
+
+        let alice = AuthIdentity {
+            subject: "alice".to_string(),
+            scopes: vec![],
+        };
+        let permission = rbac.permission_for(Some(&alice));
+        assert!(permission.is_allowed("HTTP_REQUEST"));
+        assert!(permission.is_admin());
+        assert!(permission.can_use_admin_tools());
+        assert!(!anonymous.can_use_admin_tools());
+
+        // without rbac: all tools, but not the jobs of the other sessions
+        let permission = ToolPermission::allow_all();
+        assert!(permission.is_allowed("HTTP_REQUEST"));
+        assert!(permission.can_use_admin_tools());
+        assert!(!permission.is_admin());
+    }
+}
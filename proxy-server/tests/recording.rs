@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::mock::MockTools;
+    use proxy_server::recording::{self, Recorder};
+    use rmcp::model::{CallToolRequestParam, CallToolResult, Content, ListToolsResult};
+    use rmcp::Error as McpError;
+    use serde_json::json;
+
+    fn request(name: &'static str, arguments: serde_json::Value) -> CallToolRequestParam {
+        CallToolRequestParam {
+            name: name.into(),
+            arguments: arguments.as_object().cloned(),
+        }
+    }
+
+    fn text(result: &CallToolResult) -> serde_json::Value {
+        serde_json::to_value(&result.content).unwrap()[0]["text"].clone()
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("recording-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let recorder = Recorder::open(&dir).unwrap();
+        let tools: ListToolsResult = serde_json::from_value(json!({"tools": [
+            {"name": "COMMAND", "description": "run a command", "inputSchema": {"type": "object"}}
+        ]}))
+        .unwrap();
+        recorder.record_tools(&tools);
+        let result = |text: &str| CallToolResult {
+            content: vec![Content::text(text)],
+            is_error: None,
+        };
+        recorder.record(
+            &request("COMMAND", json!({"command": "echo", "args": ["a"]})),
+            &Ok(result("first")),
+        );
+        // `_meta` is not recorded, and the last call of the same arguments is replayed
+        recorder.record(
+            &request(
+                "COMMAND",
+                json!({"command": "echo", "args": ["a"], "_meta": {"channel": "gpu"}}),
+            ),
+            &Ok(result("second")),
+        );
+        recorder.record(
+            &request("COMMAND", json!({"command": "false"})),
+            &Err(McpError::invalid_params("bad arguments", None)),
+        );
+        assert_eq!(recording::load_calls(&dir).unwrap().len(), 3);
+
+        let replay = MockTools::load_recording(&dir.to_string_lossy()).unwrap();
+        assert_eq!(replay.list_tools().tools.len(), 1);
+        let replayed = replay
+            .call(&request(
+                "COMMAND",
+                json!({"args": ["a"], "command": "echo"}),
+            ))
+            .unwrap();
+        assert_eq!(text(&replayed), "second");
+        let error = replay
+            .call(&request("COMMAND", json!({"command": "false"})))
+            .unwrap_err();
+        assert_eq!(error.message, "bad arguments");
+        // calls not recorded are rejected
+        assert!(replay
+            .call(&request("COMMAND", json!({"command": "true"})))
+            .is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,289 @@
+#[cfg(test)]
+mod tests {
+    use jobworkerp_client::jobworkerp::data::RunnerType;
+    use proxy_server::jobworkerp::artifact::{ArtifactRegistry, ArtifactsConfig};
+    use proxy_server::jobworkerp::artifact_store::{
+        ArtifactStoreConfig, LocalArtifactStore, LocalStoreConfig,
+    };
+    use proxy_server::jobworkerp::result_projection::{ResultProjections, ResultProjectionsConfig};
+    use proxy_server::result_conversion::{
+        OutputFormat, ResultContext, ResultConverter, ResultConverters, ResultPipeline,
+        TableConverter,
+    };
+    use rmcp::model::{CallToolResult, Content};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    fn context(runner_type: Option<RunnerType>) -> ResultContext {
+        ResultContext {
+            job_id: 1,
+            runner_type,
+            output_format: None,
+        }
+    }
+
+    fn content_json(result: &CallToolResult) -> Value {
+        serde_json::to_value(&result.content).unwrap()
+    }
+
+    #[test]
+    fn test_default_json_content() {
+        let converters = ResultConverters::default();
+        let result = converters
+            .convert(&context(Some(RunnerType::Command)), json!({"stdout": "ok"}))
+            .unwrap();
+        let content = content_json(&result);
+        assert_eq!(content[0]["type"], "text");
+        assert_eq!(
+            serde_json::from_str::<Value>(content[0]["text"].as_str().unwrap()).unwrap(),
+            json!({"stdout": "ok"})
+        );
+    }
+
+    #[test]
+    fn test_media_content() {
+        let converters = ResultConverters::default();
+        let image = converters
+            .convert(
+                &context(None),
+                json!({"mime_type": "image/png", "data": "iVBORw0KGgo="}),
+            )
+            .unwrap();
+        let content = content_json(&image);
+        assert_eq!(content[0]["type"], "image");
+        assert_eq!(content[0]["mimeType"], "image/png");
+
+        let audio = converters
+            .convert(
+                &context(None),
+                json!([{"content_type": "audio/wav", "data": "UklGRg=="}]),
+            )
+            .unwrap();
+        let content = content_json(&audio);
+        assert_eq!(content[0]["type"], "resource");
+        assert_eq!(content[0]["resource"]["uri"], "job://1/output");
+    }
+
+    #[test]
+    fn test_mcp_server_content_passthrough() {
+        let converters = ResultConverters::default();
+        let output = json!({"content": [{"type": "text", "text": "hello"}], "isError": true});
+        let result = converters
+            .convert(&context(Some(RunnerType::McpServer)), output.clone())
+            .unwrap();
+        assert_eq!(content_json(&result)[0]["text"], "hello");
+        assert_eq!(result.is_error, Some(true));
+
+        // not applied to other runner types
+        let result = converters
+            .convert(&context(Some(RunnerType::Command)), output)
+            .unwrap();
+        assert!(content_json(&result)[0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("isError"));
+    }
+
+    #[test]
+    fn test_command_content() {
+        let converters = ResultConverters::default();
+        let output = json!({"exit_code": 1, "stdout": "building\n", "stderr": "error: failed\n", "execution_time_ms": 12});
+        let result = converters
+            .convert(&context(Some(RunnerType::Command)), output)
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+        let content = content_json(&result);
+        assert_eq!(
+            serde_json::from_str::<Value>(content[0]["text"].as_str().unwrap()).unwrap(),
+            json!({"exit_code": 1, "execution_time_ms": 12})
+        );
+        assert_eq!(content[1]["text"], "--- stdout ---\nbuilding\n");
+        assert_eq!(content[2]["text"], "--- stderr ---\nerror: failed\n");
+
+        // empty streams are omitted
+        let result = converters
+            .convert(
+                &context(Some(RunnerType::Command)),
+                json!({"exit_code": 0, "stdout": "ok", "stderr": ""}),
+            )
+            .unwrap();
+        assert_eq!(result.is_error, None);
+        assert_eq!(result.content.len(), 2);
+    }
+
+    struct UpperCaseConverter;
+
+    impl ResultConverter for UpperCaseConverter {
+        fn convert(&self, _context: &ResultContext, output: &Value) -> Option<CallToolResult> {
+            Some(CallToolResult {
+                content: vec![Content::text(output.as_str()?.to_uppercase())],
+                is_error: None,
+            })
+        }
+    }
+
+    #[test]
+    fn test_register_converter() {
+        let mut converters = ResultConverters::default();
+        converters.register(RunnerType::Command, Arc::new(UpperCaseConverter));
+        let result = converters
+            .convert(&context(Some(RunnerType::Command)), json!("hello"))
+            .unwrap();
+        assert_eq!(content_json(&result)[0]["text"], "HELLO");
+    }
+
+    #[test]
+    fn test_output_format() {
+        let converters = ResultConverters::default();
+        let convert = |format: &str, output: Value| {
+            let context = ResultContext {
+                output_format: OutputFormat::parse(format),
+                ..context(Some(RunnerType::Command))
+            };
+            converters.convert(&context, output).unwrap()
+        };
+        let output = json!({"exit_code": 1, "stdout": "building", "execution_time_ms": 12});
+        let raw = convert("raw", output.clone());
+        assert_eq!(raw.content.len(), 1);
+        assert_eq!(content_json(&raw)[0]["text"], output.to_string());
+        // the error status of the conversion by the output type is kept
+        assert_eq!(raw.is_error, Some(true));
+        assert_eq!(
+            content_json(&convert("json", output.clone()))[0]["text"],
+            serde_json::to_string_pretty(&output).unwrap()
+        );
+        assert_eq!(
+            content_json(&convert("text", output))[0]["text"],
+            "building"
+        );
+        assert_eq!(
+            content_json(&convert(
+                "markdown",
+                json!([{"name": "a", "size": 1}, {"name": "b|c"}])
+            ))[0]["text"],
+            "| name | size |\n| --- | --- |\n| a | 1 |\n| b\\|c |  |"
+        );
+        assert_eq!(
+            content_json(&convert("markdown", json!({"id": 1, "tags": ["x", "y"]})))[0]["text"],
+            "**id**: 1\n**tags**:\n\n- x\n- y\n"
+        );
+    }
+
+    #[test]
+    fn test_output_format_from_meta() {
+        let meta = |format: Value| json!({"output_format": format}).as_object().cloned();
+        assert_eq!(
+            OutputFormat::from_meta(meta(json!("markdown")).as_ref()).unwrap(),
+            Some(OutputFormat::Markdown)
+        );
+        assert_eq!(OutputFormat::from_meta(None).unwrap(), None);
+        assert!(OutputFormat::from_meta(meta(json!("yaml")).as_ref()).is_err());
+        assert!(OutputFormat::from_meta(meta(json!(1)).as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_markdown_tables() {
+        let mut converters = ResultConverters::default();
+        converters.register_common(Arc::new(TableConverter { max_rows: 2 }));
+        let rows = json!([{"id": 1, "name": "a"}, {"id": 2, "name": "b\nc"}]);
+        let result = converters.convert(&context(None), rows.clone()).unwrap();
+        let content = content_json(&result);
+        // structured content first
+        assert_eq!(
+            serde_json::from_str::<Value>(content[0]["text"].as_str().unwrap()).unwrap(),
+            rows
+        );
+        assert_eq!(
+            content[1]["text"],
+            "| id | name |\n| --- | --- |\n| 1 | a |\n| 2 | b<br>c |"
+        );
+
+        // the only list of objects of an object output, up to max_rows
+        let result = converters
+            .convert(
+                &context(None),
+                json!({"count": 3, "rows": [{"id": 1}, {"id": 2}, {"id": 3}]}),
+            )
+            .unwrap();
+        assert_eq!(
+            content_json(&result)[1]["text"],
+            "**rows**:\n\n| id |\n| --- |\n| 1 |\n| 2 |\n\n(1 more rows)"
+        );
+
+        // other outputs are not tables
+        for output in [
+            json!([1, 2]),
+            json!({"a": [{"id": 1}], "b": [{"id": 2}]}),
+            json!([]),
+        ] {
+            let result = converters.convert(&context(None), output).unwrap();
+            assert_eq!(result.content.len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_result_pipeline() {
+        let dir = std::env::temp_dir().join("result-pipeline-test");
+        let files = dir.join("files");
+        std::fs::create_dir_all(&files).unwrap();
+        std::fs::write(files.join("report.csv"), "a,b\n1,2\n").unwrap();
+        let artifacts = ArtifactRegistry::new(ArtifactsConfig {
+            dirs: vec![files.to_string_lossy().to_string()],
+            ..Default::default()
+        });
+        let store = LocalArtifactStore::new(&LocalStoreConfig {
+            dir: dir.join("store").to_string_lossy().to_string(),
+            base_url: None,
+        });
+        let store_config = ArtifactStoreConfig {
+            offload_result_chars: Some(100),
+            upload_files: true,
+            ..Default::default()
+        };
+        let projections = ResultProjections::new(&ResultProjectionsConfig::default()).unwrap();
+        let converters = ResultConverters::default();
+        let pipeline = ResultPipeline {
+            artifacts: &artifacts,
+            artifact_store: Some(&store),
+            artifact_store_config: &store_config,
+            projections: &projections,
+            converters: &converters,
+            max_result_chars: Some(150),
+        };
+        let path = std::fs::canonicalize(files.join("report.csv")).unwrap();
+
+        // converted with the link to the uploaded file
+        let result = pipeline
+            .convert(&context(None), "tool", json!({"path": path}))
+            .await
+            .unwrap();
+        assert_eq!(result.content.len(), 2);
+        let link: Value =
+            serde_json::from_str(content_json(&result)[1]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(link["uri"], "artifact://store/jobs/1/report.csv");
+
+        // stored if larger than offload_result_chars (before the chunking)
+        let large = json!({"text": "x".repeat(200)});
+        let result = pipeline
+            .convert(&context(None), "tool", large.clone())
+            .await
+            .unwrap();
+        let offloaded: Value =
+            serde_json::from_str(content_json(&result)[0]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(offloaded["uri"], "artifact://store/jobs/1/output.json");
+
+        // returned in chunks without the store
+        let pipeline = ResultPipeline {
+            artifact_store: None,
+            ..pipeline
+        };
+        let result = pipeline
+            .convert(&context(None), "tool", large)
+            .await
+            .unwrap();
+        let chunk: Value =
+            serde_json::from_str(content_json(&result)[0]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(chunk["next_offset"], 150);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::jobworkerp::result_projection::{Projection, ResultProjections};
+    use serde_json::json;
+
+    fn envelope() -> serde_json::Value {
+        json!({
+            "status": 200,
+            "headers": {"content-type": "application/json"},
+            "body": {"items": [{"name": "a", "size": 1}, {"name": "b", "size": 2}]}
+        })
+    }
+
+    #[test]
+    fn test_json_path() {
+        let project = |path: &str| Projection::parse(path).unwrap().apply(&envelope()).unwrap();
+        assert_eq!(project("$.body.items.1.name"), json!("b"));
+        assert_eq!(project("$.body.items[*].name"), json!(["a", "b"]));
+        assert_eq!(project("$..size"), json!([1, 2]));
+        assert_eq!(project("$.missing"), json!(null));
+        assert_eq!(project("$"), envelope());
+    }
+
+    #[test]
+    fn test_jq() {
+        let project = |filter: &str| {
+            Projection::parse(filter)
+                .unwrap()
+                .apply(&envelope())
+                .unwrap()
+        };
+        assert_eq!(project(".body"), envelope()["body"]);
+        assert_eq!(project("[.body.items[] | .name]"), json!(["a", "b"]));
+        // multiple outputs are returned as a list
+        assert_eq!(project(".body.items[].size"), json!([1, 2]));
+        assert_eq!(project("empty"), json!(null));
+        assert!(Projection::parse(".body |").is_err());
+    }
+
+    #[test]
+    fn test_result_projections_from_config() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+result_projections:
+  HTTP_REQUEST: .body
+  "list_*": "$.body.items[*].name"
+"#,
+        )
+        .unwrap();
+        let projections = ResultProjections::new(&config.result_projections).unwrap();
+        assert_eq!(
+            projections.apply("HTTP_REQUEST", envelope()),
+            envelope()["body"]
+        );
+        assert_eq!(
+            projections.apply("list_files", envelope()),
+            json!(["a", "b"])
+        );
+        // other tools and failed projections return the output as it is
+        assert_eq!(projections.apply("COMMAND", envelope()), envelope());
+        assert_eq!(
+            projections.apply("HTTP_REQUEST", json!("text")),
+            json!("text")
+        );
+
+        let config = ProxyConfig::from_yaml("result_projections:\n  HTTP_REQUEST: .[\n").unwrap();
+        assert!(ResultProjections::new(&config.result_projections).is_err());
+    }
+}
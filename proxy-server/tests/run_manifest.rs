@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::client::run_manifest::{self, RunManifest, RunStatus};
+    use rmcp::model::{CallToolResult, Content};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    const MANIFEST: &str = r#"
+parallelism: 2
+calls:
+  - id: build
+    tool: COMMAND
+    arguments: {command: make}
+  - id: lint
+    tool: COMMAND
+    arguments: {command: lint}
+  - id: test
+    tool: COMMAND
+    arguments: {command: test}
+    depends_on: [build]
+  - id: deploy
+    tool: COMMAND
+    arguments: {command: deploy}
+    depends_on: [test, lint]
+  - id: notify
+    tool: SLACK
+    depends_on: [deploy]
+"#;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = RunManifest::parse(MANIFEST).unwrap();
+        assert_eq!(manifest.parallelism, 2);
+        assert_eq!(manifest.calls.len(), 5);
+        assert_eq!(manifest.calls[3].depends_on, vec!["test", "lint"]);
+        assert!(manifest.calls[4].arguments.is_empty());
+
+        let json = r#"{"calls": [{"id": "a", "tool": "COMMAND"}]}"#;
+        assert_eq!(RunManifest::parse(json).unwrap().parallelism, 4);
+
+        let duplicate = "calls: [{id: a, tool: X}, {id: a, tool: Y}]";
+        assert!(RunManifest::parse(duplicate).is_err());
+        let unknown = "calls: [{id: a, tool: X, depends_on: [b]}]";
+        assert!(RunManifest::parse(unknown).is_err());
+        let cycle = "calls: [{id: a, tool: X, depends_on: [b]}, {id: b, tool: X, depends_on: [a]}]";
+        let error = RunManifest::parse(cycle).unwrap_err().to_string();
+        assert!(error.contains("circular"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_run_manifest() {
+        let manifest = RunManifest::parse(MANIFEST).unwrap();
+        let order = Mutex::new(Vec::new());
+        let (in_flight, max_in_flight) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        let report = run_manifest::run(&manifest, |c| {
+            let (order, in_flight, max_in_flight) = (&order, &in_flight, &max_in_flight);
+            async move {
+                let n = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(n, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                order.lock().unwrap().push(c.id.clone());
+                Ok(CallToolResult {
+                    content: vec![Content::text(serde_json::to_string(&c.arguments).unwrap())],
+                    is_error: Some(false),
+                })
+            }
+        })
+        .await;
+        assert_eq!(report.succeeded, 5);
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        let order = order.into_inner().unwrap();
+        let position = |id: &str| order.iter().position(|o| o == id).unwrap();
+        assert!(position("build") < position("test"));
+        assert!(position("test") < position("deploy"));
+        assert!(position("lint") < position("deploy"));
+        assert_eq!(position("notify"), 4);
+        // results in the manifest order
+        let ids = report
+            .results
+            .iter()
+            .map(|r| r.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["build", "lint", "test", "deploy", "notify"]);
+        assert_eq!(report.results[0].output, Some(json!({"command": "make"})));
+    }
+
+    #[tokio::test]
+    async fn test_run_manifest_skips_dependents_of_failures() {
+        let manifest = RunManifest::parse(MANIFEST).unwrap();
+        let report = run_manifest::run(&manifest, |c| async move {
+            match c.id.as_str() {
+                "test" => Ok(CallToolResult {
+                    content: vec![Content::text("1 failed")],
+                    is_error: Some(true),
+                }),
+                "lint" => Err(anyhow::anyhow!("connection closed")),
+                _ => Ok(CallToolResult {
+                    content: vec![],
+                    is_error: None,
+                }),
+            }
+        })
+        .await;
+        let statuses = report
+            .results
+            .iter()
+            .map(|r| (r.id.as_str(), r.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                ("build", RunStatus::Succeeded),
+                ("lint", RunStatus::Failed),
+                ("test", RunStatus::Failed),
+                ("deploy", RunStatus::Skipped),
+                ("notify", RunStatus::Skipped),
+            ]
+        );
+        assert_eq!((report.succeeded, report.failed, report.skipped), (1, 2, 2));
+        assert_eq!(
+            report.results[1].message.as_deref(),
+            Some("connection closed")
+        );
+        assert_eq!(report.results[2].output, Some(json!("1 failed")));
+    }
+}
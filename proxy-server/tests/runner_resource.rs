@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use jobworkerp_client::jobworkerp::data::RunnerType;
+    use jobworkerp_client::jobworkerp::function::data::{
+        function_specs, FunctionSchema, FunctionSpecs, McpTool, McpToolList,
+    };
+    use proxy_server::jobworkerp::runner_resource::{self, RunnerResourceKind};
+
+    fn function(schema: function_specs::Schema) -> FunctionSpecs {
+        FunctionSpecs {
+            runner_type: RunnerType::Command as i32,
+            runner_id: Some(Default::default()),
+            worker_id: None,
+            name: "COMMAND".to_string(),
+            description: "desc".to_string(),
+            output_type: 0,
+            schema: Some(schema),
+        }
+    }
+
+    #[test]
+    fn test_parse_uri() {
+        let uri = runner_resource::uri("COMMAND", RunnerResourceKind::SettingsSchema);
+        assert_eq!(uri, "runner://COMMAND/settings_schema");
+        assert_eq!(
+            runner_resource::parse_uri(&uri),
+            Some(("COMMAND".to_string(), RunnerResourceKind::SettingsSchema))
+        );
+        assert_eq!(
+            runner_resource::parse_uri("runner://COMMAND/settings_proto"),
+            Some(("COMMAND".to_string(), RunnerResourceKind::SettingsProto))
+        );
+        assert!(runner_resource::parse_uri("runner://COMMAND/unknown").is_none());
+        assert!(runner_resource::parse_uri("runner:///output_schema").is_none());
+        assert!(runner_resource::parse_uri("job://1").is_none());
+    }
+
+    #[test]
+    fn test_schema_text() {
+        let command = function(function_specs::Schema::SingleSchema(FunctionSchema {
+            settings: None,
+            arguments: r#"{"type":"object"}"#.to_string(),
+            result_output_schema: Some(r#"{"type":"string"}"#.to_string()),
+        }));
+        assert!(
+            runner_resource::schema_text(&command, RunnerResourceKind::SettingsSchema).is_none()
+        );
+        assert_eq!(
+            runner_resource::schema_text(&command, RunnerResourceKind::OutputSchema).as_deref(),
+            Some(r#"{"type":"string"}"#)
+        );
+        assert!(
+            runner_resource::schema_text(&command, RunnerResourceKind::SettingsProto).is_none()
+        );
+
+        let mcp_server = function(function_specs::Schema::McpTools(McpToolList {
+            list: vec![McpTool {
+                name: "toolA".to_string(),
+                description: None,
+                input_schema: r#"{"type":"object"}"#.to_string(),
+                annotations: None,
+            }],
+        }));
+        let arguments =
+            runner_resource::schema_text(&mcp_server, RunnerResourceKind::ArgumentsSchema).unwrap();
+        let arguments: serde_json::Value = serde_json::from_str(&arguments).unwrap();
+        assert_eq!(arguments["toolA"]["type"], "object");
+        assert!(
+            runner_resource::schema_text(&mcp_server, RunnerResourceKind::OutputSchema).is_none()
+        );
+    }
+}
@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::auth::AuthIdentity;
+    use proxy_server::jobworkerp::scheduler::{ScheduleSpec, Scheduler};
+    use proxy_server::jobworkerp::session::SessionInfo;
+
+    fn parse(value: serde_json::Value) -> Result<Option<ScheduleSpec>, String> {
+        ScheduleSpec::parse(value.as_object().unwrap())
+    }
+
+    #[test]
+    fn test_parse_schedule_spec() {
+        assert!(parse(serde_json::json!({})).unwrap().is_none());
+
+        let once = parse(serde_json::json!({"run_after": "2030-01-01T00:00:00Z"}))
+            .unwrap()
+            .unwrap();
+        assert_eq!(once.next_run(0, None), Some(1_893_456_000_000));
+        assert_eq!(once.next_run(0, Some(1_893_456_000_000)), None);
+
+        let once = parse(serde_json::json!({"run_after": 1000}))
+            .unwrap()
+            .unwrap();
+        assert_eq!(once.next_run(0, None), Some(1000));
+
+        let interval = parse(serde_json::json!({"interval_sec": 60}))
+            .unwrap()
+            .unwrap();
+        assert_eq!(interval.next_run(1000, None), Some(61_000));
+
+        assert!(parse(serde_json::json!({"interval_sec": 0})).is_err());
+        assert!(parse(serde_json::json!({"run_after": "tomorrow"})).is_err());
+        assert!(parse(serde_json::json!({"run_after": 1000, "interval_sec": 60})).is_err());
+    }
+
+    #[test]
+    fn test_cron_next_run() {
+        // every day at 09:00 UTC
+        let cron = parse(serde_json::json!({"cron": "0 9 * * *"}))
+            .unwrap()
+            .unwrap();
+        // 2030-01-01T00:00:00Z -> 2030-01-01T09:00:00Z
+        assert_eq!(
+            cron.next_run(1_893_456_000_000, None),
+            Some(1_893_456_000_000 + 9 * 3600 * 1000)
+        );
+        assert!(parse(serde_json::json!({"cron": "invalid"})).is_err());
+    }
+
+    #[test]
+    fn test_scheduler_lifecycle() {
+        let scheduler = Scheduler::new(1);
+        let spec =
+            ScheduleSpec::parse(serde_json::json!({"interval_sec": 10}).as_object().unwrap())
+                .unwrap()
+                .unwrap();
+        let session = SessionInfo {
+            id: "s1".to_string(),
+            identity: Some(AuthIdentity {
+                subject: "alice".to_string(),
+                scopes: vec![],
+            }),
+            ..Default::default()
+        };
+        let (id, ct) = scheduler
+            .add("COMMAND", &spec, &session, Some(10_000))
+            .unwrap();
+        assert!(scheduler
+            .add("COMMAND", &spec, &SessionInfo::stdio(), Some(10_000))
+            .is_err());
+
+        scheduler.record_run(id, Some("failed".to_string()), Some(20_000));
+        let status = scheduler.get(id).unwrap();
+        assert_eq!(status.runs, 1);
+        assert_eq!(status.next_run_at, Some(20_000));
+        assert_eq!(status.last_error.as_deref(), Some("failed"));
+        assert_eq!(scheduler.list().len(), 1);
+
+        assert!(scheduler.remove(id).is_some());
+        assert!(ct.is_cancelled());
+        assert!(scheduler.list().is_empty());
+    }
+
+    #[test]
+    fn test_owner_capacity() {
+        let scheduler = Scheduler::new(10).with_owner_capacity(2);
+        let spec =
+            ScheduleSpec::parse(serde_json::json!({"interval_sec": 10}).as_object().unwrap())
+                .unwrap()
+                .unwrap();
+        let alice = |id: &str| SessionInfo {
+            id: id.to_string(),
+            identity: Some(AuthIdentity {
+                subject: "alice".to_string(),
+                scopes: vec![],
+            }),
+            ..Default::default()
+        };
+        assert!(scheduler.add("COMMAND", &spec, &alice("s1"), None).is_ok());
+        assert!(scheduler.add("COMMAND", &spec, &alice("s2"), None).is_ok());
+        // counted per subject across the sessions
+        let error = scheduler
+            .add("COMMAND", &spec, &alice("s3"), None)
+            .unwrap_err();
+        assert!(error.contains("max 2"), "{}", error);
+        // others are not limited by the schedules of alice
+        assert!(scheduler
+            .add("COMMAND", &spec, &SessionInfo::stdio(), None)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_schedule_visibility() {
+        let scheduler = Scheduler::new(10);
+        let spec =
+            ScheduleSpec::parse(serde_json::json!({"interval_sec": 10}).as_object().unwrap())
+                .unwrap()
+                .unwrap();
+        let session = |id: &str, subject: Option<&str>| SessionInfo {
+            id: id.to_string(),
+            identity: subject.map(|s| AuthIdentity {
+                subject: s.to_string(),
+                scopes: vec![],
+            }),
+            ..Default::default()
+        };
+        let (alices, _) = scheduler
+            .add("COMMAND", &spec, &session("s1", Some("alice")), None)
+            .unwrap();
+        let (anonymous, _) = scheduler
+            .add("COMMAND", &spec, &session("s2", None), None)
+            .unwrap();
+        let visible = |session: &SessionInfo| {
+            scheduler
+                .list()
+                .into_iter()
+                .filter(|s| s.is_visible_to(session))
+                .map(|s| s.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(visible(&session("s3", Some("alice"))), vec![alices]);
+        // schedules of other anonymous sessions are not visible
+        assert_eq!(visible(&session("s2", None)), vec![anonymous]);
+        assert!(visible(&session("s3", None)).is_empty());
+        // nor those of anonymous sessions to a subject named as the session
+        assert!(visible(&session("s2", Some("s2"))).is_empty());
+    }
+}
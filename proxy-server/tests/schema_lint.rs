@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use jobworkerp_client::jobworkerp::data::RunnerType;
+    use jobworkerp_client::jobworkerp::function::data::{
+        function_specs, FunctionSchema, FunctionSpecs, McpTool, McpToolList,
+    };
+    use proxy_server::schema_lint::{lint_functions, lint_schema};
+
+    fn function(name: &str, arguments: &str, result: Option<&str>) -> FunctionSpecs {
+        FunctionSpecs {
+            runner_type: RunnerType::Command as i32,
+            runner_id: Some(Default::default()),
+            worker_id: None,
+            name: name.to_string(),
+            description: "desc".to_string(),
+            output_type: 0,
+            schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+                settings: None,
+                arguments: arguments.to_string(),
+                result_output_schema: result.map(|r| r.to_string()),
+            })),
+        }
+    }
+
+    #[test]
+    fn test_lint_schema() {
+        assert!(lint_schema("t", "arguments", "").is_none());
+        assert!(lint_schema(
+            "t",
+            "arguments",
+            r#"{"$schema": "http://json-schema.org/draft-07/schema#", "type": "object"}"#
+        )
+        .is_none());
+        // yaml workflow schema
+        assert!(lint_schema("t", "settings", "type: object\nproperties: {}\n").is_none());
+
+        let warning = lint_schema("t", "arguments", r#"{"type": "objekt"}"#).unwrap();
+        assert_eq!(warning.tool, "t");
+        assert_eq!(warning.schema, "arguments");
+        assert!(!warning.message.is_empty());
+
+        let warning = lint_schema(
+            "t",
+            "arguments",
+            r#"{"type": "object", "properties": {"a": {"type": 1}}}"#,
+        )
+        .unwrap();
+        assert!(warning.path.contains("properties"));
+
+        let warning = lint_schema("t", "arguments", r#"{"type":"#).unwrap();
+        assert!(warning.message.starts_with("invalid json"));
+    }
+
+    #[test]
+    fn test_lint_functions() {
+        let mcp_server = FunctionSpecs {
+            runner_type: RunnerType::McpServer as i32,
+            schema: Some(function_specs::Schema::McpTools(McpToolList {
+                list: vec![McpTool {
+                    name: "toolA".to_string(),
+                    description: None,
+                    input_schema: r#"{"type": "object", "required": "a"}"#.to_string(),
+                    annotations: None,
+                }],
+            })),
+            ..function("server1", "", None)
+        };
+        let functions = vec![
+            function("ok", r#"{"type": "object"}"#, Some(r#"{"type": "string"}"#)),
+            function(
+                "bad_result",
+                r#"{"type": "object"}"#,
+                Some(r#"{"type": 1}"#),
+            ),
+            mcp_server,
+        ];
+        let warnings = lint_functions(&functions);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].tool, "bad_result");
+        assert_eq!(warnings[0].schema, "result");
+        assert_eq!(warnings[1].tool, "server1___toolA");
+        assert_eq!(warnings[1].schema, "input");
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::scripting::{self, ScriptingConfig};
+
+    #[test]
+    fn test_config() {
+        let config: ScriptingConfig =
+            serde_yaml::from_str("script_file: /etc/mcp/hooks.rhai\nmax_operations: 100000\n")
+                .unwrap();
+        assert!(config.is_enabled());
+        assert!(scripting::build(&ScriptingConfig::default())
+            .unwrap()
+            .is_none());
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    #[test]
+    fn test_scripting_without_feature() {
+        let config: ScriptingConfig =
+            serde_yaml::from_str("script: \"fn route(tool, args) { }\"\n").unwrap();
+        assert!(scripting::build(&config).is_err());
+    }
+
+    #[cfg(feature = "scripting")]
+    const SCRIPT: &str = r#"
+fn route(tool, args) {
+    if tool == "convert" {
+        args.value = args.value * 1000;
+        return #{ tool: "COMMAND", arguments: args };
+    }
+    if tool == "gpu" && args.size > 10 { return "gpu_large"; }
+    if tool == "denied" { throw "not today"; }
+}
+
+fn transform_result(tool, result) {
+    if tool == "COMMAND" {
+        result.content = [];
+        return result;
+    }
+}
+"#;
+
+    #[cfg(feature = "scripting")]
+    #[test]
+    fn test_route() {
+        use rmcp::model::CallToolRequestParam;
+        use serde_json::json;
+
+        let scripts = scripting::Scripts::new(SCRIPT, Some(10000)).unwrap();
+        let request = |name: &'static str, arguments: serde_json::Value| CallToolRequestParam {
+            name: name.into(),
+            arguments: arguments.as_object().cloned(),
+        };
+        let mut converted = request("convert", json!({"value": 2}));
+        assert!(scripts.route(&mut converted).unwrap());
+        assert_eq!(converted.name, "COMMAND");
+        assert_eq!(converted.arguments.unwrap()["value"], 2000);
+
+        let mut large = request("gpu", json!({"size": 20}));
+        assert!(scripts.route(&mut large).unwrap());
+        assert_eq!(large.name, "gpu_large");
+        let mut small = request("gpu", json!({"size": 1}));
+        assert!(!scripts.route(&mut small).unwrap());
+        assert_eq!(small.name, "gpu");
+
+        let error = scripts
+            .route(&mut request("denied", json!({})))
+            .unwrap_err();
+        assert!(error.message.contains("not today"));
+
+        // scripts without the hooks are rejected
+        assert!(scripting::Scripts::new("fn other() {}", None).is_err());
+    }
+
+    #[cfg(feature = "scripting")]
+    #[tokio::test]
+    async fn test_transform_result() {
+        use proxy_server::interceptor::{CallInterceptor, ToolCall};
+        use rmcp::model::{CallToolResult, Content};
+
+        let scripts = scripting::Scripts::new(SCRIPT, None).unwrap();
+        let call = |tool_name: &str| ToolCall {
+            tool_name: tool_name.to_string(),
+            target: tool_name.to_string(),
+            arguments: Default::default(),
+            metadata: Default::default(),
+            session_id: "s1".to_string(),
+            subject: None,
+        };
+        let result = || CallToolResult {
+            content: vec![Content::text("ok")],
+            is_error: None,
+        };
+        let mut transformed = result();
+        scripts.post_call(&call("COMMAND"), &mut transformed).await;
+        assert!(transformed.content.is_empty());
+        let mut unchanged = result();
+        scripts.post_call(&call("OTHER"), &mut unchanged).await;
+        assert_eq!(unchanged.content.len(), 1);
+    }
+}
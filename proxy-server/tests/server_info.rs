@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::jobworkerp::server_info::render_template;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_render_template() {
+        let vars = HashMap::from([("set_name", "set1".to_string())]);
+        assert_eq!(
+            render_template(
+                "{{default_instructions}} Tool set: {{ set_name }}. {{unknown}} {{",
+                "Default.",
+                &vars
+            ),
+            "Default. Tool set: set1. {{unknown}} {{"
+        );
+        assert_eq!(
+            render_template("{{env.SERVER_INFO_TEST_UNDEFINED}}", "", &vars),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_server_info_config() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+server:
+  name: my-proxy
+  instructions: "Backend: {{jobworkerp_address}}"
+"#,
+        )
+        .unwrap();
+        let implementation = config.server.implementation();
+        assert_eq!(implementation.name, "my-proxy");
+        let vars = HashMap::from([("jobworkerp_address", "http://backend:9000".to_string())]);
+        assert_eq!(
+            config.server.instructions("default", &vars).unwrap(),
+            "Backend: http://backend:9000"
+        );
+        // built-in instructions if not configured
+        let config = ProxyConfig::from_yaml("{}").unwrap();
+        assert_eq!(
+            config.server.instructions("default", &vars).unwrap(),
+            "default"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::auth::AuthIdentity;
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::jobworkerp::session::{
+        DisconnectPolicy, SessionClient, SessionInfo, SessionRegistry,
+    };
+    use proxy_server::jobworkerp::JobworkerpRouterConfig;
+    use proxy_server::{StdioClosed, StdioSettings};
+    use rmcp::model::{ClientInfo, Implementation, ProtocolVersion};
+    use std::time::Duration;
+
+    #[test]
+    fn test_session_registry_lifecycle() {
+        let registry = SessionRegistry::default();
+        let info = SessionInfo {
+            id: "s1".to_string(),
+            identity: Some(AuthIdentity {
+                subject: "alice".to_string(),
+                scopes: vec![],
+            }),
+            ..Default::default()
+        };
+        let state = registry.register(&info);
+        let client = SessionClient::from_peer_info(&ClientInfo {
+            protocol_version: ProtocolVersion::V_2024_11_05,
+            capabilities: Default::default(),
+            client_info: Implementation {
+                name: "client".to_string(),
+                version: "1.0".to_string(),
+            },
+        });
+        assert_eq!(client.protocol_version, "2024-11-05");
+        state.client.set(client).unwrap();
+        {
+            let _call = state.start_call();
+            let list = registry.list();
+            assert_eq!(list.len(), 1);
+            assert_eq!(list[0].subject.as_deref(), Some("alice"));
+            assert_eq!(list[0].client.as_deref(), Some("client/1.0"));
+            assert_eq!(list[0].protocol_version.as_deref(), Some("2024-11-05"));
+            assert_eq!(list[0].active_calls, 1);
+            assert_eq!(registry.active_calls("s1"), 1);
+        }
+        assert_eq!(registry.list()[0].active_calls, 0);
+        assert_eq!(registry.active_calls("unknown"), 0);
+
+        // disconnect cancels in-flight calls of the session
+        registry.unregister("s1");
+        assert!(registry.is_empty());
+        assert!(state.ct.is_cancelled());
+    }
+
+    #[test]
+    fn test_disconnect_policy() {
+        assert_eq!(DisconnectPolicy::default(), DisconnectPolicy::Cancel);
+        assert_eq!(
+            DisconnectPolicy::parse("Retain"),
+            Some(DisconnectPolicy::Retain)
+        );
+        assert_eq!(
+            DisconnectPolicy::parse("cancel"),
+            Some(DisconnectPolicy::Cancel)
+        );
+        assert_eq!(DisconnectPolicy::parse("ignore"), None);
+    }
+
+    #[test]
+    fn test_stdio_closed_exit_code() {
+        let closed: anyhow::Result<()> = Err(StdioClosed {
+            exit_code: 75,
+            running_jobs: 1,
+        }
+        .into());
+        assert_eq!(StdioClosed::exit_code_of(&closed), Some(75));
+        assert_eq!(
+            closed.unwrap_err().to_string(),
+            "stdio client closed the connection (1 jobs left running)"
+        );
+        assert_eq!(StdioClosed::exit_code_of(&Ok(())), None);
+        let failed: anyhow::Result<()> = Err(anyhow::anyhow!("serving error"));
+        assert_eq!(StdioClosed::exit_code_of(&failed), None);
+    }
+
+    #[test]
+    fn test_stdio_settings() {
+        let config = JobworkerpRouterConfig {
+            stdio_drain_sec: Some(30),
+            stdio_closed_exit_code: Some(75),
+            ..JobworkerpRouterConfig::from_env(ProxyConfig::default())
+        };
+        // shared by the stdio-only and the combined stdio and SSE servers
+        let settings = StdioSettings::of(&config);
+        assert_eq!(settings.drain, Some(Duration::from_secs(30)));
+        assert_eq!(settings.closed_exit_code, Some(75));
+    }
+}
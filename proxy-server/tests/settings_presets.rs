@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::config::ProxyConfig;
+    use serde_json::json;
+
+    #[test]
+    fn test_settings_presets_with_secret_references() {
+        std::env::set_var("SETTINGS_PRESETS_TEST_KEY", "key1");
+        let secret_file = std::env::temp_dir().join("settings_presets_test_secret");
+        std::fs::write(&secret_file, "secret1\n").unwrap();
+        let config = ProxyConfig::from_yaml(&format!(
+            r#"
+settings_presets:
+  LLM:
+    api_key: "${{env:SETTINGS_PRESETS_TEST_KEY}}"
+    auth:
+      header: "Bearer ${{file:{}}}"
+"#,
+            secret_file.display()
+        ))
+        .unwrap();
+        let presets = config.settings_presets.resolve().unwrap();
+        assert_eq!(presets.fields()["LLM"].len(), 2);
+        // the values are not shown
+        assert!(!format!("{:?}", presets).contains("key1"));
+
+        // the presets take precedence over the settings of the call
+        let mut settings = Some(json!({"model": "m1", "api_key": "from_client"}));
+        presets.apply("LLM", &mut settings);
+        assert_eq!(
+            settings,
+            Some(json!({
+                "model": "m1",
+                "api_key": "key1",
+                "auth": {"header": "Bearer secret1"}
+            }))
+        );
+        let mut settings = None;
+        presets.apply("COMMAND", &mut settings);
+        assert_eq!(settings, None);
+    }
+
+    #[test]
+    fn test_settings_presets_unresolved_reference() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+settings_presets:
+  LLM:
+    api_key: "${env:SETTINGS_PRESETS_TEST_UNDEFINED}"
+"#,
+        )
+        .unwrap();
+        assert!(config.settings_presets.resolve().is_err());
+    }
+}
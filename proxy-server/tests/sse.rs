@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use futures::StreamExt;
+    use proxy_server::auth::AuthIdentity;
+    use proxy_server::sse::{self, SseServerSettings};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    fn settings(max_sessions: Option<usize>, idle_timeout: Option<Duration>) -> SseServerSettings {
+        SseServerSettings {
+            sse_path: "/sse".to_string(),
+            post_path: "/message".to_string(),
+            keep_alive: None,
+            retry: None,
+            tenants: None,
+            max_sessions,
+            idle_timeout,
+            session_registry: None,
+        }
+    }
+
+    fn connect() -> Request<Body> {
+        Request::get("/sse").body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_max_sessions() {
+        let (router, _sessions) = sse::router(settings(Some(1), None));
+        let first = router.clone().oneshot(connect()).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        let second = router.clone().oneshot(connect()).await.unwrap();
+        assert_eq!(second.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // the slot is freed when the session is closed
+        drop(first);
+        let third = router.clone().oneshot(connect()).await.unwrap();
+        assert_eq!(third.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_idle_timeout() {
+        let (router, _sessions) = sse::router(settings(None, Some(Duration::from_millis(50))));
+        let response = router.clone().oneshot(connect()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = tokio::time::timeout(
+            Duration::from_secs(5),
+            axum::body::to_bytes(response.into_body(), usize::MAX),
+        )
+        .await
+        .expect("idle session is not closed")
+        .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.starts_with("event: endpoint\n"));
+        assert!(body.contains("event: close\n"));
+        assert!(body.contains("\"reason\":\"idle_timeout\""));
+    }
+
+    fn identity(subject: &str) -> AuthIdentity {
+        AuthIdentity {
+            subject: subject.to_string(),
+            scopes: vec![],
+        }
+    }
+
+    // the request authenticated as the identity (set by the auth layer)
+    fn authenticated(mut request: Request<Body>, subject: Option<&str>) -> Request<Body> {
+        if let Some(subject) = subject {
+            request.extensions_mut().insert(identity(subject));
+        }
+        request
+    }
+
+    fn post_message(endpoint: &str, subject: Option<&str>) -> Request<Body> {
+        let message = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        let request = Request::post(endpoint)
+            .header("content-type", "application/json")
+            .body(Body::from(message))
+            .unwrap();
+        authenticated(request, subject)
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_hint() {
+        let (router, _sessions) = sse::router(SseServerSettings {
+            retry: Some(Duration::from_secs(3)),
+            ..settings(None, None)
+        });
+        let response = router.oneshot(connect()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut body = response.into_body().into_data_stream();
+        let event = String::from_utf8(body.next().await.unwrap().unwrap().to_vec()).unwrap();
+        assert!(event.contains("event: endpoint"), "{}", event);
+        assert!(event.contains("retry: 3000"), "{}", event);
+    }
+
+    #[tokio::test]
+    async fn test_session_of_another_identity() {
+        let (router, _sessions) = sse::router(settings(None, None));
+        let response = router
+            .clone()
+            .oneshot(authenticated(connect(), Some("alice")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut body = response.into_body().into_data_stream();
+        let event = String::from_utf8(body.next().await.unwrap().unwrap().to_vec()).unwrap();
+        let endpoint = event
+            .lines()
+            .find_map(|l| l.strip_prefix("data: "))
+            .unwrap()
+            .to_string();
+        assert!(endpoint.starts_with("/message?sessionId="));
+
+        // messages of other identities or without authentication are rejected
+        for subject in [Some("bob"), None] {
+            let response = router
+                .clone()
+                .oneshot(post_message(&endpoint, subject))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+        let response = router
+            .clone()
+            .oneshot(post_message(&endpoint, Some("alice")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let response = router
+            .clone()
+            .oneshot(post_message("/message?sessionId=unknown", Some("alice")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::jobworkerp::channel::UpstreamChannelConfig;
+    use proxy_server::jobworkerp::session::SessionInfo;
+    use std::collections::HashMap;
+
+    const CONFIG: &str = r#"
+tenants:
+  header: X-Tenant
+  path_segment: true
+  backends:
+    acme:
+      jobworkerp_address: http://acme-jobworkerp:9000
+      metadata:
+        Authorization: "Bearer ${env:TENANT_TEST_TOKEN}"
+    globex:
+      jobworkerp_address: https://globex-jobworkerp:9000
+      tls_ca: /etc/globex/ca.pem
+"#;
+
+    fn session(headers: &[(&str, &str)], tenant: Option<&str>) -> SessionInfo {
+        SessionInfo {
+            id: "s1".to_string(),
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+            tenant: tenant.map(|t| t.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_select_tenant() {
+        let config = ProxyConfig::from_yaml(CONFIG).unwrap();
+        let tenants = &config.tenants;
+        assert!(tenants.is_enabled());
+
+        let selected = tenants.select(&session(&[("x-tenant", "acme")], None));
+        assert_eq!(selected.unwrap().as_deref(), Some("acme"));
+        // the path segment takes precedence over the header
+        let selected = tenants.select(&session(&[("x-tenant", "acme")], Some("globex")));
+        assert_eq!(selected.unwrap().as_deref(), Some("globex"));
+        assert!(tenants
+            .select(&session(&[("x-tenant", "initech")], None))
+            .is_err());
+        // the default backend is used without a tenant unless required
+        assert_eq!(tenants.select(&session(&[], None)).unwrap(), None);
+        let mut required = tenants.clone();
+        required.required = true;
+        assert!(required.select(&session(&[], None)).is_err());
+    }
+
+    #[test]
+    fn test_tenant_backend_settings() {
+        std::env::set_var("TENANT_TEST_TOKEN", "token1");
+        let config = ProxyConfig::from_yaml(CONFIG).unwrap();
+        let default = UpstreamChannelConfig {
+            tls_ca_path: Some("/etc/default/ca.pem".to_string()),
+            tls_domain: Some("jobworkerp.local".to_string()),
+            ..Default::default()
+        };
+        let upstream = config.tenants.backends["globex"].upstream(&default);
+        assert_eq!(upstream.tls_ca_path.as_deref(), Some("/etc/globex/ca.pem"));
+        assert_eq!(upstream.tls_domain.as_deref(), Some("jobworkerp.local"));
+
+        let tenants = config.tenants.resolve(&default).unwrap();
+        assert_eq!(
+            tenants
+                .metadata("acme")
+                .get("authorization")
+                .map(|s| s.as_str()),
+            Some("Bearer token1")
+        );
+        assert!(tenants.metadata("globex").is_empty());
+        // the credentials are not shown
+        assert!(!format!("{:?}", tenants).contains("token1"));
+    }
+
+    #[test]
+    fn test_unresolved_tenant_metadata() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+tenants:
+  header: x-tenant
+  backends:
+    acme:
+      jobworkerp_address: http://acme-jobworkerp:9000
+      metadata:
+        authorization: "${env:TENANT_TEST_UNDEFINED}"
+"#,
+        )
+        .unwrap();
+        assert!(config
+            .tenants
+            .resolve(&UpstreamChannelConfig::default())
+            .is_err());
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use jobworkerp_client::jobworkerp::data::RunnerType;
+    use jobworkerp_client::jobworkerp::function::data::{
+        function_specs, FunctionSchema, FunctionSpecs,
+    };
+    use proxy_server::jobworkerp::tool_cache::{ToolCache, ToolListDiff};
+
+    fn function(name: &str, description: &str) -> FunctionSpecs {
+        FunctionSpecs {
+            runner_type: RunnerType::Command as i32,
+            runner_id: Some(Default::default()),
+            worker_id: None,
+            name: name.to_string(),
+            description: description.to_string(),
+            output_type: 0,
+            schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+                settings: None,
+                arguments: "{}".to_string(),
+                result_output_schema: None,
+            })),
+        }
+    }
+
+    #[test]
+    fn test_tool_list_diff() {
+        let old = vec![function("a", "desc"), function("b", "desc")];
+        let new = vec![function("b", "changed"), function("c", "desc")];
+        let diff = ToolListDiff::new(&old, &new);
+        assert_eq!(diff.added, vec!["c".to_string()]);
+        assert_eq!(diff.removed, vec!["a".to_string()]);
+        assert_eq!(diff.updated, vec!["b".to_string()]);
+        // the order of the list is not a change
+        let reordered = vec![function("b", "desc"), function("a", "desc")];
+        assert!(ToolListDiff::new(&old, &reordered).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_notifies_changes() {
+        let cache = ToolCache::new();
+        let mut changes = cache.subscribe_changes();
+        // the first list is not a change
+        assert!(cache.update(vec![function("a", "desc")]).is_none());
+        assert!(cache.update(vec![function("a", "desc")]).is_none());
+        assert!(changes.try_recv().is_err());
+
+        let diff = cache
+            .update(vec![function("a", "desc"), function("b", "desc")])
+            .unwrap();
+        assert_eq!(diff.added, vec!["b".to_string()]);
+        assert!(changes.try_recv().is_ok());
+        assert_eq!(cache.get().unwrap().len(), 2);
+    }
+}
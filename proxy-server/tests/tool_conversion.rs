@@ -5,7 +5,12 @@ mod tests {
     use jobworkerp_client::jobworkerp::function::data::{
         function_specs, FunctionSchema, FunctionSpecs, McpTool, McpToolList,
     };
-    use proxy_server::tool_conversion::ToolConverter;
+    use proptest::prelude::*;
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::tool_conversion::{
+        RenamedTool, ToolConversionOptions, ToolConverter, ToolKind,
+    };
+    use serde_json::json;
 
     #[tokio::test]
     async fn test_convert_functions_to_tools_reusable_workflow() {
@@ -71,4 +76,539 @@ mod tests {
         assert_eq!(tools.tools.len(), 1);
         assert_eq!(tools.tools[0].name, "cmd1");
     }
+
+    #[tokio::test]
+    async fn test_convert_functions_with_broken_schema() {
+        let broken = FunctionSpecs {
+            runner_type: RunnerType::Command as i32,
+            runner_id: Some(Default::default()),
+            worker_id: None,
+            name: "broken1".to_string(),
+            description: "desc".to_string(),
+            output_type: 0,
+            schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+                settings: None,
+                arguments: "{\"type\":".to_string(),
+                result_output_schema: None,
+            })),
+        };
+        let no_arguments = FunctionSpecs {
+            name: "noargs1".to_string(),
+            schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+                settings: None,
+                arguments: "".to_string(),
+                result_output_schema: None,
+            })),
+            ..broken.clone()
+        };
+        let (tools, diagnostics) = ToolConverter::convert_functions_with_diagnostics(
+            vec![broken.clone(), no_arguments.clone()],
+            false,
+        );
+        assert_eq!(tools.tools.len(), 1);
+        assert_eq!(tools.tools[0].name, "noargs1");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].name, "broken1");
+        assert!(diagnostics[0].message.contains("arguments"));
+
+        // stub tool explaining the problem
+        let (tools, _) =
+            ToolConverter::convert_functions_with_diagnostics(vec![broken, no_arguments], true);
+        assert_eq!(tools.tools.len(), 2);
+        let stub = tools.tools.iter().find(|t| t.name == "broken1").unwrap();
+        assert!(stub.description.contains("UNAVAILABLE"));
+    }
+
+    fn function_with_defs() -> FunctionSpecs {
+        let settings = json!({
+            "type": "object",
+            "properties": {"mode": {"$ref": "#/$defs/Mode"}},
+            "$defs": {"Mode": {"type": "string", "enum": ["a", "b"]}}
+        });
+        let arguments = json!({
+            "type": "object",
+            "properties": {
+                "mode": {"$ref": "#/definitions/Mode"},
+                "items": {"type": "array", "items": {"$ref": "#"}}
+            },
+            "definitions": {"Mode": {"type": "integer"}}
+        });
+        FunctionSpecs {
+            runner_type: RunnerType::Command as i32,
+            runner_id: Some(Default::default()),
+            worker_id: None,
+            name: "defs1".to_string(),
+            description: "desc".to_string(),
+            output_type: 0,
+            schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+                settings: Some(settings.to_string()),
+                arguments: arguments.to_string(),
+                result_output_schema: None,
+            })),
+        }
+    }
+
+    #[test]
+    fn test_combine_schema_defs() {
+        let tool = ToolConverter::convert_normal_function(&function_with_defs()).unwrap();
+        let schema = tool.input_schema.as_ref();
+        // merged without collision (sorted by schema name: arguments first)
+        assert_eq!(schema["$defs"]["Mode"], json!({"type": "integer"}));
+        assert_eq!(
+            schema["$defs"]["settings_Mode"],
+            json!({"type": "string", "enum": ["a", "b"]})
+        );
+        let properties = &schema["properties"];
+        assert_eq!(
+            properties["arguments"]["properties"]["mode"]["$ref"],
+            "#/$defs/Mode"
+        );
+        assert_eq!(
+            properties["settings"]["properties"]["mode"]["$ref"],
+            "#/$defs/settings_Mode"
+        );
+        assert_eq!(
+            properties["arguments"]["properties"]["items"]["items"]["$ref"],
+            "#/properties/arguments"
+        );
+        assert!(properties["arguments"].get("definitions").is_none());
+        assert!(properties["settings"].get("$defs").is_none());
+    }
+
+    #[test]
+    fn test_combine_schema_inline_refs() {
+        let options = ToolConversionOptions {
+            inline_refs: true,
+            ..Default::default()
+        };
+        let tool =
+            ToolConverter::convert_normal_function_with_options(&function_with_defs(), &options)
+                .unwrap();
+        let schema = tool.input_schema.as_ref();
+        let properties = &schema["properties"];
+        assert_eq!(
+            properties["settings"]["properties"]["mode"],
+            json!({"type": "string", "enum": ["a", "b"]})
+        );
+        assert_eq!(
+            properties["arguments"]["properties"]["mode"],
+            json!({"type": "integer"})
+        );
+        // recursive ref is kept with the defs
+        assert!(properties["arguments"]["properties"]["items"]["items"]
+            .get("$ref")
+            .is_some());
+        assert!(schema.get("$defs").is_some());
+
+        // no defs left without recursive refs
+        let mut function = function_with_defs();
+        if let Some(function_specs::Schema::SingleSchema(s)) = function.schema.as_mut() {
+            s.arguments = json!({"type": "object"}).to_string();
+        }
+        let tool =
+            ToolConverter::convert_normal_function_with_options(&function, &options).unwrap();
+        assert!(tool.input_schema.get("$defs").is_none());
+        assert_eq!(
+            tool.input_schema["properties"]["settings"]["properties"]["mode"]["enum"],
+            json!(["a", "b"])
+        );
+    }
+
+    #[test]
+    fn test_describe_result_schema() {
+        let result = json!({
+            "type": "object",
+            "description": "command result",
+            "properties": {
+                "exit_code": {"type": "integer", "description": "exit status"},
+                "lines": {"type": "array", "items": {"type": "string"}},
+                "status": {"enum": ["ok", "error"]}
+            },
+            "required": ["exit_code"]
+        });
+        let mut function = function_with_defs();
+        if let Some(function_specs::Schema::SingleSchema(s)) = function.schema.as_mut() {
+            s.result_output_schema = Some(result.to_string());
+        }
+        assert_eq!(
+            ToolConverter::result_schema(&function),
+            result.as_object().cloned()
+        );
+        // documented only with the option
+        let tool = ToolConverter::convert_normal_function(&function).unwrap();
+        assert_eq!(tool.description, "desc");
+
+        let options = ToolConversionOptions {
+            describe_result: true,
+            ..Default::default()
+        };
+        let tool =
+            ToolConverter::convert_normal_function_with_options(&function, &options).unwrap();
+        assert_eq!(
+            tool.description,
+            "desc\n\nreturns: object\ncommand result\n- exit_code (integer): exit status\n- lines (array of string, optional)\n- status (one of \"ok\", \"error\", optional)"
+        );
+
+        // invalid result schema is ignored
+        if let Some(function_specs::Schema::SingleSchema(s)) = function.schema.as_mut() {
+            s.result_output_schema = Some("{".to_string());
+        }
+        let tool =
+            ToolConverter::convert_normal_function_with_options(&function, &options).unwrap();
+        assert_eq!(tool.description, "desc");
+        assert!(ToolConverter::result_schema(&function).is_none());
+    }
+
+    #[test]
+    fn test_divide_legacy_names() {
+        assert_eq!(
+            ToolConverter::divide_names("server1___toolA"),
+            Some(("server1".to_string(), "toolA".to_string()))
+        );
+        // the tool name may contain the delimiter
+        assert_eq!(
+            ToolConverter::divide_names("server1___tool___A"),
+            Some(("server1".to_string(), "tool___A".to_string()))
+        );
+        assert_eq!(ToolConverter::divide_names("toolA"), None);
+        assert_eq!(
+            ToolConverter::combine_names("server1", "toolA"),
+            "server1___toolA"
+        );
+    }
+
+    #[test]
+    fn test_combine_escaped_names() {
+        let combined = ToolConverter::combine_names("my___server", "toolA");
+        assert_eq!(combined, "___11_my___server___toolA");
+        assert_eq!(
+            ToolConverter::divide_names(&combined),
+            Some(("my___server".to_string(), "toolA".to_string()))
+        );
+        let combined = ToolConverter::combine_names("server_", "_toolA");
+        assert_eq!(combined, "___7_server____toolA");
+        assert_eq!(
+            ToolConverter::divide_names(&combined),
+            Some(("server_".to_string(), "_toolA".to_string()))
+        );
+        // broken escaped names
+        assert_eq!(ToolConverter::divide_names("___x_server___toolA"), None);
+        assert_eq!(ToolConverter::divide_names("___100_server___toolA"), None);
+        assert_eq!(ToolConverter::divide_names("___6_server_toolA"), None);
+    }
+
+    #[test]
+    fn test_disambiguate_colliding_names() {
+        let runner = FunctionSpecs {
+            runner_type: RunnerType::Command as i32,
+            runner_id: Some(Default::default()),
+            worker_id: None,
+            name: "COMMAND".to_string(),
+            description: "desc".to_string(),
+            output_type: 0,
+            schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+                settings: None,
+                arguments: "{\"type\":\"object\"}".to_string(),
+                result_output_schema: None,
+            })),
+        };
+        let worker = FunctionSpecs {
+            worker_id: Some(Default::default()),
+            ..runner.clone()
+        };
+        let mcp_server = FunctionSpecs {
+            runner_type: RunnerType::McpServer as i32,
+            name: "srv".to_string(),
+            schema: Some(function_specs::Schema::McpTools(McpToolList {
+                list: vec![McpTool {
+                    name: "t".to_string(),
+                    description: None,
+                    input_schema: "{\"type\":\"object\"}".to_string(),
+                    annotations: None,
+                }],
+            })),
+            ..runner.clone()
+        };
+        let mcp_named_worker = FunctionSpecs {
+            name: "srv___t".to_string(),
+            ..worker.clone()
+        };
+        // listed before the runner: the resolution order decides
+        let functions = vec![worker, mcp_named_worker, runner, mcp_server];
+        let converted = ToolConverter::convert_functions_with_options(
+            functions.clone(),
+            &ToolConversionOptions::default(),
+        );
+        let names = converted
+            .result
+            .tools
+            .iter()
+            .map(|t| t.name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["worker_COMMAND", "worker_srv___t", "COMMAND", "srv___t"]
+        );
+        assert_eq!(
+            converted.renamed,
+            vec![
+                RenamedTool {
+                    name: "worker_COMMAND".to_string(),
+                    original: "COMMAND".to_string(),
+                    kind: ToolKind::Worker,
+                },
+                RenamedTool {
+                    name: "worker_srv___t".to_string(),
+                    original: "srv___t".to_string(),
+                    kind: ToolKind::Worker,
+                },
+            ]
+        );
+
+        // custom prefix, prepended again while colliding
+        let options = ToolConversionOptions {
+            collision_prefix: Some("x_".to_string()),
+            ..Default::default()
+        };
+        let mut functions = functions;
+        functions[1].name = "x_COMMAND".to_string();
+        let converted = ToolConverter::convert_functions_with_options(functions, &options);
+        assert_eq!(converted.renamed.len(), 1);
+        assert_eq!(converted.renamed[0].name, "x_x_COMMAND");
+    }
+
+    #[test]
+    fn test_kind_prefixed_names() {
+        let runner = FunctionSpecs {
+            runner_type: RunnerType::Command as i32,
+            runner_id: Some(Default::default()),
+            worker_id: None,
+            name: "COMMAND".to_string(),
+            description: "desc".to_string(),
+            output_type: 0,
+            schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+                settings: None,
+                arguments: "{\"type\":\"object\"}".to_string(),
+                result_output_schema: None,
+            })),
+        };
+        let worker = FunctionSpecs {
+            worker_id: Some(Default::default()),
+            ..runner.clone()
+        };
+        let workflow = FunctionSpecs {
+            runner_type: RunnerType::ReusableWorkflow as i32,
+            name: "flow1".to_string(),
+            ..worker.clone()
+        };
+        let mcp_server = FunctionSpecs {
+            runner_type: RunnerType::McpServer as i32,
+            name: "srv".to_string(),
+            schema: Some(function_specs::Schema::McpTools(McpToolList {
+                list: vec![McpTool {
+                    name: "t".to_string(),
+                    description: None,
+                    input_schema: "{\"type\":\"object\"}".to_string(),
+                    annotations: None,
+                }],
+            })),
+            ..runner.clone()
+        };
+        let mcp_named_runner = FunctionSpecs {
+            name: "srv___t".to_string(),
+            ..runner.clone()
+        };
+        let options = ToolConversionOptions {
+            kind_prefixes: true,
+            ..Default::default()
+        };
+        let converted = ToolConverter::convert_functions_with_options(
+            vec![runner, worker, workflow, mcp_server, mcp_named_runner],
+            &options,
+        );
+        let names = converted
+            .result
+            .tools
+            .iter()
+            .map(|t| t.name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "runner.COMMAND",
+                "worker.COMMAND",
+                "wf.flow1",
+                "runner.mcp_srv___t",
+                "runner.srv___t"
+            ]
+        );
+        // only the collision in the same kind prefix
+        assert_eq!(
+            converted.renamed,
+            vec![RenamedTool {
+                name: "runner.mcp_srv___t".to_string(),
+                original: "srv___t".to_string(),
+                kind: ToolKind::McpTool,
+            }]
+        );
+
+        assert_eq!(
+            ToolConverter::strip_kind_prefix("runner.srv___t"),
+            Some((ToolKind::Runner, "srv___t"))
+        );
+        assert_eq!(
+            ToolConverter::strip_kind_prefix("worker.COMMAND"),
+            Some((ToolKind::Worker, "COMMAND"))
+        );
+        assert_eq!(
+            ToolConverter::strip_kind_prefix("wf.flow1"),
+            Some((ToolKind::Worker, "flow1"))
+        );
+        assert_eq!(ToolConverter::strip_kind_prefix("COMMAND"), None);
+    }
+
+    #[test]
+    fn test_tool_aliases() {
+        let function = |name: &str| FunctionSpecs {
+            runner_type: RunnerType::Command as i32,
+            runner_id: Some(Default::default()),
+            worker_id: None,
+            name: name.to_string(),
+            description: "desc".to_string(),
+            output_type: 0,
+            schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+                settings: None,
+                arguments: "{\"type\":\"object\"}".to_string(),
+                result_output_schema: None,
+            })),
+        };
+        let config = ProxyConfig::from_yaml(
+            r#"
+tool_aliases:
+  run_shell:
+    tool: COMMAND
+    description: Run a shell command
+  fetch:
+    tool: HTTP_REQUEST
+  HTTP_REQUEST:
+    tool: COMMAND
+  missing:
+    tool: UNKNOWN
+"#,
+        )
+        .unwrap();
+        let options = ToolConversionOptions {
+            aliases: config.tool_aliases,
+            ..Default::default()
+        };
+        let converted = ToolConverter::convert_functions_with_options(
+            vec![function("COMMAND"), function("HTTP_REQUEST")],
+            &options,
+        );
+        let tools = converted
+            .result
+            .tools
+            .iter()
+            .map(|t| (t.name.to_string(), t.description.to_string()))
+            .collect::<Vec<_>>();
+        // "HTTP_REQUEST" is taken and "missing" has no tool
+        assert_eq!(
+            tools,
+            vec![
+                ("run_shell".to_string(), "Run a shell command".to_string()),
+                ("fetch".to_string(), "desc".to_string()),
+            ]
+        );
+        assert_eq!(converted.aliased.len(), 2);
+        assert_eq!(converted.aliased["run_shell"], "COMMAND");
+        assert_eq!(converted.aliased["fetch"], "HTTP_REQUEST");
+    }
+
+    #[test]
+    fn test_preset_settings_removed_from_schema() {
+        let runner = FunctionSpecs {
+            runner_type: RunnerType::Command as i32,
+            runner_id: Some(Default::default()),
+            worker_id: None,
+            name: "LLM".to_string(),
+            description: "desc".to_string(),
+            output_type: 0,
+            schema: Some(function_specs::Schema::SingleSchema(FunctionSchema {
+                settings: Some(
+                    json!({
+                        "type": "object",
+                        "properties": {
+                            "api_key": {"type": "string"},
+                            "model": {"type": "string"}
+                        },
+                        "required": ["api_key", "model"]
+                    })
+                    .to_string(),
+                ),
+                arguments: "{\"type\":\"object\"}".to_string(),
+                result_output_schema: None,
+            })),
+        };
+        let options = |fields: &[&str]| ToolConversionOptions {
+            preset_settings: [(
+                "LLM".to_string(),
+                fields.iter().map(|f| f.to_string()).collect(),
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let tool =
+            ToolConverter::convert_normal_function_with_options(&runner, &options(&["api_key"]))
+                .unwrap();
+        let settings = &tool.input_schema["properties"]["settings"];
+        assert_eq!(settings["properties"], json!({"model": {"type": "string"}}));
+        assert_eq!(settings["required"], json!(["model"]));
+
+        // no settings field left
+        let tool = ToolConverter::convert_normal_function_with_options(
+            &runner,
+            &options(&["api_key", "model"]),
+        )
+        .unwrap();
+        assert!(tool.input_schema["properties"].get("settings").is_none());
+        assert!(!tool
+            .input_schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .is_some_and(|r| r.contains(&json!("settings"))));
+
+        // workers have their own settings
+        let worker = FunctionSpecs {
+            worker_id: Some(Default::default()),
+            ..runner
+        };
+        let tool =
+            ToolConverter::convert_normal_function_with_options(&worker, &options(&["api_key"]))
+                .unwrap();
+        assert!(tool.input_schema["properties"]["settings"]["properties"]
+            .get("api_key")
+            .is_some());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_combine_divide_round_trip(
+            server_name in "[a-zA-Z0-9_-]{0,16}",
+            tool_name in "[a-zA-Z0-9_-]{0,16}",
+        ) {
+            let combined = ToolConverter::combine_names(&server_name, &tool_name);
+            prop_assert_eq!(
+                ToolConverter::divide_names(&combined),
+                Some((server_name, tool_name))
+            );
+        }
+
+        #[test]
+        fn prop_divide_arbitrary_names_without_panic(combined in "\\PC{0,32}") {
+            if let Some((server_name, tool_name)) = ToolConverter::divide_names(&combined) {
+                prop_assert!(combined.contains(server_name.as_str()));
+                prop_assert!(combined.ends_with(tool_name.as_str()));
+            }
+        }
+    }
 }
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::config::ProxyConfig;
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_defaults_from_config() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+tool_defaults:
+  COMMAND:
+    arguments:
+      with_memory_monitoring: true
+  "api_*":
+    base_url: https://api.example.com
+    headers:
+      accept: application/json
+"#,
+        )
+        .unwrap();
+        let defaults = config.tool_defaults;
+
+        let mut arguments = json!({"arguments": {"command": "ls"}}).as_object().cloned();
+        defaults.apply("COMMAND", &mut arguments);
+        assert_eq!(
+            arguments.map(serde_json::Value::Object),
+            Some(json!({"arguments": {"command": "ls", "with_memory_monitoring": true}}))
+        );
+
+        // the arguments of the call take precedence (objects are merged)
+        let mut arguments = json!({
+            "base_url": "https://other.example.com",
+            "headers": {"x-id": "1"}
+        })
+        .as_object()
+        .cloned();
+        defaults.apply("api_users", &mut arguments);
+        assert_eq!(
+            arguments.map(serde_json::Value::Object),
+            Some(json!({
+                "base_url": "https://other.example.com",
+                "headers": {"x-id": "1", "accept": "application/json"}
+            }))
+        );
+
+        // no arguments
+        let mut arguments = None;
+        defaults.apply("api_items", &mut arguments);
+        assert_eq!(
+            arguments.map(serde_json::Value::Object),
+            Some(json!({
+                "base_url": "https://api.example.com",
+                "headers": {"accept": "application/json"}
+            }))
+        );
+
+        let mut arguments = None;
+        defaults.apply("HTTP_REQUEST", &mut arguments);
+        assert_eq!(arguments, None);
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::tool_export::{self, ExportFormat};
+    use rmcp::model::ListToolsResult;
+    use serde_json::{json, Value};
+
+    fn tools() -> ListToolsResult {
+        serde_json::from_value(json!({"tools": [
+            {
+                "name": "greet",
+                "description": "Greet someone\nwith details",
+                "inputSchema": {"type": "object", "properties": {"name": {"type": "string"}}}
+            },
+            {"name": "COMMAND", "description": "Run a command", "inputSchema": {"type": "object"}}
+        ]}))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(ExportFormat::parse("json"), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::parse("OpenAPI"), Some(ExportFormat::OpenApi));
+        assert_eq!(ExportFormat::parse("yaml"), None);
+    }
+
+    #[test]
+    fn test_export_json() {
+        let exported: Value =
+            serde_json::from_str(&tool_export::export(&tools(), ExportFormat::Json).unwrap())
+                .unwrap();
+        // sorted by name
+        assert_eq!(exported["tools"][0]["name"], "COMMAND");
+        assert_eq!(exported["tools"][1]["name"], "greet");
+        assert_eq!(
+            exported["tools"][1]["inputSchema"]["properties"]["name"]["type"],
+            "string"
+        );
+    }
+
+    #[test]
+    fn test_export_openapi() {
+        let exported: Value =
+            serde_json::from_str(&tool_export::export(&tools(), ExportFormat::OpenApi).unwrap())
+                .unwrap();
+        assert_eq!(exported["openapi"], "3.1.0");
+        let operation = &exported["paths"]["/tools/greet"]["post"];
+        assert_eq!(operation["operationId"], "greet");
+        assert_eq!(operation["summary"], "Greet someone");
+        assert_eq!(
+            operation["requestBody"]["content"]["application/json"]["schema"]["properties"]["name"]
+                ["type"],
+            "string"
+        );
+        assert!(exported["paths"]["/tools/COMMAND"]["post"].is_object());
+    }
+}
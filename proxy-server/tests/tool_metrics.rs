@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::jobworkerp::tool_metrics::{
+        self, ErrorClass, Histogram, ToolMetrics, BUCKETS,
+    };
+    use rmcp::model::{CallToolResult, Content};
+    use rmcp::Error as McpError;
+    use std::time::{Duration, Instant};
+
+    fn ok(is_error: Option<bool>) -> Result<CallToolResult, McpError> {
+        Ok(CallToolResult {
+            content: vec![Content::text("done")],
+            is_error,
+        })
+    }
+
+    #[test]
+    fn test_error_class() {
+        assert_eq!(ErrorClass::of(&ok(None)), None);
+        assert_eq!(ErrorClass::of(&ok(Some(true))), Some(ErrorClass::Tool));
+        assert_eq!(
+            ErrorClass::of(&Err(McpError::invalid_params("missing command", None))),
+            Some(ErrorClass::Validation)
+        );
+        assert_eq!(
+            ErrorClass::of(&Err(McpError::internal_error(
+                "Failed to enqueue job: request timed out",
+                None
+            ))),
+            Some(ErrorClass::Timeout)
+        );
+        assert_eq!(
+            ErrorClass::of(&Err(McpError::internal_error("connection refused", None))),
+            Some(ErrorClass::Backend)
+        );
+    }
+
+    #[test]
+    fn test_histogram() {
+        let mut histogram = Histogram::default();
+        assert_eq!(histogram.quantile(0.5), 0.0);
+        for ms in [3, 8, 40, 40, 900, 1_000_000] {
+            histogram.observe(Duration::from_millis(ms));
+        }
+        assert_eq!(histogram.count(), 6);
+        let cumulative = histogram.cumulative();
+        assert_eq!(cumulative.len(), BUCKETS.len() + 1);
+        assert_eq!(&cumulative[..4], &[1, 2, 2, 4]);
+        assert_eq!(cumulative[BUCKETS.len() - 1], 5);
+        assert_eq!(cumulative[BUCKETS.len()], 6);
+        assert_eq!(histogram.quantile(0.5), 0.05);
+        assert_eq!(histogram.quantile(0.8), 1.0);
+        assert_eq!(histogram.quantile(1.0), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_render_prometheus() {
+        let metrics = ToolMetrics::default();
+        metrics.record_enqueue("COMMAND", Duration::from_millis(20));
+        metrics.record_call("COMMAND", Duration::from_millis(200), &ok(None));
+        metrics.record_call("COMMAND", Duration::from_secs(2), &ok(Some(true)));
+        metrics.record_call(
+            "say \"hi\"",
+            Duration::from_millis(1),
+            &Err(McpError::invalid_params("bad", None)),
+        );
+        let stats = metrics.snapshot();
+        assert_eq!(stats["COMMAND"].duration.count(), 2);
+        assert_eq!(stats["COMMAND"].error_count(), 1);
+
+        let text = metrics.render_prometheus();
+        assert!(text.contains("# TYPE mcp_tool_call_duration_seconds histogram"));
+        assert!(text.contains("mcp_tool_enqueue_seconds_bucket{tool=\"COMMAND\",le=\"0.025\"} 1"));
+        assert!(
+            text.contains("mcp_tool_call_duration_seconds_bucket{tool=\"COMMAND\",le=\"0.25\"} 1")
+        );
+        assert!(
+            text.contains("mcp_tool_call_duration_seconds_bucket{tool=\"COMMAND\",le=\"+Inf\"} 2")
+        );
+        assert!(text.contains("mcp_tool_call_duration_seconds_count{tool=\"COMMAND\"} 2"));
+        assert!(text.contains("mcp_tool_errors_total{tool=\"COMMAND\",class=\"tool\"} 1"));
+        assert!(
+            text.contains("mcp_tool_errors_total{tool=\"say \\\"hi\\\"\",class=\"validation\"} 1")
+        );
+        // tools without enqueued jobs have no enqueue histogram
+        assert!(!text.contains("mcp_tool_enqueue_seconds_count{tool=\"say"));
+    }
+
+    #[tokio::test]
+    async fn test_call_elapsed() {
+        assert!(tool_metrics::call_elapsed().is_none());
+        let started = Instant::now() - Duration::from_millis(50);
+        let elapsed =
+            tool_metrics::scope_call(started, async { tool_metrics::call_elapsed() }).await;
+        assert!(elapsed.unwrap() >= Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::config::ProxyConfig;
+    use proxy_server::tool_prompt::{self, ToolPromptConfig};
+    use rmcp::model::Tool;
+    use serde_json::json;
+
+    fn tool() -> Tool {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "url": {"type": "string", "format": "uri"},
+                "method": {"type": "string", "enum": ["GET", "POST"]},
+                "headers": {"type": "array", "items": {"type": "string"}},
+                "timeout_sec": {"type": "integer", "default": 30},
+                "body": {"type": "string"},
+            },
+            "required": ["url", "method", "headers"],
+        });
+        Tool::new(
+            "HTTP_REQUEST",
+            "Send a http request",
+            schema.as_object().cloned().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_example_arguments() {
+        let example = tool_prompt::example_arguments(&tool().input_schema);
+        assert_eq!(
+            example,
+            json!({
+                "url": "<uri>",
+                "method": "GET",
+                "headers": ["<string>"],
+                // optional fields with defaults are included
+                "timeout_sec": 30,
+            })
+        );
+    }
+
+    #[test]
+    fn test_prompt_name() {
+        assert_eq!(tool_prompt::prompt_name("COMMAND"), "use-COMMAND");
+        assert_eq!(tool_prompt::tool_name("use-COMMAND"), Some("COMMAND"));
+        assert_eq!(tool_prompt::tool_name("use-"), None);
+        assert_eq!(tool_prompt::tool_name("COMMAND"), None);
+        assert_eq!(tool_prompt::prompt(&tool()).name, "use-HTTP_REQUEST");
+    }
+
+    #[test]
+    fn test_render_with_config() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+tool_prompts:
+  HTTP_REQUEST:
+    caveats:
+      - only hosts in the allow list are reachable
+    example:
+      url: https://example.com
+      method: GET
+      headers: []
+"#,
+        )
+        .unwrap();
+        let prompt_config: &ToolPromptConfig = &config.tool_prompts["HTTP_REQUEST"];
+        let text = tool_prompt::render(&tool(), Some(prompt_config));
+        assert!(text.starts_with("Call the `HTTP_REQUEST` tool."));
+        assert!(text.contains("\"https://example.com\""));
+        assert!(text.contains("Caveats:\n- only hosts in the allow list are reachable\n"));
+
+        let text = tool_prompt::render(&tool(), None);
+        assert!(text.contains("\"<uri>\""));
+        assert!(!text.contains("Caveats:"));
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::jobworkerp::worker_definition;
+    use proxy_server::jobworkerp::worker_options::ResponseTypeOption;
+
+    #[test]
+    fn test_parse_definitions() {
+        let definitions = worker_definition::parse_definitions(
+            r#"
+workers:
+  - name: list_files
+    description: List the files of a directory
+    runner: COMMAND
+    channel: io
+    response_type: direct
+    broadcast_results: false
+  - name: nightly_report
+    workflow:
+      document: {dsl: 1.0.0, namespace: ops, name: report, version: 1.0.0}
+      do:
+        - echo: {run: {runner: {name: COMMAND, arguments: {command: echo}}}}
+"#,
+        )
+        .unwrap();
+        assert_eq!(definitions.len(), 2);
+        let command = &definitions[0];
+        assert_eq!(command.runner.as_deref(), Some("COMMAND"));
+        assert_eq!(command.channel.as_deref(), Some("io"));
+        assert_eq!(
+            command.options.response_type,
+            Some(ResponseTypeOption::Direct)
+        );
+        assert_eq!(command.options.broadcast_results, Some(false));
+        assert!(definitions[1].workflow.is_some());
+
+        // a single definition (json)
+        let single = worker_definition::parse_definitions(
+            r#"{"name": "fetch", "runner": "HTTP_REQUEST", "settings": {"base_url": "https://example.com"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            single[0].settings.as_ref().unwrap()["base_url"],
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        let parse = worker_definition::parse_definitions;
+        assert!(parse("name: x\n").is_err());
+        assert!(parse("name: x\nrunner: COMMAND\nworkflow: {document: {}}\n").is_err());
+        assert!(parse("name: x\nworkflow: {document: {}}\nsettings: {}\n").is_err());
+        assert!(parse("runner: COMMAND\n").is_err());
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use jobworkerp_client::jobworkerp::data::ResponseType;
+    use proxy_server::config::ProxyConfig;
+
+    #[test]
+    fn test_worker_options_from_config() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+worker_options:
+  default:
+    broadcast_results: false
+  workflow:
+    broadcast_results: false
+  runners:
+    COMMAND:
+      response_type: no_result
+      broadcast_results: true
+    "HTTP_*":
+      response_type: direct
+"#,
+        )
+        .unwrap();
+        let options = config.worker_options;
+
+        let command = options.for_runner("COMMAND");
+        assert_eq!(command.response_type(), ResponseType::NoResult);
+        assert_eq!(command.broadcast_results, Some(true));
+        assert_eq!(
+            command.worker_params("w1"),
+            serde_json::json!({
+                "name": "w1",
+                "response_type": ResponseType::NoResult as i32,
+                "broadcast_results": true,
+            })
+        );
+
+        let http = options.for_runner("HTTP_REQUEST");
+        assert_eq!(http.response_type(), ResponseType::Direct);
+        assert_eq!(http.broadcast_results, Some(false));
+
+        let other = options.for_runner("GRPC_UNARY");
+        assert_eq!(other.response_type, None);
+        assert_eq!(
+            other.worker_params("w2"),
+            serde_json::json!({"name": "w2", "broadcast_results": false})
+        );
+
+        let workflow = options.for_workflow();
+        assert_eq!(workflow.response_type(), ResponseType::Direct);
+        assert_eq!(workflow.broadcast_results, Some(false));
+    }
+
+    #[test]
+    fn test_default_workflow_options() {
+        let workflow = ProxyConfig::default().worker_options.for_workflow();
+        assert_eq!(workflow.response_type(), ResponseType::Direct);
+        assert_eq!(workflow.broadcast_results, Some(true));
+    }
+
+    #[test]
+    fn test_allowed_channels() {
+        let config = ProxyConfig::from_yaml(
+            r#"
+worker_options:
+  channels:
+    - gpu
+    - "highmem-*"
+"#,
+        )
+        .unwrap();
+        let options = config.worker_options;
+        assert!(options.is_channel_allowed("gpu"));
+        assert!(options.is_channel_allowed("highmem-64g"));
+        assert!(!options.is_channel_allowed("workflow"));
+        // no channel can be selected by default
+        assert!(!ProxyConfig::default()
+            .worker_options
+            .is_channel_allowed("gpu"));
+    }
+}
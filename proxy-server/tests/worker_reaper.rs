@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::jobworkerp::worker_reaper::{EphemeralWorkerStats, EphemeralWorkers};
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_worker_name_by_settings() {
+        let a = EphemeralWorkers::worker_name("COMMAND", Some(&json!({"a": 1})));
+        assert!(a.starts_with("mcp-ephemeral-COMMAND-"));
+        assert_eq!(
+            a,
+            EphemeralWorkers::worker_name("COMMAND", Some(&json!({"a": 1})))
+        );
+        assert_ne!(
+            a,
+            EphemeralWorkers::worker_name("COMMAND", Some(&json!({"a": 2})))
+        );
+        assert_ne!(a, EphemeralWorkers::worker_name("COMMAND", None));
+    }
+
+    #[tokio::test]
+    async fn test_expire_only_idle_workers() {
+        let workers = Arc::new(EphemeralWorkers::new(Duration::ZERO));
+        let lease1 = workers.acquire("w1").await;
+        let lease2 = workers.acquire("w1").await;
+        let _lease3 = workers.acquire("w2").await;
+        assert_eq!(workers.stats().created, 2);
+
+        drop(lease1);
+        assert!(workers.take_expired().is_empty());
+        drop(lease2);
+        assert_eq!(workers.take_expired(), vec!["w1".to_string()]);
+        // being deleted: not taken twice
+        assert!(workers.take_expired().is_empty());
+        workers.finish_delete("w1", &Ok(true));
+
+        // reused after deletion: created again
+        let _lease4 = workers.acquire("w1").await;
+        assert_eq!(
+            workers.stats(),
+            EphemeralWorkerStats {
+                created: 3,
+                deleted: 1,
+                delete_failed: 0,
+                active: 2,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_deletion() {
+        let workers = Arc::new(EphemeralWorkers::new(Duration::ZERO));
+        drop(workers.acquire("w1").await);
+        assert_eq!(workers.take_expired(), vec!["w1".to_string()]);
+
+        let acquire = tokio::spawn({
+            let workers = workers.clone();
+            async move {
+                let _lease = workers.acquire("w1").await;
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!acquire.is_finished());
+        workers.finish_delete("w1", &Ok(true));
+        tokio::time::timeout(Duration::from_secs(1), acquire)
+            .await
+            .unwrap()
+            .unwrap();
+        // created again after the deletion
+        assert_eq!(workers.stats().created, 2);
+    }
+
+    #[tokio::test]
+    async fn test_record_delete_results() {
+        let workers = Arc::new(EphemeralWorkers::new(Duration::ZERO));
+        drop(workers.acquire("w1").await);
+        drop(workers.acquire("w2").await);
+        let mut expired = workers.take_expired();
+        expired.sort();
+        assert_eq!(expired, vec!["w1".to_string(), "w2".to_string()]);
+        // not found: not counted as deleted
+        workers.finish_delete("w1", &Ok(false));
+        // failed: kept to be deleted again
+        workers.finish_delete("w2", &Err(anyhow::anyhow!("unavailable")));
+        assert_eq!(
+            workers.stats(),
+            EphemeralWorkerStats {
+                created: 2,
+                deleted: 0,
+                delete_failed: 1,
+                active: 1,
+            }
+        );
+        assert_eq!(workers.take_expired(), vec!["w2".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_keep_workers_within_ttl() {
+        let workers = Arc::new(EphemeralWorkers::new(Duration::from_secs(60)));
+        drop(workers.acquire("w1").await);
+        assert!(workers.take_expired().is_empty());
+        assert_eq!(workers.stats().active, 1);
+    }
+}
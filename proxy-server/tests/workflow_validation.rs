@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use proxy_server::argument_errors::FieldError;
+    use proxy_server::jobworkerp::workflow_validation::{self, WorkflowValidation};
+    use serde_json::json;
+
+    const DEFINITION_YAML: &str = r#"
+document:
+  dsl: 1.0.0
+  namespace: ci
+  name: nightly
+  version: 1.0.0
+do:
+  - fetch:
+      run:
+        runner:
+          name: HTTP_REQUEST
+          arguments: {url: "https://example.com"}
+  - branch:
+      fork:
+        branches:
+          - notify:
+              run:
+                worker:
+                  name: slack_notifier
+          - echo:
+              run:
+                runner:
+                  name: COMMAND
+"#;
+
+    #[test]
+    fn test_parse_definition() {
+        // a bare definition is put into workflow_data
+        let definition = workflow_validation::parse_definition(DEFINITION_YAML).unwrap();
+        assert_eq!(definition["workflow_data"]["document"]["name"], "nightly");
+
+        let arguments = workflow_validation::parse_definition(
+            r#"{"workflow_data": {"document": {"name": "x"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(arguments["workflow_data"]["document"]["name"], "x");
+
+        assert!(workflow_validation::parse_definition("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_referenced_functions() {
+        let definition = workflow_validation::parse_definition(DEFINITION_YAML).unwrap();
+        let (runners, workers) =
+            workflow_validation::referenced_functions(&definition["workflow_data"]);
+        assert_eq!(
+            runners.into_iter().collect::<Vec<_>>(),
+            vec!["COMMAND", "HTTP_REQUEST"]
+        );
+        assert_eq!(
+            workers.into_iter().collect::<Vec<_>>(),
+            vec!["slack_notifier"]
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        assert!(WorkflowValidation::default().is_valid());
+        let validation = WorkflowValidation {
+            errors: vec![FieldError {
+                path: "workflow_data.document".to_string(),
+                message: "\"name\" is a required property".to_string(),
+                received: json!({}),
+            }],
+            unknown_runners: vec!["LLM".to_string()],
+            unknown_workers: vec![],
+        };
+        assert!(!validation.is_valid());
+        assert_eq!(
+            validation.report(),
+            "workflow_data.document: \"name\" is a required property (received: {})\nrunner not found: LLM"
+        );
+    }
+}